@@ -0,0 +1,244 @@
+use std::{
+    collections::{HashMap, HashSet, VecDeque},
+    fmt::Display,
+    io::IsTerminal,
+    path::{Path, PathBuf},
+    sync::{Mutex, OnceLock},
+};
+
+use colored::Colorize;
+use inquire::list_option::ListOption;
+
+use crate::{error::ProjectError, messages::t, output::notice};
+
+/// Answers given ahead of time for interactive prompts, so scripts and CI can drive the
+/// wizards. Each prompt has a key like `add.name`. Keys that repeat (`add.variables`)
+/// take an array and answer one prompt per element.
+#[derive(Default)]
+struct Answers {
+    values: HashMap<String, VecDeque<String>>,
+    /// Keys that were answered at all, so loops stop once their answers run out
+    scripted: HashSet<String>,
+}
+
+static ANSWERS: OnceLock<Mutex<Answers>> = OnceLock::new();
+
+/// `add.save_to_cache` and `CMM_ANSWER_ADD_SAVE_TO_CACHE` name the same prompt
+fn normalize(key: &str) -> String {
+    key.replace(['.', '-'], "_").to_uppercase()
+}
+
+fn flatten(prefix: &str, value: toml::Value, answers: &mut Answers) {
+    match value {
+        toml::Value::Table(table) => table.into_iter().for_each(|(key, value)| {
+            let key = match prefix.is_empty() {
+                true => key,
+                false => format!("{prefix}.{key}"),
+            };
+            flatten(&key, value, answers);
+        }),
+
+        // An empty array still counts as answered, so the prompt is skipped
+        toml::Value::Array(values) => {
+            answers.scripted.insert(normalize(prefix));
+            values
+                .into_iter()
+                .for_each(|value| flatten(prefix, value, answers))
+        }
+
+        other => {
+            let answer = match other {
+                toml::Value::String(val) => val,
+                val => val.to_string(),
+            };
+
+            let key = normalize(prefix);
+            answers.scripted.insert(key.clone());
+            answers.values.entry(key).or_default().push_back(answer);
+        }
+    }
+}
+
+/// The file given with `--answers <FILE>`, or `CMM_ANSWERS`
+fn answers_path(path: Option<&Path>) -> Option<PathBuf> {
+    path.map(Path::to_owned).or_else(|| {
+        std::env::var("CMM_ANSWERS")
+            .ok()
+            .filter(|val| !val.is_empty())
+            .map(PathBuf::from)
+    })
+}
+
+/// Loads answers from the answers file and `CMM_ANSWER_<KEY>` environment variables
+pub fn load(path: Option<&Path>) -> Result<(), ProjectError> {
+    let mut answers = Answers::default();
+
+    if let Some(path) = answers_path(path) {
+        let contents = std::fs::read_to_string(&path)
+            .map_err(|err| ProjectError::CannotOpenFile(path.clone(), err.to_string()))?;
+
+        let table = contents
+            .parse::<toml::Table>()
+            .map_err(|err| ProjectError::CannotOpenFile(path, err.to_string()))?;
+
+        flatten("", toml::Value::Table(table), &mut answers);
+    }
+
+    std::env::vars()
+        .filter_map(|(var, val)| Some((var.strip_prefix("CMM_ANSWER_")?.to_owned(), val)))
+        .for_each(|(key, val)| {
+            answers.scripted.insert(key.clone());
+            answers.values.entry(key).or_default().push_back(val);
+        });
+
+    ANSWERS.set(Mutex::new(answers)).ok();
+
+    Ok(())
+}
+
+fn take(key: &str) -> Option<String> {
+    ANSWERS
+        .get()?
+        .lock()
+        .ok()?
+        .values
+        .get_mut(&normalize(key))?
+        .pop_front()
+}
+
+fn take_all(key: &str) -> Vec<String> {
+    std::iter::from_fn(|| take(key)).collect()
+}
+
+fn is_scripted(key: &str) -> bool {
+    ANSWERS
+        .get()
+        .and_then(|answers| answers.lock().ok())
+        .is_some_and(|answers| answers.scripted.contains(&normalize(key)))
+}
+
+/// Whether a prompt can get an answer, either from the user or from the answers given
+pub fn can_answer(key: &str) -> bool {
+    std::io::stdin().is_terminal() || is_scripted(key)
+}
+
+fn parse_bool(answer: &str) -> Option<bool> {
+    match answer.to_lowercase().as_str() {
+        "y" | "yes" | "true" | "1" => Some(true),
+        "n" | "no" | "false" | "0" => Some(false),
+        _ => None,
+    }
+}
+
+/// Answers match an option by its position or its text
+fn find_option<T: Display>(options: &[T], answer: &str) -> Option<usize> {
+    answer
+        .parse::<usize>()
+        .ok()
+        .filter(|i| *i < options.len())
+        .or_else(|| {
+            options
+                .iter()
+                .position(|option| option.to_string().eq_ignore_ascii_case(answer))
+        })
+}
+
+fn warn_unused(key: &str, answer: &str) {
+    notice!(
+        "{}",
+        t!(
+            "{} ignoring answer '{}' for '{}'",
+            t!("warning:").yellow(),
+            answer,
+            key
+        )
+    );
+}
+
+/// Without a terminal the prompt can't be asked, so say which answer is missing instead
+/// of failing inside inquire. Cancelling a prompt stops the command.
+fn unanswered<T>(key: &str, result: inquire::error::InquireResult<T>) -> Result<T, ProjectError> {
+    result.map_err(|err| match err {
+        inquire::InquireError::NotTTY => ProjectError::MissingAnswer(key.to_owned()),
+        inquire::InquireError::OperationCanceled | inquire::InquireError::OperationInterrupted => {
+            ProjectError::Cancelled
+        }
+        err => ProjectError::PromptFailed(key.to_owned(), err.to_string()),
+    })
+}
+
+pub fn text(key: &str, prompt: inquire::Text) -> Result<String, ProjectError> {
+    match take(key) {
+        Some(answer) => Ok(answer),
+        None => unanswered(key, prompt.prompt()),
+    }
+}
+
+/// Prompts that can be skipped. Once a scripted key runs out of answers it is skipped too,
+/// which ends loops like `add.variables`.
+pub fn text_skippable(key: &str, prompt: inquire::Text) -> Result<Option<String>, ProjectError> {
+    match take(key) {
+        Some(answer) => Ok(Some(answer)),
+        None if is_scripted(key) => Ok(None),
+        None => unanswered(key, prompt.prompt_skippable()),
+    }
+}
+
+pub fn confirm(key: &str, prompt: inquire::Confirm) -> Result<bool, ProjectError> {
+    if let Some(answer) = take(key) {
+        match parse_bool(&answer) {
+            Some(answer) => return Ok(answer),
+            None => warn_unused(key, &answer),
+        }
+    }
+
+    unanswered(key, prompt.prompt())
+}
+
+pub fn select<T: Display>(
+    key: &str,
+    mut prompt: inquire::Select<T>,
+) -> Result<ListOption<T>, ProjectError> {
+    if let Some(answer) = take(key) {
+        match find_option(&prompt.options, &answer) {
+            Some(index) => return Ok(ListOption::new(index, prompt.options.swap_remove(index))),
+            None => warn_unused(key, &answer),
+        }
+    }
+
+    unanswered(key, prompt.raw_prompt())
+}
+
+pub fn multi_select<T: Display>(
+    key: &str,
+    mut prompt: inquire::MultiSelect<T>,
+) -> Result<Vec<ListOption<T>>, ProjectError> {
+    let answers = take_all(key);
+    if answers.is_empty() {
+        return unanswered(key, prompt.raw_prompt());
+    }
+
+    let mut indices = answers
+        .iter()
+        .filter_map(|answer| {
+            let index = find_option(&prompt.options, answer);
+            if index.is_none() {
+                warn_unused(key, answer);
+            }
+            index
+        })
+        .collect::<Vec<_>>();
+
+    indices.sort_unstable();
+    indices.dedup();
+
+    // Remove from the back so earlier indices stay valid
+    let mut selected = indices
+        .into_iter()
+        .rev()
+        .map(|index| ListOption::new(index, prompt.options.remove(index)))
+        .collect::<Vec<_>>();
+
+    selected.reverse();
+    Ok(selected)
+}
@@ -0,0 +1,222 @@
+use std::{
+    fs::File,
+    path::{Path, PathBuf},
+};
+
+use colored::Colorize;
+use flate2::{read::GzDecoder, write::GzEncoder, Compression};
+
+use crate::{error::ProjectError, messages::t, output::status};
+
+/// First entry of every archive, saying where the paths inside it were made
+const MANIFEST: &str = ".cmm-cache.toml";
+
+const BUILD_DIR: &str = "build";
+
+/// Folders CMake downloads into inside each profile's build folder: FetchContent and CPM
+/// sources and the CPM.cmake script. Conan keeps its packages in its own cache, which CI
+/// has to cache separately.
+const DEPENDENCY_DIRS: [&str; 2] = ["_deps", "cmake"];
+
+/// Build system files needing their paths fixed are never this large
+const MAX_FIXUP_SIZE: u64 = 4 * 1024 * 1024;
+
+#[derive(serde::Deserialize, serde::Serialize)]
+struct Manifest {
+    /// Project folder the archive was saved from
+    root: String,
+}
+
+/// The project folder as CMake writes it into its files
+pub(crate) fn project_root() -> Result<String, ProjectError> {
+    let root = std::env::current_dir()
+        .and_then(|dir| dir.canonicalize())
+        .map_err(|err| ProjectError::CannotOpenFile(".".into(), err.to_string()))?;
+
+    let root = root.to_string_lossy().replace('\\', "/");
+    Ok(root.strip_prefix("//?/").unwrap_or(&root).to_owned())
+}
+
+/// Folders to pack: the dependency folders of every profile, or the whole build folder
+fn archived_paths(build: bool) -> Vec<PathBuf> {
+    let build_dir = Path::new(BUILD_DIR);
+
+    if build {
+        return build_dir
+            .is_dir()
+            .then(|| build_dir.to_owned())
+            .into_iter()
+            .collect();
+    }
+
+    let mut paths = std::fs::read_dir(build_dir)
+        .into_iter()
+        .flatten()
+        .filter_map(|entry| entry.ok())
+        .map(|entry| entry.path())
+        .filter(|profile| profile.is_dir())
+        .flat_map(|profile| DEPENDENCY_DIRS.map(|dir| profile.join(dir)))
+        .filter(|path| path.is_dir())
+        .collect::<Vec<_>>();
+
+    paths.sort();
+    paths
+}
+
+/// `cmm cache save <ARCHIVE> [--build]`. Packs downloaded dependencies, or the whole build
+/// folder with `build`, into a gzipped tarball.
+pub fn save(archive: &Path, build: bool) -> Result<(), ProjectError> {
+    let paths = archived_paths(build);
+
+    if paths.is_empty() {
+        println!(
+            "{}",
+            t!(
+                "{} nothing to save yet, run 'cmm fetch' first",
+                t!("warning:").yellow()
+            )
+        );
+        return Ok(());
+    }
+
+    let write_error =
+        |err: std::io::Error| ProjectError::FailedToCreateFile(archive.to_owned(), err.to_string());
+
+    let manifest = toml::to_string(&Manifest {
+        root: project_root()?,
+    })
+    .unwrap();
+
+    let file = File::create(archive).map_err(write_error)?;
+    let mut tar = tar::Builder::new(GzEncoder::new(file, Compression::default()));
+    // Dependencies can contain symlinks, which should stay links and not be copied
+    tar.follow_symlinks(false);
+
+    let mut header = tar::Header::new_gnu();
+    header.set_size(manifest.len() as u64);
+    header.set_mode(0o644);
+    header.set_cksum();
+    tar.append_data(&mut header, MANIFEST, manifest.as_bytes())
+        .map_err(write_error)?;
+
+    for path in &paths {
+        tar.append_dir_all(path, path).map_err(write_error)?;
+        status!("{}", t!("Packed '{}'", path.display()));
+    }
+
+    tar.into_inner()
+        .and_then(|gz| gz.finish())
+        .map_err(write_error)?;
+
+    status!(
+        "{}",
+        t!(
+            "{} saving cache to '{}'",
+            t!("Finished").green().bold(),
+            archive.display()
+        )
+    );
+
+    Ok(())
+}
+
+/// Rewrites paths to the project folder the archive was saved from in text files, so CMake
+/// doesn't reject the cache for having been made somewhere else. Returns how many changed.
+fn fix_paths(files: &[PathBuf], old_root: &str, new_root: &str) -> usize {
+    files
+        .iter()
+        .filter(|path| {
+            path.metadata()
+                .is_ok_and(|metadata| metadata.len() <= MAX_FIXUP_SIZE)
+        })
+        .filter_map(|path| Some((path, std::fs::read_to_string(path).ok()?)))
+        .filter(|(_, contents)| contents.contains(old_root))
+        .filter(|(path, contents)| {
+            std::fs::write(path, contents.replace(old_root, new_root)).is_ok()
+        })
+        .count()
+}
+
+/// `cmm cache restore <ARCHIVE>`. Unpacks an archive made with `cmm cache save` and fixes
+/// up the paths inside if the project has moved. A missing archive is a cache miss, not
+/// an error.
+pub fn restore(archive: &Path) -> Result<(), ProjectError> {
+    if !archive.exists() {
+        println!(
+            "{}",
+            t!("No cache at '{}', nothing restored", archive.display())
+        );
+        return Ok(());
+    }
+
+    let read_error =
+        |err: std::io::Error| ProjectError::CannotOpenFile(archive.to_owned(), err.to_string());
+
+    let file = File::open(archive).map_err(read_error)?;
+    let mut tar = tar::Archive::new(GzDecoder::new(file));
+    tar.set_preserve_mtime(true);
+
+    let mut entries = tar.entries().map_err(read_error)?;
+
+    let manifest = entries
+        .next()
+        .transpose()
+        .map_err(read_error)?
+        .and_then(|mut entry| {
+            let is_manifest = entry.path().is_ok_and(|path| path == Path::new(MANIFEST));
+            let mut contents = String::new();
+            std::io::Read::read_to_string(&mut entry, &mut contents).ok()?;
+
+            is_manifest.then_some(contents)
+        })
+        .and_then(|contents| toml::from_str::<Manifest>(&contents).ok())
+        .ok_or(ProjectError::CannotOpenFile(
+            archive.to_owned(),
+            "not a cmm cache archive".into(),
+        ))?;
+
+    let root = project_root()?;
+    let mut files = Vec::new();
+
+    for entry in entries {
+        let mut entry = entry.map_err(read_error)?;
+        let path = entry.path().map_err(read_error)?.into_owned();
+
+        // Archives only ever hold the build folder, never anything else in the project
+        if !path.starts_with(BUILD_DIR) {
+            continue;
+        }
+
+        entry.unpack_in(".").map_err(read_error)?;
+
+        if entry.header().entry_type().is_file() {
+            files.push(path);
+        }
+    }
+
+    status!("{}", t!("Restored {} files", files.len()));
+
+    if manifest.root != root {
+        let fixed = fix_paths(&files, &manifest.root, &root);
+        println!(
+            "{}",
+            t!(
+                "Moved paths in {} files from '{}' to '{}'",
+                fixed,
+                manifest.root,
+                root
+            )
+        );
+    }
+
+    println!(
+        "{}",
+        t!(
+            "{} restoring cache from '{}'",
+            t!("Finished").green().bold(),
+            archive.display()
+        )
+    );
+
+    Ok(())
+}
@@ -0,0 +1,195 @@
+use std::{fmt::Write, path::Path};
+
+use cmakemake_core::{
+    build::LAST_BUILD_LOG,
+    cmake_cache::{self, CMakeCache},
+    error::ProjectError,
+    messages::t,
+    output::status,
+    CONFIG_NAME,
+};
+
+/// Lines of the last build's output included in the report
+const LOG_TAIL: usize = 60;
+
+/// Config keys whose values are never included, matched anywhere in the key
+const SECRET_KEYS: [&str; 4] = ["token", "password", "secret", "api_key"];
+
+/// The first line `<tool> --version` prints, which names the tool as well as the version
+fn version_line(tool: &str) -> String {
+    duct::cmd!(tool, "--version")
+        .stderr_to_stdout()
+        .stdout_capture()
+        .unchecked()
+        .run()
+        .ok()
+        .and_then(|output| {
+            String::from_utf8_lossy(&output.stdout)
+                .lines()
+                .find(|line| !line.trim().is_empty())
+                .map(|line| line.trim().to_owned())
+        })
+        .unwrap_or_else(|| t!("not found").into())
+}
+
+/// The C++ compiler cmake would use: the one the debug build was configured with, else
+/// CXX, else the system's default
+fn compiler() -> String {
+    CMakeCache::read(Path::new("build/debug"))
+        .and_then(|cache| cache.get("CMAKE_CXX_COMPILER").map(String::from))
+        .or_else(|| std::env::var("CXX").ok().filter(|cxx| !cxx.is_empty()))
+        .unwrap_or_else(|| match cfg!(windows) {
+            true => "cl".into(),
+            false => "c++".into(),
+        })
+}
+
+/// Replaces `user:token@` in urls with `***@`
+fn redact_credentials(line: &str) -> String {
+    let mut redacted = String::new();
+    let mut rest = line;
+
+    while let Some(scheme) = rest.find("://") {
+        let (before, after) = rest.split_at(scheme + 3);
+        redacted.push_str(before);
+
+        let host_end = after.find(['/', ' ', '"', '\'']).unwrap_or(after.len());
+        rest = match after[..host_end].rfind('@') {
+            Some(at) => {
+                redacted.push_str("***");
+                &after[at..]
+            }
+            None => after,
+        };
+    }
+
+    redacted + rest
+}
+
+/// Replaces email addresses with `<email>`, leaving `git@host` ssh urls alone
+fn redact_emails(line: &str) -> String {
+    let is_email_char = |c: char| c.is_ascii_alphanumeric() || "._%+-@".contains(c);
+
+    let mut redacted = String::new();
+    let mut word = String::new();
+
+    // The newline ends the last word and is taken off again
+    for c in line.chars().chain(['\n']) {
+        if is_email_char(c) {
+            word.push(c);
+            continue;
+        }
+
+        let is_email = word.split_once('@').is_some_and(|(user, domain)| {
+            !user.is_empty() && user != "git" && domain.contains('.') && !domain.contains('@')
+        });
+
+        match is_email {
+            true => redacted.push_str("<email>"),
+            false => redacted.push_str(&word),
+        }
+        redacted.push(c);
+        word.clear();
+    }
+
+    redacted.pop();
+    redacted
+}
+
+/// Takes out what shouldn't end up in a public issue: the home folder, which names the
+/// user, credentials in urls, email addresses and the values of secret looking keys
+fn redact(text: &str) -> String {
+    let home = directories::BaseDirs::new()
+        .map(|dirs| dirs.home_dir().to_string_lossy().into_owned())
+        .filter(|home| home.len() > 1);
+
+    text.lines()
+        .map(|line| {
+            let key = line.split('=').next().unwrap_or_default().to_lowercase();
+            if line.contains('=') && SECRET_KEYS.iter().any(|secret| key.contains(secret)) {
+                return format!("{}= <redacted>", line.split('=').next().unwrap());
+            }
+
+            let line = match &home {
+                Some(home) => line.replace(home.as_str(), "~"),
+                None => line.to_owned(),
+            };
+            redact_emails(&redact_credentials(&line))
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+fn section(report: &mut String, title: &str, lang: &str, contents: &str) {
+    writeln!(
+        report,
+        "\n## {}\n\n```{}\n{}\n```",
+        title,
+        lang,
+        contents.trim_end()
+    )
+    .unwrap();
+}
+
+/// `cmm bugreport`. Writes what's usually asked for in an issue to `output`: tool versions,
+/// the platform, the config and the end of the last build's output, with personal details
+/// taken out.
+pub fn write_report(output: &Path) -> Result<(), ProjectError> {
+    status!("{}", t!("Collecting bug report"));
+
+    let mut report = String::from("# cmakemake bug report\n");
+
+    let versions = [
+        ("cmm", format!("cmakemake {}", env!("CARGO_PKG_VERSION"))),
+        ("cmake", version_line("cmake")),
+        ("compiler", version_line(&compiler())),
+        ("git", version_line("git")),
+        ("ninja", version_line("ninja")),
+    ]
+    .iter()
+    .map(|(name, version)| format!("{: <10}{}", name, version))
+    .collect::<Vec<_>>()
+    .join("\n");
+    section(&mut report, "Versions", "", &redact(&versions));
+
+    let mut platform = format!(
+        "{: <10}{} ({})\n{: <10}{}",
+        "os",
+        std::env::consts::OS,
+        std::env::consts::FAMILY,
+        "arch",
+        std::env::consts::ARCH
+    );
+    for (var, value) in cmake_cache::environment() {
+        write!(platform, "\n{}={}", var, value).unwrap();
+    }
+    section(&mut report, "Platform", "", &redact(&platform));
+
+    let config = match std::fs::read_to_string(CONFIG_NAME) {
+        Ok(config) => redact(&config),
+        Err(_) => t!("No {} in the current folder", CONFIG_NAME),
+    };
+    section(&mut report, CONFIG_NAME, "toml", &config);
+
+    let log = match std::fs::read_to_string(LAST_BUILD_LOG) {
+        Ok(log) => {
+            let lines = log.lines().collect::<Vec<_>>();
+            redact(&lines[lines.len().saturating_sub(LOG_TAIL)..].join("\n"))
+        }
+        Err(_) => t!("No build recorded yet").into(),
+    };
+    section(&mut report, "Last build", "", &log);
+
+    std::fs::write(output, report)
+        .map_err(|err| ProjectError::FailedToCreateFile(output.to_owned(), err.to_string()))?;
+
+    println!(
+        "{}",
+        t!(
+            "Wrote '{}'. Check it over before attaching it to an issue.",
+            output.display()
+        )
+    );
+
+    Ok(())
+}
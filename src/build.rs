@@ -0,0 +1,579 @@
+use std::{
+    io::{BufRead, BufReader, Write},
+    path::Path,
+};
+
+use colored::Colorize;
+
+use crate::{
+    cmake_cache, conan,
+    config::{self, ConfigFile},
+    error::{DisplayError, ProjectError},
+    generate::{
+        cmake_lists, config_hash, confirm_replace, generate_cmake, has_hash_comment, is_generated,
+        keep_blocks, CACHE_LAUNCHER_OPTION,
+    },
+    generator, git, history,
+    lock::ProjectLock,
+    lockfile,
+    messages::t,
+    output::{self, notice, status, Verbosity},
+    patches, requires, triage,
+    util::get_config,
+    warnings,
+};
+
+/// How to configure and compile a project
+pub struct BuildOptions {
+    /// Fail instead of warning when dependency checkouts have been modified
+    pub frozen: bool,
+    pub profile: String,
+    /// Only build this target (and what it depends on)
+    pub target: Option<String>,
+    /// Workspace member to use when run from a workspace root
+    pub package: Option<String>,
+    /// Overrides `cmake.generator`
+    pub generator: Option<String>,
+    /// Compile through ccache or sccache when the project finds one
+    pub cache_launcher: bool,
+    /// Regenerate over a CMakeLists.txt that was edited by hand without asking
+    pub force: bool,
+    /// Wait for another cmm process to release the project instead of failing
+    pub wait: bool,
+}
+
+impl Default for BuildOptions {
+    fn default() -> Self {
+        Self {
+            frozen: false,
+            profile: config::DEFAULT_PROFILE.into(),
+            target: None,
+            package: None,
+            generator: None,
+            cache_launcher: true,
+            force: false,
+            wait: false,
+        }
+    }
+}
+
+impl BuildOptions {
+    /// Each profile configures into its own folder so switching doesn't force a rebuild
+    pub fn build_dir(&self) -> String {
+        format!("build/{}", self.profile)
+    }
+}
+
+/// Regenerates CMakeLists.txt if needed, then configures and compiles the project in the
+/// current directory
+pub fn build_project(options: &BuildOptions) -> Result<(), ProjectError> {
+    let started = std::time::SystemTime::now();
+    let instant = std::time::Instant::now();
+
+    let result = build(options, instant);
+
+    if output::is_json() {
+        output::report_item("builds", build_summary(options, &result, instant, started));
+    }
+
+    result
+}
+
+/// What `--output json` says about a build. Diagnostics come from the build log, when this
+/// build got as far as writing it.
+fn build_summary(
+    options: &BuildOptions,
+    result: &Result<(), ProjectError>,
+    instant: std::time::Instant,
+    started: std::time::SystemTime,
+) -> serde_json::Value {
+    let log = std::fs::metadata(LAST_BUILD_LOG)
+        .and_then(|metadata| metadata.modified())
+        .is_ok_and(|modified| modified >= started)
+        .then(|| std::fs::read_to_string(LAST_BUILD_LOG).ok())
+        .flatten()
+        .unwrap_or_default();
+
+    serde_json::json!({
+        "project": get_config().ok().map(|config| config.project.name),
+        "profile": options.profile,
+        "build_dir": options.build_dir(),
+        "target": options.target,
+        "success": result.is_ok(),
+        "duration_secs": instant.elapsed().as_secs_f64(),
+        "diagnostics": warnings::diagnostics(&log),
+    })
+}
+
+fn build(options: &BuildOptions, instant: std::time::Instant) -> Result<(), ProjectError> {
+    status!("{}", t!("Building Project"));
+
+    let _lock = ProjectLock::acquire(options.wait)?;
+    let build_type = configure_project(options)?;
+
+    // Output is only captured when there are warnings to compare or sort, so compilers
+    // otherwise keep the terminal and their colours. JSON reports the diagnostics in it.
+    let baseline = warnings::read_baseline()?;
+    let capture = baseline.is_some() || output::is_json();
+    let output = compile(options, &build_type, false, capture)?;
+
+    if let (Some(baseline), Some(output)) = (baseline, &output) {
+        warnings::check_new_warnings(&baseline, output, get_config()?.warnings.on_new)?;
+    }
+
+    // Losing a history entry isn't worth failing the build over
+    history::record_build(options, instant.elapsed().as_secs_f64(), output.as_deref())
+        .display_error();
+
+    status!(
+        "{}",
+        t!(
+            "{} building c++ project in {}s",
+            t!("Finished").green().bold(),
+            format!("{:.3}", instant.elapsed().as_secs_f32())
+        )
+    );
+
+    Ok(())
+}
+
+/// The build type of the profile being built
+pub fn build_type(options: &BuildOptions) -> Result<String, ProjectError> {
+    get_config()?
+        .profile(&options.profile)
+        .map(|profile| profile.build_type)
+        .ok_or_else(|| ProjectError::UnknownProfile(options.profile.clone()))
+}
+
+/// Builds the project, then installs it with the rules from `[install]`, into `prefix` if
+/// given
+pub fn install_project(options: &BuildOptions, prefix: Option<&Path>) -> Result<(), ProjectError> {
+    if get_config()?.install.is_none() {
+        return Err(ProjectError::MissingInstallRules);
+    }
+
+    build_project(options)?;
+
+    let _lock = ProjectLock::acquire(options.wait)?;
+    let build_type = build_type(options)?;
+
+    status!("\n{}", t!("Installing project").green());
+
+    let mut install_args = vec![
+        "--install".into(),
+        options.build_dir(),
+        "--config".into(),
+        build_type,
+    ];
+    if let Some(prefix) = prefix {
+        install_args.extend(["--prefix".into(), prefix.to_string_lossy().into_owned()]);
+    }
+
+    let output = output::run(output::command("cmake", install_args))
+        .map_err(|_| ProjectError::MissingProgram("cmake".into()))?;
+
+    if !output.status.success() {
+        Err(ProjectError::FailedToRunProcess(
+            "cmake --install".into(),
+            output.status.code(),
+        ))?;
+    }
+
+    status!(
+        "{}",
+        t!("{} installing project", t!("Finished").green().bold())
+    );
+
+    Ok(())
+}
+
+/// Builds the project, then runs CPack to package what `[install]` installs. `generators`
+/// overrides `packaging.generators`.
+pub fn package_project(options: &BuildOptions, generators: &[String]) -> Result<(), ProjectError> {
+    let config = get_config()?;
+    if config.packaging.is_none() {
+        return Err(ProjectError::MissingPackaging);
+    }
+    if config.install.is_none() {
+        return Err(ProjectError::MissingInstallRules);
+    }
+
+    build_project(options)?;
+
+    let _lock = ProjectLock::acquire(options.wait)?;
+    let build_type = build_type(options)?;
+
+    status!("\n{}", t!("Packaging project").green());
+
+    let mut cpack_args = vec!["-C".to_owned(), build_type, "-B".into(), PACKAGE_DIR.into()];
+    if !generators.is_empty() {
+        cpack_args.extend(["-G".into(), generators.join(";")]);
+    }
+
+    // CPack reads the CPackConfig.cmake configuring wrote to the build folder
+    let output = output::run(output::command("cpack", cpack_args).dir(options.build_dir()))
+        .map_err(|_| ProjectError::MissingProgram("cpack".into()))?;
+
+    if !output.status.success() {
+        Err(ProjectError::FailedToRunProcess(
+            "cpack".into(),
+            output.status.code(),
+        ))?;
+    }
+
+    status!(
+        "{}",
+        t!(
+            "{} packaging project into {}/{}",
+            t!("Finished").green().bold(),
+            options.build_dir(),
+            PACKAGE_DIR
+        )
+    );
+
+    Ok(())
+}
+
+/// Compiles the configured project, from scratch with `clean_first`. With `capture` the
+/// output is returned as well as printed. It's captured anyway to report or hide
+/// dependency diagnostics when `[warnings]` asks to.
+pub(crate) fn compile(
+    options: &BuildOptions,
+    build_type: &str,
+    clean_first: bool,
+    capture: bool,
+) -> Result<Option<String>, ProjectError> {
+    status!("\n{}", t!("Compiling c++ project").green());
+
+    // CMake's target graph takes care of building anything the target depends on
+    // --config picks the configuration for multi config generators and is ignored otherwise
+    let build_dir = options.build_dir();
+    let mut build_args = vec!["--build", &build_dir, "--config", build_type];
+    if let Some(target) = &options.target {
+        build_args.extend(["--target", target]);
+    }
+    if clean_first {
+        build_args.push("--clean-first");
+    }
+    // Shows each compiler command
+    if output::verbosity() == Verbosity::Trace {
+        build_args.push("--verbose");
+    }
+
+    let expression = output::command("cmake", build_args);
+
+    let config = get_config()?;
+    let settings = &config.warnings;
+    let sorting = settings.report || settings.hide_dependencies;
+
+    let (status, output) = match capture || sorting {
+        false => (
+            Some(
+                output::run(expression)
+                    .map_err(|_| ProjectError::MissingProgram("cmake".into()))?
+                    .status,
+            ),
+            None,
+        ),
+        true => {
+            let origins = warnings::Origins::new(&config)?;
+            let mut filter = warnings::DependencyFilter::new(&origins, settings.hide_dependencies);
+
+            let (status, output) = run_captured(expression, |line| filter.print(line))?;
+            filter.finish();
+            save_build_log(&output, true);
+
+            if sorting {
+                warnings::report_origins(&output, &origins, settings);
+            }
+            (status, Some(output))
+        }
+    };
+
+    match status {
+        Some(status) if status.success() => Ok(output),
+        status => Err(ProjectError::FailedToRunProcess(
+            "cmake".into(),
+            status.and_then(|status| status.code()),
+        )),
+    }
+}
+
+/// The output of the last configure, followed by the compiler's when it was captured.
+/// Kept for `cmm bugreport`.
+pub const LAST_BUILD_LOG: &str = ".cache/last-build.log";
+
+fn save_build_log(output: &str, append: bool) {
+    let path = Path::new(LAST_BUILD_LOG);
+
+    // Only ever read for bug reports, so failing to write it isn't worth reporting
+    std::fs::create_dir_all(path.parent().unwrap()).ok();
+    std::fs::OpenOptions::new()
+        .create(true)
+        .write(true)
+        .append(append)
+        .truncate(!append)
+        .open(path)
+        .and_then(|mut file| file.write_all(output.as_bytes()))
+        .ok();
+}
+
+/// Runs cmake, passing its output to `print` as it comes while keeping a copy. With
+/// `--quiet` it's only printed, all at once, if cmake fails.
+fn run_captured(
+    expression: duct::Expression,
+    mut print: impl FnMut(&str),
+) -> Result<(Option<std::process::ExitStatus>, String), ProjectError> {
+    let quiet = output::is_quiet();
+    let reader = expression
+        .stderr_to_stdout()
+        .unchecked()
+        .reader()
+        .map_err(|err| ProjectError::FailedToStartProcess("cmake".into(), err.to_string()))?;
+
+    let mut output = String::new();
+    for line in BufReader::new(&reader).split(b'\n') {
+        let line = String::from_utf8_lossy(&line.unwrap_or_default()).into_owned();
+        if !quiet {
+            print(line.trim_end_matches('\r'));
+        }
+        output.push_str(&line);
+        output.push('\n');
+    }
+
+    let status = reader.try_wait().ok().flatten().map(|output| output.status);
+    if quiet && !status.is_some_and(|status| status.success()) {
+        output::show_held_back(output.as_bytes());
+    }
+
+    Ok((status, output))
+}
+
+/// Does everything a build needs the network for without compiling anything: initialises
+/// submodules, installs Conan packages and configures, which downloads fetch and CPM
+/// dependencies. Lets CI cache dependencies separately from build output.
+pub fn fetch_project(options: &BuildOptions) -> Result<(), ProjectError> {
+    status!("{}", t!("Fetching dependencies"));
+
+    let _lock = ProjectLock::acquire(options.wait)?;
+    let instant = std::time::Instant::now();
+
+    git::init_submodules()?;
+    configure_project(options)?;
+
+    status!(
+        "{}",
+        t!(
+            "{} fetching dependencies and configuring in {}s",
+            t!("Finished").green().bold(),
+            format!("{:.3}", instant.elapsed().as_secs_f32())
+        )
+    );
+
+    Ok(())
+}
+
+/// Checks the project, regenerates CMakeLists.txt if needed and configures the build
+/// folder for the profile. Returns the profile's build type.
+pub(crate) fn configure_project(options: &BuildOptions) -> Result<String, ProjectError> {
+    let mut config = get_config()?;
+
+    if config.profile(&options.profile).is_none() {
+        return Err(ProjectError::UnknownProfile(options.profile.clone()));
+    }
+
+    check_in_source(&config)?;
+    requires::check_requirements(&config.requires)?;
+
+    // Before patching, so patches are applied to the locked checkouts
+    lockfile::sync(&mut config, options.frozen)?;
+
+    patches::apply_all_patches(&config)?;
+    git::check_dependency_checkouts(&config, options.frozen)?;
+
+    if !Path::new("CMakeLists.txt").exists() {
+        notice!(
+            "{}",
+            t!("{} CMakeLists.txt doesn't exist", t!("warning:").yellow())
+        );
+        generate_cmake(true)?;
+        status!();
+    } else {
+        let path = Path::new("CMakeLists.txt");
+        let existing = std::fs::read_to_string(path)
+            .map_err(|err| ProjectError::CannotOpenFile(path.to_owned(), err.to_string()))?;
+
+        // Files written with --no-hash-comment stay that way and are compared in full
+        let hash_comment = has_hash_comment(&existing);
+        let up_to_date = match hash_comment {
+            true => existing.starts_with(&format!("# {}\n", config_hash(&config))),
+            false => existing == keep_blocks(&existing, cmake_lists(&config, false)),
+        };
+
+        if !up_to_date && !is_generated(&existing) {
+            let replacement = keep_blocks(&existing, cmake_lists(&config, true));
+            confirm_replace(&existing, &replacement, options.force)?;
+            generate_cmake(true)?;
+            status!();
+        } else if !up_to_date {
+            notice!(
+                "{}",
+                t!(
+                    "{} CMakeLists.txt out of date. Regenerating.",
+                    t!("warning:").yellow()
+                )
+            );
+            generate_cmake(hash_comment)?;
+            status!();
+        }
+    }
+
+    status!(
+        "{} ({})",
+        t!("Generating CMake build system").green(),
+        options.profile
+    );
+
+    let build_dir = options.build_dir();
+    let generator = generator::resolve(options.generator.as_deref(), &config)?;
+    let build_type = config.profile(&options.profile).unwrap().build_type;
+
+    // Said out loud so settings picked up from a developer's shell aren't a surprise
+    let overridden = options.generator.is_some() || config.cmake.generator.is_some();
+    for (var, value) in cmake_cache::environment() {
+        match var == "CMAKE_GENERATOR" && overridden {
+            true => notice!(
+                "{}",
+                t!(
+                    "Ignoring {}={} from the environment, --generator or cmake.generator is set",
+                    var,
+                    value
+                )
+            ),
+            false => notice!("{}", t!("Using {}={} from the environment", var, value)),
+        }
+    }
+
+    conan::install(&config, &build_dir, &build_type)?;
+
+    let mut configure_args = vec![
+        "-B".to_owned(),
+        build_dir.clone(),
+        format!("-DCMM_PROFILE={}", options.profile),
+        format!("-DCMAKE_BUILD_TYPE={}", build_type),
+    ];
+
+    // Passed either way so turning the cache back on updates the cmake cache
+    if !config.cmake.cache_programs().is_empty() {
+        let enabled = match options.cache_launcher {
+            true => "ON",
+            false => "OFF",
+        };
+        configure_args.push(format!("-D{}={}", CACHE_LAUNCHER_OPTION, enabled));
+    }
+
+    if let Some(generator) = &generator {
+        configure_args.extend(["-G".to_owned(), generator.clone()]);
+    }
+
+    let settings = cmake_cache::ConfigureSettings::new(generator);
+
+    // cmake only reads the variable itself from 3.21
+    if let Some(toolchain) = &settings.toolchain_file {
+        configure_args.push(format!("-DCMAKE_TOOLCHAIN_FILE={}", toolchain));
+    }
+
+    // Reusing a cache made with a different generator, compiler or toolchain fails with
+    // errors that don't explain themselves, so start from scratch instead
+    let stale = cmake_cache::stale_reasons(Path::new(&build_dir), &settings);
+
+    // A fresh cache takes every value from CMakeLists.txt anyway
+    let variables = cmake_cache::dependency_variables(&config);
+    let updates = match stale.is_empty() {
+        true => cmake_cache::variable_updates(Path::new(&build_dir), &variables),
+        false => Vec::new(),
+    };
+
+    if !updates.is_empty() {
+        status!(
+            "{}",
+            t!(
+                "Updating changed dependency variables in the cmake cache: {}",
+                updates.join(" ")
+            )
+        );
+        configure_args.extend(updates);
+    }
+
+    if !stale.is_empty() {
+        stale.iter().for_each(|reason| {
+            notice!(
+                "{}",
+                t!(
+                    "{} cmake cache is stale: {}",
+                    t!("warning:").yellow(),
+                    reason
+                )
+            )
+        });
+
+        match cmake_cache::supports_fresh() {
+            true => {
+                notice!("{}", t!("Reconfiguring with --fresh"));
+                configure_args.push("--fresh".into());
+            }
+            false => {
+                notice!("{}", t!("Reconfiguring without the old cache"));
+                cmake_cache::remove_cache(Path::new(&build_dir))?;
+            }
+        }
+    }
+
+    if output::verbosity() == Verbosity::Trace {
+        configure_args.push("--log-level=VERBOSE".into());
+    }
+
+    // Kept so a failure can be explained afterwards
+    let (status, output) = run_captured(output::command("cmake", configure_args), |line| {
+        println!("{}", line)
+    })?;
+    save_build_log(&output, false);
+
+    if !status.is_some_and(|status| status.success()) {
+        // Troubleshooting is a bonus, the configure error is what gets reported
+        triage::offer(&build_dir, &options.profile, &output).display_error();
+
+        Err(ProjectError::FailedToRunProcess(
+            format!("cmake -B {}", build_dir),
+            status.and_then(|status| status.code()),
+        ))?;
+    }
+
+    cmake_cache::save_variables(Path::new(&build_dir), &variables);
+
+    Ok(build_type)
+}
+
+/// Where `cmm package` puts packages, inside the profile's build folder
+pub const PACKAGE_DIR: &str = "packages";
+
+/// Files cmake leaves behind when configured directly in the project folder
+pub const IN_SOURCE_FILES: [&str; 8] = [
+    "CMakeCache.txt",
+    "CMakeFiles",
+    "cmake_install.cmake",
+    "CTestTestfile.cmake",
+    "Makefile",
+    "build.ninja",
+    ".ninja_deps",
+    ".ninja_log",
+];
+
+/// Refuses to continue if cmake has been configured inside the project folder, as the
+/// stray cache takes priority over our build directories and breaks them in confusing ways
+pub fn check_in_source(config: &ConfigFile) -> Result<(), ProjectError> {
+    match !config.cmake.allow_in_source && Path::new("CMakeCache.txt").exists() {
+        true => Err(ProjectError::InSourceBuild),
+        false => Ok(()),
+    }
+}
@@ -0,0 +1,1157 @@
+use std::{ffi::OsString, path::PathBuf};
+
+use clap::{
+    builder::styling::Style, ArgAction, Args, CommandFactory, Parser, Subcommand, ValueEnum,
+};
+use clap_complete::{
+    env::{Bash, EnvCompleter, Fish, Powershell, Zsh},
+    ArgValueCandidates, CompleteEnv, CompletionCandidate,
+};
+use cmakemake_core::{
+    build::BuildOptions,
+    config,
+    dependencies::AddArgs,
+    generate,
+    messages::t,
+    util::{get_cache, get_config},
+    workspace,
+};
+use colored::Colorize;
+
+/// Set by completion scripts when they call back into cmm for candidates
+const COMPLETE_VAR: &str = "CMM_COMPLETE";
+
+/// Names completions are registered for
+const BIN_NAMES: [&str; 2] = ["cmm", "cmakemake"];
+
+/// Lists a command's examples at the end of its help, styled like clap's own headings
+fn examples(examples: &[(&str, &str)]) -> String {
+    let heading = Style::new().bold().underline();
+
+    let examples = examples
+        .iter()
+        .map(|(example, description)| format!("  {}\n          {}", example, description))
+        .collect::<Vec<_>>()
+        .join("\n");
+
+    format!("{heading}Examples:{heading:#}\n{examples}")
+}
+
+fn candidates(names: impl IntoIterator<Item = String>) -> Vec<CompletionCandidate> {
+    names.into_iter().map(CompletionCandidate::new).collect()
+}
+
+/// Dependencies of the project in the current directory
+fn dependency_candidates() -> Vec<CompletionCandidate> {
+    get_config()
+        .map(|config| candidates(config.dependency_names()))
+        .unwrap_or_default()
+}
+
+fn profile_candidates() -> Vec<CompletionCandidate> {
+    let profiles = get_config()
+        .map(|config| config.profiles())
+        .unwrap_or_else(|_| config::Profile::defaults());
+
+    candidates(profiles.into_keys())
+}
+
+fn executable_candidates() -> Vec<CompletionCandidate> {
+    get_config()
+        .map(|config| candidates(generate::runnable_names(&config)))
+        .unwrap_or_default()
+}
+
+/// The project and its targets, which dependencies can be linked into
+fn link_target_candidates() -> Vec<CompletionCandidate> {
+    get_config()
+        .map(|config| {
+            candidates(
+                std::iter::once(config.project.name)
+                    .chain(config.targets.into_iter().map(|target| target.name)),
+            )
+        })
+        .unwrap_or_default()
+}
+
+fn member_candidates() -> Vec<CompletionCandidate> {
+    workspace::get_workspace_config()
+        .map(|config| candidates(config.workspace.members))
+        .unwrap_or_default()
+}
+
+/// Repos of dependencies saved to the cache, for `cmm add git <TAB>`
+fn cached_candidates() -> Vec<CompletionCandidate> {
+    get_cache()
+        .map(|cache| {
+            cache
+                .git_submodules
+                .into_iter()
+                .map(|cached| {
+                    CompletionCandidate::new(cached.submodule.repo).help(Some(cached.name.into()))
+                })
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// Answers a completion script asking for candidates and exits. Does nothing on normal runs.
+pub fn complete() {
+    CompleteEnv::with_factory(Cli::command)
+        .var(COMPLETE_VAR)
+        .complete();
+}
+
+/// The command line with an alias from the project's `[alias]` table replaced by what it
+/// stands for, like cargo's aliases. Aliases can't replace cmm's own commands or refer to
+/// other aliases.
+pub fn args() -> Vec<OsString> {
+    let mut args = std::env::args_os().collect::<Vec<_>>();
+
+    // The command is the first argument that isn't a global option or the file --answers takes
+    let mut position = 1;
+    while let Some(arg) = args.get(position).and_then(|arg| arg.to_str()) {
+        match arg {
+            "--answers" => position += 2,
+            _ if arg.starts_with('-') => position += 1,
+            _ => break,
+        }
+    }
+
+    let Some(name) = args.get(position).and_then(|arg| arg.to_str()) else {
+        return args;
+    };
+    let Some(expansion) = get_config()
+        .ok()
+        .and_then(|mut config| config.alias.remove(name))
+    else {
+        return args;
+    };
+
+    if Cli::command().find_subcommand(name).is_some() {
+        eprintln!(
+            "{}",
+            t!(
+                "{} alias '{}' is ignored, there's already a command with that name",
+                t!("warning:").yellow(),
+                name
+            )
+        );
+        return args;
+    }
+
+    args.splice(
+        position..=position,
+        expansion.split_whitespace().map(OsString::from),
+    );
+    args
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+pub enum Shell {
+    Bash,
+    Zsh,
+    Fish,
+    Powershell,
+}
+
+/// `cmm completions <SHELL>`. The script calls back into this executable for candidates, so
+/// dependency names and cached repos are always up to date.
+pub fn print_completions(shell: Shell) {
+    let completer: &dyn EnvCompleter = match shell {
+        Shell::Bash => &Bash,
+        Shell::Zsh => &Zsh,
+        Shell::Fish => &Fish,
+        Shell::Powershell => &Powershell,
+    };
+
+    let exe = std::env::current_exe()
+        .map(|exe| exe.to_string_lossy().into_owned())
+        .unwrap_or_else(|_| "cmakemake".to_owned());
+
+    let mut stdout = std::io::stdout();
+    for bin in BIN_NAMES {
+        completer
+            .write_registration(COMPLETE_VAR, bin, bin, &exe, &mut stdout)
+            .ok();
+    }
+}
+
+/// A c++ project setup tool
+#[derive(Parser)]
+#[command(
+    name = "cmakemake",
+    version,
+    after_help = "'cmm' is the recommended alias"
+)]
+pub struct Cli {
+    /// Answer prompts from a toml file instead of asking, eg. 'add.name = "glfw"'.
+    /// CMM_ANSWERS=<FILE> and CMM_ANSWER_ADD_NAME=glfw also work.
+    #[arg(long, global = true, value_name = "FILE")]
+    pub answers: Option<PathBuf>,
+
+    /// Wait for another cmm process using the project to finish instead of failing
+    #[arg(long, global = true)]
+    pub wait: bool,
+
+    /// Only print warnings, errors and what the command was asked for, not progress or
+    /// what cmake, git and conan print unless they fail.
+    /// Exit codes say how a command failed: 1 other errors, 2 bad arguments, 3 project or
+    /// config problems, 4 build failures, 5 failing tests, 6 project locked, 130 cancelled.
+    #[arg(short, long, global = true)]
+    pub quiet: bool,
+
+    /// Print more. -v shows each command cmm runs, -vv also has cmake and the build tool
+    /// show everything they do, like each compiler command.
+    #[arg(short, long, global = true, action = ArgAction::Count, conflicts_with = "quiet")]
+    pub verbose: u8,
+
+    #[command(subcommand)]
+    pub command: Option<Command>,
+}
+
+/// Options shared by every command that builds the project
+#[derive(Args)]
+pub struct BuildArgs {
+    /// Use the release profile
+    #[arg(long, conflicts_with = "profile")]
+    release: bool,
+
+    /// Build with AddressSanitizer into build/asan (the asan profile)
+    #[arg(long, conflicts_with_all = ["release", "profile", "ubsan", "tsan", "coverage"])]
+    asan: bool,
+
+    /// Build with UndefinedBehaviorSanitizer into build/ubsan (the ubsan profile)
+    #[arg(long, conflicts_with_all = ["release", "profile", "tsan", "coverage"])]
+    ubsan: bool,
+
+    /// Build with ThreadSanitizer into build/tsan (the tsan profile)
+    #[arg(long, conflicts_with_all = ["release", "profile", "coverage"])]
+    tsan: bool,
+
+    /// Build with coverage instrumentation into build/coverage (the coverage profile)
+    #[arg(long, conflicts_with_all = ["release", "profile"])]
+    coverage: bool,
+
+    /// Build profile from the config to use (default: debug)
+    #[arg(long, value_name = "NAME", add = ArgValueCandidates::new(profile_candidates))]
+    profile: Option<String>,
+
+    /// Fail if dependency checkouts in external/ have been modified or CMakeMake.lock needs
+    /// updating
+    #[arg(long)]
+    frozen: bool,
+
+    /// From a workspace root, only use this member (default: all)
+    #[arg(short, long, value_name = "MEMBER", add = ArgValueCandidates::new(member_candidates))]
+    package: Option<String>,
+
+    /// CMake generator to configure with (overrides cmake.generator)
+    #[arg(short = 'G', long, value_name = "NAME")]
+    generator: Option<String>,
+
+    /// Don't compile through ccache or sccache, even when installed
+    #[arg(long)]
+    no_cache_launcher: bool,
+
+    /// Replace a CMakeLists.txt edited by hand when regenerating it, without asking. The
+    /// old file is kept as CMakeLists.txt.bak.
+    #[arg(long)]
+    force: bool,
+}
+
+impl BuildArgs {
+    pub fn options(self, wait: bool) -> BuildOptions {
+        let shorthand = [
+            (self.release, "release"),
+            (self.asan, "asan"),
+            (self.ubsan, "ubsan"),
+            (self.tsan, "tsan"),
+            (self.coverage, "coverage"),
+        ]
+        .into_iter()
+        .find_map(|(set, profile)| set.then(|| profile.to_owned()));
+
+        let profile = shorthand
+            .or(self.profile)
+            .unwrap_or_else(|| config::DEFAULT_PROFILE.to_owned());
+
+        BuildOptions {
+            frozen: self.frozen,
+            profile,
+            target: None,
+            package: self.package,
+            generator: self.generator,
+            cache_launcher: !self.no_cache_launcher,
+            force: self.force,
+            wait,
+        }
+    }
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+pub enum Headers {
+    Include,
+    Src,
+}
+
+/// How `list`, `build` and `check` print their results
+#[derive(Clone, Copy, Default, PartialEq, Eq, ValueEnum)]
+pub enum OutputFormat {
+    #[default]
+    Human,
+    /// One JSON object on the last line of stdout, for editors and CI. Progress and tool
+    /// output are left out.
+    Json,
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+pub enum ProjectTemplate {
+    /// A window with an OpenGL context, using GLFW
+    GlfwOpengl,
+    /// A window with a renderer, using SDL2
+    Sdl2,
+    /// A command line tool with subcommands parsed by CLI11, and Catch2 tests
+    Cli,
+}
+
+fn parse_variable(var: &str) -> Result<(String, String), String> {
+    var.split_once('=')
+        .map(|(name, value)| (name.to_owned(), value.to_owned()))
+        .ok_or_else(|| format!("expected NAME=VALUE, got '{}'", var))
+}
+
+#[derive(Args)]
+pub struct AddCommand {
+    /// Kind of dependency to add without prompts
+    #[arg(value_parser = ["git", "fetch", "conan", "local", "find", "test-framework"])]
+    pub kind: Option<String>,
+
+    /// Repo url, path, package name, NAME/VERSION conan reference or gtest | catch2
+    #[arg(requires = "kind", add = ArgValueCandidates::new(cached_candidates))]
+    pub value: Option<String>,
+
+    /// Name of the dependency (default: the folder name)
+    #[arg(long)]
+    name: Option<String>,
+
+    /// Git tag to check out (for fetch, any tag, branch or commit)
+    #[arg(long)]
+    tag: Option<String>,
+
+    /// Git branch to check out
+    #[arg(long)]
+    branch: Option<String>,
+
+    /// Build the dependency from its sources instead of its CMakeLists.txt
+    #[arg(long, value_parser = ["all", "root", "header"])]
+    files: Option<String>,
+
+    /// Set a cmake variable before adding the dependency
+    #[arg(long = "var", value_name = "NAME=VALUE", value_parser = parse_variable)]
+    variables: Vec<(String, String)>,
+
+    /// Mark a find_package dependency as REQUIRED
+    #[arg(long)]
+    required: bool,
+
+    /// Name to link against when it differs from the package name
+    #[arg(long, value_name = "NAME")]
+    link_name: Option<String>,
+
+    /// Install prefix to search for a find_package dependency, eg. ~/opt/glfw
+    #[arg(long, value_name = "PATH")]
+    prefix_path: Option<String>,
+
+    /// Add a git dependency with CPMAddPackage instead of as a submodule
+    #[arg(long)]
+    cpm: bool,
+
+    /// find_package name of a conan dependency (default: its name)
+    #[arg(long, value_name = "NAME")]
+    package: Option<String>,
+
+    /// Don't link the dependency to the project
+    #[arg(long)]
+    no_link: bool,
+}
+
+impl AddCommand {
+    pub fn args(self) -> AddArgs {
+        AddArgs {
+            positional: self.value,
+            name: self.name,
+            tag: self.tag,
+            branch: self.branch,
+            files: self.files,
+            link_name: self.link_name,
+            package: self.package,
+            variables: self.variables,
+            required: self.required,
+            prefix_path: self.prefix_path,
+            link: !self.no_link,
+            cpm: self.cpm,
+        }
+    }
+}
+
+#[derive(Subcommand)]
+pub enum Command {
+    /// Create a new project
+    ///
+    /// Creates a new folder containing a git repo, a config file, a hello world main.cpp
+    /// and an include/ folder for headers. Inside a workspace the project is added as a
+    /// member. With --build the new project is built and run straight away to check the
+    /// toolchain works.
+    #[command(after_help = examples(&[
+        ("cmm new my_game", "Create a project in ./my_game"),
+        ("cmm new my_tool --headers src", "Create a project without an include/ folder"),
+        ("cmm new my_game --build", "Create a project and check it builds and runs"),
+        ("cmm new my_game --template sdl2", "Create a project that opens an SDL2 window"),
+        ("cmm new my_tool --template cli", "Create a command line tool with tests"),
+    ]))]
+    New {
+        /// Name of the project and the folder to create
+        name: String,
+
+        /// Put headers in include/, or next to the sources in src/
+        #[arg(long, value_enum, default_value = "include")]
+        headers: Headers,
+
+        /// Build and run the hello world program once created
+        #[arg(long)]
+        build: bool,
+
+        /// Start from a template instead of hello world, adding the dependencies it needs.
+        /// Windowing templates get a resources/ folder copied next to the executable on
+        /// each build.
+        #[arg(long, value_enum)]
+        template: Option<ProjectTemplate>,
+    },
+
+    /// Create a project from existing code
+    ///
+    /// Creates a config file in the current directory. Existing src/ and include/ folders,
+    /// or sources elsewhere, are detected and added to cmake.files. Git init is skipped
+    /// inside an existing repo.
+    #[command(after_help = examples(&[("cmm init", "Adopt the code in the current folder")]))]
+    Init {
+        /// Name of the project (default: the folder name)
+        name: Option<String>,
+    },
+
+    /// Add a dependency
+    ///
+    /// Adds a cached, git submodule, FetchContent, Conan, find_package, local or monorepo
+    /// dependency. Without arguments the dependency is chosen interactively. 'fetch'
+    /// dependencies are downloaded by CMake instead of being added to the repo. 'git'
+    /// dependencies can be added with CPM.cmake instead of as submodules using --cpm.
+    /// 'conan' dependencies are installed with Conan 2 when building, from a generated
+    /// conanfile.txt. 'test-framework' sets up GoogleTest or Catch2 with a tests/ folder.
+    #[command(after_help = examples(&[
+        ("cmm add", "Start the add dependency prompts"),
+        ("cmm add git https://github.com/glfw/glfw --tag 3.4", "Add glfw as a submodule"),
+        (
+            "cmm add fetch https://github.com/fmtlib/fmt --tag 11.0.2 --link-name fmt::fmt",
+            "Add fmt, downloaded with FetchContent",
+        ),
+        (
+            "cmm add git https://github.com/gabime/spdlog --tag v1.14.1 --cpm",
+            "Add spdlog with CPM.cmake",
+        ),
+        ("cmm add conan zlib/1.3.1 --package ZLIB", "Add zlib from Conan, found with find_package(ZLIB)"),
+        ("cmm add find OpenGL --required", "Add a find_package dependency"),
+        ("cmm add test-framework gtest", "Set up GoogleTest unit tests"),
+    ]))]
+    Add(AddCommand),
+
+    /// Remove a dependency
+    ///
+    /// Removes a dependency from the config and unlinks it. Git submodules can also be
+    /// deinitialised and deleted.
+    #[command(after_help = examples(&[
+        ("cmm remove glfw", "Remove glfw, asking before deleting files"),
+        ("cmm remove glfw --purge", "Remove glfw and its submodule"),
+    ]))]
+    Remove {
+        /// Name of the dependency to remove
+        #[arg(add = ArgValueCandidates::new(dependency_candidates))]
+        name: String,
+
+        /// Remove the submodule and its files without asking
+        #[arg(long, conflicts_with = "keep_files")]
+        purge: bool,
+
+        /// Only remove the dependency from the config
+        #[arg(long)]
+        keep_files: bool,
+    },
+
+    /// Link a dependency into the project
+    ///
+    /// Adds a dependency the config already declares to what the project links, without
+    /// adding it again. Once the config has targets, asks which of the project and its
+    /// targets link it, unless --target is given.
+    #[command(after_help = examples(&[
+        ("cmm link glfw", "Link glfw into the project"),
+        ("cmm link fmt --target tool", "Link fmt into the tool target"),
+    ]))]
+    Link {
+        /// Name of the dependency, its link name or a target
+        #[arg(add = ArgValueCandidates::new(dependency_candidates))]
+        name: String,
+
+        /// Project or target to link it into. Can be given more than once.
+        #[arg(long, short, add = ArgValueCandidates::new(link_target_candidates))]
+        target: Vec<String>,
+    },
+
+    /// Stop linking a dependency
+    ///
+    /// Removes a dependency from what the project links while keeping it in the config.
+    /// When several targets link it, asks which to unlink it from, unless --target is
+    /// given.
+    #[command(after_help = examples(&[
+        ("cmm unlink glfw", "Stop linking glfw"),
+        ("cmm unlink fmt --target tool", "Stop only the tool target linking fmt"),
+    ]))]
+    Unlink {
+        /// Name of the dependency, its link name or a target
+        #[arg(add = ArgValueCandidates::new(dependency_candidates))]
+        name: String,
+
+        /// Project or target to unlink it from. Can be given more than once.
+        #[arg(long, short, add = ArgValueCandidates::new(link_target_candidates))]
+        target: Vec<String>,
+    },
+
+    /// Generate cmake build script
+    ///
+    /// Generates CMakeLists.txt from the project config. The same config always generates
+    /// the same file. Lines between '# cmm:keep-start' and '# cmm:keep-end' are carried
+    /// over as they are, after the same generated line as before.
+    #[command(after_help = examples(&[
+        ("cmm cmake", "Regenerate CMakeLists.txt"),
+        ("cmm cmake --no-hash-comment", "Regenerate CMakeLists.txt for checking into version control"),
+        ("cmm cmake --dry-run", "Show what regenerating would change"),
+    ]))]
+    Cmake {
+        /// Leave out the config hash on the first line, which can differ between machines.
+        /// Later builds keep it out.
+        #[arg(long)]
+        no_hash_comment: bool,
+
+        /// Show a diff against the current CMakeLists.txt instead of writing it
+        #[arg(long)]
+        dry_run: bool,
+    },
+
+    /// Build project code
+    ///
+    /// Checks tool versions from [requires], regenerates CMakeLists.txt if out of date,
+    /// then configures and compiles the project. CMAKE_GENERATOR, CMAKE_TOOLCHAIN_FILE, CC,
+    /// CXX and CMAKE_PREFIX_PATH from the environment are used, and listed when set.
+    #[command(after_help = examples(&[
+        ("cmm build", "Build the project into build/debug"),
+        ("cmm build --frozen", "Build, refusing modified dependencies"),
+        ("cmm build -p engine", "Build the 'engine' workspace member"),
+        ("cmm build -G ninja", "Configure and build with Ninja"),
+        ("cmm build --asan", "Build with AddressSanitizer into build/asan"),
+    ]))]
+    Build {
+        /// Only build this target and what it depends on
+        target: Option<String>,
+
+        #[command(flatten)]
+        build: BuildArgs,
+
+        /// Print the result as JSON: the profile, whether it succeeded, how long it took and
+        /// the compiler's warnings and errors
+        #[arg(long, value_enum, default_value_t)]
+        output: OutputFormat,
+    },
+
+    /// Download dependencies and configure without compiling
+    ///
+    /// Does everything 'cmm build' needs the network for: clones submodules, installs Conan
+    /// packages and configures the build folder, which downloads fetched and CPM
+    /// dependencies. Nothing is compiled, so CI can cache this step separately.
+    #[command(after_help = examples(&[
+        ("cmm fetch", "Download dependencies and configure build/debug"),
+        (
+            "cmm fetch --frozen && cmm build --frozen",
+            "Split a CI job into a cacheable fetch step and a compile step",
+        ),
+    ]))]
+    Fetch {
+        #[command(flatten)]
+        build: BuildArgs,
+    },
+
+    /// Build and run project code
+    ///
+    /// Builds the project then runs the resulting executable. Examples, benchmarks and tests
+    /// can be run too, as example_<file>, bench_<file> and test_<file>. Library projects
+    /// have no executable of their own, so they ask which of these to run. Commands in
+    /// hooks.pre_run run first, and the run stops if one fails.
+    #[command(after_help = examples(&[
+        ("cmm run", "Build and run the project"),
+        ("cmm run --bin example_basic", "Build and run examples/basic.cpp"),
+        ("cmm run --bin tests", "Build and run the 'tests' target"),
+        ("cmm run -- --port 8080", "Build and run the project with arguments"),
+        ("cmm run skip_build --backtrace", "Run without building and trace crashes"),
+        ("cmm run --profile asan --sanitize", "Run a sanitized build with its suppressions"),
+    ]))]
+    Run {
+        /// Run the last built executable without rebuilding
+        #[arg(value_name = "skip_build", value_parser = ["skip_build"])]
+        skip_build: Option<String>,
+
+        /// Executable target, example, benchmark or test to run (default: the project, or ask)
+        #[arg(long, value_name = "NAME", add = ArgValueCandidates::new(executable_candidates))]
+        bin: Option<String>,
+
+        /// Rerun under gdb and print a stack trace if the program crashes
+        #[arg(long)]
+        backtrace: bool,
+
+        /// Run the program inside a pseudo-terminal (for TUI programs)
+        #[arg(long)]
+        pty: bool,
+
+        /// Export ASAN_OPTIONS, UBSAN_OPTIONS and LSAN_OPTIONS from [sanitizers], for
+        /// programs built with sanitizers
+        #[arg(long)]
+        sanitize: bool,
+
+        /// Print the environment the program would run with, marking variables from .env
+        /// and .env.local, instead of running it
+        #[arg(long)]
+        print_env: bool,
+
+        #[command(flatten)]
+        build: BuildArgs,
+
+        /// Arguments passed to the program
+        #[arg(last = true, value_name = "ARGS")]
+        program_args: Vec<String>,
+    },
+
+    /// Build and install the project
+    ///
+    /// Builds the project, then runs 'cmake --install' with the rules generated from the
+    /// [install] section: binaries go to bin/ and lib/, header folders to include/ and
+    /// resource folders to share/<project>/. The prefix comes from --prefix, then
+    /// install.prefix, then CMake's default.
+    #[command(after_help = examples(&[
+        ("cmm install --release --prefix ~/.local", "Install a release build into ~/.local"),
+        ("cmm install --release --prefix dist", "Stage a release build in ./dist for packaging"),
+    ]))]
+    Install {
+        /// Folder to install into
+        #[arg(long, value_name = "PATH")]
+        prefix: Option<PathBuf>,
+
+        #[command(flatten)]
+        build: BuildArgs,
+    },
+
+    /// Build installers with CPack
+    ///
+    /// Builds the project, then runs cpack to package what the [install] rules install.
+    /// Generators, maintainer and description come from [packaging], the version and
+    /// license from [project]. Packages are written to build/<profile>/packages.
+    #[command(after_help = examples(&[
+        ("cmm package --release", "Build the packages listed in packaging.generators"),
+        ("cmm package --release --format DEB --format RPM", "Build .deb and .rpm packages"),
+    ]))]
+    Package {
+        /// CPack generator to use instead of packaging.generators, eg. DEB, RPM, ZIP or NSIS
+        #[arg(long, value_name = "GENERATOR")]
+        format: Vec<String>,
+
+        #[command(flatten)]
+        build: BuildArgs,
+    },
+
+    /// Build and run the unit tests
+    ///
+    /// Builds the project and the tests set up with 'cmm add test-framework', then runs
+    /// them with ctest. Failed tests are rerun up to tests.retries times, and ones that
+    /// pass on a retry are reported as flaky. --report also writes a JUnit XML report for
+    /// CI test dashboards, which needs ctest 3.21 or newer.
+    #[command(after_help = examples(&[
+        ("cmm test", "Build and run the tests"),
+        ("cmm test --report test-results.xml", "Run the tests and write a JUnit report"),
+        ("cmm test --list", "List every test case"),
+        ("cmm test 'Parser.*'", "Run the gtest cases in the Parser suite"),
+        ("cmm test --pick", "Choose the test cases to run"),
+    ]))]
+    Test {
+        /// Only run these test cases, by name or a gtest/Catch2 pattern, straight from the
+        /// test executable instead of through ctest
+        #[arg(conflicts_with_all = ["report", "list", "pick"])]
+        cases: Vec<String>,
+
+        /// List the test cases in the test executable
+        #[arg(long, conflicts_with_all = ["report", "pick"])]
+        list: bool,
+
+        /// Choose the test cases to run from a list
+        #[arg(long, conflicts_with = "report")]
+        pick: bool,
+
+        /// Write a JUnit XML report of the results to FILE
+        #[arg(long, value_name = "FILE")]
+        report: Option<PathBuf>,
+
+        #[command(flatten)]
+        build: BuildArgs,
+    },
+
+    /// Report which lines the tests run
+    ///
+    /// Builds with coverage instrumentation (the coverage profile, or --profile with
+    /// coverage = true), runs the tests and prints a per-file summary with gcovr. Clang
+    /// builds read their counters with 'llvm-cov gcov'. Needs gcovr ('pip install gcovr').
+    #[command(after_help = examples(&[
+        ("cmm coverage", "Print line coverage for each file"),
+        ("cmm coverage --html", "Also write build/coverage/coverage-html/index.html"),
+    ]))]
+    Coverage {
+        /// Also write an HTML report with annotated sources
+        #[arg(long)]
+        html: bool,
+
+        #[command(flatten)]
+        build: BuildArgs,
+    },
+
+    /// Pin every git dependency to its current commit
+    ///
+    /// Rewrites CMakeMake.lock with the commit each submodule has checked out and the
+    /// commit each fetch and cpm dependency's tag points to now. Builds keep dependencies on
+    /// the locked commits, restoring submodule checkouts and downloading fetched
+    /// dependencies by commit. Run this after changing a dependency by hand.
+    #[command(after_help = examples(&[("cmm lock", "Lock dependencies at their current commits")]))]
+    Lock,
+
+    /// Move git dependencies to a newer tag
+    ///
+    /// Lists the tags of each submodule, fetch and cpm dependency's repo and asks which
+    /// newer one to move to. Submodules are checked out at the chosen tag, fetched
+    /// dependencies have their tag changed, and both the config and CMakeMake.lock are
+    /// updated. Submodules with local changes are skipped.
+    #[command(after_help = examples(&[
+        ("cmm update", "Choose new tags for every git dependency"),
+        ("cmm update fmt --tag 11.1.0", "Move 'fmt' to tag 11.1.0"),
+    ]))]
+    Update {
+        /// Only update this dependency (default: all)
+        #[arg(add = ArgValueCandidates::new(dependency_candidates))]
+        name: Option<String>,
+
+        /// Move NAME to this tag without asking
+        #[arg(long, requires = "name")]
+        tag: Option<String>,
+    },
+
+    /// Generate files about the project's dependencies
+    ///
+    /// Works on the dependencies checked out in external/, with their tags from the config
+    /// and the commits they are checked out at.
+    #[command(after_help = examples(&[
+        ("cmm deps licenses-header", "Write THIRD_PARTY_NOTICES.md"),
+    ]))]
+    Deps {
+        #[command(subcommand)]
+        action: DepsAction,
+    },
+
+    /// Save or restore downloaded dependencies for CI caching
+    ///
+    /// Packs the dependencies CMake downloaded into each profile's build folder
+    /// (FetchContent and CPM sources) into a .tar.gz, or unpacks one. Paths inside are
+    /// fixed up when the project is restored somewhere else, so CI runners with a
+    /// different checkout folder can use the cache. Conan packages live in Conan's own
+    /// cache and aren't included. Restoring a missing archive does nothing, so a cache miss
+    /// isn't an error.
+    #[command(after_help = examples(&[
+        ("cmm cache restore .ci/deps.tar.gz", "Prime the build folder from a CI cache"),
+        (
+            "cmm fetch && cmm cache save .ci/deps.tar.gz",
+            "Download dependencies and pack them for the CI cache",
+        ),
+    ]))]
+    Cache {
+        #[command(subcommand)]
+        action: CacheAction,
+    },
+
+    /// Accept the compiler warnings the project has now
+    ///
+    /// Rebuilds the project from scratch and records how many warnings each file has in
+    /// CMakeMake.warnings.toml. Later builds fail when a file gets more warnings than
+    /// recorded, or only warn with warnings.on_new = "warn", so legacy code can be cleaned
+    /// up a file at a time without turning on -Werror. Commit the file and rerun this after
+    /// fixing warnings to lower the baseline.
+    #[command(after_help = examples(&[
+        ("cmm warnings baseline", "Record the current warnings of the debug profile"),
+    ]))]
+    Warnings {
+        #[command(subcommand)]
+        action: WarningsAction,
+    },
+
+    /// Show recent builds and how they changed over time
+    ///
+    /// Every successful 'cmm build' records how long it took, its profile, its warning count
+    /// and the size of the project executable in the cmakemake data folder. This lists the
+    /// most recent ones and the change in build time and size from the first to the last
+    /// for each profile, to catch creeping regressions. Warnings are only counted for
+    /// projects with a warnings baseline, which capture the build output.
+    #[command(after_help = examples(&[
+        ("cmm history", "Show the last 20 builds"),
+        ("cmm history --profile release -n 50", "Show the last 50 release builds"),
+    ]))]
+    History {
+        /// Only show builds of this profile
+        #[arg(long, value_name = "NAME", add = ArgValueCandidates::new(profile_candidates))]
+        profile: Option<String>,
+
+        /// How many builds to show
+        #[arg(short = 'n', long, default_value_t = 20)]
+        limit: usize,
+    },
+
+    /// Check the tools building needs are installed
+    ///
+    /// Looks for cmake, a C++ compiler, git and ninja or make, and inside a project checks
+    /// their versions against cmake.minimum_required and [requires]. Prints a hint for
+    /// each problem and fails when there are any.
+    #[command(after_help = examples(&[("cmm doctor", "Check a new machine is ready to build")]))]
+    Doctor,
+
+    /// Write a report to attach to bug reports
+    ///
+    /// Collects the versions of cmm, cmake, the compiler, git and ninja, the platform,
+    /// the config and the end of the last build's output into one file. The home folder,
+    /// emails, credentials in urls and secret looking config values are taken out, but
+    /// check the file before sharing it.
+    #[command(after_help = examples(&[
+        ("cmm bugreport", "Write cmm-bugreport.md"),
+        ("cmm bugreport -o report.md", "Write the report somewhere else"),
+    ]))]
+    Bugreport {
+        /// File to write the report to
+        #[arg(short, long, value_name = "FILE", default_value = "cmm-bugreport.md")]
+        output: PathBuf,
+    },
+
+    /// List CMake generators and whether they are installed
+    ///
+    /// Shows the generators cmakemake can detect, with the short names accepted by
+    /// --generator and cmake.generator.
+    Generators,
+
+    /// Rebuild the project whenever its files change
+    ///
+    /// Builds the project, then watches the source folder, include dirs, extra targets,
+    /// tests and the config, rebuilding after each change.
+    #[command(after_help = examples(&[
+        ("cmm watch", "Rebuild on every change"),
+        ("cmm watch --clear", "Rebuild on every change with a clean screen"),
+    ]))]
+    Watch {
+        /// Only build this target and what it depends on
+        target: Option<String>,
+
+        /// Clear the screen before each build
+        #[arg(long)]
+        clear: bool,
+
+        /// Wait until files stop changing for this long before building
+        #[arg(long, value_name = "MS", default_value_t = crate::watch::DEFAULT_DEBOUNCE_MS)]
+        debounce: u64,
+
+        #[command(flatten)]
+        build: BuildArgs,
+    },
+
+    /// Remove c++ build files (and optionally cmake files)
+    ///
+    /// Removes the build/ folder, and with 'all' also removes CMakeLists.txt. Each profile
+    /// builds into its own folder inside build/, and --profile removes just that one.
+    /// --target only removes one target's object files, so it's compiled from scratch on
+    /// the next build. A build/ folder that is a symlink or resolves outside the project is
+    /// left alone unless --force is given. --keep-cache-objects is for timing clean
+    /// rebuilds through ccache: it prints the cache's statistics and keeps the
+    /// cmake.cache_dir folder when it's inside the build folder.
+    #[command(after_help = examples(&[
+        ("cmm clean", "Remove build/"),
+        ("cmm clean --profile release", "Remove only build/release"),
+        ("cmm clean --target demo", "Recompile the demo target in every profile"),
+        ("cmm clean all", "Remove build/ and CMakeLists.txt"),
+        ("cmm clean --in-source", "Remove a stray CMakeCache.txt and friends"),
+        (
+            "cmm clean --keep-cache-objects --zero-stats",
+            "Start a clean rebuild with fresh compiler cache statistics",
+        ),
+    ]))]
+    Clean {
+        /// Also remove the generated CMakeLists.txt
+        #[arg(value_name = "all", value_parser = ["all"])]
+        all: Option<String>,
+
+        /// Only remove cmake files left in the project folder by an in-source build
+        #[arg(long, conflicts_with_all = ["all", "force", "profile", "target"])]
+        in_source: bool,
+
+        /// Only remove the build folder of this profile
+        #[arg(long, value_name = "NAME", add = ArgValueCandidates::new(profile_candidates))]
+        profile: Option<String>,
+
+        /// Only remove the object files of this target
+        #[arg(long, value_name = "NAME", conflicts_with = "all")]
+        target: Option<String>,
+
+        /// Clear build/ even when it is a symlink or outside the project
+        #[arg(long)]
+        force: bool,
+
+        /// Print the compiler cache's statistics and keep the project's cache folder
+        #[arg(long, conflicts_with_all = ["in_source", "target"])]
+        keep_cache_objects: bool,
+
+        /// Also reset the statistics of the project's own compiler cache
+        #[arg(long, requires = "keep_cache_objects")]
+        zero_stats: bool,
+    },
+
+    /// Create a .ignore file for external/ and res/
+    ///
+    /// Creates a .ignore file so search tools skip dependency and resource folders.
+    Ignore,
+
+    /// List configured dependencies
+    ///
+    /// Lists every find, local, submodule and monorepo dependency with its path, variables
+    /// and whether it is linked to the project.
+    #[command(after_help = examples(&[
+        ("cmm list", "List the project's dependencies"),
+        ("cmm list --output json", "List them for a script or editor"),
+    ]))]
+    List {
+        #[arg(long, value_enum, default_value_t)]
+        output: OutputFormat,
+    },
+
+    /// Show details about a dependency
+    ///
+    /// Prints the configuration of a dependency and any patches applied to it.
+    #[command(after_help = examples(&[("cmm info glfw", "Show the glfw dependency")]))]
+    Info {
+        /// Name of the dependency
+        #[arg(add = ArgValueCandidates::new(dependency_candidates))]
+        name: String,
+    },
+
+    /// Repair project state
+    ///
+    /// Compares .gitmodules, external/ and the config and interactively fixes mismatches,
+    /// or upgrades a config written by an older cmm.
+    Migrate {
+        #[command(subcommand)]
+        target: MigrateTarget,
+    },
+
+    /// Clean up after an interrupted 'cmm add git'
+    ///
+    /// Removes submodules that were registered but never checked out, empty folders in
+    /// external/ and cached repos under .git/modules that stop a submodule being added again,
+    /// and clones dependencies whose submodule was never checked out. Submodules with files
+    /// in them are left for 'cmm migrate gitmodules'.
+    #[command(after_help = examples(&[("cmm repair", "Fix a submodule add cut off by a network drop")]))]
+    Repair,
+
+    /// Set default preferences for new projects
+    ///
+    /// Reruns the first run setup (author, c++ standard, generator, license and whether
+    /// clean and remove use the system trash).
+    Setup,
+
+    /// Create starter files for optional features
+    #[command(after_help = examples(&[(
+        "cmm scaffold sanitizer-suppressions",
+        "Create sanitizers/asan.supp, ubsan.supp and lsan.supp",
+    )]))]
+    Scaffold {
+        #[command(subcommand)]
+        target: ScaffoldTarget,
+    },
+
+    /// Check CMakeMake.toml for mistakes without generating anything
+    ///
+    /// Reports every path the config names that doesn't exist, local dependency and target
+    /// names that are invalid or used twice, and linked dependencies the config doesn't
+    /// declare. Fails when any are found.
+    #[command(after_help = examples(&[("cmm check", "Check the config before committing it")]))]
+    Check {
+        /// Print the problems found as JSON
+        #[arg(long, value_enum, default_value_t)]
+        output: OutputFormat,
+    },
+
+    /// Check the config builds every source file once
+    ///
+    /// Flags sources no source entry or glob matches, sources matched by several entries
+    /// and so compiled twice, and excluded sources that still have a header declaring
+    /// them. These usually only show up as linker errors. Fails when any are found.
+    #[command(after_help = examples(&[("cmm validate", "Check the project's source entries")]))]
+    Validate,
+
+    /// Move a source or header file
+    ///
+    /// Moves the file, updates config entries naming it and rewrites includes of it in the
+    /// project's own code, then regenerates CMakeLists.txt. Includes are updated as text,
+    /// so check the result builds.
+    #[command(after_help = examples(&[
+        ("cmm mv src/old.cpp src/render/old.cpp", "Move a source into a subfolder"),
+        ("cmm mv include/app/util.hpp include/app/core/util.hpp", "Move a header and fix its includes"),
+    ]))]
+    Mv {
+        /// File to move
+        from: String,
+
+        /// Where to move it
+        to: String,
+    },
+
+    /// Generate code from templates
+    #[command(after_help = examples(&[
+        ("cmm generate class HttpServer", "Create http_server.hpp and http_server.cpp"),
+        ("cmm generate class render/Mesh", "Create render/mesh.hpp and render/mesh.cpp"),
+    ]))]
+    Generate {
+        #[command(subcommand)]
+        target: GenerateTarget,
+    },
+
+    /// Print a shell completion script
+    ///
+    /// Prints a script completing commands, flags, dependency names, profiles, targets and
+    /// cached dependency repos for 'cmm' and 'cmakemake'. The script asks cmm for
+    /// candidates, so source it from your shell's startup file rather than saving it, and
+    /// it stays in step with upgrades.
+    #[command(after_help = examples(&[
+        ("echo 'source <(cmm completions bash)' >> ~/.bashrc", "Enable completions in bash"),
+        ("echo 'source <(cmm completions zsh)' >> ~/.zshrc", "Enable completions in zsh"),
+        (
+            "cmm completions fish > ~/.config/fish/completions/cmm.fish",
+            "Enable completions in fish",
+        ),
+    ]))]
+    Completions {
+        #[arg(value_enum)]
+        shell: Shell,
+    },
+
+    /// Create or extend a workspace
+    ///
+    /// Creates a workspace folder holding several member projects, or registers an
+    /// existing project as a member. The workspace CMakeLists.txt adds every member as a
+    /// subdirectory.
+    #[command(after_help = examples(&[
+        ("cmm workspace new games", "Create an empty workspace"),
+        ("cmm workspace add tools/editor", "Add an existing project"),
+    ]))]
+    Workspace {
+        #[command(subcommand)]
+        action: WorkspaceAction,
+    },
+}
+
+impl Command {
+    /// `--output` for the commands that have it
+    pub fn output_format(&self) -> OutputFormat {
+        match self {
+            Command::Build { output, .. }
+            | Command::List { output }
+            | Command::Check { output } => *output,
+            _ => OutputFormat::Human,
+        }
+    }
+}
+
+#[derive(Subcommand)]
+pub enum CacheAction {
+    /// Write downloaded dependencies to ARCHIVE
+    Save {
+        archive: PathBuf,
+
+        /// Save the whole build folder, including compiled objects
+        #[arg(long)]
+        build: bool,
+    },
+
+    /// Unpack ARCHIVE into the build folder
+    Restore { archive: PathBuf },
+}
+
+#[derive(Subcommand)]
+pub enum DepsAction {
+    /// Write THIRD_PARTY_NOTICES.md with the name, version and license text of every
+    /// dependency in external/. Once written, 'cmm update' keeps it up to date.
+    #[command(visible_alias = "notices")]
+    LicensesHeader,
+}
+
+#[derive(Subcommand)]
+pub enum WarningsAction {
+    /// Rebuild and record the warning count of every file
+    Baseline {
+        #[command(flatten)]
+        build: BuildArgs,
+    },
+}
+
+#[derive(Subcommand)]
+pub enum ScaffoldTarget {
+    /// Commented suppression files for each sanitizer, registered in [sanitizers] so
+    /// 'cmm run --sanitize' passes them to the runtime
+    SanitizerSuppressions,
+}
+
+#[derive(Subcommand)]
+pub enum GenerateTarget {
+    /// A header and source file for a class. Headers go in include/<project>/ when the
+    /// project has an include folder. Uses the namespace, include guard style and license
+    /// from the config, and adds the source to cmake.files when no glob covers it.
+    Class {
+        /// Name of the class, optionally in a folder, eg. 'render/Mesh'
+        name: String,
+
+        /// Namespace to put the class in (default: codegen.namespace or the project name)
+        #[arg(long)]
+        namespace: Option<String>,
+    },
+}
+
+#[derive(Subcommand)]
+pub enum MigrateTarget {
+    /// Reconcile git submodules with configured dependencies: re-clone, remove or import
+    /// out of sync submodules
+    Gitmodules,
+    /// Save the config in the layout this version of cmm writes, keeping the old one as
+    /// CMakeMake.toml.bak
+    Config,
+}
+
+#[derive(Subcommand)]
+pub enum WorkspaceAction {
+    /// Create a workspace in ./<NAME>
+    New { name: String },
+
+    /// Register an existing project folder as a member
+    Add { path: String },
+}
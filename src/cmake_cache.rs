@@ -0,0 +1,243 @@
+use std::{
+    collections::{BTreeMap, HashMap},
+    path::Path,
+};
+
+use crate::{
+    config::ConfigFile,
+    error::ProjectError,
+    requires::{self, Version},
+};
+
+/// The first CMake that understands `--fresh`
+const FRESH_SINCE: &str = "3.24";
+
+/// Values read from a build folder's CMakeCache.txt
+pub struct CMakeCache(HashMap<String, String>);
+
+impl CMakeCache {
+    pub fn read(build_dir: &Path) -> Option<Self> {
+        let contents = std::fs::read_to_string(build_dir.join("CMakeCache.txt")).ok()?;
+
+        // Entries look like `NAME:TYPE=VALUE`
+        let entries = contents
+            .lines()
+            .filter(|line| !line.starts_with('#') && !line.starts_with("//"))
+            .filter_map(|line| {
+                let (key, value) = line.split_once('=')?;
+                let name = key.split(':').next()?;
+                Some((name.to_owned(), value.to_owned()))
+            })
+            .collect();
+
+        Some(Self(entries))
+    }
+
+    pub fn get(&self, name: &str) -> Option<&str> {
+        self.0.get(name).map(String::as_str)
+    }
+}
+
+/// Settings the next configure will use. `None` means cmake picks it.
+#[derive(Default)]
+pub struct ConfigureSettings {
+    pub generator: Option<String>,
+    pub c_compiler: Option<String>,
+    pub cxx_compiler: Option<String>,
+    pub toolchain_file: Option<String>,
+}
+
+/// Environment variables cmake reads when configuring
+pub const ENVIRONMENT_VARS: [&str; 5] = [
+    "CMAKE_GENERATOR",
+    "CMAKE_TOOLCHAIN_FILE",
+    "CC",
+    "CXX",
+    "CMAKE_PREFIX_PATH",
+];
+
+fn env_var(name: &str) -> Option<String> {
+    std::env::var(name).ok().filter(|val| !val.is_empty())
+}
+
+/// The variables from `ENVIRONMENT_VARS` that are set, with their values
+pub fn environment() -> Vec<(&'static str, String)> {
+    ENVIRONMENT_VARS
+        .into_iter()
+        .filter_map(|var| Some((var, env_var(var)?)))
+        .collect()
+}
+
+impl ConfigureSettings {
+    /// The compilers and toolchain come from the environment like they do for cmake
+    pub fn new(generator: Option<String>) -> Self {
+        Self {
+            generator,
+            c_compiler: env_var("CC"),
+            cxx_compiler: env_var("CXX"),
+            toolchain_file: env_var("CMAKE_TOOLCHAIN_FILE"),
+        }
+    }
+}
+
+fn same_path(a: &str, b: &str) -> bool {
+    match (Path::new(a).canonicalize(), Path::new(b).canonicalize()) {
+        (Ok(a), Ok(b)) => a == b,
+        _ => a == b,
+    }
+}
+
+/// Compilers are often given by name (`clang++`) while the cache stores the full path
+fn same_program(wanted: &str, cached: &str) -> bool {
+    match Path::new(wanted).is_absolute() {
+        true => same_path(wanted, cached),
+        false => Path::new(cached)
+            .file_name()
+            .is_some_and(|name| name.to_string_lossy() == wanted),
+    }
+}
+
+/// Whether the installed cmake can start over from an existing cache with `--fresh`
+pub fn supports_fresh() -> bool {
+    let since = Version::parse(FRESH_SINCE).unwrap();
+    requires::tool_version("cmake").is_some_and(|version| version >= since)
+}
+
+/// Deletes the cache and what cmake worked out with it, for versions without `--fresh`
+pub fn remove_cache(build_dir: &Path) -> Result<(), ProjectError> {
+    let cache = build_dir.join("CMakeCache.txt");
+    std::fs::remove_file(&cache)
+        .map_err(|err| ProjectError::FailedToRemove(cache, err.to_string()))?;
+
+    let files = build_dir.join("CMakeFiles");
+    if files.exists() {
+        std::fs::remove_dir_all(&files)
+            .map_err(|err| ProjectError::FailedToRemove(files, err.to_string()))?;
+    }
+
+    Ok(())
+}
+
+/// Reasons the cache in `build_dir` can't be reused with `settings`. Cmake refuses to
+/// change these on an existing cache, so any mismatch needs a fresh configure.
+pub fn stale_reasons(build_dir: &Path, settings: &ConfigureSettings) -> Vec<String> {
+    let cache = match CMakeCache::read(build_dir) {
+        Some(cache) => cache,
+        None => return Vec::new(),
+    };
+
+    let mut reasons = Vec::new();
+
+    let project_dir = std::env::current_dir().unwrap_or_default();
+    if let Some(home) = cache.get("CMAKE_HOME_DIRECTORY") {
+        if !same_path(home, &project_dir.to_string_lossy()) {
+            reasons.push(format!("project moved from '{}'", home));
+        }
+    }
+
+    if let (Some(wanted), Some(cached)) = (&settings.generator, cache.get("CMAKE_GENERATOR")) {
+        if wanted != cached {
+            reasons.push(format!(
+                "generator changed from '{}' to '{}'",
+                cached, wanted
+            ));
+        }
+    }
+
+    let compilers = [
+        (&settings.c_compiler, "CMAKE_C_COMPILER"),
+        (&settings.cxx_compiler, "CMAKE_CXX_COMPILER"),
+    ];
+
+    for (wanted, variable) in compilers {
+        if let (Some(wanted), Some(cached)) = (wanted, cache.get(variable)) {
+            if !same_program(wanted, cached) {
+                reasons.push(format!(
+                    "compiler changed from '{}' to '{}'",
+                    cached, wanted
+                ));
+            }
+        }
+    }
+
+    let cached_toolchain = cache.get("CMAKE_TOOLCHAIN_FILE").unwrap_or_default();
+    let wanted_toolchain = settings.toolchain_file.as_deref().unwrap_or_default();
+    if !same_path(wanted_toolchain, cached_toolchain) {
+        reasons.push(format!(
+            "toolchain file changed from '{}' to '{}'",
+            cached_toolchain, wanted_toolchain
+        ));
+    }
+
+    reasons
+}
+
+/// The dependency variables a build folder was last configured with, so changes to them
+/// can be applied to its cache
+pub const VARIABLES_FILE: &str = "cmm-variables.toml";
+
+/// Variables the config sets for its dependencies. When several set the same one the last
+/// wins, like it does in the generated file.
+pub fn dependency_variables(config: &ConfigFile) -> BTreeMap<String, String> {
+    let dependencies = &config.dependencies;
+
+    dependencies
+        .fetch
+        .iter()
+        .chain(&dependencies.cpm)
+        .flat_map(|fetch| &fetch.variables)
+        .chain(dependencies.local.iter().flat_map(|local| &local.variables))
+        .map(|(name, value)| (name.clone(), value.trim().to_owned()))
+        .collect()
+}
+
+/// The `-D` argument setting a variable the way the config's `set()` value does:
+/// `OFF CACHE BOOL ""` is `-DNAME:BOOL=OFF`, plain values are passed without quotes
+fn define_arg(name: &str, value: &str) -> String {
+    let (value, cache_type) = match value.split_once(" CACHE ") {
+        Some((value, rest)) => (value, rest.split_whitespace().next()),
+        None => (value, None),
+    };
+    let value = value.trim().trim_matches('"');
+
+    match cache_type {
+        Some(cache_type) => format!("-D{}:{}={}", name, cache_type, value),
+        None => format!("-D{}={}", name, value),
+    }
+}
+
+/// Configure arguments updating the cache in `build_dir` for dependency variables changed
+/// since it was last configured. Cache entries from `set(... CACHE ...)` keep their old
+/// value otherwise, and removed variables would linger. Empty for build folders that
+/// haven't been configured by cmm yet.
+pub fn variable_updates(build_dir: &Path, variables: &BTreeMap<String, String>) -> Vec<String> {
+    let previous = std::fs::read_to_string(build_dir.join(VARIABLES_FILE))
+        .ok()
+        .and_then(|contents| toml::from_str::<BTreeMap<String, String>>(&contents).ok());
+
+    let Some(previous) = previous else {
+        return Vec::new();
+    };
+
+    let changed = variables
+        .iter()
+        .filter(|(name, value)| previous.get(*name) != Some(value))
+        .map(|(name, value)| define_arg(name, value));
+
+    let removed = previous
+        .keys()
+        .filter(|name| !variables.contains_key(*name))
+        .map(|name| format!("-U{}", name));
+
+    changed.chain(removed).collect()
+}
+
+/// Records the variables a successful configure used
+pub fn save_variables(build_dir: &Path, variables: &BTreeMap<String, String>) {
+    std::fs::create_dir_all(build_dir).ok();
+    std::fs::write(
+        build_dir.join(VARIABLES_FILE),
+        toml::to_string(variables).unwrap(),
+    )
+    .ok();
+}
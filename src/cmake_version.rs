@@ -0,0 +1,133 @@
+use colored::Colorize;
+
+use crate::{
+    config::{ConfigFile, TargetKind},
+    output::notice,
+    requires::Version,
+};
+
+/// Something the generated CMakeLists.txt uses that older versions of CMake don't understand
+pub struct Feature {
+    pub description: &'static str,
+    pub since: &'static str,
+}
+
+/// Features the generated file will use for this config. CMakePresets.json, `FILE_SET`
+/// and `SYSTEM` on add_subdirectory or FetchContent_Declare aren't listed because nothing
+/// cmm generates uses them; they need entries here once it does.
+pub fn used_features(config: &ConfigFile) -> Vec<Feature> {
+    let mut features = Vec::new();
+
+    if config
+        .profiles()
+        .values()
+        .any(|profile| !profile.definitions.is_empty())
+    {
+        features.push(Feature {
+            description: "add_compile_definitions for profile definitions",
+            since: "3.12",
+        });
+    }
+
+    // The first version that knows each CMAKE_CXX_STANDARD value
+    let standard_since = match config.cmake.cxx_standard {
+        Some(26) => Some("3.25"),
+        Some(23) => Some("3.20"),
+        Some(20) => Some("3.12"),
+        Some(17) => Some("3.8"),
+        _ => None,
+    };
+
+    if let Some(since) = standard_since {
+        features.push(Feature {
+            description: "CMAKE_CXX_STANDARD for cmake.cxx_standard",
+            since,
+        });
+    }
+
+    if !config.cmake.link_options().is_empty() {
+        features.push(Feature {
+            description: "add_link_options for cmake.link_flags",
+            since: "3.13",
+        });
+    }
+
+    if config
+        .targets
+        .iter()
+        .any(|target| target.kind == TargetKind::Object)
+    {
+        features.push(Feature {
+            description: "$<TARGET_OBJECTS> in target_sources for object targets",
+            since: "3.9",
+        });
+    }
+
+    if !config.dependencies.conan.is_empty() {
+        features.push(Feature {
+            description: "list(PREPEND) and the find_package files Conan generates",
+            since: "3.15",
+        });
+    }
+
+    if !config.dependencies.cpm.is_empty() {
+        features.push(Feature {
+            description: "CPM.cmake for cpm dependencies",
+            since: "3.14",
+        });
+    }
+
+    if !config.dependencies.fetch.is_empty() {
+        features.push(Feature {
+            description: "FetchContent_MakeAvailable for fetch dependencies",
+            since: "3.14",
+        });
+    }
+
+    if config.tests.is_some() {
+        features.push(Feature {
+            description: "FetchContent_MakeAvailable for the test framework",
+            since: "3.14",
+        });
+    }
+
+    features
+}
+
+/// The newest feature in use, when the configured minimum is too old for it
+fn raised_floor(config: &ConfigFile) -> Option<(Version, Feature)> {
+    let configured = Version::parse(&config.cmake.minimum_required)?;
+
+    used_features(config)
+        .into_iter()
+        .filter_map(|feature| Some((Version::parse(feature.since)?, feature)))
+        .max_by(|(a, _), (b, _)| a.cmp(b))
+        .filter(|(floor, _)| *floor > configured)
+}
+
+/// The version to write to cmake_minimum_required. Raised to the newest feature in use
+/// when the configured minimum is too old to configure the generated file.
+pub fn required_version(config: &ConfigFile) -> String {
+    match raised_floor(config) {
+        Some((floor, _)) => floor.to_string(),
+        None => config.cmake.minimum_required.clone(),
+    }
+}
+
+/// Warns when `required_version` had to raise the configured minimum
+pub fn warn_if_raised(config: &ConfigFile) {
+    let (floor, feature) = match raised_floor(config) {
+        Some(raised) => raised,
+        None => return,
+    };
+
+    notice!(
+        "{} {} needs CMake {}, raising cmake_minimum_required from {} to {}",
+        "warning:".yellow(),
+        feature.description,
+        feature.since,
+        config.cmake.minimum_required,
+        floor
+    );
+    notice!("Set cmake.minimum_required in the config to silence this");
+}
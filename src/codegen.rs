@@ -0,0 +1,224 @@
+use std::path::Path;
+
+use colored::Colorize;
+
+use crate::{
+    config::{ConfigFile, SourceType},
+    error::ProjectError,
+    generate::{join_path, matching_entries},
+    messages::t,
+    util::init_file,
+};
+
+fn is_identifier(name: &str) -> bool {
+    let mut chars = name.chars();
+
+    chars
+        .next()
+        .is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
+
+/// `HttpServer` and `XMLParser` become `http_server` and `xml_parser`
+fn file_name(class: &str) -> String {
+    let chars = class.chars().collect::<Vec<_>>();
+    let mut name = String::new();
+
+    for (i, &c) in chars.iter().enumerate() {
+        if c.is_ascii_uppercase() && i > 0 {
+            let prev = chars[i - 1];
+            let next_lower = chars.get(i + 1).is_some_and(|c| c.is_ascii_lowercase());
+
+            if prev.is_ascii_lowercase()
+                || prev.is_ascii_digit()
+                || (prev.is_ascii_uppercase() && next_lower)
+            {
+                name.push('_');
+            }
+        }
+
+        name.push(c.to_ascii_lowercase());
+    }
+
+    name
+}
+
+/// The project name as a namespace, eg. `my-app` becomes `my_app`
+fn default_namespace(project: &str) -> String {
+    let namespace = project
+        .chars()
+        .map(|c| match c.is_ascii_alphanumeric() {
+            true => c.to_ascii_lowercase(),
+            false => '_',
+        })
+        .collect::<String>();
+
+    match namespace.starts_with(|c: char| c.is_ascii_digit()) {
+        true => format!("_{namespace}"),
+        false => namespace,
+    }
+}
+
+/// SPDX and copyright lines for the project's license, if it has one
+fn license_header(config: &ConfigFile) -> String {
+    let Some(license) = &config.project.license else {
+        return String::new();
+    };
+
+    let mut header = format!("// SPDX-License-Identifier: {}\n", license);
+    if !config.project.authors.is_empty() {
+        header += &format!("// Copyright {}\n", config.project.authors.join(", "));
+    }
+
+    header + "\n"
+}
+
+/// The folder public headers go in when the project keeps them apart from its sources,
+/// the include dir that resolves to `include/`
+fn has_include_dir(config: &ConfigFile) -> bool {
+    config
+        .cmake
+        .files
+        .include_dirs
+        .iter()
+        .flat_map(|(_, dirs)| dirs)
+        .any(|dir| join_path(&config.cmake.source_dir, dir) == "include")
+}
+
+/// `cmm generate class <NAME>`. Creates a header and source file for a class from the
+/// [codegen] templates. Headers go in include/<project>/ when the project has an include
+/// folder, next to the source otherwise. A folder in the name, eg. `render/Mesh`, is
+/// used for both. The source is added to cmake.files when no glob covers it.
+pub fn generate_class(
+    config: &mut ConfigFile,
+    name: &str,
+    namespace: Option<&str>,
+) -> Result<(), ProjectError> {
+    let (folder, class) = match name.rsplit_once('/') {
+        Some((folder, class)) => (format!("{folder}/"), class),
+        None => (String::new(), name),
+    };
+
+    if !is_identifier(class) {
+        return Err(ProjectError::InvalidIdentifier(class.to_owned()));
+    }
+
+    let namespace = namespace
+        .map(str::to_owned)
+        .or_else(|| config.codegen.namespace.clone())
+        .unwrap_or_else(|| default_namespace(&config.project.name));
+
+    let stem = format!("{}{}", folder, file_name(class));
+    let source_dir = &config.cmake.source_dir;
+
+    let (header, include) = match has_include_dir(config) {
+        true => {
+            let include = format!("{}/{}.hpp", config.project.name, stem);
+            (format!("include/{include}"), include)
+        }
+        false => (
+            join_path(source_dir, &format!("{stem}.hpp")),
+            format!("{stem}.hpp"),
+        ),
+    };
+    let source = join_path(source_dir, &format!("{stem}.cpp"));
+
+    for file in [&header, &source] {
+        if Path::new(file).exists() {
+            return Err(ProjectError::FileAlreadyExists(file.into()));
+        }
+    }
+
+    let license = license_header(config);
+
+    let (guard_start, guard_end) = match config.codegen.include_guards {
+        true => {
+            let guard = include
+                .chars()
+                .map(|c| match c.is_ascii_alphanumeric() {
+                    true => c.to_ascii_uppercase(),
+                    false => '_',
+                })
+                .collect::<String>();
+
+            (
+                format!("#ifndef {guard}\n#define {guard}\n"),
+                format!("\n#endif // {guard}\n"),
+            )
+        }
+        false => ("#pragma once\n".to_owned(), String::new()),
+    };
+
+    let header_contents = format!(
+        "{license}{guard_start}
+namespace {namespace}
+{{
+
+class {class}
+{{
+public:
+    {class}();
+    ~{class}();
+}};
+
+}} // namespace {namespace}
+{guard_end}"
+    );
+
+    let source_contents = format!(
+        r#"{license}#include "{include}"
+
+namespace {namespace}
+{{
+
+{class}::{class}()
+{{
+}}
+
+{class}::~{class}()
+{{
+}}
+
+}} // namespace {namespace}
+"#
+    );
+
+    for (file, contents) in [(&header, header_contents), (&source, source_contents)] {
+        let path = Path::new(file);
+        if let Some(parent) = path.parent().filter(|p| !p.as_os_str().is_empty()) {
+            std::fs::create_dir_all(parent).map_err(|err| {
+                ProjectError::FailedToCreateFolder(parent.to_owned(), err.to_string())
+            })?;
+        }
+
+        init_file(path, contents.as_bytes())?;
+        println!("{}", t!("Created '{}'", file));
+    }
+
+    let files = &mut config.cmake.files;
+    if matching_entries(files, source_dir, &source).is_empty() {
+        let relative = format!("{stem}.cpp");
+
+        match files
+            .source_files
+            .iter_mut()
+            .find(|(source_type, _)| matches!(source_type, SourceType::File))
+        {
+            Some((_, names)) => names.push(relative),
+            None => files.source_files.push((SourceType::File, vec![relative])),
+        }
+
+        println!("{}", t!("Added '{}' to cmake.files", source));
+    }
+
+    println!(
+        "{}",
+        t!(
+            "{} generating class '{}'",
+            t!("Finished").green().bold(),
+            class
+        )
+    );
+
+    Ok(())
+}
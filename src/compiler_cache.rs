@@ -0,0 +1,101 @@
+use std::path::{Component, Path, PathBuf};
+
+use colored::Colorize;
+
+use crate::{
+    config::ConfigFile,
+    error::ProjectError,
+    messages::t,
+    output::{self, status},
+    requires::tool_version,
+};
+
+/// The first of the project's cache programs that is installed, which is the one cmake
+/// finds when configuring
+pub fn installed_program(config: &ConfigFile) -> Option<&str> {
+    config
+        .cmake
+        .cache_programs()
+        .into_iter()
+        .find(|program| tool_version(program).is_some())
+}
+
+/// Whether `program` is ccache, which is the only cache that reads its folder from the
+/// environment on every compile. sccache's server keeps one folder for everything.
+fn is_ccache(program: &str) -> bool {
+    Path::new(program)
+        .file_stem()
+        .is_some_and(|stem| stem == "ccache")
+}
+
+/// The project's own ccache folder from cmake.cache_dir, relative to the project folder.
+/// A leading `./` is dropped so it compares with paths inside the build folder.
+pub fn cache_dir(config: &ConfigFile) -> Option<PathBuf> {
+    let dir = config.cmake.cache_dir.as_deref()?;
+
+    Some(
+        Path::new(dir)
+            .components()
+            .filter(|component| *component != Component::CurDir)
+            .collect(),
+    )
+}
+
+fn run(config: &ConfigFile, program: &str, arg: &str) -> Result<(), ProjectError> {
+    let mut expression = output::command(program, [arg]);
+    if let Some(dir) = cache_dir(config) {
+        expression = expression.env("CCACHE_DIR", dir);
+    }
+
+    let output = output::run(expression).unwrap();
+
+    if !output.status.success() {
+        Err(ProjectError::FailedToRunProcess(
+            format!("{} {}", program, arg),
+            output.status.code(),
+        ))?;
+    }
+
+    Ok(())
+}
+
+/// Prints the cache's hit and miss counts, to compare against after a clean rebuild
+pub fn show_stats(config: &ConfigFile) -> Result<(), ProjectError> {
+    let Some(program) = installed_program(config) else {
+        println!(
+            "{}",
+            t!(
+                "{} no compiler cache is installed, only removing build files",
+                t!("warning:").yellow()
+            )
+        );
+        return Ok(());
+    };
+
+    println!("{}", t!("Statistics of {} before cleaning:", program));
+    run(config, program, "-s")
+}
+
+/// Resets the cache's statistics, keeping what it has cached. Only done for a cache the
+/// project has to itself, so other projects' numbers aren't lost.
+pub fn zero_stats(config: &ConfigFile) -> Result<(), ProjectError> {
+    let Some(program) = installed_program(config) else {
+        return Ok(());
+    };
+
+    if !is_ccache(program) || cache_dir(config).is_none() {
+        println!(
+            "{}",
+            t!(
+                "{} not zeroing the statistics of a shared compiler cache, set 'cmake.cache_dir' to give ccache a folder for this project",
+                t!("warning:").yellow()
+            )
+        );
+        return Ok(());
+    }
+
+    run(config, program, "-z")?;
+    status!("{}", t!("Zeroed the statistics of {}", program));
+
+    Ok(())
+}
@@ -0,0 +1,109 @@
+use std::path::Path;
+
+use colored::Colorize;
+
+use crate::{
+    config::ConfigFile,
+    error::ProjectError,
+    messages::t,
+    output::{self, notice},
+};
+
+pub const CONANFILE: &str = "conanfile.txt";
+
+/// Written next to the installed packages so unchanged dependencies aren't reinstalled
+/// on every build
+const STAMP: &str = ".cmm-conan";
+
+/// Folder inside the build directory Conan installs the find_package files to. The
+/// generated CMakeLists.txt adds it to CMAKE_PREFIX_PATH.
+pub const OUTPUT_DIR: &str = "conan";
+
+/// The contents of conanfile.txt for `config`
+pub fn conanfile(config: &ConfigFile) -> String {
+    let mut requires = config
+        .dependencies
+        .conan
+        .iter()
+        .map(|conan| conan.reference())
+        .collect::<Vec<_>>();
+    requires.sort();
+
+    format!(
+        "# Generated by cmakemake from {}\n\n[requires]\n{}\n\n[generators]\nCMakeDeps\n",
+        crate::CONFIG_NAME,
+        requires.join("\n")
+    )
+}
+
+/// Writes conanfile.txt when the project has Conan dependencies and it has changed
+pub fn write_conanfile(config: &ConfigFile) -> Result<(), ProjectError> {
+    if config.dependencies.conan.is_empty() {
+        return Ok(());
+    }
+
+    let path = Path::new(CONANFILE);
+    let contents = conanfile(config);
+
+    if std::fs::read_to_string(path).is_ok_and(|existing| existing == contents) {
+        return Ok(());
+    }
+
+    std::fs::write(path, contents)
+        .map_err(|err| ProjectError::FailedToCreateFile(path.to_owned(), err.to_string()))
+}
+
+fn run_conan(args: &[&str]) -> Result<(), ProjectError> {
+    let output = output::run(output::command("conan", args))
+        .map_err(|_| ProjectError::MissingProgram("conan".into()))?;
+
+    match output.status.success() {
+        true => Ok(()),
+        false => Err(ProjectError::FailedToRunProcess(
+            format!("conan {}", args[0]),
+            output.status.code(),
+        )),
+    }
+}
+
+/// Installs the project's Conan dependencies into `build_dir` for `build_type`. Does
+/// nothing without Conan dependencies or when they are already installed.
+pub fn install(config: &ConfigFile, build_dir: &str, build_type: &str) -> Result<(), ProjectError> {
+    if config.dependencies.conan.is_empty() {
+        return Ok(());
+    }
+
+    write_conanfile(config)?;
+
+    let output_dir = Path::new(build_dir).join(OUTPUT_DIR);
+    let stamp_path = output_dir.join(STAMP);
+    let stamp = format!("{}{}\n", conanfile(config), build_type);
+
+    if std::fs::read_to_string(&stamp_path).is_ok_and(|existing| existing == stamp) {
+        return Ok(());
+    }
+
+    notice!("{}", t!("Installing Conan dependencies").green());
+
+    // A fresh Conan install has no default profile and refuses to install anything
+    run_conan(&["profile", "detect", "--exist-ok"])?;
+
+    let output_arg = format!("--output-folder={}", output_dir.display());
+    let build_type_arg = format!("build_type={}", build_type);
+
+    run_conan(&[
+        "install",
+        ".",
+        &output_arg,
+        "--build=missing",
+        "-s",
+        &build_type_arg,
+    ])?;
+
+    std::fs::write(&stamp_path, stamp)
+        .map_err(|err| ProjectError::FailedToCreateFile(stamp_path.clone(), err.to_string()))?;
+
+    notice!();
+
+    Ok(())
+}
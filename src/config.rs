@@ -1,40 +1,170 @@
+use std::collections::BTreeMap;
+
+use crate::schema;
+
 #[derive(serde::Deserialize, serde::Serialize, Default, Hash)]
 #[serde(default)]
 pub struct ConfigFile {
+    /// Layout the config is written in, see `schema::CONFIG_VERSION`
+    pub config_version: u32,
     pub project: Project,
     pub cmake: CMake,
     pub dependencies: Dependencies,
+    pub profiles: BTreeMap<String, Profile>,
+    pub targets: Vec<Target>,
+    /// Tool version requirements, eg. `cmake = ">=3.20"`
+    pub requires: BTreeMap<String, String>,
+    /// Shorthands for commands, eg. `rr = "run --release --"`. Arguments after the alias are
+    /// added to the end.
+    pub alias: BTreeMap<String, String>,
+    pub tests: Option<Tests>,
+    pub install: Option<Install>,
+    pub packaging: Option<Packaging>,
+    pub warnings: Warnings,
+    pub sanitizers: Sanitizers,
+    pub codegen: Codegen,
+    pub hooks: Hooks,
 }
 
 #[derive(serde::Deserialize, serde::Serialize, Hash)]
 #[serde(default)]
 pub struct Project {
     pub name: String,
-    pub version: ordered_float::OrderedFloat<f64>,
+    pub kind: ProjectKind,
+    pub version: String,
+    pub authors: Vec<String>,
+    pub license: Option<String>,
 }
 
 impl Default for Project {
     fn default() -> Self {
         Self {
             name: String::from("Unnamed Project"),
-            version: ordered_float::OrderedFloat(1.0),
+            kind: ProjectKind::default(),
+            version: String::from("1.0"),
+            authors: Vec::new(),
+            license: None,
         }
     }
 }
 
+/// What the project's own target is built as
+#[derive(serde::Deserialize, serde::Serialize, Clone, Copy, Hash, Default, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum ProjectKind {
+    #[default]
+    Executable,
+    /// Has nothing to run itself, `cmm run` picks one of its examples or tests instead
+    Library,
+}
+
 #[derive(serde::Deserialize, serde::Serialize, Hash)]
 #[serde(default)]
 pub struct CMake {
-    pub minimum_required: ordered_float::OrderedFloat<f64>,
+    pub minimum_required: String,
+    /// Folder the project's source and include paths are relative to
+    pub source_dir: String,
+    /// Disables the checks that stop cmake being configured in the project folder
+    pub allow_in_source: bool,
+    /// CMake generator to configure with, eg. `Ninja`. Unset leaves the choice to cmake.
+    pub generator: Option<String>,
+    /// Compiler cache to compile through, eg. `ccache`. Unset uses sccache or ccache when
+    /// either is installed, `none` never uses one.
+    pub cache_launcher: Option<String>,
+    /// Folder ccache keeps this project's objects and statistics in, eg. `build/ccache`.
+    /// Lets `cmm clean --keep-cache-objects --zero-stats` reset the statistics of just this
+    /// project. Unset shares the user's cache with everything else.
+    pub cache_dir: Option<String>,
+    /// C++ standard the project needs, eg. `20`. Unset leaves it to the compiler's default.
+    pub cxx_standard: Option<u32>,
+    /// Allows compiler extensions like `-std=gnu++20` instead of `-std=c++20`
+    pub cxx_extensions: bool,
+    /// Warning flags for the project's targets, picked for MSVC style (`/W4`) or GCC style
+    /// (`-Wall`) compilers. Unset leaves the compiler's defaults.
+    pub warning_level: Option<WarningLevel>,
+    /// Compile options for the project's targets in every configuration
+    pub cxx_flags: Vec<String>,
+    /// Compile options added in Debug builds only
+    pub cxx_flags_debug: Vec<String>,
+    /// Compile options added in Release builds only
+    pub cxx_flags_release: Vec<String>,
+    /// Linker options for everything linked in the project, in every configuration
+    pub link_flags: Vec<String>,
+    pub link_flags_debug: Vec<String>,
+    pub link_flags_release: Vec<String>,
+    /// Folders copied next to the executable on every build, so it can load assets
+    /// relative to itself
+    pub resources: Vec<String>,
+    /// How much CMakeLists.txt explains itself
+    pub output_style: OutputStyle,
+    /// CMake code written as it is before `project()`, for policies and settings cmake
+    /// needs that early, like CMAKE_OSX_DEPLOYMENT_TARGET
+    pub prelude: Option<String>,
+    /// CMake code written as it is at the end of CMakeLists.txt, after every target is
+    /// declared, for anything cmm has no setting for
+    pub postlude: Option<String>,
     pub files: ProjectFiles,
 }
 
+impl CMake {
+    /// Options for every configuration followed by the Debug and Release only ones, the
+    /// latter wrapped in generator expressions so multi config generators pick the right ones
+    fn config_options(all: &[String], debug: &[String], release: &[String]) -> Vec<String> {
+        let config_expr = |build_type: &'static str| {
+            move |value: &String| format!(r#""$<$<CONFIG:{}>:{}>""#, build_type, value)
+        };
+
+        all.iter()
+            .map(|value| format!(r#""{}""#, value))
+            .chain(debug.iter().map(config_expr("Debug")))
+            .chain(release.iter().map(config_expr("Release")))
+            .collect()
+    }
+
+    /// The warning level's flags, then the configured ones so they can turn single
+    /// warnings back off
+    pub fn compile_options(&self) -> Vec<String> {
+        let warnings = self.warning_level.map_or(Vec::new(), WarningLevel::options);
+
+        warnings
+            .into_iter()
+            .chain(Self::config_options(
+                &self.cxx_flags,
+                &self.cxx_flags_debug,
+                &self.cxx_flags_release,
+            ))
+            .collect()
+    }
+
+    /// Programs the generated CMakeLists.txt looks for to cache compiles, none when the
+    /// project turned the cache off
+    pub fn cache_programs(&self) -> Vec<&str> {
+        match self.cache_launcher.as_deref() {
+            Some("none") => Vec::new(),
+            Some(program) => vec![program],
+            None => vec!["sccache", "ccache"],
+        }
+    }
+
+    pub fn link_options(&self) -> Vec<String> {
+        Self::config_options(
+            &self.link_flags,
+            &self.link_flags_debug,
+            &self.link_flags_release,
+        )
+    }
+}
+
 #[derive(serde::Deserialize, serde::Serialize, Clone, Hash)]
 #[serde(default)]
 pub struct ProjectFiles {
     pub source_files: Vec<(SourceType, Vec<String>)>,
     pub include_dirs: Vec<(IncludeType, Vec<String>)>,
     pub exclude_files: Vec<String>,
+    /// Files and folders that aren't part of the executable itself, eg.
+    /// `[["example", ["../examples"]]]`. Folders stand for the C and C++ files in them.
+    /// Only `cmake.files` has roles, targets build everything into themselves.
+    pub roles: Vec<(FileRole, Vec<String>)>,
 }
 
 impl Default for ProjectFiles {
@@ -49,6 +179,7 @@ impl ProjectFiles {
             source_files: vec![(SourceType::GlobRecurse, vec![".".into()])],
             include_dirs: vec![(IncludeType::Public, vec![".".into()])],
             exclude_files: Vec::new(),
+            roles: Vec::new(),
         }
     }
 
@@ -57,6 +188,7 @@ impl ProjectFiles {
             source_files: vec![(SourceType::Glob, vec![".".into()])],
             include_dirs: vec![(IncludeType::Public, vec![".".into()])],
             exclude_files: Vec::new(),
+            roles: Vec::new(),
         }
     }
 
@@ -65,19 +197,91 @@ impl ProjectFiles {
             source_files: Vec::new(),
             include_dirs: vec![(IncludeType::Interface, Vec::new())],
             exclude_files: Vec::new(),
+            roles: Vec::new(),
         }
     }
+
+    /// Entries tagged with `role`, in the order they're listed
+    pub fn role(&self, role: FileRole) -> Vec<&String> {
+        self.roles
+            .iter()
+            .filter(|(entry_role, _)| *entry_role == role)
+            .flat_map(|(_, entries)| entries)
+            .collect()
+    }
 }
 
 impl Default for CMake {
     fn default() -> Self {
         Self {
-            minimum_required: ordered_float::OrderedFloat(3.15),
+            minimum_required: String::from("3.15"),
+            source_dir: String::from("src"),
+            allow_in_source: false,
+            generator: None,
+            cache_launcher: None,
+            cache_dir: None,
+            cxx_standard: None,
+            cxx_extensions: false,
+            warning_level: None,
+            cxx_flags: Vec::new(),
+            cxx_flags_debug: Vec::new(),
+            cxx_flags_release: Vec::new(),
+            link_flags: Vec::new(),
+            link_flags_debug: Vec::new(),
+            link_flags_release: Vec::new(),
+            resources: Vec::new(),
+            output_style: OutputStyle::default(),
+            prelude: None,
+            postlude: None,
             files: ProjectFiles::default(),
         }
     }
 }
 
+#[derive(serde::Deserialize, serde::Serialize, Clone, Copy, Hash, Default, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum OutputStyle {
+    /// No section comments or blank lines, so diffs only show real changes
+    Compact,
+    /// A comment naming each section
+    #[default]
+    Normal,
+    /// Banners explaining each section, for learning what the file does
+    Verbose,
+}
+
+/// Warning presets, the same on every compiler
+#[derive(serde::Deserialize, serde::Serialize, Clone, Copy, Hash, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum WarningLevel {
+    /// `/W3` or `-Wall`
+    Normal,
+    /// `/W4` or `-Wall -Wextra -Wpedantic`
+    High,
+    /// Like high, with warnings as errors
+    Strict,
+}
+
+impl WarningLevel {
+    /// Compile options for the level. clang-cl takes MSVC's flags, so the check is on the
+    /// command line style rather than the compiler. The generator expression for it needs
+    /// cmake 3.30, the variable is there from 3.14.
+    fn options(self) -> Vec<String> {
+        let (msvc, gcc) = match self {
+            WarningLevel::Normal => ("/W3", "-Wall"),
+            WarningLevel::High => ("/W4", "-Wall;-Wextra;-Wpedantic"),
+            WarningLevel::Strict => ("/W4;/WX", "-Wall;-Wextra;-Wpedantic;-Werror"),
+        };
+
+        let is_msvc = "$<STREQUAL:${CMAKE_CXX_COMPILER_FRONTEND_VARIANT},MSVC>";
+
+        vec![
+            format!(r#""$<{}:{}>""#, is_msvc, msvc),
+            format!(r#""$<$<NOT:{}>:{}>""#, is_msvc, gcc),
+        ]
+    }
+}
+
 #[derive(serde::Deserialize, serde::Serialize, Clone, Copy, Hash)]
 pub enum SourceType {
     File,
@@ -85,6 +289,33 @@ pub enum SourceType {
     GlobRecurse,
 }
 
+/// What a group of files is for, which decides the target they're built into
+#[derive(serde::Deserialize, serde::Serialize, Clone, Copy, Hash, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum FileRole {
+    /// Compiled into the executable along with `source_files`
+    Main,
+    /// Compiled into the test executable when `[tests]` is set up. Otherwise each file is
+    /// a test of its own, passing when it returns 0.
+    Test,
+    /// Each file is an executable of its own showing how the project is used
+    Example,
+    /// Each file is a benchmark executable of its own
+    Bench,
+}
+
+impl FileRole {
+    /// The role as it's written in the config
+    pub fn name(self) -> &'static str {
+        match self {
+            FileRole::Main => "main",
+            FileRole::Test => "test",
+            FileRole::Example => "example",
+            FileRole::Bench => "bench",
+        }
+    }
+}
+
 #[derive(serde::Deserialize, serde::Serialize, Clone, Copy, Hash)]
 pub enum IncludeType {
     Public,
@@ -96,16 +327,80 @@ pub enum IncludeType {
 pub struct Dependencies {
     pub find: Vec<FindDependency>,
     pub local: Vec<LocalDependency>,
+    /// Downloaded by CMake when configuring instead of living in the repo
+    pub fetch: Vec<FetchDependency>,
+    /// Added with CPMAddPackage, declared the same way as fetch dependencies
+    pub cpm: Vec<FetchDependency>,
+    /// Installed with Conan 2 before configuring
+    pub conan: Vec<ConanDependency>,
+    /// Extra install prefixes find dependencies are searched in, eg. `~/opt/glfw`. `~` is the
+    /// home folder and relative paths are relative to the project.
+    pub prefix_paths: Vec<String>,
 
     pub project_dependencies: Vec<String>,
 }
 
+#[derive(serde::Deserialize, serde::Serialize, Hash, Clone)]
+pub struct FetchDependency {
+    pub name: String,
+    pub repo: String,
+    /// Tag, branch or commit to check out. Unset uses the default branch.
+    #[serde(default)]
+    pub tag: Option<String>,
+    #[serde(default)]
+    pub custom_link_name: Option<String>,
+    #[serde(default)]
+    pub variables: Vec<(String, String)>,
+    /// CMake code written as it is once the dependency is added, eg. to set properties on
+    /// its targets
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub extra_cmake: Option<String>,
+}
+
+#[derive(serde::Deserialize, serde::Serialize, Hash, Clone)]
+pub struct ConanDependency {
+    pub name: String,
+    pub version: String,
+    /// Name passed to find_package when it differs from the Conan package name
+    #[serde(default)]
+    pub package: Option<String>,
+    #[serde(default)]
+    pub custom_link_name: Option<String>,
+    /// CMake code written as it is once the dependency is added, eg. to set properties on
+    /// its targets
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub extra_cmake: Option<String>,
+}
+
+impl ConanDependency {
+    /// The reference written to conanfile.txt, eg. `fmt/11.0.2`
+    pub fn reference(&self) -> String {
+        format!("{}/{}", self.name, self.version)
+    }
+
+    pub fn package(&self) -> &str {
+        self.package.as_deref().unwrap_or(&self.name)
+    }
+
+    /// Conan's CMakeDeps names targets `<package>::<package>` unless the recipe says otherwise
+    pub fn link_name(&self) -> String {
+        match &self.custom_link_name {
+            Some(link_name) => link_name.clone(),
+            None => format!("{0}::{0}", self.package()),
+        }
+    }
+}
+
 #[derive(serde::Deserialize, serde::Serialize, Hash, Clone)]
 pub struct FindDependency {
     pub name: String,
     pub required: bool,
     #[serde(default)]
     pub custom_link_name: Option<String>,
+    /// CMake code written as it is once the dependency is added, eg. to set properties on
+    /// its targets
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub extra_cmake: Option<String>,
 }
 
 #[derive(serde::Deserialize, serde::Serialize, Clone, Hash)]
@@ -114,6 +409,16 @@ pub struct LocalDependency {
     pub name: String,
     pub local_type: LocalType,
     pub variables: Vec<(String, String)>,
+    /// Tag the submodule is checked out at, kept up to date by `cmm update`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tag: Option<String>,
+    /// Lives elsewhere in the same repository. Never managed with git, deleted or ignored.
+    #[serde(default)]
+    pub monorepo: bool,
+    /// CMake code written as it is once the dependency is added, eg. to set properties on
+    /// its targets
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub extra_cmake: Option<String>,
 }
 
 #[derive(serde::Deserialize, serde::Serialize, Clone, Hash)]
@@ -125,12 +430,355 @@ pub enum LocalType {
     },
 }
 
+/// An extra executable or library built alongside the main project executable
+#[derive(serde::Deserialize, serde::Serialize, Clone, Hash)]
+pub struct Target {
+    pub name: String,
+    #[serde(default)]
+    pub kind: TargetKind,
+    /// Folder the target's files are relative to
+    #[serde(default = "Target::default_path")]
+    pub path: String,
+    #[serde(default)]
+    pub files: ProjectFiles,
+    /// Libraries to link, either dependencies or other targets
+    #[serde(default)]
+    pub dependencies: Vec<String>,
+}
+
+impl Target {
+    fn default_path() -> String {
+        String::from("src")
+    }
+}
+
+#[derive(serde::Deserialize, serde::Serialize, Clone, Copy, Hash, Default, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum TargetKind {
+    #[default]
+    Executable,
+    Library,
+    /// Sources compiled once and shared between the targets that depend on it
+    Object,
+}
+
+/// Unit tests built into a separate executable and registered with ctest
+#[derive(serde::Deserialize, serde::Serialize, Clone, Hash)]
+pub struct Tests {
+    pub framework: TestFramework,
+    /// Git tag of the framework to fetch
+    pub version: String,
+    /// Folder holding the test sources
+    #[serde(default = "Tests::default_path")]
+    pub path: String,
+    /// Times `cmm test` reruns failed tests before counting them as failing
+    #[serde(default)]
+    pub retries: u32,
+}
+
+impl Tests {
+    pub fn default_path() -> String {
+        String::from("tests")
+    }
+
+    pub fn new(framework: TestFramework) -> Self {
+        let version = match framework {
+            TestFramework::GTest => "v1.14.0",
+            TestFramework::Catch2 => "v3.5.2",
+        };
+
+        Self {
+            framework,
+            version: version.into(),
+            path: Self::default_path(),
+            retries: 0,
+        }
+    }
+}
+
+#[derive(serde::Deserialize, serde::Serialize, Clone, Copy, Hash, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum TestFramework {
+    GTest,
+    Catch2,
+}
+
+/// What `cmm install` copies into the install prefix
+#[derive(serde::Deserialize, serde::Serialize, Clone, Default, Hash)]
+#[serde(default)]
+pub struct Install {
+    /// Executable and library targets installed to bin/ and lib/. The project name is the
+    /// main executable.
+    pub binaries: Vec<String>,
+    /// Folders whose contents are installed to include/, relative to the project folder
+    pub headers: Vec<String>,
+    /// Folders whose contents are installed to share/<project name>/
+    pub resources: Vec<String>,
+    /// Install prefix used when neither `--prefix` nor CMAKE_INSTALL_PREFIX is given
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub prefix: Option<String>,
+}
+
+/// CPack settings for `cmm package`, which packages what `[install]` installs
+#[derive(serde::Deserialize, serde::Serialize, Clone, Default, Hash)]
+#[serde(default)]
+pub struct Packaging {
+    /// CPack generators to run, eg. `["DEB", "RPM", "ZIP"]`. Empty uses CPack's default.
+    pub generators: Vec<String>,
+    /// Package maintainer, defaults to the first author
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub maintainer: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+}
+
+/// How builds treat compiler warnings recorded with `cmm warnings baseline`
+#[derive(serde::Deserialize, serde::Serialize, Default, Hash)]
+#[serde(default)]
+pub struct Warnings {
+    /// What to do when a file has more warnings than its baseline
+    pub on_new: NewWarnings,
+    /// After compiling, count warnings and errors in project files apart from ones in
+    /// dependencies
+    pub report: bool,
+    /// Leave warnings and errors in dependencies out of the terminal. They're still in the
+    /// build log.
+    pub hide_dependencies: bool,
+}
+
+#[derive(serde::Deserialize, serde::Serialize, Clone, Copy, Hash, Default, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum NewWarnings {
+    #[default]
+    Fail,
+    Warn,
+}
+
+/// Templates for the files `cmm generate` creates
+#[derive(serde::Deserialize, serde::Serialize, Default, Hash)]
+#[serde(default)]
+pub struct Codegen {
+    /// Namespace generated code goes in. Defaults to the project name.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub namespace: Option<String>,
+    /// Use `#ifndef` include guards instead of `#pragma once`
+    pub include_guards: bool,
+}
+
+/// Shell commands cmm runs from the project folder, see [`crate::hooks`]
+#[derive(serde::Deserialize, serde::Serialize, Default, Hash)]
+#[serde(default)]
+pub struct Hooks {
+    /// Run by `cmm run` after building, before the program starts, eg. to start a local
+    /// server or copy fresh assets. The run stops if one fails.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub pre_run: Vec<String>,
+}
+
+/// Runtime settings `cmm run --sanitize` passes to sanitized programs
+#[derive(serde::Deserialize, serde::Serialize, Default, Hash)]
+#[serde(default)]
+pub struct Sanitizers {
+    /// Exported as `ASAN_OPTIONS`
+    pub address: SanitizerRuntime,
+    /// Exported as `UBSAN_OPTIONS`
+    pub undefined: SanitizerRuntime,
+    /// Exported as `LSAN_OPTIONS`
+    pub leak: SanitizerRuntime,
+}
+
+#[derive(serde::Deserialize, serde::Serialize, Default, Hash)]
+#[serde(default)]
+pub struct SanitizerRuntime {
+    /// Runtime flags, eg. `["detect_leaks=1", "halt_on_error=0"]`
+    pub options: Vec<String>,
+    /// Suppressions file, relative to the project folder
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub suppressions: Option<String>,
+}
+
+pub const DEFAULT_PROFILE: &str = "debug";
+
+#[derive(serde::Deserialize, serde::Serialize, Clone, Hash)]
+#[serde(default)]
+pub struct Profile {
+    pub build_type: String,
+    pub cxx_flags: Vec<String>,
+    pub definitions: Vec<String>,
+    /// Sanitizers to build with, eg. `["address", "undefined"]`, added as `-fsanitize`
+    /// compile and link flags
+    pub sanitize: Vec<String>,
+    /// Build with `--coverage` so `cmm coverage` can report which lines tests ran
+    pub coverage: bool,
+}
+
+impl Default for Profile {
+    fn default() -> Self {
+        Self::debug()
+    }
+}
+
+impl Profile {
+    pub fn debug() -> Self {
+        Self {
+            build_type: String::from("Debug"),
+            cxx_flags: Vec::new(),
+            definitions: Vec::new(),
+            sanitize: Vec::new(),
+            coverage: false,
+        }
+    }
+
+    pub fn release() -> Self {
+        Self {
+            build_type: String::from("Release"),
+            cxx_flags: Vec::new(),
+            definitions: Vec::new(),
+            sanitize: Vec::new(),
+            coverage: false,
+        }
+    }
+
+    /// A debug build with `sanitizer` enabled
+    pub fn sanitized(sanitizer: &str) -> Self {
+        Self {
+            sanitize: vec![sanitizer.into()],
+            ..Self::debug()
+        }
+    }
+
+    /// A debug build instrumented for coverage
+    pub fn coverage() -> Self {
+        Self {
+            coverage: true,
+            ..Self::debug()
+        }
+    }
+
+    /// Compile and link flags for the profile's sanitizers and coverage
+    pub fn instrumentation(&self) -> (Vec<String>, Vec<String>) {
+        let mut compile = Vec::new();
+        let mut link = Vec::new();
+
+        if !self.sanitize.is_empty() {
+            let sanitize = format!("-fsanitize={}", self.sanitize.join(","));
+            compile.extend([sanitize.clone(), "-fno-omit-frame-pointer".into()]);
+            link.push(sanitize);
+        }
+
+        if self.coverage {
+            compile.push("--coverage".into());
+            link.push("--coverage".into());
+        }
+
+        (compile, link)
+    }
+
+    pub fn defaults() -> BTreeMap<String, Profile> {
+        BTreeMap::from([
+            ("debug".into(), Profile::debug()),
+            ("release".into(), Profile::release()),
+        ])
+    }
+
+    /// Profiles for `--asan`, `--ubsan`, `--tsan` and `--coverage`. Available in every
+    /// project without being written to its config.
+    pub fn instrumented_defaults() -> BTreeMap<String, Profile> {
+        BTreeMap::from([
+            ("asan".into(), Profile::sanitized("address")),
+            ("ubsan".into(), Profile::sanitized("undefined")),
+            ("tsan".into(), Profile::sanitized("thread")),
+            ("coverage".into(), Profile::coverage()),
+        ])
+    }
+}
+
 impl ConfigFile {
     pub fn new(name: String) -> Self {
-        let mut config = Self::default();
+        let mut config = Self {
+            config_version: schema::CONFIG_VERSION,
+            profiles: Profile::defaults(),
+            ..Default::default()
+        };
         config.project.name = name;
         config
     }
+
+    /// All profiles, falling back to the built in debug, release, sanitizer and coverage
+    /// profiles when the config doesn't define its own
+    pub fn profiles(&self) -> BTreeMap<String, Profile> {
+        let mut profiles = Profile::defaults();
+        profiles.extend(Profile::instrumented_defaults());
+        profiles.extend(self.profiles.clone());
+        profiles
+    }
+
+    pub fn profile(&self, name: &str) -> Option<Profile> {
+        self.profiles().remove(name)
+    }
+
+    /// The main project executable followed by every executable target. Library projects
+    /// only have their targets.
+    pub fn executable_names(&self) -> Vec<String> {
+        (self.project.kind == ProjectKind::Executable)
+            .then(|| self.project.name.clone())
+            .into_iter()
+            .chain(
+                self.targets
+                    .iter()
+                    .filter(|target| target.kind == TargetKind::Executable)
+                    .map(|target| target.name.clone()),
+            )
+            .collect()
+    }
+
+    /// Every dependency the config declares, whatever kind it is
+    pub fn dependency_names(&self) -> Vec<String> {
+        let dependencies = &self.dependencies;
+
+        dependencies
+            .find
+            .iter()
+            .map(|find| &find.name)
+            .chain(dependencies.local.iter().map(|local| &local.name))
+            .chain(dependencies.fetch.iter().map(|fetch| &fetch.name))
+            .chain(dependencies.cpm.iter().map(|cpm| &cpm.name))
+            .chain(dependencies.conan.iter().map(|conan| &conan.name))
+            .cloned()
+            .collect()
+    }
+}
+
+/// User wide preferences, written by the first run setup
+#[derive(serde::Deserialize, serde::Serialize, Default)]
+#[serde(default)]
+pub struct GlobalConfig {
+    pub author: Option<String>,
+    pub cxx_standard: Option<u32>,
+    pub generator: Option<String>,
+    pub license: Option<String>,
+    /// Move removed build folders, dependencies and CMakeLists.txt to the system trash
+    pub use_trash: bool,
+    /// Don't keep the previous CMakeMake.toml as CMakeMake.toml.bak when cmm changes it
+    pub skip_config_backup: bool,
+    /// Files `cmm new` starts projects with instead of the built in hello world
+    pub starter: Starter,
+}
+
+/// The `[starter]` section of the user config, see [`crate::starter`]
+#[derive(serde::Deserialize, serde::Serialize, Default)]
+#[serde(default)]
+pub struct Starter {
+    /// Used as src/main.cpp for projects without a template
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub main: Option<String>,
+    /// main.cpp for projects on a particular C++ standard, eg. `"23" = "main23.cpp"`
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub main_by_standard: BTreeMap<String, String>,
+    /// Extra files every new project gets, from where they go in the project to the file
+    /// to copy, eg. `".clang-format" = "clang-format"`
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub files: BTreeMap<String, String>,
 }
 
 #[derive(serde::Deserialize, serde::Serialize, Default)]
@@ -0,0 +1,122 @@
+use std::path::Path;
+
+use colored::Colorize;
+
+use crate::{
+    build::BuildOptions, cmake_cache::CMakeCache, error::ProjectError, messages::t,
+    requires::tool_version, testing::run_tests, util::get_config,
+};
+
+/// Profile `cmm coverage` builds when the chosen one isn't instrumented
+const COVERAGE_PROFILE: &str = "coverage";
+
+/// Where `cmm coverage --html` writes its report, inside the profile's build folder
+const HTML_DIR: &str = "coverage-html";
+
+/// Deletes the counters left by earlier runs, which gcov would otherwise add to this run's
+fn remove_counters(dir: &Path) {
+    for entry in std::fs::read_dir(dir).into_iter().flatten().flatten() {
+        let path = entry.path();
+
+        if path.is_dir() {
+            remove_counters(&path);
+        } else if path.extension().is_some_and(|ext| ext == "gcda") {
+            std::fs::remove_file(&path).ok();
+        }
+    }
+}
+
+/// `cmm coverage [--html]`. Builds with coverage instrumentation, runs the tests and
+/// reports which lines of the project they ran with gcovr. Builds compiled by clang read
+/// their counters with `llvm-cov gcov`. The report is written even when tests fail, so
+/// the run still fails afterwards.
+pub fn coverage_report(mut options: BuildOptions, html: bool) -> Result<(), ProjectError> {
+    let config = get_config()?;
+    let Some(tests) = &config.tests else {
+        return Err(ProjectError::NoTests);
+    };
+
+    if !config
+        .profile(&options.profile)
+        .is_some_and(|profile| profile.coverage)
+    {
+        options.profile = COVERAGE_PROFILE.into();
+    }
+
+    // Checked before building so a missing tool doesn't waste a compile
+    if tool_version("gcovr").is_none() {
+        return Err(ProjectError::MissingProgram("gcovr".into()));
+    }
+
+    let build_dir = options.build_dir();
+    remove_counters(Path::new(&build_dir));
+
+    let result = run_tests(&options, None);
+    if let Err(err) = &result {
+        if !matches!(err, ProjectError::FailingTests(_)) {
+            return result;
+        }
+    }
+
+    println!("\n{}", t!("Writing coverage report").green());
+
+    let mut args = vec![
+        "--root".to_owned(),
+        ".".into(),
+        "--exclude".into(),
+        "build/".into(),
+        "--exclude".into(),
+        "external/".into(),
+        "--exclude".into(),
+        format!("{}/", tests.path),
+    ];
+
+    let compiler = CMakeCache::read(Path::new(&build_dir))
+        .and_then(|cache| cache.get("CMAKE_CXX_COMPILER_ID").map(str::to_owned));
+    if compiler.is_some_and(|id| id.contains("Clang")) {
+        args.extend(["--gcov-executable".into(), "llvm-cov gcov".into()]);
+    }
+
+    let html_report = Path::new(&build_dir).join(HTML_DIR).join("index.html");
+    if html {
+        let dir = Path::new(&build_dir).join(HTML_DIR);
+        std::fs::create_dir_all(&dir)
+            .map_err(|err| ProjectError::FailedToCreateFolder(dir.clone(), err.to_string()))?;
+
+        args.extend([
+            "--html-details".into(),
+            html_report.to_string_lossy().into_owned(),
+        ]);
+    }
+
+    args.push(build_dir);
+
+    let status = duct::cmd("gcovr", args)
+        .unchecked()
+        .run()
+        .map_err(|_| ProjectError::MissingProgram("gcovr".into()))?
+        .status;
+
+    if !status.success() {
+        return Err(ProjectError::FailedToRunProcess(
+            "gcovr".into(),
+            status.code(),
+        ));
+    }
+
+    if html {
+        println!(
+            "{}",
+            t!("Wrote coverage report to '{}'", html_report.display())
+        );
+    }
+
+    result?;
+
+    println!(
+        "{}",
+        t!("{} reporting coverage", t!("Finished").green().bold())
+    );
+
+    Ok(())
+}
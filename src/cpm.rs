@@ -0,0 +1,57 @@
+use std::path::Path;
+
+use crate::{config::ConfigFile, error::ProjectError, util::create_dir};
+
+/// Where the CPM.cmake loader is vendored in the project
+pub const CPM_PATH: &str = "cmake/CPM.cmake";
+
+pub const CPM_VERSION: &str = "0.40.2";
+
+/// Downloads the real CPM.cmake into the build folder (or CPM_SOURCE_CACHE) the first time
+/// it's needed, the same way CPM's own get_cpm.cmake does
+fn loader() -> String {
+    format!(
+        r#"# Written by cmakemake. Change CPM_DOWNLOAD_VERSION to update CPM.cmake.
+set(CPM_DOWNLOAD_VERSION {CPM_VERSION})
+
+if(CPM_SOURCE_CACHE)
+    set(CPM_DOWNLOAD_LOCATION "${{CPM_SOURCE_CACHE}}/cpm/CPM_${{CPM_DOWNLOAD_VERSION}}.cmake")
+elseif(DEFINED ENV{{CPM_SOURCE_CACHE}})
+    set(CPM_DOWNLOAD_LOCATION "$ENV{{CPM_SOURCE_CACHE}}/cpm/CPM_${{CPM_DOWNLOAD_VERSION}}.cmake")
+else()
+    set(CPM_DOWNLOAD_LOCATION "${{CMAKE_BINARY_DIR}}/cmake/CPM_${{CPM_DOWNLOAD_VERSION}}.cmake")
+endif()
+
+get_filename_component(CPM_DOWNLOAD_LOCATION ${{CPM_DOWNLOAD_LOCATION}} ABSOLUTE)
+
+if(NOT EXISTS ${{CPM_DOWNLOAD_LOCATION}})
+    message(STATUS "Downloading CPM.cmake to ${{CPM_DOWNLOAD_LOCATION}}")
+    file(DOWNLOAD
+        https://github.com/cpm-cmake/CPM.cmake/releases/download/v${{CPM_DOWNLOAD_VERSION}}/CPM.cmake
+        ${{CPM_DOWNLOAD_LOCATION}}
+    )
+endif()
+
+include(${{CPM_DOWNLOAD_LOCATION}})
+"#
+    )
+}
+
+/// Vendors the CPM.cmake loader when the project has CPM dependencies. An existing file is
+/// left alone so a pinned or updated CPM version isn't overwritten.
+pub fn write_loader(config: &ConfigFile) -> Result<(), ProjectError> {
+    let path = Path::new(CPM_PATH);
+
+    if config.dependencies.cpm.is_empty() || path.exists() {
+        return Ok(());
+    }
+
+    if let Some(parent) = path.parent() {
+        if !parent.exists() {
+            create_dir(parent)?;
+        }
+    }
+
+    std::fs::write(path, loader())
+        .map_err(|err| ProjectError::FailedToCreateFile(path.to_owned(), err.to_string()))
+}
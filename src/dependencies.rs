@@ -1,58 +1,84 @@
 use std::path::PathBuf;
 
+use colored::Colorize;
+
 use crate::{
-    config::{self, CacheSubmodule, ConfigFile, FindDependency, LocalDependency},
+    answers,
+    config::{
+        self, CacheSubmodule, ConanDependency, ConfigFile, FetchDependency, FindDependency,
+        LocalDependency,
+    },
     error::{DisplayError, ProjectError},
+    git::{discard_submodule, read_submodules, remove_submodule},
+    messages::t,
+    output::{self, notice, status},
+    patches,
     util::{
-        dep_flag_validation, folder_validator, get_cache, not_own_folder_validator, path_formater,
-        write_cache, FolderAutocomplete,
+        dep_flag_validation, folder_validator, get_cache, in_repo_validator,
+        not_own_folder_validator, path_formater, write_cache, FolderAutocomplete,
     },
+    validate,
 };
 
-fn get_dependency_variables() -> Vec<(String, String)> {
+fn get_dependency_variables() -> Result<Vec<(String, String)>, ProjectError> {
     let mut flags = Vec::new();
 
-    println!("Any variables/flags");
-    loop {
-        match inquire::Text::new(" > ")
+    println!("{}", t!("Any variables/flags"));
+    while let Some(val) = answers::text_skippable(
+        "add.variables",
+        inquire::Text::new(" > ")
             .with_validator(dep_flag_validation)
             .with_placeholder("[NAME] [VALUES]...")
-            .with_help_message("Any Variables/Flags")
-            .prompt_skippable()
-            .unwrap()
-        {
-            Some(val) => {
-                if val.is_empty() {
-                    break;
-                }
-                flags.push(val)
-            }
-            None => break,
+            .with_help_message(t!("Any Variables/Flags")),
+    )? {
+        if val.is_empty() {
+            break;
         }
+        flags.push(val)
     }
 
     let variables = flags
         .into_iter()
         .map(|var| {
-            let name = var.split_whitespace().nth(0).unwrap().to_owned();
+            let name = var.split_whitespace().next().unwrap().to_owned();
             let value = var[name.len()..].to_owned();
 
             (name, value)
         })
         .collect();
 
-    variables
+    Ok(variables)
 }
 
-fn get_is_project_dependency(config: &mut ConfigFile, name: String) {
-    if inquire::Confirm::new("Add as project dependency?")
-        .with_placeholder("y/n")
-        .with_default(true)
-        .prompt()
-        .unwrap()
-    {
+/// Folders for the custom file setup of a local dependency, relative to it
+fn get_folders(key: &str, title: &str) -> Result<Vec<String>, ProjectError> {
+    let mut folders = Vec::new();
+
+    println!("{}", title);
+    while let Some(val) = answers::text_skippable(
+        key,
+        inquire::Text::new(" > ").with_help_message(t!("Press enter or esc to proceed")),
+    )? {
+        if val.is_empty() {
+            break;
+        }
+        folders.push(val);
+    }
+
+    Ok(folders)
+}
+
+fn get_is_project_dependency(config: &mut ConfigFile, name: String) -> Result<(), ProjectError> {
+    if answers::confirm(
+        "add.link",
+        inquire::Confirm::new(t!("Add as project dependency?"))
+            .with_placeholder("y/n")
+            .with_default(true),
+    )? {
         config.dependencies.project_dependencies.push(name);
     }
+
+    Ok(())
 }
 
 pub fn add_local_dependency_path(
@@ -60,64 +86,72 @@ pub fn add_local_dependency_path(
     path: String,
 ) -> Result<LocalDependency, ProjectError> {
     let path_buf = PathBuf::from(&path);
-    if path_buf.exists() {
-        ProjectError::CannotOpenFile(path_buf.clone(), "Path doesn't exist".to_owned());
+    if !path_buf.exists() {
+        return Err(ProjectError::CannotOpenFile(
+            path_buf.clone(),
+            "Path doesn't exist".to_owned(),
+        ));
     }
 
     let default_name = path_buf.file_name().unwrap_or_default().to_str().unwrap();
-    let name = inquire::Text::new("Dependency Name:")
-        .with_default(default_name)
-        .with_validator(inquire::validator::ValueRequiredValidator::default())
-        .prompt()
-        .unwrap();
-
-    let variables = get_dependency_variables();
-
-    let local_type = match inquire::Confirm::new("Dependency uses CMake?")
-        .with_placeholder("y/n")
-        .prompt()
-        .unwrap()
-    {
+    let name = answers::text(
+        "add.name",
+        inquire::Text::new(t!("Dependency Name:"))
+            .with_default(default_name)
+            .with_validator(inquire::validator::ValueRequiredValidator::default()),
+    )?;
+
+    let variables = get_dependency_variables()?;
+
+    let local_type = match answers::confirm(
+        "add.uses_cmake",
+        inquire::Confirm::new(t!("Dependency uses CMake?")).with_placeholder("y/n"),
+    )? {
         true => config::LocalType::CMake,
 
         false => {
-            let files = inquire::Select::new(
-                "Included files",
-                vec![
-                    "All",         // 0
-                    "Root",        // 1
-                    "Header Only", // 2
-                    "Custom",      // 3
-                ],
-            )
-            .raw_prompt()
-            .unwrap();
+            let files = answers::select(
+                "add.files",
+                inquire::Select::new(
+                    t!("Included files"),
+                    vec![
+                        t!("All"),         // 0
+                        t!("Root"),        // 1
+                        t!("Header Only"), // 2
+                        t!("Custom"),      // 3
+                    ],
+                ),
+            )?;
 
             let files = match files.index {
                 0 => config::ProjectFiles::all(),
                 1 => config::ProjectFiles::root(),
                 2 => config::ProjectFiles::header(),
-                3 => todo!(),
+                3 => config::ProjectFiles {
+                    source_files: vec![(
+                        config::SourceType::GlobRecurse,
+                        get_folders("add.source_folders", t!("Source folders"))?,
+                    )],
+                    include_dirs: vec![(
+                        config::IncludeType::Public,
+                        get_folders("add.include_folders", t!("Include folders"))?,
+                    )],
+                    ..config::ProjectFiles::all()
+                },
 
                 _ => return Err(ProjectError::UnknownArgument(files.value.into())),
             };
 
             let mut dependencies = Vec::new();
-            println!("Library dependencies");
-            loop {
-                match inquire::Text::new(" > ")
-                    .with_help_message("Press enter or esc to proceed")
-                    .prompt_skippable()
-                    .unwrap()
-                {
-                    Some(val) => {
-                        if val.is_empty() {
-                            break;
-                        }
-                        dependencies.push(val);
-                    }
-                    None => break,
+            println!("{}", t!("Library dependencies"));
+            while let Some(val) = answers::text_skippable(
+                "add.library_dependencies",
+                inquire::Text::new(" > ").with_help_message(t!("Press enter or esc to proceed")),
+            )? {
+                if val.is_empty() {
+                    break;
                 }
+                dependencies.push(val);
             }
 
             config::LocalType::Source {
@@ -132,41 +166,129 @@ pub fn add_local_dependency_path(
         name: name.clone(),
         local_type,
         variables,
+        tag: None,
+        monorepo: false,
+        extra_cmake: None,
     };
 
     config.dependencies.local.push(local_dependency.clone());
 
-    get_is_project_dependency(config, name);
+    get_is_project_dependency(config, name)?;
 
     Ok(local_dependency)
 }
 
 pub fn add_local_dependency(config: &mut ConfigFile) -> Result<(), ProjectError> {
-    let path = inquire::Text::new("Path:")
-        .with_validator(inquire::validator::ValueRequiredValidator::default())
-        .with_help_message("Choose a path relative to the project folder")
-        .with_autocomplete(FolderAutocomplete(std::env::current_dir().unwrap()))
-        .with_validator(folder_validator)
-        .with_validator(not_own_folder_validator)
-        .with_formatter(&path_formater)
-        .prompt()
-        .unwrap();
+    let path = answers::text(
+        "add.path",
+        inquire::Text::new(t!("Path:"))
+            .with_validator(inquire::validator::ValueRequiredValidator::default())
+            .with_help_message(t!("Choose a path relative to the project folder"))
+            .with_autocomplete(FolderAutocomplete(std::env::current_dir().unwrap()))
+            .with_validator(folder_validator)
+            .with_validator(not_own_folder_validator)
+            .with_formatter(&path_formater),
+    )?;
+
+    add_local_dependency_path(config, path)?;
+
+    Ok(())
+}
+
+pub fn add_monorepo_dependency(config: &mut ConfigFile) -> Result<(), ProjectError> {
+    let path = answers::text(
+        "add.path",
+        inquire::Text::new(t!("Path:"))
+            .with_validator(inquire::validator::ValueRequiredValidator::default())
+            .with_help_message(t!(
+                "Choose a folder elsewhere in this repository, relative to the project"
+            ))
+            .with_autocomplete(FolderAutocomplete(std::env::current_dir().unwrap()))
+            .with_validator(folder_validator)
+            .with_validator(not_own_folder_validator)
+            .with_validator(in_repo_validator)
+            .with_formatter(&path_formater),
+    )?;
 
     add_local_dependency_path(config, path)?;
 
+    // add_local_dependency_path always pushes the new dependency last
+    if let Some(local) = config.dependencies.local.last_mut() {
+        local.monorepo = true;
+    }
+
+    Ok(())
+}
+
+/// Whether a git dependency should be added with CPMAddPackage instead of as a submodule
+fn use_cpm() -> Result<bool, ProjectError> {
+    let mode = answers::select(
+        "add.mode",
+        inquire::Select::new(
+            t!("Add dependency as:"),
+            vec![t!("Git Submodule"), t!("CPM Package")],
+        ),
+    )?;
+
+    Ok(mode.index == 1)
+}
+
+/// Adds a git dependency CPM downloads when configuring. The repo isn't cloned so the
+/// setup a submodule would be scanned for is asked for instead.
+fn add_cpm_package(
+    config: &mut ConfigFile,
+    repo: String,
+    tag: Option<String>,
+) -> Result<(), ProjectError> {
+    let name = answers::text(
+        "add.name",
+        inquire::Text::new(t!("Dependency Name:"))
+            .with_default(submodule_name(&repo))
+            .with_validator(inquire::validator::ValueRequiredValidator::default()),
+    )?;
+
+    let variables = get_dependency_variables()?;
+
+    let custom_link_name = answers::text_skippable(
+        "add.link_name",
+        inquire::Text::new(t!("Specify Dependency link name: (optional)")),
+    )?
+    .filter(|val| !val.is_empty());
+
+    config.dependencies.cpm.push(FetchDependency {
+        name: name.clone(),
+        repo,
+        tag,
+        custom_link_name: custom_link_name.clone(),
+        variables,
+        extra_cmake: None,
+    });
+
+    get_is_project_dependency(config, custom_link_name.unwrap_or(name))?;
+
     Ok(())
 }
 
 pub fn add_git_submodule(config: &mut ConfigFile) -> Result<(), ProjectError> {
-    let repo = inquire::Text::new("Fetch Git Repo:")
-        .with_validator(inquire::validator::ValueRequiredValidator::default())
-        .prompt()
-        .unwrap();
-
-    let tag = match inquire::Text::new("Git Tag (optional):")
-        .prompt_skippable()
-        .unwrap()
-    {
+    let repo = answers::text(
+        "add.repo",
+        inquire::Text::new(t!("Fetch Git Repo:"))
+            .with_validator(inquire::validator::ValueRequiredValidator::default()),
+    )?;
+
+    let tag =
+        match answers::text_skippable("add.tag", inquire::Text::new(t!("Git Tag (optional):")))? {
+            Some(val) => match val.is_empty() {
+                true => None,
+                false => Some(val),
+            },
+            None => None,
+        };
+
+    let branch = match answers::text_skippable(
+        "add.branch",
+        inquire::Text::new(t!("Git Branch (optional):")),
+    )? {
         Some(val) => match val.is_empty() {
             true => None,
             false => Some(val),
@@ -174,26 +296,27 @@ pub fn add_git_submodule(config: &mut ConfigFile) -> Result<(), ProjectError> {
         None => None,
     };
 
-    let branch = match inquire::Text::new("Git Branch (optional):")
-        .prompt_skippable()
-        .unwrap()
-    {
-        Some(val) => match val.is_empty() {
-            true => None,
-            false => Some(val),
-        },
-        None => None,
-    };
+    if use_cpm()? {
+        return add_cpm_package(config, repo, tag.or(branch));
+    }
 
     let folder_path = add_submodule(&repo, tag.as_ref(), branch.as_ref())?;
-    let local_setup = add_local_dependency_path(config, folder_path)?;
+    let mut local_setup = add_local_dependency_path(config, folder_path)?;
 
-    if inquire::Confirm::new("Save dependency to cache?")
-        .with_default(true)
-        .with_placeholder("Y/n")
-        .prompt()
-        .unwrap()
-    {
+    // Recorded so `cmm update` knows which tags are newer
+    local_setup.tag = tag.clone();
+    if let Some(local) = config.dependencies.local.last_mut() {
+        local.tag = tag.clone();
+    }
+
+    patches::apply_patches(&local_setup.name, &local_setup.path)?;
+
+    if answers::confirm(
+        "add.save_to_cache",
+        inquire::Confirm::new(t!("Save dependency to cache?"))
+            .with_default(true)
+            .with_placeholder("Y/n"),
+    )? {
         cache_git_submodule(config::GitSubmodule {
             repo,
             tag,
@@ -205,12 +328,103 @@ pub fn add_git_submodule(config: &mut ConfigFile) -> Result<(), ProjectError> {
     Ok(())
 }
 
+/// Adds a dependency CMake downloads with FetchContent when configuring
+pub fn add_fetch_dependency(config: &mut ConfigFile) -> Result<(), ProjectError> {
+    let repo = answers::text(
+        "add.repo",
+        inquire::Text::new(t!("Fetch Git Repo:"))
+            .with_validator(inquire::validator::ValueRequiredValidator::default()),
+    )?;
+
+    let tag = answers::text_skippable("add.tag", inquire::Text::new(t!("Git Tag (optional):")))?
+        .filter(|val| !val.is_empty());
+
+    let name = answers::text(
+        "add.name",
+        inquire::Text::new(t!("Dependency Name:"))
+            .with_default(submodule_name(&repo))
+            .with_validator(inquire::validator::ValueRequiredValidator::default()),
+    )?;
+
+    let variables = get_dependency_variables()?;
+
+    let custom_link_name = answers::text_skippable(
+        "add.link_name",
+        inquire::Text::new(t!("Specify Dependency link name: (optional)")),
+    )?
+    .filter(|val| !val.is_empty());
+
+    config.dependencies.fetch.push(FetchDependency {
+        name: name.clone(),
+        repo,
+        tag,
+        custom_link_name: custom_link_name.clone(),
+        variables,
+        extra_cmake: None,
+    });
+
+    get_is_project_dependency(config, custom_link_name.unwrap_or(name))?;
+
+    Ok(())
+}
+
+/// Splits a Conan reference like `fmt/11.0.2` into its name and version
+fn parse_conan_reference(reference: &str) -> Result<(String, String), ProjectError> {
+    match reference.split_once('/') {
+        Some((name, version)) if !name.is_empty() && !version.is_empty() => {
+            Ok((name.to_owned(), version.to_owned()))
+        }
+        _ => Err(ProjectError::UnknownArgument(reference.to_owned())),
+    }
+}
+
+/// Adds a package installed with Conan when building
+pub fn add_conan_dependency(config: &mut ConfigFile) -> Result<(), ProjectError> {
+    let reference = answers::text(
+        "add.reference",
+        inquire::Text::new(t!("Conan Reference:"))
+            .with_placeholder("fmt/11.0.2")
+            .with_validator(|input: &str| {
+                Ok(match parse_conan_reference(input) {
+                    Ok(_) => inquire::validator::Validation::Valid,
+                    Err(_) => {
+                        inquire::validator::Validation::Invalid(t!("Expected NAME/VERSION").into())
+                    }
+                })
+            }),
+    )?;
+
+    let (name, version) = parse_conan_reference(&reference)?;
+
+    let package = answers::text_skippable(
+        "add.package",
+        inquire::Text::new(t!("find_package name: (optional)")).with_placeholder(&name),
+    )?
+    .filter(|val| !val.is_empty());
+
+    let custom_link_name = answers::text_skippable(
+        "add.link_name",
+        inquire::Text::new(t!("Specify Dependency link name: (optional)")),
+    )?
+    .filter(|val| !val.is_empty());
+
+    let conan = ConanDependency {
+        name,
+        version,
+        package,
+        custom_link_name,
+        extra_cmake: None,
+    };
+
+    get_is_project_dependency(config, conan.link_name())?;
+    config.dependencies.conan.push(conan);
+
+    Ok(())
+}
+
 fn submodule_name(repo: &str) -> &str {
-    let lib_name = repo.split(|val| val == '/').last().unwrap();
-    let lib_name = lib_name
-        .split(|char| char == '.')
-        .next()
-        .unwrap_or(lib_name);
+    let lib_name = repo.split('/').next_back().unwrap();
+    let lib_name = lib_name.split('.').next().unwrap_or(lib_name);
 
     lib_name
 }
@@ -224,30 +438,46 @@ fn add_submodule(
     let lib_name = submodule_name(repo);
 
     let folder_path = format!("external/{}", lib_name);
+    let existed = std::path::Path::new(&folder_path).exists();
 
-    let cmd_output = duct::cmd!("git", "submodule", "add", &repo, &folder_path)
-        .stderr_to_stdout()
-        .unchecked()
-        .run()
-        .unwrap();
+    let cmd_output = output::run(output::command(
+        "git",
+        ["submodule", "add", repo, &folder_path],
+    ))
+    .unwrap();
 
     if !cmd_output.status.success() {
+        // A clone cut off part way can leave the folder, a .gitmodules entry and a cached
+        // repo behind, which stop the same submodule being added again. A folder that was
+        // already there isn't ours to delete.
+        match existed {
+            false => discard_submodule(&folder_path).display_error(),
+            true => println!(
+                "{} {}",
+                t!("hint:").cyan(),
+                t!(
+                    "if '{}' was left by an earlier failed add, 'cmm repair' cleans it up",
+                    folder_path
+                )
+            ),
+        }
+
         Err(ProjectError::FailedToRunProcess(
             format!("git submodule add {} {}", &repo, &folder_path),
             cmd_output.status.code(),
         ))?;
     }
 
-    let cmd_output = duct::cmd!("git", "submodule", "update", "--init", "--recursive")
-        .stderr_to_stdout()
-        .unchecked()
-        .run()
-        .unwrap();
+    let cmd_output = output::run(output::command(
+        "git",
+        ["submodule", "update", "--init", "--recursive"],
+    ))
+    .unwrap();
 
     if !cmd_output.status.success() {
         // Don't return from function with error's at this point
         Err::<(), _>(ProjectError::FailedToRunProcess(
-            format!("git submodule update --init --recursive"),
+            "git submodule update --init --recursive".to_string(),
             cmd_output.status.code(),
         ))
         .display_error();
@@ -256,18 +486,18 @@ fn add_submodule(
     let cmd = match (&tag, &branch) {
         (Some(tag), Some(branch)) => {
             let tags = format!("tags/{}", tag);
-            println!("Switching to '{}' on branch '{}'", tags, branch);
+            println!("{}", t!("Switching to '{}' on branch '{}'", tags, branch));
             Some(duct::cmd!("git", "checkout", tags, "-b", branch))
         }
 
         (Some(tag), None) => {
             let tags = format!("tags/{}", tag);
-            println!("Switching to '{}'", tags);
+            println!("{}", t!("Switching to '{}'", tags));
             Some(duct::cmd!("git", "checkout", tags))
         }
 
         (None, Some(branch)) => {
-            println!("Switching to branch '{}'", branch);
+            println!("{}", t!("Switching to branch '{}'", branch));
             Some(duct::cmd!("git", "checkout", "-b", branch))
         }
 
@@ -286,7 +516,7 @@ fn add_submodule(
         if !cmd_output.status.success() {
             // Don't return from function with error's at this point
             Err::<(), _>(ProjectError::FailedToRunProcess(
-                format!("git checkout..."),
+                "git checkout...".to_string(),
                 cmd_output.status.code(),
             ))
             .display_error();
@@ -321,21 +551,53 @@ pub fn add_cached_dependency(config: &mut ConfigFile) -> Result<(), ProjectError
     let cache = get_cache()?;
 
     if cache.git_submodules.is_empty() {
-        println!("No cached dependencies available.");
+        println!("{}", t!("No cached dependencies available."));
         return Ok(());
     }
 
-    let selection = inquire::MultiSelect::new(
-        "Choose a dependency:",
-        cache.git_submodules.iter().map(|val| &val.name).collect(),
-    )
-    .raw_prompt()
-    .unwrap();
+    let selection = answers::multi_select(
+        "add.cached",
+        inquire::MultiSelect::new(
+            t!("Choose a dependency:"),
+            cache.git_submodules.iter().map(|val| &val.name).collect(),
+        ),
+    )?;
+
+    let cpm = use_cpm()?;
 
     let val = selection
         .into_iter()
         .map(|entry| {
             let CacheSubmodule { submodule, .. } = cache.git_submodules.get(entry.index).unwrap();
+            let local_setup = &submodule.local_setup;
+
+            match (&local_setup.local_type, cpm) {
+                (config::LocalType::CMake, true) => {
+                    config.dependencies.cpm.push(FetchDependency {
+                        name: local_setup.name.clone(),
+                        repo: submodule.repo.clone(),
+                        tag: submodule.tag.clone().or(submodule.branch.clone()),
+                        custom_link_name: None,
+                        variables: local_setup.variables.clone(),
+                        extra_cmake: None,
+                    });
+
+                    get_is_project_dependency(config, local_setup.name.clone())?;
+                    return Ok(());
+                }
+
+                // CPM only adds projects that have their own CMakeLists.txt
+                (config::LocalType::Source { .. }, true) => println!(
+                    "{}",
+                    t!(
+                        "{} '{}' is built from its sources, adding it as a submodule",
+                        t!("warning:").yellow(),
+                        local_setup.name
+                    )
+                ),
+
+                (_, false) => {}
+            }
 
             add_submodule(
                 &submodule.repo,
@@ -343,12 +605,14 @@ pub fn add_cached_dependency(config: &mut ConfigFile) -> Result<(), ProjectError
                 submodule.branch.as_ref(),
             )?;
 
+            patches::apply_patches(&submodule.local_setup.name, &submodule.local_setup.path)?;
+
             config
                 .dependencies
                 .local
                 .push(submodule.local_setup.clone());
 
-            get_is_project_dependency(config, submodule.local_setup.name.clone());
+            get_is_project_dependency(config, submodule.local_setup.name.clone())?;
 
             Ok(())
         })
@@ -362,32 +626,636 @@ pub fn add_cached_dependency(config: &mut ConfigFile) -> Result<(), ProjectError
 }
 
 pub fn add_find_dependency(config: &mut ConfigFile) -> Result<(), ProjectError> {
-    let name = inquire::Text::new("Dependency Name:")
-        .with_validator(inquire::validator::ValueRequiredValidator::default())
-        .prompt()
-        .unwrap();
-
-    let required = inquire::Confirm::new("Dependency required?")
-        .with_default(true)
-        .with_placeholder("Y/n")
-        .prompt()
-        .unwrap();
-
-    let custom_link_name = inquire::Text::new("Specify Dependency link name: (optional)")
-        .prompt_skippable()
-        .unwrap()
-        .and_then(|val| match val.is_empty() {
-            true => None,
-            false => Some(val),
-        });
+    let name = answers::text(
+        "add.name",
+        inquire::Text::new(t!("Dependency Name:"))
+            .with_validator(inquire::validator::ValueRequiredValidator::default()),
+    )?;
+
+    let required = answers::confirm(
+        "add.required",
+        inquire::Confirm::new(t!("Dependency required?"))
+            .with_default(true)
+            .with_placeholder("Y/n"),
+    )?;
+
+    let custom_link_name = answers::text_skippable(
+        "add.link_name",
+        inquire::Text::new(t!("Specify Dependency link name: (optional)")),
+    )?
+    .and_then(|val| match val.is_empty() {
+        true => None,
+        false => Some(val),
+    });
+
+    let prefix_path = answers::text_skippable(
+        "add.prefix_path",
+        inquire::Text::new(t!("Install prefix to search: (optional)")).with_help_message(t!(
+            "For packages installed outside the system folders, eg. ~/opt/"
+        )),
+    )?
+    .unwrap_or_default();
 
     config.dependencies.find.push(FindDependency {
         name: name.clone(),
         required,
         custom_link_name: custom_link_name.clone(),
+        extra_cmake: None,
     });
+    add_prefix_path(config, prefix_path);
+
+    get_is_project_dependency(config, custom_link_name.unwrap_or(name))?;
+
+    Ok(())
+}
+
+/// Adds a folder for find_package to search, unless it's empty or already there
+pub(crate) fn add_prefix_path(config: &mut ConfigFile, path: String) {
+    let path = path.trim();
+    let prefix_paths = &mut config.dependencies.prefix_paths;
+
+    if !path.is_empty() && !prefix_paths.iter().any(|existing| existing == path) {
+        prefix_paths.push(path.into());
+    }
+}
+
+/// Options shared by the non-interactive forms of `cmm add`
+pub struct AddArgs {
+    /// Repo url, path, package name or conan reference, depending on the kind
+    pub positional: Option<String>,
+    pub name: Option<String>,
+    pub tag: Option<String>,
+    pub branch: Option<String>,
+    /// Build the dependency from its sources: `all`, `root` or `header`
+    pub files: Option<String>,
+    pub link_name: Option<String>,
+    pub package: Option<String>,
+    pub variables: Vec<(String, String)>,
+    pub required: bool,
+    /// Install prefix to search for a find dependency
+    pub prefix_path: Option<String>,
+    /// Link the dependency to the project
+    pub link: bool,
+    pub cpm: bool,
+}
+
+impl Default for AddArgs {
+    fn default() -> Self {
+        Self {
+            positional: None,
+            name: None,
+            tag: None,
+            branch: None,
+            files: None,
+            link_name: None,
+            package: None,
+            variables: Vec::new(),
+            required: false,
+            prefix_path: None,
+            link: true,
+            cpm: false,
+        }
+    }
+}
+
+impl AddArgs {
+    fn local_type(&self) -> Result<config::LocalType, ProjectError> {
+        let files = match self.files.as_deref() {
+            None => return Ok(config::LocalType::CMake),
+            Some("all") => config::ProjectFiles::all(),
+            Some("root") => config::ProjectFiles::root(),
+            Some("header") => config::ProjectFiles::header(),
+            Some(other) => return Err(ProjectError::UnknownArgument(other.into())),
+        };
+
+        Ok(config::LocalType::Source {
+            files,
+            dependencies: Vec::new(),
+        })
+    }
+
+    fn push_local(
+        self,
+        config: &mut ConfigFile,
+        path: String,
+        tag: Option<String>,
+    ) -> Result<(), ProjectError> {
+        let name = match self.name.clone() {
+            Some(name) => name,
+            None => PathBuf::from(&path)
+                .file_name()
+                .unwrap_or_default()
+                .to_string_lossy()
+                .to_string(),
+        };
+
+        config.dependencies.local.push(LocalDependency {
+            path: path.clone(),
+            name: name.clone(),
+            local_type: self.local_type()?,
+            variables: self.variables,
+            tag,
+            monorepo: false,
+            extra_cmake: None,
+        });
 
-    get_is_project_dependency(config, custom_link_name.unwrap_or(name));
+        if self.link {
+            config.dependencies.project_dependencies.push(name.clone());
+        }
+
+        patches::apply_patches(&name, &path)
+    }
+
+    /// Adds a FetchContent dependency, or a CPM one with `cpm`
+    fn push_fetch(self, config: &mut ConfigFile, cpm: bool) -> Result<(), ProjectError> {
+        let repo = self
+            .positional
+            .ok_or(ProjectError::MissingArgument("git repo url".into()))?;
+
+        let name = self
+            .name
+            .unwrap_or_else(|| submodule_name(&repo).to_owned());
+
+        let dependency = FetchDependency {
+            name: name.clone(),
+            repo,
+            tag: self.tag.or(self.branch),
+            custom_link_name: self.link_name.clone(),
+            variables: self.variables,
+            extra_cmake: None,
+        };
+
+        match cpm {
+            true => config.dependencies.cpm.push(dependency),
+            false => config.dependencies.fetch.push(dependency),
+        }
+
+        if self.link {
+            config
+                .dependencies
+                .project_dependencies
+                .push(self.link_name.unwrap_or(name));
+        }
+
+        Ok(())
+    }
+}
+
+/// Adds a dependency described entirely by command line arguments, without any prompts.
+/// eg. `cmm add git <REPO> --tag v1.2` or `cmm add find SDL2 --required`
+pub fn add_from_args(
+    config: &mut ConfigFile,
+    kind: &str,
+    args: AddArgs,
+) -> Result<(), ProjectError> {
+    match kind {
+        "git" if args.cpm => args.push_fetch(config, true)?,
+
+        "git" => {
+            let repo = args
+                .positional
+                .clone()
+                .ok_or(ProjectError::MissingArgument("git repo url".into()))?;
+
+            let folder_path = add_submodule(&repo, args.tag.as_ref(), args.branch.as_ref())?;
+            let tag = args.tag.clone();
+            args.push_local(config, folder_path, tag)?;
+        }
+
+        "local" => {
+            let path = args
+                .positional
+                .as_deref()
+                .map(path_formater)
+                .ok_or(ProjectError::MissingArgument("dependency path".into()))?;
+
+            if !PathBuf::from(&path).is_dir() {
+                return Err(ProjectError::CannotOpenFile(
+                    path.into(),
+                    "Path doesn't exist".to_owned(),
+                ));
+            }
+
+            args.push_local(config, path, None)?;
+        }
+
+        "fetch" => args.push_fetch(config, false)?,
+
+        "conan" => {
+            let reference = args.positional.ok_or(ProjectError::MissingArgument(
+                "conan reference <NAME/VERSION>".into(),
+            ))?;
+
+            let (name, version) = parse_conan_reference(&reference)?;
+
+            let conan = ConanDependency {
+                name,
+                version,
+                package: args.package,
+                custom_link_name: args.link_name,
+                extra_cmake: None,
+            };
+
+            if args.link {
+                config
+                    .dependencies
+                    .project_dependencies
+                    .push(conan.link_name());
+            }
+
+            config.dependencies.conan.push(conan);
+        }
+
+        "find" => {
+            let name = args
+                .positional
+                .ok_or(ProjectError::MissingArgument("package name".into()))?;
+
+            config.dependencies.find.push(FindDependency {
+                name: name.clone(),
+                required: args.required,
+                custom_link_name: args.link_name.clone(),
+                extra_cmake: None,
+            });
+            add_prefix_path(config, args.prefix_path.unwrap_or_default());
+
+            if args.link {
+                config
+                    .dependencies
+                    .project_dependencies
+                    .push(args.link_name.unwrap_or(name));
+            }
+        }
+
+        _ => return Err(ProjectError::UnknownArgument(kind.into())),
+    }
+
+    Ok(())
+}
+
+/// Removes a name from everything that links against it
+fn unlink(config: &mut ConfigFile, name: &str) {
+    config
+        .dependencies
+        .project_dependencies
+        .retain(|dep| dep != name);
+
+    config
+        .targets
+        .iter_mut()
+        .for_each(|target| target.dependencies.retain(|dep| dep != name));
+}
+
+/// Removes every local dependency at `path` from the config
+pub fn remove_local(config: &mut ConfigFile, path: &str) {
+    let removed = config
+        .dependencies
+        .local
+        .iter()
+        .filter(|local| local.path == path)
+        .map(|local| local.name.clone())
+        .collect::<Vec<_>>();
+
+    config.dependencies.local.retain(|local| local.path != path);
+    removed.iter().for_each(|name| unlink(config, name));
+}
+
+/// `cmm remove <NAME>`. Submodules are deleted without asking with `purge`, or left on
+/// disk with `keep_files`.
+pub fn remove_dependency(
+    config: &mut ConfigFile,
+    name: &str,
+    purge: bool,
+    keep_files: bool,
+) -> Result<(), ProjectError> {
+    let name = name.to_owned();
+
+    // Find dependencies
+    if let Some(index) = config
+        .dependencies
+        .find
+        .iter()
+        .position(|find| find.name == name)
+    {
+        let find = config.dependencies.find.remove(index);
+        unlink(config, find.custom_link_name.as_ref().unwrap_or(&find.name));
+
+        status!(
+            "{}",
+            t!("{} find dependency '{}'", t!("Removed").green(), name)
+        );
+        return Ok(());
+    }
+
+    // Fetch dependencies
+    if let Some(index) = config
+        .dependencies
+        .fetch
+        .iter()
+        .position(|fetch| fetch.name == name)
+    {
+        let fetch = config.dependencies.fetch.remove(index);
+        unlink(
+            config,
+            fetch.custom_link_name.as_ref().unwrap_or(&fetch.name),
+        );
+
+        status!(
+            "{}",
+            t!("{} fetch dependency '{}'", t!("Removed").green(), name)
+        );
+        return Ok(());
+    }
+
+    // CPM dependencies
+    if let Some(index) = config
+        .dependencies
+        .cpm
+        .iter()
+        .position(|cpm| cpm.name == name)
+    {
+        let cpm = config.dependencies.cpm.remove(index);
+        unlink(config, cpm.custom_link_name.as_ref().unwrap_or(&cpm.name));
+
+        status!(
+            "{}",
+            t!("{} cpm dependency '{}'", t!("Removed").green(), name)
+        );
+        return Ok(());
+    }
+
+    // Conan dependencies
+    if let Some(index) = config
+        .dependencies
+        .conan
+        .iter()
+        .position(|conan| conan.name == name)
+    {
+        let conan = config.dependencies.conan.remove(index);
+        unlink(config, &conan.link_name());
+
+        status!(
+            "{}",
+            t!("{} conan dependency '{}'", t!("Removed").green(), name)
+        );
+        return Ok(());
+    }
+
+    // Local dependencies
+    let local = config
+        .dependencies
+        .local
+        .iter()
+        .find(|local| local.name == name)
+        .cloned()
+        .ok_or(ProjectError::UnknownDependency(name.clone()))?;
+
+    remove_local(config, &local.path);
+    status!(
+        "{}",
+        t!(
+            "{} dependency '{}' from config",
+            t!("Removed").green(),
+            name
+        )
+    );
+
+    // Monorepo dependencies are other project code, so never touch their files
+    if local.monorepo || keep_files {
+        return Ok(());
+    }
+
+    let submodule = read_submodules()
+        .unwrap_or_default()
+        .into_iter()
+        .find(|submodule| submodule.path == local.path);
+
+    let Some(submodule) = submodule else {
+        return Ok(());
+    };
+
+    let message = t!("Also remove submodule and delete '{}'?", submodule.path);
+    let delete = purge
+        || (answers::can_answer("remove.delete_files")
+            && answers::confirm(
+                "remove.delete_files",
+                inquire::Confirm::new(&message)
+                    .with_default(false)
+                    .with_placeholder("y/N"),
+            )?);
+
+    if delete {
+        remove_submodule(&submodule)?;
+        status!(
+            "{}",
+            t!("{} submodule '{}'", t!("Removed").green(), submodule.path)
+        );
+    }
+
+    Ok(())
+}
+
+/// What linking `name` adds to a link list: a dependency's link name, or the name itself
+/// for link names and the project's targets. `None` when the config doesn't declare it.
+fn declared_link_name(config: &ConfigFile, name: &str) -> Option<String> {
+    let dependencies = &config.dependencies;
+
+    let find = dependencies
+        .find
+        .iter()
+        .find(|find| find.name == name)
+        .map(|find| find.custom_link_name.as_ref().unwrap_or(&find.name).clone());
+    let fetch = || {
+        dependencies
+            .fetch
+            .iter()
+            .chain(&dependencies.cpm)
+            .find(|fetch| fetch.name == name)
+            .map(|fetch| {
+                fetch
+                    .custom_link_name
+                    .as_ref()
+                    .unwrap_or(&fetch.name)
+                    .clone()
+            })
+    };
+    let conan = || {
+        dependencies
+            .conan
+            .iter()
+            .find(|conan| conan.name == name)
+            .map(|conan| conan.link_name())
+    };
+
+    find.or_else(fetch).or_else(conan).or_else(|| {
+        validate::link_names(config)
+            .contains(&name.to_owned())
+            .then(|| name.to_owned())
+    })
+}
+
+/// The link list of the project (named after it) or one of its targets
+fn link_list<'a>(config: &'a mut ConfigFile, target: &str) -> Option<&'a mut Vec<String>> {
+    match target == config.project.name {
+        true => Some(&mut config.dependencies.project_dependencies),
+        false => config
+            .targets
+            .iter_mut()
+            .find(|existing| existing.name == target)
+            .map(|existing| &mut existing.dependencies),
+    }
+}
+
+/// Which of `candidates` to change: the `--target`s given, else asked once the config has
+/// targets. Without anyone to ask, the first candidate or all of them with `all`.
+fn pick_link_targets(
+    config: &ConfigFile,
+    key: &str,
+    prompt: &str,
+    candidates: Vec<String>,
+    targets: &[String],
+    all: bool,
+) -> Result<Vec<String>, ProjectError> {
+    if let Some(unknown) = targets.iter().find(|target| {
+        **target != config.project.name
+            && !config
+                .targets
+                .iter()
+                .any(|existing| existing.name == **target)
+    }) {
+        return Err(ProjectError::UnknownTarget(unknown.clone()));
+    }
+
+    if !targets.is_empty() {
+        return Ok(targets.to_vec());
+    }
+
+    if candidates.len() <= 1 || !answers::can_answer(key) {
+        let count = if all { candidates.len() } else { 1 };
+        return Ok(candidates.into_iter().take(count).collect());
+    }
+
+    let defaults = match all {
+        true => (0..candidates.len()).collect(),
+        false => vec![0],
+    };
+
+    Ok(answers::multi_select(
+        key,
+        inquire::MultiSelect::new(prompt, candidates).with_default(&defaults),
+    )?
+    .into_iter()
+    .map(|option| option.value)
+    .collect())
+}
+
+/// `cmm link <NAME>`. Links a dependency the config already declares into the project, or
+/// the `targets` given, without going through `cmm add` again.
+pub fn link_dependency(
+    config: &mut ConfigFile,
+    name: &str,
+    targets: &[String],
+) -> Result<(), ProjectError> {
+    let link_name = declared_link_name(config, name)
+        .ok_or_else(|| ProjectError::UnknownDependency(name.to_owned()))?;
+
+    if targets.contains(&link_name) {
+        return Err(ProjectError::SelfLink(link_name));
+    }
+
+    // A target can't link itself
+    let candidates = std::iter::once(config.project.name.clone())
+        .chain(config.targets.iter().map(|target| target.name.clone()))
+        .filter(|target| *target != link_name)
+        .collect();
+
+    let targets = pick_link_targets(
+        config,
+        "link.targets",
+        &t!("Link '{}' into:", link_name),
+        candidates,
+        targets,
+        false,
+    )?;
+
+    for target in targets {
+        let list = link_list(config, &target).unwrap();
+
+        match list.contains(&link_name) {
+            true => status!("{}", t!("'{}' already links '{}'", target, link_name)),
+            false => {
+                list.push(link_name.clone());
+                status!(
+                    "{}",
+                    t!("{} '{}' into '{}'", t!("Linked").green(), link_name, target)
+                );
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// `cmm unlink <NAME>`. Stops the project, or the `targets` given, linking a dependency
+/// while keeping it in the config.
+pub fn unlink_dependency(
+    config: &mut ConfigFile,
+    name: &str,
+    targets: &[String],
+) -> Result<(), ProjectError> {
+    // Names that aren't declared anymore can still be left in a link list
+    let link_name = declared_link_name(config, name).unwrap_or_else(|| name.to_owned());
+
+    let candidates = std::iter::once((
+        &config.project.name,
+        &config.dependencies.project_dependencies,
+    ))
+    .chain(
+        config
+            .targets
+            .iter()
+            .map(|target| (&target.name, &target.dependencies)),
+    )
+    .filter(|(_, list)| list.contains(&link_name))
+    .map(|(target, _)| target.clone())
+    .collect::<Vec<_>>();
+
+    if candidates.is_empty() {
+        notice!(
+            "{}",
+            t!("{} nothing links '{}'", t!("warning:").yellow(), link_name)
+        );
+        return Ok(());
+    }
+
+    let targets = pick_link_targets(
+        config,
+        "unlink.targets",
+        &t!("Unlink '{}' from:", link_name),
+        candidates,
+        targets,
+        true,
+    )?;
+
+    for target in targets {
+        let list = link_list(config, &target).unwrap();
+
+        match list.contains(&link_name) {
+            true => {
+                list.retain(|linked| *linked != link_name);
+                status!(
+                    "{}",
+                    t!(
+                        "{} '{}' from '{}'",
+                        t!("Unlinked").green(),
+                        link_name,
+                        target
+                    )
+                );
+            }
+            false => status!("{}", t!("'{}' doesn't link '{}'", target, link_name)),
+        }
+    }
 
     Ok(())
 }
@@ -0,0 +1,193 @@
+use std::path::Path;
+
+use colored::Colorize;
+
+use cmakemake_core::{
+    cmake_version,
+    config::ConfigFile,
+    error::ProjectError,
+    generator,
+    messages::t,
+    requires::{install_hint, matches_requirement, tool_version, Version},
+    util::get_config,
+    CONFIG_NAME,
+};
+
+/// Compilers looked for when CXX isn't set, in the order cmake prefers them
+const COMPILERS: [&str; 4] = ["c++", "g++", "clang++", "cl"];
+
+/// A program `cmm doctor` looks for
+struct Tool {
+    /// Shown in place of the program, eg. "compiler"
+    label: String,
+    program: String,
+    /// Versions the project accepts, like `>=3.15, <4`
+    requirement: Option<String>,
+    /// Only one of the optional tools is needed, which `check_generators` looks at
+    optional: bool,
+}
+
+impl Tool {
+    fn new(program: &str) -> Self {
+        Self {
+            label: program.to_owned(),
+            program: program.to_owned(),
+            requirement: None,
+            optional: false,
+        }
+    }
+}
+
+/// The C++ compiler cmake would pick: CXX, else the first compiler found
+fn compiler() -> Tool {
+    let program = std::env::var("CXX")
+        .ok()
+        .filter(|cxx| !cxx.is_empty())
+        .or_else(|| {
+            COMPILERS
+                .iter()
+                .find(|compiler| tool_version(compiler).is_some())
+                .map(|compiler| compiler.to_string())
+        })
+        .unwrap_or_else(|| match cfg!(windows) {
+            true => "cl".into(),
+            false => "g++".into(),
+        });
+
+    Tool {
+        label: t!("compiler").into(),
+        program,
+        requirement: None,
+        optional: false,
+    }
+}
+
+/// Everything the project needs, with the versions its config asks for
+fn tools(config: Option<&ConfigFile>) -> Vec<Tool> {
+    let mut tools = vec![Tool::new("cmake"), compiler(), Tool::new("git")];
+
+    for program in ["ninja", "make"] {
+        tools.push(Tool {
+            optional: true,
+            ..Tool::new(program)
+        });
+    }
+
+    let config = match config {
+        Some(config) => config,
+        None => return tools,
+    };
+
+    // The minimum the generated file is written with, which is raised for newer features
+    tools[0].requirement = Some(format!(">={}", cmake_version::required_version(config)));
+
+    if !config.dependencies.conan.is_empty() {
+        tools.push(Tool::new("conan"));
+    }
+
+    for (program, requirement) in &config.requires {
+        match tools.iter_mut().find(|tool| &tool.program == program) {
+            Some(tool) => {
+                tool.optional = false;
+                tool.requirement = Some(match &tool.requirement {
+                    Some(existing) => format!("{}, {}", existing, requirement),
+                    None => requirement.clone(),
+                });
+            }
+            None => tools.push(Tool {
+                requirement: Some(requirement.clone()),
+                ..Tool::new(program)
+            }),
+        }
+    }
+
+    tools
+}
+
+/// Prints how `tool` was found, returning whether it's a problem
+fn check_tool(tool: &Tool) -> bool {
+    let found = tool_version(&tool.program);
+    let version = found
+        .as_ref()
+        .map_or_else(|| "-".to_owned(), Version::to_string);
+
+    let (status, problem) = match (&found, &tool.requirement) {
+        (None, _) if tool.optional => (t!("not found").dimmed(), false),
+        (None, _) => (t!("not found").red(), true),
+        (Some(found), Some(requirement)) if !matches_requirement(found, requirement) => {
+            (t!("needs {}", requirement).red(), true)
+        }
+        (Some(_), _) => (t!("ok").green(), false),
+    };
+
+    let label = match tool.label == tool.program {
+        true => tool.label.clone(),
+        false => format!("{} ({})", tool.label, tool.program),
+    };
+    println!("\t{: <20}{: <14}{}", label, version, status);
+
+    if problem {
+        println!(
+            "\t    {} {}",
+            t!("hint:").cyan(),
+            install_hint(&tool.program)
+        );
+    }
+
+    problem
+}
+
+/// cmake needs a build tool for one of its generators, returning whether there's none
+fn check_generators() -> bool {
+    let installed = generator::installed();
+
+    match installed.is_empty() {
+        true => {
+            println!(
+                "\t{: <20}{: <14}{}",
+                t!("generators"),
+                "-",
+                t!("no build tool found").red()
+            );
+            println!("\t    {} {}", t!("hint:").cyan(), install_hint("ninja"));
+        }
+        false => println!("\t{: <20}{}", t!("generators"), installed.join(", ")),
+    }
+
+    installed.is_empty()
+}
+
+/// `cmm doctor`. Looks for the tools building needs and checks their versions against
+/// what the config asks for, with a hint to fix each problem. Works outside projects too,
+/// just without the version checks.
+pub fn check_environment() -> Result<(), ProjectError> {
+    let config = match Path::new(CONFIG_NAME).exists() {
+        true => Some(get_config()?),
+        false => None,
+    };
+
+    println!("{}", t!("Checking the tools cmm needs"));
+
+    let mut problems = tools(config.as_ref())
+        .iter()
+        .filter(|tool| check_tool(tool))
+        .count();
+
+    if check_generators() {
+        problems += 1;
+    }
+
+    if problems > 0 {
+        return Err(ProjectError::MissingTools(problems));
+    }
+
+    println!(
+        "{}",
+        t!(
+            "{} checking tools, no problems found",
+            t!("Finished").green().bold()
+        )
+    );
+
+    Ok(())
+}
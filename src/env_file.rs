@@ -0,0 +1,148 @@
+use std::{path::Path, sync::Mutex};
+
+use colored::Colorize;
+
+use crate::{messages::t, output::notice};
+
+/// Files variables are loaded from, later ones overriding earlier ones. `.env.local` is for
+/// values kept out of version control, like API keys.
+pub const ENV_FILES: [&str; 2] = [".env", ".env.local"];
+
+/// Variables cmm set from env files, with the file each came from
+static APPLIED: Mutex<Vec<(String, &'static str)>> = Mutex::new(Vec::new());
+
+/// Unquotes a value. Double quotes understand `\n`, `\t`, `\"` and `\\`, single quotes
+/// keep everything as it is. Anything after the closing quote, or after ` #` in unquoted
+/// values, is a comment.
+fn parse_value(value: &str) -> String {
+    if let Some(quoted) = value.strip_prefix('\'') {
+        return quoted.split('\'').next().unwrap_or_default().to_owned();
+    }
+
+    if let Some(quoted) = value.strip_prefix('"') {
+        let mut unescaped = String::with_capacity(quoted.len());
+        let mut chars = quoted.chars();
+
+        while let Some(c) = chars.next() {
+            match c {
+                '"' => break,
+                '\\' => match chars.next() {
+                    Some('n') => unescaped.push('\n'),
+                    Some('t') => unescaped.push('\t'),
+                    Some(other) => unescaped.push(other),
+                    None => unescaped.push('\\'),
+                },
+                c => unescaped.push(c),
+            }
+        }
+
+        return unescaped;
+    }
+
+    match value.find(" #") {
+        Some(comment) => value[..comment].trim_end().to_owned(),
+        None => value.to_owned(),
+    }
+}
+
+/// `NAME=value` lines of an env file, skipping blank lines and `#` comments. An `export`
+/// in front is allowed so the file can be sourced by a shell too. Returns the line numbers
+/// of lines that aren't assignments.
+pub fn parse(contents: &str) -> (Vec<(String, String)>, Vec<usize>) {
+    let mut variables = Vec::new();
+    let mut invalid = Vec::new();
+
+    for (index, line) in contents.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+
+        let line = line.strip_prefix("export ").unwrap_or(line);
+
+        match line.split_once('=') {
+            Some((name, value)) if !name.trim().is_empty() && !name.trim().contains(' ') => {
+                variables.push((name.trim().to_owned(), parse_value(value.trim())))
+            }
+            _ => invalid.push(index + 1),
+        }
+    }
+
+    (variables, invalid)
+}
+
+/// Variables from the env files in `dir`, with the file each came from. A variable in
+/// several files keeps the value of the last one.
+pub fn variables(dir: &Path) -> Vec<(String, String, &'static str)> {
+    let mut variables: Vec<(String, String, &'static str)> = Vec::new();
+
+    for file in ENV_FILES {
+        let Ok(contents) = std::fs::read_to_string(dir.join(file)) else {
+            continue;
+        };
+
+        let (parsed, invalid) = parse(&contents);
+
+        for line in invalid {
+            notice!(
+                "{}",
+                t!(
+                    "{} ignoring line {} of {}, it isn't NAME=value",
+                    t!("warning:").yellow(),
+                    line,
+                    file
+                )
+            );
+        }
+
+        for (name, value) in parsed {
+            variables.retain(|(existing, ..)| *existing != name);
+            variables.push((name, value, file));
+        }
+    }
+
+    variables
+}
+
+/// Exports the current folder's env files to cmm's environment, so cmake, the build, the
+/// program `cmm run` starts and everything else cmm runs see them. Variables already set
+/// in the environment win, so they can be overridden for one command. Returns the names
+/// set, for `unapply`.
+pub fn apply() -> Vec<String> {
+    let mut applied = APPLIED.lock().unwrap();
+    let mut names = Vec::new();
+
+    for (name, value, file) in variables(Path::new(".")) {
+        if std::env::var_os(&name).is_some() {
+            continue;
+        }
+
+        std::env::set_var(&name, value);
+        applied.push((name.clone(), file));
+        names.push(name);
+    }
+
+    names
+}
+
+/// Takes variables set by `apply` back out, eg. when leaving a workspace member
+pub fn unapply(names: &[String]) {
+    APPLIED
+        .lock()
+        .unwrap()
+        .retain(|(name, _)| !names.contains(name));
+
+    for name in names {
+        std::env::remove_var(name);
+    }
+}
+
+/// The env file a variable came from, when cmm set it from one
+pub fn source(name: &str) -> Option<&'static str> {
+    APPLIED
+        .lock()
+        .unwrap()
+        .iter()
+        .find(|(applied, _)| applied == name)
+        .map(|(_, file)| *file)
+}
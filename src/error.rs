@@ -2,104 +2,650 @@ use std::{error::Error, path::PathBuf};
 
 use colored::Colorize;
 
-use crate::CONFIG_NAME;
+use crate::{messages::t, CONFIG_BACKUP_NAME, CONFIG_NAME};
+
+/// Exit codes `cmm` finishes with, so scripts can tell failures apart. They stay the same
+/// between releases. Clap exits with 2 itself for arguments it can't parse.
+pub const EXIT_SUCCESS: i32 = 0;
+/// Anything not covered below, mostly files that couldn't be read or written
+pub const EXIT_FAILURE: i32 = 1;
+/// Missing or invalid arguments, or a prompt that has no answer without a terminal
+pub const EXIT_USAGE: i32 = 2;
+/// The project or its config is in a state the command can't work with
+pub const EXIT_CONFIG: i32 = 3;
+/// cmake, the compiler or another tool the command runs failed
+pub const EXIT_BUILD: i32 = 4;
+/// The project built but its tests failed
+pub const EXIT_TESTS: i32 = 5;
+/// Another cmm process holds the project lock, rerunning later or with --wait can work
+pub const EXIT_LOCKED: i32 = 6;
+/// A prompt was cancelled with esc or ctrl-c, like shells report an interrupt
+pub const EXIT_CANCELLED: i32 = 130;
 
 #[derive(Debug)]
 pub enum ProjectError {
     MissingName,
+    MissingArgument(String),
     UnknownArgument(String),
     InvalidProjectDirectory,
+    ProjectAlreadyExists,
+    FileAlreadyExists(PathBuf),
+    PathOutsideProject(PathBuf),
+    InvalidIdentifier(String),
+    InSourceBuild,
+    EditedCMakeLists,
+    MissingInstallRules,
+    MissingPackaging,
+    NoTests,
+    NothingToRun,
+    FailingTests(Vec<String>),
+    ProjectLocked(Option<u32>),
+    SymlinkedBuildDir(PathBuf, PathBuf),
+    OutsideProject(PathBuf, PathBuf),
 
     FailedToCreateFolder(PathBuf, String),
     FailedToInitGit(String),
+    FailedToOpenGit(String),
     FailedToCreateFile(PathBuf, String),
     CannotOpenFile(PathBuf, String),
+    CorruptConfig(String),
+    FailedToRemove(PathBuf, String),
+
+    DirtyDependencies(Vec<String>),
+    LockfileOutdated(Vec<String>),
+    NewWarnings(Vec<String>),
+    ValidationFailed(usize),
+    UnknownDependency(String),
+    UnknownTag(String, String),
+    UnknownProfile(String),
+    UnknownTarget(String),
+    /// `cmm link` asked to link a target into itself
+    SelfLink(String),
+    UnknownMember(String),
+    NotInWorkspace,
+    FailedToApplyPatch(String, String),
 
     FailedToRunProcess(String, Option<i32>),
+    FailedToStartProcess(String, String),
+    MissingProgram(String),
+    GeneratorNotInstalled(String, Vec<String>),
+    UnmetRequirement(String, Option<String>, String),
+    MissingTools(usize),
+    /// A prompt that can't be asked without a terminal and has no answer given
+    MissingAnswer(String),
+    /// A prompt was cancelled with esc or ctrl-c
+    Cancelled,
+    PromptFailed(String, String),
 }
 
 impl Error for ProjectError {}
 
+impl ProjectError {
+    /// Which of the `EXIT_*` codes the error ends `cmm` with
+    pub fn exit_code(&self) -> i32 {
+        match self {
+            ProjectError::MissingName
+            | ProjectError::MissingArgument(_)
+            | ProjectError::UnknownArgument(_)
+            | ProjectError::InvalidIdentifier(_)
+            | ProjectError::MissingAnswer(_) => EXIT_USAGE,
+
+            ProjectError::InvalidProjectDirectory
+            | ProjectError::ProjectAlreadyExists
+            | ProjectError::FileAlreadyExists(_)
+            | ProjectError::PathOutsideProject(_)
+            | ProjectError::InSourceBuild
+            | ProjectError::EditedCMakeLists
+            | ProjectError::MissingInstallRules
+            | ProjectError::MissingPackaging
+            | ProjectError::NoTests
+            | ProjectError::NothingToRun
+            | ProjectError::SymlinkedBuildDir(..)
+            | ProjectError::OutsideProject(..)
+            | ProjectError::DirtyDependencies(_)
+            | ProjectError::LockfileOutdated(_)
+            | ProjectError::ValidationFailed(_)
+            | ProjectError::UnknownDependency(_)
+            | ProjectError::UnknownTag(..)
+            | ProjectError::UnknownProfile(_)
+            | ProjectError::UnknownTarget(_)
+            | ProjectError::SelfLink(_)
+            | ProjectError::UnknownMember(_)
+            | ProjectError::NotInWorkspace
+            | ProjectError::FailedToApplyPatch(..)
+            | ProjectError::CorruptConfig(_)
+            | ProjectError::UnmetRequirement(..) => EXIT_CONFIG,
+
+            ProjectError::NewWarnings(_)
+            | ProjectError::FailedToRunProcess(..)
+            | ProjectError::FailedToStartProcess(..)
+            | ProjectError::MissingProgram(_)
+            | ProjectError::GeneratorNotInstalled(..)
+            | ProjectError::MissingTools(_) => EXIT_BUILD,
+
+            ProjectError::FailingTests(_) => EXIT_TESTS,
+
+            ProjectError::ProjectLocked(_) => EXIT_LOCKED,
+
+            ProjectError::Cancelled => EXIT_CANCELLED,
+
+            ProjectError::FailedToCreateFolder(..)
+            | ProjectError::FailedToInitGit(_)
+            | ProjectError::FailedToOpenGit(_)
+            | ProjectError::FailedToCreateFile(..)
+            | ProjectError::CannotOpenFile(..)
+            | ProjectError::FailedToRemove(..)
+            | ProjectError::PromptFailed(..) => EXIT_FAILURE,
+        }
+    }
+}
+
 impl std::fmt::Display for ProjectError {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
             ProjectError::MissingName => write!(
                 f,
-                "{} {}",
-                "error:".red(),
-                "please provide a suitable project name",
+                "{}",
+                t!(
+                    "{} please provide a suitable project name",
+                    t!("error:").red(),
+                )
+            ),
+
+            ProjectError::MissingArgument(argument) => write!(
+                f,
+                "{}",
+                t!(
+                    "{} missing argument: {}",
+                    t!("error:").red(),
+                    argument.bold(),
+                )
             ),
 
             ProjectError::UnknownArgument(argument) => write!(
                 f,
-                "{} {} '{}'",
-                "error:".red(),
-                "unknown argument",
-                argument.bold(),
+                "{}",
+                t!(
+                    "{} unknown argument '{}'",
+                    t!("error:").red(),
+                    argument.bold(),
+                )
             ),
 
             ProjectError::InvalidProjectDirectory => write!(
                 f,
-                "{} {} {}",
-                "error:".red(),
-                "current directory doesn't contain a",
-                CONFIG_NAME,
+                "{}",
+                t!(
+                    "{} current directory doesn't contain a {}",
+                    t!("error:").red(),
+                    CONFIG_NAME,
+                )
+            ),
+
+            ProjectError::ProjectAlreadyExists => write!(
+                f,
+                "{}",
+                t!(
+                    "{} current directory already contains a {}",
+                    t!("error:").red(),
+                    CONFIG_NAME,
+                )
+            ),
+
+            ProjectError::FileAlreadyExists(path) => write!(
+                f,
+                "{}",
+                t!(
+                    "{} '{}' already exists",
+                    t!("error:").red(),
+                    path.display(),
+                )
+            ),
+
+            ProjectError::PathOutsideProject(path) => write!(
+                f,
+                "{}",
+                t!(
+                    "{} '{}' is outside the project",
+                    t!("error:").red(),
+                    path.display(),
+                )
+            ),
+
+            ProjectError::InvalidIdentifier(name) => write!(
+                f,
+                "{}",
+                t!(
+                    "{} '{}' is not a valid C++ identifier",
+                    t!("error:").red(),
+                    name.bold(),
+                )
+            ),
+
+            ProjectError::InSourceBuild => write!(
+                f,
+                "{}",
+                t!(
+                    "{} found CMakeCache.txt in the project folder from an in-source build. Remove it with '{}' or set cmake.allow_in_source",
+                    t!("error:").red(),
+                    "cmm clean --in-source".bold(),
+                )
+            ),
+
+            ProjectError::EditedCMakeLists => write!(
+                f,
+                "{}",
+                t!(
+                    "{} not replacing the hand-edited CMakeLists.txt. Rerun with '{}' to replace it, keeping a backup",
+                    t!("error:").red(),
+                    "--force".bold(),
+                )
+            ),
+
+            ProjectError::MissingInstallRules => write!(
+                f,
+                "{}",
+                t!(
+                    "{} nothing to install, add an {} section to {} listing binaries, headers or resources",
+                    t!("error:").red(),
+                    "[install]".bold(),
+                    CONFIG_NAME,
+                )
+            ),
+
+            ProjectError::MissingPackaging => write!(
+                f,
+                "{}",
+                t!(
+                    "{} packaging isn't set up, add a {} section to {}, eg. generators = [\"DEB\", \"ZIP\"]",
+                    t!("error:").red(),
+                    "[packaging]".bold(),
+                    CONFIG_NAME,
+                )
+            ),
+
+            ProjectError::NothingToRun => write!(
+                f,
+                "{}",
+                t!(
+                    "{} library project has nothing to run, list examples in '{}' or add tests with '{}'",
+                    t!("error:").red(),
+                    "cmake.files.roles".bold(),
+                    "cmm add test-framework".bold(),
+                )
+            ),
+
+            ProjectError::NoTests => write!(
+                f,
+                "{}",
+                t!(
+                    "{} project has no tests, set them up with '{}'",
+                    t!("error:").red(),
+                    "cmm add test-framework".bold(),
+                )
+            ),
+
+            ProjectError::FailingTests(tests) if tests.is_empty() => {
+                write!(f, "{}", t!("{} tests failed", t!("error:").red()))
+            }
+
+            ProjectError::FailingTests(tests) => write!(
+                f,
+                "{}",
+                t!(
+                    "{} {} tests failing: {}",
+                    t!("error:").red(),
+                    tests.len(),
+                    tests.join(", ")
+                )
+            ),
+
+            ProjectError::ProjectLocked(pid) => {
+                let pid = match pid {
+                    Some(pid) => format!(" (pid {pid})"),
+                    None => String::new(),
+                };
+
+                write!(
+                    f,
+                    "{}",
+                    t!(
+                        "{} another cmm process{} is using this project. Wait for it to finish or rerun with '{}'",
+                        t!("error:").red(),
+                        pid,
+                        "--wait".bold(),
+                    )
+                )
+            }
+
+            ProjectError::SymlinkedBuildDir(path, target) => write!(
+                f,
+                "{}",
+                t!(
+                    "{} '{}' is a symlink to '{}', not removing it. Use '{}' to clear the folder it points to",
+                    t!("error:").red(),
+                    path.display(),
+                    target.display(),
+                    "cmm clean --force".bold(),
+                )
+            ),
+
+            ProjectError::OutsideProject(path, target) => write!(
+                f,
+                "{}",
+                t!(
+                    "{} '{}' resolves to '{}' outside the project, not removing it. Use '{}' to remove it anyway",
+                    t!("error:").red(),
+                    path.display(),
+                    target.display(),
+                    "cmm clean --force".bold(),
+                )
             ),
 
             ProjectError::FailedToCreateFolder(name, error) => write!(
                 f,
-                "{} {} '{}' {} {}",
-                "error:".red(),
-                "failed to create folder",
-                name.display(),
-                "with error:",
-                error.red(),
+                "{}",
+                t!(
+                    "{} failed to create folder '{}' with error: {}",
+                    t!("error:").red(),
+                    name.display(),
+                    error.red(),
+                )
             ),
 
             ProjectError::FailedToInitGit(error) => write!(
                 f,
-                "{} {} {}",
-                "error:".red(),
-                "failed to init git repo with error:",
-                error.red(),
+                "{}",
+                t!(
+                    "{} failed to init git repo with error: {}",
+                    t!("error:").red(),
+                    error.red(),
+                )
+            ),
+
+            ProjectError::FailedToOpenGit(error) => write!(
+                f,
+                "{}",
+                t!(
+                    "{} failed to open git repo with error: {}",
+                    t!("error:").red(),
+                    error.red(),
+                )
             ),
 
             ProjectError::FailedToCreateFile(file, error) => write!(
                 f,
-                "{} {} '{}' {} {}",
-                "error:".red(),
-                "failed to create file",
-                file.display(),
-                "with error:",
-                error.red(),
+                "{}",
+                t!(
+                    "{} failed to create file '{}' with error: {}",
+                    t!("error:").red(),
+                    file.display(),
+                    error.red(),
+                )
             ),
 
             ProjectError::CannotOpenFile(file, error) => write!(
                 f,
-                "{} {} '{}' {} {}",
-                "error:".red(),
-                "failed to open file",
-                file.display(),
-                "with error:",
-                error.red(),
+                "{}",
+                t!(
+                    "{} failed to open file '{}' with error: {}",
+                    t!("error:").red(),
+                    file.display(),
+                    error.red(),
+                )
+            ),
+
+            ProjectError::CorruptConfig(error) => write!(
+                f,
+                "{}",
+                t!(
+                    "{} {} can't be read: {}\nThe version from before cmm last changed it is in {}, copy it over {} to restore it",
+                    t!("error:").red(),
+                    CONFIG_NAME,
+                    error.trim_end().red(),
+                    CONFIG_BACKUP_NAME.bold(),
+                    CONFIG_NAME,
+                )
+            ),
+
+            ProjectError::FailedToRemove(path, error) => write!(
+                f,
+                "{}",
+                t!(
+                    "{} failed to remove '{}' with error: {}",
+                    t!("error:").red(),
+                    path.display(),
+                    error.red(),
+                )
+            ),
+
+            ProjectError::DirtyDependencies(paths) => write!(
+                f,
+                "{}",
+                t!(
+                    "{} refusing to build with --frozen, modified dependencies: {}",
+                    t!("error:").red(),
+                    paths.join(", ").bold(),
+                )
+            ),
+
+            ProjectError::LockfileOutdated(names) => write!(
+                f,
+                "{}",
+                t!(
+                    "{} refusing to build with --frozen, {} is out of date for: {}",
+                    t!("error:").red(),
+                    crate::lockfile::LOCK_NAME,
+                    names.join(", ").bold(),
+                )
+            ),
+
+            ProjectError::ValidationFailed(count) => write!(
+                f,
+                "{}",
+                t!("{} found {} problems in the project", t!("error:").red(), count)
+            ),
+
+            ProjectError::NewWarnings(files) => write!(
+                f,
+                "{}",
+                t!(
+                    "{} new compiler warnings in: {}. Fix them or accept them with '{}'",
+                    t!("error:").red(),
+                    files.join(", ").bold(),
+                    "cmm warnings baseline".bold(),
+                )
+            ),
+
+            ProjectError::UnknownDependency(name) => write!(
+                f,
+                "{}",
+                t!(
+                    "{} no dependency named '{}'",
+                    t!("error:").red(),
+                    name.bold(),
+                )
+            ),
+
+            ProjectError::UnknownTag(name, tag) => write!(
+                f,
+                "{}",
+                t!(
+                    "{} '{}' has no tag '{}'",
+                    t!("error:").red(),
+                    name,
+                    tag.bold(),
+                )
+            ),
+
+            ProjectError::UnknownProfile(name) => write!(
+                f,
+                "{}",
+                t!(
+                    "{} no build profile named '{}'",
+                    t!("error:").red(),
+                    name.bold(),
+                )
+            ),
+
+            ProjectError::UnknownTarget(name) => write!(
+                f,
+                "{}",
+                t!(
+                    "{} no executable target named '{}'",
+                    t!("error:").red(),
+                    name.bold(),
+                )
+            ),
+
+            ProjectError::SelfLink(name) => write!(
+                f,
+                "{}",
+                t!("{} target '{}' can't link itself", t!("error:").red(), name.bold())
+            ),
+
+            ProjectError::UnknownMember(name) => write!(
+                f,
+                "{}",
+                t!(
+                    "{} no workspace member named '{}'",
+                    t!("error:").red(),
+                    name.bold(),
+                )
+            ),
+
+            ProjectError::NotInWorkspace => write!(
+                f,
+                "{}",
+                t!(
+                    "{} current directory doesn't contain a {}",
+                    t!("error:").red(),
+                    crate::workspace::WORKSPACE_CONFIG_NAME,
+                )
+            ),
+
+            ProjectError::FailedToApplyPatch(name, patch) => write!(
+                f,
+                "{}",
+                t!(
+                    "{} failed to apply patch '{}' to dependency '{}'",
+                    t!("error:").red(),
+                    patch,
+                    name.bold(),
+                )
             ),
 
             ProjectError::FailedToRunProcess(process, code) => {
                 let error_code = match code {
-                    Some(code) => format!("exit code {}", code),
-                    None => String::from("an unknown error code"),
+                    Some(code) => t!("exit code {}", code),
+                    None => t!("an unknown error code").to_owned(),
                 };
 
                 write!(
                     f,
-                    "{} {} '{}' {} {}",
-                    "error:".red(),
-                    "run process",
+                    "{}",
+                    t!(
+                        "{} run process '{}' exited with {}",
+                        t!("error:").red(),
+                        process,
+                        error_code,
+                    )
+                )
+            }
+
+            ProjectError::FailedToStartProcess(process, error) => write!(
+                f,
+                "{}",
+                t!(
+                    "{} failed to start process '{}' with error: {}",
+                    t!("error:").red(),
                     process,
-                    "exited with",
-                    error_code,
+                    error.red(),
+                )
+            ),
+
+            ProjectError::MissingProgram(program) => write!(
+                f,
+                "{}",
+                t!(
+                    "{} failed to find program '{}'. Is it installed and on your PATH?",
+                    t!("error:").red(),
+                    program.bold(),
+                )
+            ),
+
+            ProjectError::GeneratorNotInstalled(generator, installed) => {
+                let installed = match installed.is_empty() {
+                    true => t!("none").to_owned(),
+                    false => installed.join(", "),
+                };
+
+                write!(
+                    f,
+                    "{}",
+                    t!(
+                        "{} generator '{}' isn't installed\n{} installed generators: {}",
+                        t!("error:").red(),
+                        generator.bold(),
+                        t!("hint:").cyan(),
+                        installed,
+                    )
                 )
             }
+
+            ProjectError::UnmetRequirement(tool, found, required) => {
+                let found = match found {
+                    Some(version) => t!("found version {}", version),
+                    None => t!("it wasn't found").to_owned(),
+                };
+
+                write!(
+                    f,
+                    "{}",
+                    t!(
+                        "{} project requires {} {} but {}\n{} {}",
+                        t!("error:").red(),
+                        tool.bold(),
+                        required,
+                        found,
+                        t!("hint:").cyan(),
+                        crate::requires::install_hint(tool),
+                    )
+                )
+            }
+
+            ProjectError::MissingTools(count) => write!(
+                f,
+                "{}",
+                t!(
+                    "{} found {} problems with the tools cmm needs",
+                    t!("error:").red(),
+                    count
+                )
+            ),
+
+            ProjectError::MissingAnswer(key) => write!(
+                f,
+                "{}",
+                t!(
+                    "{} no answer given for prompt '{}'",
+                    t!("error:").red(),
+                    key
+                )
+            ),
+
+            ProjectError::Cancelled => write!(f, "{}", t!("Cancelled")),
+
+            ProjectError::PromptFailed(key, error) => write!(
+                f,
+                "{}",
+                t!(
+                    "{} prompt '{}' failed with error: {}",
+                    t!("error:").red(),
+                    key,
+                    error.red()
+                )
+            ),
         }
     }
 }
@@ -0,0 +1,1767 @@
+use std::{
+    collections::HashMap,
+    hash::{Hash, Hasher},
+    io::Write,
+    path::{Path, PathBuf},
+};
+
+use colored::Colorize;
+
+use crate::{
+    answers,
+    build::check_in_source,
+    cmake_version, conan,
+    config::{self, ConfigFile},
+    cpm,
+    error::ProjectError,
+    lockfile,
+    messages::t,
+    output::{notice, status},
+    testing,
+    util::get_config,
+};
+
+/// Joins a config path onto the folder it is relative to, resolving `.` and `..` so
+/// `src/../include` is written as `include`
+pub(crate) fn join_path(base: &str, dir: &str) -> String {
+    let mut parts: Vec<&str> = Vec::new();
+
+    for part in base.split('/').chain(dir.split('/')) {
+        match part {
+            "" | "." => {}
+            ".." if parts.last().is_some_and(|last| *last != "..") => {
+                parts.pop();
+            }
+            part => parts.push(part),
+        }
+    }
+
+    match parts.is_empty() {
+        true => ".".into(),
+        false => parts.join("/"),
+    }
+}
+
+/// CMake code from the config, written as it is
+fn write_snippet(file: &mut impl Write, snippet: Option<&str>) -> Result<(), std::io::Error> {
+    match snippet {
+        Some(snippet) => writeln!(file, "{}", snippet.trim_end()),
+        None => Ok(()),
+    }
+}
+
+/// A `dependencies.prefix_paths` entry as CMake sees it. The home folder comes from the
+/// environment and relative paths from the project folder, so the same entry works on
+/// every machine.
+fn prefix_path_expr(path: &str) -> String {
+    let path = path.replace('\\', "/");
+
+    if path == "~" {
+        "$ENV{HOME}".into()
+    } else if let Some(rest) = path.strip_prefix("~/") {
+        format!("$ENV{{HOME}}/{}", rest)
+    } else if path.starts_with('/') || Path::new(&path).is_absolute() {
+        path
+    } else {
+        format!("${{CMAKE_CURRENT_SOURCE_DIR}}/{}", join_path("", &path))
+    }
+}
+
+fn write_source_files(
+    file: &mut impl Write,
+    source_name: &str,
+    path: &str,
+    files: &config::ProjectFiles,
+) -> Result<(), std::io::Error> {
+    if files.source_files.is_empty() {
+        return Ok(());
+    }
+
+    let mut individual_files = Vec::new();
+    let mut glob_dirs = Vec::new();
+    let mut glob_recurse_dirs = Vec::new();
+
+    files
+        .source_files
+        .iter()
+        .for_each(|(source_type, files)| match source_type {
+            config::SourceType::File => individual_files.extend(files),
+            config::SourceType::Glob => glob_dirs.extend(files),
+            config::SourceType::GlobRecurse => glob_recurse_dirs.extend(files),
+        });
+
+    let mut files_initialized = false;
+
+    if !individual_files.is_empty() {
+        writeln!(
+            file,
+            "set({source_name} {})",
+            individual_files
+                .into_iter()
+                .map(|name| format!(r#""{}/{}""#, path, name))
+                .collect::<Vec<_>>()
+                .join(" ")
+        )?;
+        files_initialized = true;
+    }
+
+    let mut write_glob_type = |glob_type: &str, dirs: Vec<&String>| -> Result<(), std::io::Error> {
+        if dirs.is_empty() {
+            return Ok(());
+        }
+
+        let dirs_string = dirs
+            .iter()
+            .map(|dir| {
+                let path = join_path(path, dir);
+                format!(r#""{path}/*.cpp" "{path}/*.c" "{path}/*.hpp" "{path}/*.h""#)
+            })
+            .collect::<Vec<_>>()
+            .join(" ");
+
+        let src_name = format!("{source_name}_{glob_type}");
+
+        match files_initialized {
+            true => {
+                writeln!(file, r#"file({glob_type} {src_name} {})"#, dirs_string)?;
+                writeln!(file, "list(APPEND {source_name} ${{{src_name}}})")?;
+            }
+
+            false => {
+                writeln!(file, r#"file({glob_type} {source_name} {})"#, dirs_string)?;
+                files_initialized = true;
+            }
+        }
+
+        Ok(())
+    };
+
+    write_glob_type("GLOB", glob_dirs)?;
+    write_glob_type("GLOB_RECURSE", glob_recurse_dirs)?;
+
+    if !files.exclude_files.is_empty() {
+        let to_remove = files
+            .exclude_files
+            .iter()
+            .map(|name| format!(r#""{}/{}""#, path, name))
+            .collect::<Vec<_>>()
+            .join(" ");
+
+        writeln!(file, "list(REMOVE_ITEM {source_name} {to_remove})")?;
+    }
+
+    Ok(())
+}
+
+/// The entries of `files` whose sources include `file`, as they appear in the generated
+/// file, eg. `GLOB_RECURSE "src"`. Empty when nothing matches or the file is excluded.
+pub(crate) fn matching_entries(
+    files: &config::ProjectFiles,
+    path: &str,
+    file: &str,
+) -> Vec<String> {
+    let excluded = files
+        .exclude_files
+        .iter()
+        .any(|excluded| join_path(path, excluded) == file);
+
+    if excluded {
+        return Vec::new();
+    }
+
+    let (dir, globbed) = match file.rsplit_once('/') {
+        Some((dir, name)) => (dir, name),
+        None => (".", file),
+    };
+
+    // Role files are taken back out of the source entries, so they're only built once
+    let role_globbed = globbed.ends_with(".cpp") || globbed.ends_with(".c");
+    let role_entries = files
+        .roles
+        .iter()
+        .flat_map(|(role, entries)| entries.iter().map(move |entry| (role, entry)))
+        .filter(|(_, entry)| {
+            let entry_path = join_path(path, entry);
+            entry_path == file || (role_globbed && entry_path == dir)
+        })
+        .map(|(role, entry)| format!(r#"{} "{}""#, role.name(), join_path(path, entry)))
+        .collect::<Vec<_>>();
+
+    if !role_entries.is_empty() {
+        return role_entries;
+    }
+
+    let globbed = ["cpp", "c", "hpp", "h"]
+        .iter()
+        .any(|ext| globbed.ends_with(&format!(".{ext}")));
+
+    files
+        .source_files
+        .iter()
+        .flat_map(|(source_type, paths)| paths.iter().map(move |path| (source_type, path)))
+        .filter_map(|(source_type, entry)| {
+            let entry_path = join_path(path, entry);
+
+            let matches = match source_type {
+                config::SourceType::File => entry_path == file,
+                config::SourceType::Glob => globbed && entry_path == dir,
+                config::SourceType::GlobRecurse => {
+                    globbed
+                        && (entry_path == "."
+                            || entry_path == dir
+                            || dir.starts_with(&format!("{entry_path}/")))
+                }
+            };
+
+            let entry = match source_type {
+                config::SourceType::File => format!(r#""{}""#, entry_path),
+                config::SourceType::Glob => format!(r#"GLOB "{}""#, entry_path),
+                config::SourceType::GlobRecurse => format!(r#"GLOB_RECURSE "{}""#, entry_path),
+            };
+
+            matches.then_some(entry)
+        })
+        .collect()
+}
+
+fn write_include_dirs(
+    file: &mut impl Write,
+    name: &str,
+    path: &str,
+    files: &config::ProjectFiles,
+) -> Result<(), std::io::Error> {
+    let mut other = Vec::new();
+    let mut interfaces = Vec::new();
+
+    files
+        .include_dirs
+        .iter()
+        .for_each(|(include_type, dirs)| match include_type {
+            config::IncludeType::Public => other.extend(dirs),
+            config::IncludeType::Interface => interfaces.extend(dirs),
+        });
+
+    let mut write_include_type =
+        |include_type: &str, dirs: Vec<&String>| -> Result<(), std::io::Error> {
+            if dirs.is_empty() {
+                return Ok(());
+            }
+
+            let dirs = dirs
+                .into_iter()
+                .map(|dir| format!(r#""{}""#, join_path(path, dir)))
+                .collect::<Vec<_>>()
+                .join(" ");
+
+            writeln!(
+                file,
+                "target_include_directories({name} {include_type} {dirs})"
+            )?;
+
+            Ok(())
+        };
+
+    write_include_type("PUBLIC", other)?;
+    write_include_type("INTERFACE", interfaces)?;
+
+    Ok(())
+}
+
+/// Compile options from the cmake section. Kept off dependencies, whose code the
+/// project's warning flags shouldn't apply to.
+fn write_compile_options(
+    file: &mut impl Write,
+    name: &str,
+    cmake: &config::CMake,
+) -> Result<(), std::io::Error> {
+    let options = cmake.compile_options();
+
+    if options.is_empty() {
+        return Ok(());
+    }
+
+    writeln!(
+        file,
+        "target_compile_options({name} PRIVATE {})",
+        options.join(" ")
+    )
+}
+
+/// Links `dependencies` into a target. Object library targets are compiled in directly
+/// with $<TARGET_OBJECTS> so their sources are only built once.
+fn write_target_links(
+    file: &mut impl Write,
+    name: &str,
+    visibility: &str,
+    dependencies: &[String],
+    targets: &[config::Target],
+) -> Result<(), std::io::Error> {
+    let (objects, libraries): (Vec<&String>, Vec<&String>) = dependencies.iter().partition(|dep| {
+        targets
+            .iter()
+            .any(|target| &&target.name == dep && target.kind == config::TargetKind::Object)
+    });
+
+    if !objects.is_empty() {
+        let sources = objects
+            .iter()
+            .map(|object| format!("$<TARGET_OBJECTS:{object}>"))
+            .collect::<Vec<_>>()
+            .join(" ");
+
+        let includes = objects
+            .iter()
+            .map(|object| format!("$<TARGET_PROPERTY:{object},INCLUDE_DIRECTORIES>"))
+            .collect::<Vec<_>>()
+            .join(" ");
+
+        writeln!(file, "target_sources({name} PRIVATE {sources})")?;
+        writeln!(
+            file,
+            "target_include_directories({name} PRIVATE {includes})"
+        )?;
+    }
+
+    if !libraries.is_empty() {
+        writeln!(
+            file,
+            "target_link_libraries({name} {visibility} {})",
+            libraries
+                .iter()
+                .map(|lib| lib.as_str())
+                .collect::<Vec<_>>()
+                .join(" ")
+        )?;
+    }
+
+    Ok(())
+}
+
+fn write_target(
+    file: &mut impl Write,
+    target: &config::Target,
+    targets: &[config::Target],
+) -> Result<(), std::io::Error> {
+    let name = &target.name;
+    let src_name = format!("{}_SOURCES", name.to_uppercase());
+
+    writeln!(file, "\n#Target: {name}")?;
+
+    write_source_files(file, &src_name, &target.path, &target.files)?;
+
+    let has_sources = !target.files.source_files.is_empty();
+
+    match (target.kind, has_sources) {
+        (config::TargetKind::Executable, _) => {
+            writeln!(file, "add_executable({name} ${{{src_name}}})")?
+        }
+        (config::TargetKind::Library, true) => {
+            writeln!(file, "add_library({name} ${{{src_name}}})")?
+        }
+        (config::TargetKind::Library, false) => writeln!(file, "add_library({name} INTERFACE)")?,
+        (config::TargetKind::Object, _) => {
+            writeln!(file, "add_library({name} OBJECT ${{{src_name}}})")?
+        }
+    }
+
+    write_include_dirs(file, name, &target.path, &target.files)?;
+
+    let visibility = match (target.kind, has_sources) {
+        (config::TargetKind::Library, false) => "INTERFACE",
+        (config::TargetKind::Library, true) | (config::TargetKind::Object, _) => "PUBLIC",
+        (config::TargetKind::Executable, _) => "PRIVATE",
+    };
+
+    write_target_links(file, name, visibility, &target.dependencies, targets)
+}
+
+/// Roles built into executables of their own, with their section title. Each executable
+/// is named after its file, starting with the role's name.
+const ROLE_EXECUTABLES: [(config::FileRole, &str); 3] = [
+    (config::FileRole::Test, "Tests"),
+    (config::FileRole::Example, "Examples"),
+    (config::FileRole::Bench, "Benchmarks"),
+];
+
+/// Option a library project's consumers can turn off, eg. `DEMO_BUILD_TESTS`. Executables
+/// aren't added as subdirectories of other projects, so they have none.
+fn consumer_option(config: &ConfigFile, suffix: &str) -> Option<String> {
+    let prefix = config
+        .project
+        .name
+        .chars()
+        .map(|c| match c.is_ascii_alphanumeric() {
+            true => c.to_ascii_uppercase(),
+            false => '_',
+        })
+        .collect::<String>();
+
+    (config.project.kind == config::ProjectKind::Library).then(|| format!("{prefix}_{suffix}"))
+}
+
+/// The consumer option a role's executables are built behind
+fn role_option(config: &ConfigFile, role: config::FileRole) -> Option<String> {
+    match role {
+        config::FileRole::Test => consumer_option(config, "BUILD_TESTS"),
+        config::FileRole::Example => consumer_option(config, "BUILD_EXAMPLES"),
+        config::FileRole::Main | config::FileRole::Bench => None,
+    }
+}
+
+/// Writes `block` inside `if(option)` so projects adding the library as a subdirectory can
+/// leave it out. The block's section comment stays in front of the `if`.
+fn write_guarded(
+    file: &mut impl Write,
+    option: Option<&str>,
+    block: &[u8],
+) -> Result<(), std::io::Error> {
+    let Some(option) = option else {
+        return file.write_all(block);
+    };
+
+    let block = String::from_utf8_lossy(block);
+    let mut lines = block.lines().peekable();
+
+    while let Some(line) = lines.next_if(|line| line.is_empty() || line.starts_with('#')) {
+        writeln!(file, "{line}")?;
+    }
+
+    writeln!(file, "if({option})")?;
+    for line in lines {
+        match line.is_empty() {
+            true => writeln!(file)?,
+            false => writeln!(file, "    {line}")?,
+        }
+    }
+    writeln!(file, "endif()")
+}
+
+/// Options for projects that add a library as a subdirectory, defaulting to on only when
+/// the library is built on its own
+fn write_consumer_options(
+    file: &mut impl Write,
+    config: &ConfigFile,
+) -> Result<(), std::io::Error> {
+    let files = &config.cmake.files;
+    let has_tests = config.tests.is_some() || !files.role(config::FileRole::Test).is_empty();
+
+    let options = [
+        (has_tests, "BUILD_TESTS", "Build the tests"),
+        (
+            !files.role(config::FileRole::Example).is_empty(),
+            "BUILD_EXAMPLES",
+            "Build the examples",
+        ),
+        (config.install.is_some(), "INSTALL", "Add the install rules"),
+    ]
+    .into_iter()
+    .filter(|(used, ..)| *used)
+    .filter_map(|(_, suffix, description)| {
+        consumer_option(config, suffix).map(|option| (option, description))
+    })
+    .collect::<Vec<_>>();
+
+    if options.is_empty() {
+        return Ok(());
+    }
+
+    writeln!(
+        file,
+        "
+#Consumer Options:"
+    )?;
+    writeln!(
+        file,
+        r#"string(COMPARE EQUAL "${{CMAKE_SOURCE_DIR}}" "${{PROJECT_SOURCE_DIR}}" CMM_TOP_LEVEL)"#
+    )?;
+    for (option, description) in options {
+        writeln!(
+            file,
+            r#"option({option} "{description}" ${{CMM_TOP_LEVEL}})"#
+        )?;
+    }
+
+    Ok(())
+}
+
+/// Whether a file role entry names a single source instead of a folder of them
+fn is_source_file(entry: &str) -> bool {
+    Path::new(entry)
+        .extension()
+        .is_some_and(|ext| ["c", "cc", "cpp", "cxx"].contains(&&*ext.to_string_lossy()))
+}
+
+/// Names of the executables built from example, bench and test files, in the order the
+/// generated file adds them. Test files built into the `[tests]` executable give its name
+/// instead.
+pub fn role_executable_names(config: &ConfigFile) -> Vec<String> {
+    let files = &config.cmake.files;
+    let mut names = Vec::new();
+
+    for (role, _) in ROLE_EXECUTABLES {
+        if role == config::FileRole::Test && config.tests.is_some() {
+            names.push(format!("{}_tests", config.project.name));
+            continue;
+        }
+
+        let mut sources = files
+            .role(role)
+            .into_iter()
+            .map(|entry| join_path(&config.cmake.source_dir, entry))
+            .flat_map(|entry| match is_source_file(&entry) {
+                true => vec![PathBuf::from(entry)],
+                false => std::fs::read_dir(entry)
+                    .into_iter()
+                    .flatten()
+                    .flatten()
+                    .map(|file| file.path())
+                    .filter(|path| {
+                        path.extension()
+                            .is_some_and(|ext| ext == "cpp" || ext == "c")
+                    })
+                    .collect(),
+            })
+            .filter_map(|path| Some(path.file_stem()?.to_string_lossy().into_owned()))
+            .collect::<Vec<_>>();
+        sources.sort();
+
+        names.extend(
+            sources
+                .into_iter()
+                .map(|stem| format!("{}_{}", role.name(), stem)),
+        );
+    }
+
+    names
+}
+
+/// Everything `cmm run` can run: the project and executable targets, then the executables
+/// built from example, bench and test files
+pub fn runnable_names(config: &ConfigFile) -> Vec<String> {
+    let mut names = config.executable_names();
+    names.extend(role_executable_names(config));
+    names
+}
+
+/// The list variable holding the files of a role
+fn role_variable(role: config::FileRole) -> &'static str {
+    match role {
+        config::FileRole::Main => "CMM_MAIN_SOURCES",
+        config::FileRole::Test => "CMM_TEST_SOURCES",
+        config::FileRole::Example => "CMM_EXAMPLE_SOURCES",
+        config::FileRole::Bench => "CMM_BENCH_SOURCES",
+    }
+}
+
+/// Globs the files of each role. Main files are added to `source_name`, the others are
+/// taken out of it so a folder of examples inside the source folder isn't compiled into
+/// the executable too.
+fn write_role_sources(
+    file: &mut impl Write,
+    source_name: &str,
+    path: &str,
+    files: &config::ProjectFiles,
+) -> Result<(), std::io::Error> {
+    let roles = [config::FileRole::Main]
+        .into_iter()
+        .chain(ROLE_EXECUTABLES.iter().map(|(role, _)| *role));
+
+    for role in roles {
+        let entries = files.role(role);
+        if entries.is_empty() {
+            continue;
+        }
+
+        let variable = role_variable(role);
+        let globs = entries
+            .into_iter()
+            .map(|entry| {
+                let entry = join_path(path, entry);
+
+                match is_source_file(&entry) {
+                    true => format!(r#""{entry}""#),
+                    false => format!(r#""{entry}/*.cpp" "{entry}/*.c""#),
+                }
+            })
+            .collect::<Vec<_>>()
+            .join(" ");
+
+        writeln!(file, "file(GLOB {variable} {globs})")?;
+
+        match role {
+            config::FileRole::Main => writeln!(file, "list(APPEND {source_name} ${{{variable}}})")?,
+            _ => writeln!(
+                file,
+                "if({variable})\n    list(REMOVE_ITEM {source_name} ${{{variable}}})\nendif()"
+            )?,
+        }
+    }
+
+    Ok(())
+}
+
+/// Links the project into its tests and examples when it's a library they can use
+fn write_library_link(
+    file: &mut impl Write,
+    name: &str,
+    config: &ConfigFile,
+) -> Result<(), std::io::Error> {
+    match config.project.kind {
+        config::ProjectKind::Library => writeln!(
+            file,
+            r#"target_link_libraries({name} PRIVATE "${{PROJECT_NAME}}")"#
+        ),
+        config::ProjectKind::Executable => Ok(()),
+    }
+}
+
+/// Builds each test, example and benchmark file into an executable of its own, named
+/// after the file, with the project's include dirs, compile options and dependencies.
+/// Test files go into the test executable instead when there is one.
+fn write_role_executables(
+    file: &mut impl Write,
+    config: &ConfigFile,
+) -> Result<(), std::io::Error> {
+    let files = &config.cmake.files;
+
+    for (role, title) in ROLE_EXECUTABLES {
+        if files.role(role).is_empty() {
+            continue;
+        }
+
+        let variable = role_variable(role);
+        let option = role_option(config, role);
+        let mut block = Vec::new();
+
+        if role == config::FileRole::Test && config.tests.is_some() {
+            writeln!(
+                block,
+                "target_sources({} PRIVATE ${{{variable}}})",
+                testing::TEST_TARGET
+            )?;
+            write_guarded(file, option.as_deref(), &block)?;
+            continue;
+        }
+
+        writeln!(block, "\n#{title}:")?;
+        if role == config::FileRole::Test {
+            writeln!(block, "enable_testing()")?;
+        }
+
+        let name = format!(r#""{}_${{name}}""#, role.name());
+        let mut body = Vec::new();
+
+        writeln!(
+            body,
+            r#"get_filename_component(name "${{source}}" NAME_WE)"#
+        )?;
+        writeln!(body, r#"add_executable({name} "${{source}}")"#)?;
+        write_include_dirs(&mut body, &name, &config.cmake.source_dir, files)?;
+        write_compile_options(&mut body, &name, &config.cmake)?;
+        write_target_links(
+            &mut body,
+            &name,
+            "PRIVATE",
+            &config.dependencies.project_dependencies,
+            &config.targets,
+        )?;
+        write_library_link(&mut body, &name, config)?;
+
+        // Without a framework a test passes by returning 0
+        if role == config::FileRole::Test {
+            writeln!(body, r#"add_test(NAME "${{name}}" COMMAND {name})"#)?;
+        }
+
+        writeln!(block, "foreach(source ${{{variable}}})")?;
+        for line in String::from_utf8_lossy(&body).lines() {
+            writeln!(block, "    {line}")?;
+        }
+        writeln!(block, "endforeach()")?;
+
+        write_guarded(file, option.as_deref(), &block)?;
+    }
+
+    Ok(())
+}
+
+/// Install rules for `cmm install`. Paths use GNUInstallDirs so they suit the platform.
+fn write_install(
+    file: &mut impl Write,
+    project_name: &str,
+    install: &config::Install,
+) -> Result<(), std::io::Error> {
+    writeln!(file, "\n#Install:")?;
+    writeln!(file, "include(GNUInstallDirs)")?;
+
+    // Only a default, CMAKE_INSTALL_PREFIX and --prefix still win
+    if let Some(prefix) = &install.prefix {
+        writeln!(
+            file,
+            "if(CMAKE_INSTALL_PREFIX_INITIALIZED_TO_DEFAULT)\n    set(CMAKE_INSTALL_PREFIX \"{}\" CACHE PATH \"Install prefix\" FORCE)\nendif()",
+            prefix
+        )?;
+    }
+
+    if !install.binaries.is_empty() {
+        let targets = install
+            .binaries
+            .iter()
+            .map(|name| match name == project_name {
+                true => r#""${PROJECT_NAME}""#.to_owned(),
+                false => format!(r#""{name}""#),
+            })
+            .collect::<Vec<_>>()
+            .join(" ");
+
+        writeln!(
+            file,
+            "install(TARGETS {targets}\n    RUNTIME DESTINATION ${{CMAKE_INSTALL_BINDIR}}\n    LIBRARY DESTINATION ${{CMAKE_INSTALL_LIBDIR}}\n    ARCHIVE DESTINATION ${{CMAKE_INSTALL_LIBDIR}})"
+        )?;
+    }
+
+    // The trailing slash installs the folder's contents rather than the folder itself
+    for headers in &install.headers {
+        writeln!(
+            file,
+            r#"install(DIRECTORY "{}/" DESTINATION ${{CMAKE_INSTALL_INCLUDEDIR}})"#,
+            headers.trim_end_matches('/')
+        )?;
+    }
+
+    for resources in &install.resources {
+        writeln!(
+            file,
+            r#"install(DIRECTORY "{}/" DESTINATION "${{CMAKE_INSTALL_DATADIR}}/${{PROJECT_NAME}}")"#,
+            resources.trim_end_matches('/')
+        )?;
+    }
+
+    Ok(())
+}
+
+/// CPack configuration for `cmm package`. The version, license and maintainer come from
+/// `[project]` unless `[packaging]` overrides them.
+fn write_packaging(
+    file: &mut impl Write,
+    project: &config::Project,
+    packaging: &config::Packaging,
+) -> Result<(), std::io::Error> {
+    writeln!(file, "\n#Packaging:")?;
+    writeln!(file, r#"set(CPACK_PACKAGE_NAME "${{PROJECT_NAME}}")"#)?;
+    writeln!(file, r#"set(CPACK_PACKAGE_VERSION "{}")"#, project.version)?;
+
+    if let Some(description) = &packaging.description {
+        writeln!(
+            file,
+            r#"set(CPACK_PACKAGE_DESCRIPTION_SUMMARY "{}")"#,
+            description.replace('"', "\\\"")
+        )?;
+    }
+
+    // Debian packages can't be built without a maintainer
+    if let Some(maintainer) = packaging.maintainer.as_ref().or(project.authors.first()) {
+        writeln!(file, r#"set(CPACK_PACKAGE_CONTACT "{}")"#, maintainer)?;
+        writeln!(
+            file,
+            r#"set(CPACK_DEBIAN_PACKAGE_MAINTAINER "{}")"#,
+            maintainer
+        )?;
+    }
+
+    if let Some(license) = &project.license {
+        writeln!(file, r#"set(CPACK_RPM_PACKAGE_LICENSE "{}")"#, license)?;
+    }
+
+    writeln!(
+        file,
+        "if(EXISTS \"${{CMAKE_CURRENT_SOURCE_DIR}}/LICENSE\")\n    set(CPACK_RESOURCE_FILE_LICENSE \"${{CMAKE_CURRENT_SOURCE_DIR}}/LICENSE\")\nendif()"
+    )?;
+
+    if !packaging.generators.is_empty() {
+        writeln!(
+            file,
+            r#"set(CPACK_GENERATOR "{}")"#,
+            packaging.generators.join(";")
+        )?;
+    }
+
+    writeln!(file, "include(CPack)")
+}
+
+/// Hash of the config written to the first line of CMakeLists.txt, so builds can tell
+/// when it needs regenerating
+pub fn config_hash(config: &ConfigFile) -> u64 {
+    let mut hasher = std::hash::DefaultHasher::new();
+    config.hash(&mut hasher);
+    hasher.finish()
+}
+
+/// Whether a generated file starts with the config hash
+pub fn has_hash_comment(contents: &str) -> bool {
+    contents
+        .lines()
+        .next()
+        .and_then(|line| line.strip_prefix("# "))
+        .is_some_and(|hash| hash.parse::<u64>().is_ok())
+}
+
+/// First line of files written with --no-hash-comment, which marks them as generated
+/// without changing between machines
+pub const GENERATED_MARKER: &str = "# Generated by cmakemake from CMakeMake.toml";
+
+/// Whether cmakemake wrote the file, rather than it being written by hand or elsewhere
+pub fn is_generated(contents: &str) -> bool {
+    has_hash_comment(contents) || contents.lines().next() == Some(GENERATED_MARKER)
+}
+
+/// Lines around a block of CMakeLists.txt that regenerating carries over as it is
+pub const KEEP_START: &str = "# cmm:keep-start";
+pub const KEEP_END: &str = "# cmm:keep-end";
+
+/// A block between `KEEP_START` and `KEEP_END`, with the last generated line before it
+struct KeptBlock<'a> {
+    /// The line, and how many times it came before, as generated files repeat lines like
+    /// `endif()`. None when the block comes before anything generated.
+    after: Option<(&'a str, usize)>,
+    lines: Vec<&'a str>,
+}
+
+/// Blocks marked to keep in `existing`, in order. A block missing its end marker runs to
+/// the end of the file.
+fn kept_blocks(existing: &str) -> Vec<KeptBlock<'_>> {
+    let mut blocks = Vec::new();
+    let mut seen = HashMap::<&str, usize>::new();
+    let mut after = None;
+    let mut current: Option<KeptBlock> = None;
+
+    for line in existing.lines() {
+        match &mut current {
+            Some(block) => {
+                block.lines.push(line);
+                if line.trim() == KEEP_END {
+                    blocks.extend(current.take());
+                }
+            }
+            None if line.trim() == KEEP_START => {
+                current = Some(KeptBlock {
+                    after,
+                    lines: vec![line],
+                })
+            }
+            None => {
+                let count = seen.entry(line).or_default();
+                if !line.trim().is_empty() {
+                    after = Some((line, *count));
+                }
+                *count += 1;
+            }
+        }
+    }
+
+    blocks.extend(current);
+    blocks
+}
+
+fn push_lines(file: &mut String, lines: &[&str]) {
+    for line in lines {
+        file.push_str(line);
+        file.push('\n');
+    }
+}
+
+/// Puts the blocks marked to keep in `existing` into the newly generated `contents`. Each
+/// goes after the same generated line it followed before, or at the end when that line is
+/// gone. The first line holds the config hash, so a block after it stays after it, and a
+/// block before anything generated goes right after it so the file still counts as
+/// generated.
+pub fn keep_blocks(existing: &str, contents: String) -> String {
+    let blocks = kept_blocks(existing);
+    if blocks.is_empty() {
+        return contents;
+    }
+
+    let first_line = existing.lines().next();
+    let lines = contents.lines().collect::<Vec<_>>();
+
+    let mut at_start = Vec::new();
+    let mut insert_after = vec![Vec::new(); lines.len()];
+    let mut at_end = Vec::new();
+
+    for block in &blocks {
+        let found = match block.after {
+            None if is_generated(&contents) => Some(0),
+            None => {
+                at_start.push(block);
+                continue;
+            }
+            Some((after, _)) if Some(after) == first_line => Some(0),
+            Some((after, count)) => {
+                let matches = lines
+                    .iter()
+                    .enumerate()
+                    .filter(|(_, line)| **line == after)
+                    .map(|(index, _)| index)
+                    .collect::<Vec<_>>();
+
+                // The same occurrence, or the last when some were removed
+                matches.get(count).or(matches.last()).copied()
+            }
+        };
+
+        match found {
+            Some(index) => insert_after[index].push(block),
+            None => at_end.push(block),
+        }
+    }
+
+    let mut merged = String::new();
+    at_start
+        .iter()
+        .for_each(|block| push_lines(&mut merged, &block.lines));
+
+    for (line, blocks) in lines.iter().zip(&insert_after) {
+        push_lines(&mut merged, &[line]);
+        blocks
+            .iter()
+            .for_each(|block| push_lines(&mut merged, &block.lines));
+    }
+
+    at_end
+        .iter()
+        .for_each(|block| push_lines(&mut merged, &block.lines));
+
+    merged
+}
+
+/// Lines only in `old` with `-`, lines only in `new` with `+` and shared lines with a space,
+/// in order. Files are a few hundred lines, so the quadratic longest common subsequence is
+/// fine.
+fn line_diff<'a>(old: &'a str, new: &'a str) -> Vec<(char, &'a str)> {
+    let old = old.lines().collect::<Vec<_>>();
+    let new = new.lines().collect::<Vec<_>>();
+
+    // common[i][j] is the longest common subsequence of old[i..] and new[j..]
+    let mut common = vec![vec![0usize; new.len() + 1]; old.len() + 1];
+    for i in (0..old.len()).rev() {
+        for j in (0..new.len()).rev() {
+            common[i][j] = match old[i] == new[j] {
+                true => common[i + 1][j + 1] + 1,
+                false => common[i + 1][j].max(common[i][j + 1]),
+            };
+        }
+    }
+
+    let (mut i, mut j) = (0, 0);
+    let mut diff = Vec::new();
+
+    while i < old.len() || j < new.len() {
+        if i < old.len() && j < new.len() && old[i] == new[j] {
+            diff.push((' ', old[i]));
+            i += 1;
+            j += 1;
+        } else if j < new.len() && (i == old.len() || common[i][j + 1] >= common[i + 1][j]) {
+            diff.push(('+', new[j]));
+            j += 1;
+        } else {
+            diff.push(('-', old[i]));
+            i += 1;
+        }
+    }
+
+    diff
+}
+
+/// Unchanged lines shown around each change
+const DIFF_CONTEXT: usize = 2;
+
+/// Prints the changes from `old` to `new` coloured, with a few unchanged lines around each
+/// and the line number in `new` where it starts
+fn print_diff(old: &str, new: &str) {
+    let diff = line_diff(old, new);
+    let changed = diff
+        .iter()
+        .enumerate()
+        .filter(|(_, (sign, _))| *sign != ' ')
+        .map(|(index, _)| index)
+        .collect::<Vec<_>>();
+
+    let near_change = |index: usize| {
+        changed
+            .iter()
+            .any(|&change| index + DIFF_CONTEXT >= change && index <= change + DIFF_CONTEXT)
+    };
+
+    let mut new_line = 1;
+    let mut in_hunk = false;
+
+    for (index, (sign, line)) in diff.iter().enumerate() {
+        if near_change(index) {
+            if !in_hunk {
+                println!("{}", format!("@@ line {} @@", new_line).cyan());
+                in_hunk = true;
+            }
+
+            let line = format!("{}{}", sign, line);
+            match sign {
+                '-' => println!("{}", line.red()),
+                '+' => println!("{}", line.green()),
+                _ => println!("{}", line.dimmed()),
+            }
+        } else {
+            in_hunk = false;
+        }
+
+        if *sign != '-' {
+            new_line += 1;
+        }
+    }
+}
+
+/// Where a replaced hand-written CMakeLists.txt is kept
+pub const BACKUP_NAME: &str = "CMakeLists.txt.bak";
+
+/// Asks before regenerating over a CMakeLists.txt that cmakemake didn't write, showing what
+/// would change, and keeps the old file as CMakeLists.txt.bak. Without a terminal to ask
+/// on, `force` has to be given.
+pub(crate) fn confirm_replace(
+    existing: &str,
+    replacement: &str,
+    force: bool,
+) -> Result<(), ProjectError> {
+    notice!(
+        "{}",
+        t!(
+            "{} CMakeLists.txt was edited by hand or not generated by cmakemake",
+            t!("warning:").yellow()
+        )
+    );
+
+    print_diff(existing, replacement);
+
+    let key = "build.replace_cmakelists";
+    let replace = force
+        || (answers::can_answer(key)
+            && answers::confirm(
+                key,
+                inquire::Confirm::new(&t!(
+                    "Replace CMakeLists.txt? The current file is kept as {}",
+                    BACKUP_NAME
+                ))
+                .with_default(false),
+            )?);
+
+    if !replace {
+        return Err(ProjectError::EditedCMakeLists);
+    }
+
+    std::fs::write(BACKUP_NAME, existing)
+        .map_err(|err| ProjectError::FailedToCreateFile(BACKUP_NAME.into(), err.to_string()))?;
+
+    notice!("{}", t!("Kept the old CMakeLists.txt as {}", BACKUP_NAME));
+
+    Ok(())
+}
+
+/// What each section of CMakeLists.txt is for, shown in its banner by the verbose style
+const SECTION_NOTES: [(&str, &str); 12] = [
+    (
+        "Consumer Options",
+        "Parts of the library a project adding it with add_subdirectory can leave out. They default to on only when the library is built on its own.",
+    ),
+    (
+        "Project Config Flags",
+        "Settings for the whole project, like the C++ standard. compile_commands.json is written for editors and language servers.",
+    ),
+    (
+        "Compiler Cache",
+        "Compiles through ccache or sccache when one is installed, so rebuilding unchanged files is fast.",
+    ),
+    (
+        "Build Profiles",
+        "Flags for each profile in CMakeMake.toml. 'cmm build --profile NAME' sets CMM_PROFILE and the build type.",
+    ),
+    (
+        "Project Dependencies",
+        "Libraries the project uses, found on the system, downloaded while configuring or built from submodules.",
+    ),
+    (
+        "Project Files",
+        "The project's executable, built from its sources, with its include folders, compile options and libraries.",
+    ),
+    (
+        "Target",
+        "A library or executable from [[targets]] in CMakeMake.toml.",
+    ),
+    (
+        "Tests",
+        "The test executable, registered with ctest so 'cmm test' can run each case.",
+    ),
+    (
+        "Examples",
+        "Each file tagged as an example in cmake.files is an executable of its own.",
+    ),
+    (
+        "Benchmarks",
+        "Each file tagged as a bench in cmake.files is an executable of its own.",
+    ),
+    ("Install", "What 'cmm install' copies, and where to."),
+    (
+        "Packaging",
+        "Settings for cpack, which 'cmm package' runs to make installers and archives.",
+    ),
+];
+
+/// Width of the verbose style's banners
+const BANNER_WIDTH: usize = 78;
+
+/// Rewrites the `#Section:` comments for the config's output style: left out along with
+/// blank lines when compact, turned into banners with a note on what the section does when
+/// verbose. The first line's `# ...` comment always stays, it marks the file as generated.
+fn apply_output_style(contents: &str, style: config::OutputStyle) -> String {
+    if style == config::OutputStyle::Normal {
+        return contents.to_owned();
+    }
+
+    let rule = format!("# {}", "-".repeat(BANNER_WIDTH - 2));
+    let mut output = String::with_capacity(contents.len());
+
+    for line in contents.lines() {
+        let section = line
+            .strip_prefix('#')
+            .filter(|title| !title.is_empty() && !title.starts_with(' '))
+            .map(|title| title.trim_end_matches(':'));
+
+        match (style, section) {
+            (config::OutputStyle::Compact, Some(_)) => continue,
+            (config::OutputStyle::Compact, None) if line.trim().is_empty() => continue,
+
+            (config::OutputStyle::Verbose, Some(title)) => {
+                let name = title.split(':').next().unwrap_or(title);
+
+                output.push_str(&format!("{rule}\n# {title}\n"));
+                if let Some((_, note)) = SECTION_NOTES.iter().find(|(section, _)| *section == name)
+                {
+                    wrap_comment(&mut output, note);
+                }
+                output.push_str(&rule);
+            }
+
+            _ => output.push_str(line),
+        }
+
+        output.push('\n');
+    }
+
+    output
+}
+
+/// Adds `text` as `#` comment lines that fit in the banner width
+fn wrap_comment(output: &mut String, text: &str) {
+    let mut line = String::from("#");
+
+    for word in text.split_whitespace() {
+        if line.len() + word.len() + 1 > BANNER_WIDTH {
+            output.push_str(&line);
+            output.push('\n');
+            line = String::from("#");
+        }
+        line.push(' ');
+        line.push_str(word);
+    }
+
+    output.push_str(&line);
+    output.push('\n');
+}
+
+/// Trailing whitespace and runs of blank lines depend on which sections are empty, so
+/// they're cleaned up afterwards to keep diffs down to real changes
+fn normalize_whitespace(contents: &str) -> String {
+    let mut output = String::with_capacity(contents.len());
+    let mut blank = true;
+
+    for line in contents.lines().map(str::trim_end) {
+        if line.is_empty() && blank {
+            continue;
+        }
+
+        blank = line.is_empty();
+        output.push_str(line);
+        output.push('\n');
+    }
+
+    output.truncate(output.trim_end().len());
+    output.push('\n');
+    output
+}
+
+/// CMake option `cmm build --no-cache-launcher` turns off
+pub const CACHE_LAUNCHER_OPTION: &str = "CMM_CACHE_LAUNCHER";
+
+/// The contents of CMakeLists.txt for `config`. The same config always gives the same
+/// output, so a generated file checked into version control only changes with the config.
+pub fn cmake_lists(config: &ConfigFile, hash_comment: bool) -> String {
+    let mut file = Vec::new();
+
+    // Config Hash
+    match hash_comment {
+        true => writeln!(file, "# {}\n", config_hash(config)).unwrap(),
+        false => writeln!(file, "{}\n", GENERATED_MARKER).unwrap(),
+    }
+
+    // Project Setup
+    writeln!(
+        file,
+        "cmake_minimum_required(VERSION {})",
+        cmake_version::required_version(config)
+    )
+    .unwrap();
+
+    write_snippet(&mut file, config.cmake.prelude.as_deref()).unwrap();
+    writeln!(file, r#"project("{}")"#, config.project.name).unwrap();
+
+    if !config.cmake.allow_in_source {
+        writeln!(
+            file,
+            "\nif(CMAKE_SOURCE_DIR STREQUAL CMAKE_BINARY_DIR)\n    message(FATAL_ERROR \"In-source builds are disabled. Use 'cmm build' or 'cmake -B build'\")\nendif()"
+        )
+        .unwrap();
+    }
+
+    write_consumer_options(&mut file, config).unwrap();
+
+    // Project top config
+    writeln!(file, "\n#Project Config Flags:").unwrap();
+
+    writeln!(file, "set(CMAKE_EXPORT_COMPILE_COMMANDS ON)").unwrap();
+
+    if let Some(standard) = config.cmake.cxx_standard {
+        let extensions = match config.cmake.cxx_extensions {
+            true => "ON",
+            false => "OFF",
+        };
+
+        writeln!(file, "set(CMAKE_CXX_STANDARD {})", standard).unwrap();
+        writeln!(file, "set(CMAKE_CXX_STANDARD_REQUIRED ON)").unwrap();
+        writeln!(file, "set(CMAKE_CXX_EXTENSIONS {})", extensions).unwrap();
+    }
+
+    // Directory wide so dependencies built as part of the project link the same way
+    let link_options = config.cmake.link_options();
+    if !link_options.is_empty() {
+        writeln!(file, "add_link_options({})", link_options.join(" ")).unwrap();
+    }
+
+    // Compiler Cache, found when configuring so the file doesn't depend on this machine.
+    // Set before dependencies are added so they are cached too.
+    let cache_programs = config.cmake.cache_programs();
+    if !cache_programs.is_empty() {
+        writeln!(file, "\n#Compiler Cache:").unwrap();
+        writeln!(
+            file,
+            r#"option({} "Compile through a compiler cache when one is installed" ON)"#,
+            CACHE_LAUNCHER_OPTION
+        )
+        .unwrap();
+        writeln!(
+            file,
+            "if({} AND NOT CMAKE_CXX_COMPILER_LAUNCHER)",
+            CACHE_LAUNCHER_OPTION
+        )
+        .unwrap();
+
+        // Searched again each time so changing cmake.cache_launcher takes effect
+        writeln!(file, "    unset(CMM_CACHE_PROGRAM CACHE)").unwrap();
+        writeln!(
+            file,
+            "    find_program(CMM_CACHE_PROGRAM NAMES {})",
+            cache_programs.join(" ")
+        )
+        .unwrap();
+        // ccache reads its folder from the environment, so a folder of the project's own
+        // goes in front of each compile
+        let launcher = match &config.cmake.cache_dir {
+            Some(dir) => format!(
+                r#""${{CMAKE_COMMAND}}" -E env "CCACHE_DIR={}" "${{CMM_CACHE_PROGRAM}}""#,
+                prefix_path_expr(dir)
+            ),
+            None => r#""${CMM_CACHE_PROGRAM}""#.into(),
+        };
+        writeln!(
+            file,
+            "    if(CMM_CACHE_PROGRAM)\n        set(CMAKE_C_COMPILER_LAUNCHER {launcher})\n        set(CMAKE_CXX_COMPILER_LAUNCHER {launcher})\n    endif()\nendif()"
+        )
+        .unwrap();
+    }
+
+    // Build Profiles
+    writeln!(file, "\n#Build Profiles:").unwrap();
+    writeln!(
+        file,
+        r#"set(CMM_PROFILE "{}" CACHE STRING "cmakemake build profile")"#,
+        config::DEFAULT_PROFILE
+    )
+    .unwrap();
+
+    // Single config generators get their build type from the profile on the command line,
+    // this only covers running cmake by hand
+    writeln!(
+        file,
+        "if(NOT CMAKE_CONFIGURATION_TYPES AND NOT CMAKE_BUILD_TYPE)\n    set(CMAKE_BUILD_TYPE Debug)\nendif()"
+    )
+    .unwrap();
+
+    // Flags are wrapped in generator expressions so they apply to the right configuration
+    // with multi config generators too
+    config.profiles().iter().for_each(|(name, profile)| {
+        if profile.cxx_flags.is_empty()
+            && profile.definitions.is_empty()
+            && profile.sanitize.is_empty()
+            && !profile.coverage
+        {
+            return;
+        }
+
+        let config_expr =
+            |value: &String| format!(r#""$<$<CONFIG:{}>:{}>""#, profile.build_type, value);
+
+        writeln!(file, r#"if(CMM_PROFILE STREQUAL "{}")"#, name).unwrap();
+
+        if !profile.cxx_flags.is_empty() {
+            writeln!(
+                file,
+                "    add_compile_options({})",
+                profile
+                    .cxx_flags
+                    .iter()
+                    .map(config_expr)
+                    .collect::<Vec<_>>()
+                    .join(" ")
+            )
+            .unwrap();
+        }
+
+        let (compile, link) = profile.instrumentation();
+
+        if !compile.is_empty() {
+            writeln!(
+                file,
+                "    add_compile_options({})",
+                compile
+                    .iter()
+                    .map(config_expr)
+                    .collect::<Vec<_>>()
+                    .join(" ")
+            )
+            .unwrap();
+        }
+
+        // The build type's own linker flags only apply to that configuration already
+        if !link.is_empty() {
+            for kind in ["EXE", "SHARED"] {
+                writeln!(
+                    file,
+                    r#"    string(APPEND CMAKE_{}_LINKER_FLAGS_{} " {}")"#,
+                    kind,
+                    profile.build_type.to_uppercase(),
+                    link.join(" ")
+                )
+                .unwrap();
+            }
+        }
+
+        if !profile.definitions.is_empty() {
+            writeln!(
+                file,
+                "    add_compile_definitions({})",
+                profile
+                    .definitions
+                    .iter()
+                    .map(config_expr)
+                    .collect::<Vec<_>>()
+                    .join(" ")
+            )
+            .unwrap();
+        }
+
+        writeln!(file, "endif()").unwrap();
+    });
+
+    // Project Dependencies
+    writeln!(file, "\n#Project Dependencies:").unwrap();
+
+    config.dependencies.prefix_paths.iter().for_each(|path| {
+        writeln!(
+            file,
+            r#"list(APPEND CMAKE_PREFIX_PATH "{}")"#,
+            prefix_path_expr(path)
+        )
+        .unwrap();
+    });
+
+    if !config.dependencies.prefix_paths.is_empty() {
+        writeln!(file).unwrap();
+    }
+
+    // Sorted so the order dependencies were added in doesn't show up in diffs. Local
+    // dependencies keep their order as variables set for one carry on to the next.
+    let mut find_dependencies = config.dependencies.find.iter().collect::<Vec<_>>();
+    find_dependencies.sort_by(|a, b| a.name.cmp(&b.name));
+
+    find_dependencies.into_iter().for_each(|find| {
+        let required = match find.required {
+            true => " REQUIRED",
+            false => "",
+        };
+
+        writeln!(file, "find_package({}{})", find.name, required).unwrap();
+        write_snippet(&mut file, find.extra_cmake.as_deref()).unwrap();
+    });
+
+    if !config.dependencies.find.is_empty() {
+        writeln!(file).unwrap();
+    }
+
+    // `cmm build` installs these into the build directory before configuring
+    if !config.dependencies.conan.is_empty() {
+        let mut conan_dependencies = config.dependencies.conan.iter().collect::<Vec<_>>();
+        conan_dependencies.sort_by(|a, b| a.name.cmp(&b.name));
+
+        writeln!(
+            file,
+            r#"list(PREPEND CMAKE_PREFIX_PATH "${{CMAKE_BINARY_DIR}}/{}")"#,
+            conan::OUTPUT_DIR
+        )
+        .unwrap();
+
+        conan_dependencies.into_iter().for_each(|conan| {
+            writeln!(file, "find_package({} CONFIG REQUIRED)", conan.package()).unwrap();
+            write_snippet(&mut file, conan.extra_cmake.as_deref()).unwrap();
+        });
+
+        writeln!(file).unwrap();
+    }
+
+    if !config.dependencies.fetch.is_empty() {
+        let mut fetch_dependencies = config.dependencies.fetch.iter().collect::<Vec<_>>();
+        fetch_dependencies.sort_by(|a, b| a.name.cmp(&b.name));
+
+        writeln!(file, "include(FetchContent)").unwrap();
+
+        fetch_dependencies.iter().for_each(|fetch| {
+            let mut variables = fetch.variables.iter().collect::<Vec<_>>();
+            variables.sort();
+
+            variables
+                .into_iter()
+                .for_each(|var| writeln!(file, "set({: <20} {})", var.0, var.1).unwrap());
+
+            let tag = match &fetch.tag {
+                Some(tag) => format!(" GIT_TAG {tag}"),
+                None => String::new(),
+            };
+
+            writeln!(
+                file,
+                "FetchContent_Declare({} GIT_REPOSITORY {}{})",
+                fetch.name, fetch.repo, tag
+            )
+            .unwrap();
+        });
+
+        writeln!(
+            file,
+            "FetchContent_MakeAvailable({})",
+            fetch_dependencies
+                .iter()
+                .map(|fetch| fetch.name.as_str())
+                .collect::<Vec<_>>()
+                .join(" ")
+        )
+        .unwrap();
+
+        // After MakeAvailable, which is when the dependencies' targets exist
+        fetch_dependencies
+            .iter()
+            .for_each(|fetch| write_snippet(&mut file, fetch.extra_cmake.as_deref()).unwrap());
+        writeln!(file).unwrap();
+    }
+
+    if !config.dependencies.cpm.is_empty() {
+        let mut cpm_dependencies = config.dependencies.cpm.iter().collect::<Vec<_>>();
+        cpm_dependencies.sort_by(|a, b| a.name.cmp(&b.name));
+
+        writeln!(file, "include({})", cpm::CPM_PATH).unwrap();
+
+        cpm_dependencies.into_iter().for_each(|cpm| {
+            let mut variables = cpm.variables.iter().collect::<Vec<_>>();
+            variables.sort();
+
+            let options = variables
+                .into_iter()
+                .map(|(var, value)| format!(r#" "{} {}""#, var, value.trim()))
+                .collect::<String>();
+
+            let options = match options.is_empty() {
+                true => options,
+                false => format!(" OPTIONS{options}"),
+            };
+
+            // Without a tag CPM checks out v<VERSION>, HEAD is the repo's default branch
+            writeln!(
+                file,
+                "CPMAddPackage(NAME {} GIT_REPOSITORY {} GIT_TAG {}{})",
+                cpm.name,
+                cpm.repo,
+                cpm.tag.as_deref().unwrap_or("HEAD"),
+                options
+            )
+            .unwrap();
+            write_snippet(&mut file, cpm.extra_cmake.as_deref()).unwrap();
+        });
+
+        writeln!(file).unwrap();
+    }
+
+    config.dependencies.local.iter().for_each(|local| {
+        let mut variables = local.variables.iter().collect::<Vec<_>>();
+        variables.sort();
+
+        variables
+            .into_iter()
+            .for_each(|var| writeln!(file, "set({: <20} {})", var.0, var.1).unwrap());
+
+        match &local.local_type {
+            // Folders outside the project need an explicit binary directory
+            config::LocalType::CMake => match local.monorepo {
+                true => writeln!(file, "add_subdirectory({} {})", local.path, local.name).unwrap(),
+                false => writeln!(file, "add_subdirectory({})", local.path).unwrap(),
+            },
+
+            config::LocalType::Source {
+                files,
+                dependencies,
+            } => {
+                let name = &local.name;
+
+                let src_name = format!("{}_SOURCES", name.to_uppercase());
+
+                write_source_files(&mut file, &src_name, &local.path, files).unwrap();
+
+                match files.source_files.is_empty() {
+                    true => writeln!(file, "add_library({name} INTERFACE)").unwrap(),
+                    false => writeln!(file, "add_library({name} ${{{src_name}}})").unwrap(),
+                }
+
+                write_include_dirs(&mut file, name, &local.path, files).unwrap();
+
+                if !dependencies.is_empty() {
+                    writeln!(
+                        file,
+                        "target_link_libraries({name} PUBLIC {})",
+                        dependencies.join(" ")
+                    )
+                    .unwrap();
+                }
+            }
+        }
+
+        write_snippet(&mut file, local.extra_cmake.as_deref()).unwrap();
+        writeln!(file).unwrap();
+    });
+
+    writeln!(file, "#Project Files:").unwrap();
+
+    // Project files
+    write_source_files(
+        &mut file,
+        "SOURCES",
+        &config.cmake.source_dir,
+        &config.cmake.files,
+    )
+    .unwrap();
+
+    write_role_sources(
+        &mut file,
+        "SOURCES",
+        &config.cmake.source_dir,
+        &config.cmake.files,
+    )
+    .unwrap();
+
+    // Link files
+    match config.project.kind {
+        config::ProjectKind::Executable => {
+            writeln!(file, r#"add_executable("${{PROJECT_NAME}}" ${{SOURCES}})"#).unwrap()
+        }
+        config::ProjectKind::Library => {
+            writeln!(file, r#"add_library("${{PROJECT_NAME}}" ${{SOURCES}})"#).unwrap()
+        }
+    }
+
+    write_include_dirs(
+        &mut file,
+        r#""${PROJECT_NAME}""#,
+        &config.cmake.source_dir,
+        &config.cmake.files,
+    )
+    .unwrap();
+
+    write_compile_options(&mut file, r#""${PROJECT_NAME}""#, &config.cmake).unwrap();
+
+    write_target_links(
+        &mut file,
+        r#""${PROJECT_NAME}""#,
+        "PRIVATE",
+        &config.dependencies.project_dependencies,
+        &config.targets,
+    )
+    .unwrap();
+
+    // Copied by a target of its own so changed assets are copied without relinking
+    if !config.cmake.resources.is_empty() {
+        writeln!(
+            file,
+            r#"add_custom_target("${{PROJECT_NAME}}_resources" ALL"#
+        )
+        .unwrap();
+        config.cmake.resources.iter().for_each(|dir| {
+            let dir = join_path(".", dir);
+            writeln!(
+                file,
+                r#"    COMMAND "${{CMAKE_COMMAND}}" -E copy_directory "${{CMAKE_CURRENT_SOURCE_DIR}}/{dir}" "$<TARGET_FILE_DIR:${{PROJECT_NAME}}>/{dir}""#
+            )
+            .unwrap()
+        });
+        writeln!(file, ")").unwrap();
+        writeln!(
+            file,
+            r#"add_dependencies("${{PROJECT_NAME}}" "${{PROJECT_NAME}}_resources")"#
+        )
+        .unwrap();
+    }
+
+    // Extra targets
+    config
+        .targets
+        .iter()
+        .for_each(|target| write_target(&mut file, target, &config.targets).unwrap());
+
+    if let Some(tests) = &config.tests {
+        let mut block = Vec::new();
+        testing::write_tests(&mut block, tests).unwrap();
+
+        // Tests include project headers the same way the project does
+        write_include_dirs(
+            &mut block,
+            testing::TEST_TARGET,
+            &config.cmake.source_dir,
+            &config.cmake.files,
+        )
+        .unwrap();
+        write_compile_options(&mut block, testing::TEST_TARGET, &config.cmake).unwrap();
+        write_target_links(
+            &mut block,
+            testing::TEST_TARGET,
+            "PRIVATE",
+            &config.dependencies.project_dependencies,
+            &config.targets,
+        )
+        .unwrap();
+        write_library_link(&mut block, testing::TEST_TARGET, config).unwrap();
+
+        let option = consumer_option(config, "BUILD_TESTS");
+        write_guarded(&mut file, option.as_deref(), &block).unwrap();
+    }
+
+    write_role_executables(&mut file, config).unwrap();
+
+    if let Some(install) = &config.install {
+        let mut block = Vec::new();
+        write_install(&mut block, &config.project.name, install).unwrap();
+
+        let option = consumer_option(config, "INSTALL");
+        write_guarded(&mut file, option.as_deref(), &block).unwrap();
+    }
+
+    if let Some(packaging) = &config.packaging {
+        write_packaging(&mut file, &config.project, packaging).unwrap();
+    }
+
+    if config.cmake.postlude.is_some() {
+        writeln!(file).unwrap();
+        write_snippet(&mut file, config.cmake.postlude.as_deref()).unwrap();
+    }
+
+    normalize_whitespace(&apply_output_style(
+        &String::from_utf8(file).unwrap(),
+        config.cmake.output_style,
+    ))
+}
+
+/// `cmm cmake --dry-run`. Shows how regenerating would change CMakeLists.txt without
+/// writing anything, so config changes can be reviewed first.
+pub fn preview_cmake(hash_comment: bool) -> Result<(), ProjectError> {
+    let mut config = get_config()?;
+    lockfile::pin(&mut config, &lockfile::read_lockfile()?);
+
+    let existing = match std::fs::read_to_string("CMakeLists.txt") {
+        Ok(existing) => existing,
+        Err(_) => {
+            println!(
+                "{}",
+                t!("CMakeLists.txt doesn't exist yet and would be created")
+            );
+            String::new()
+        }
+    };
+
+    let contents = keep_blocks(&existing, cmake_lists(&config, hash_comment));
+
+    if existing == contents {
+        println!(
+            "{}",
+            t!("CMakeLists.txt is up to date, nothing would change")
+        );
+        return Ok(());
+    }
+
+    print_diff(&existing, &contents);
+
+    Ok(())
+}
+
+/// Writes CMakeLists.txt for the project in the current directory. Without `hash_comment`
+/// the file doesn't change between machines or toolchains, but builds have to regenerate
+/// it to find out whether it is out of date.
+pub fn generate_cmake(hash_comment: bool) -> Result<(), ProjectError> {
+    status!("{}", t!("Generating CMakeLists.txt from config"));
+
+    let instant = std::time::Instant::now();
+
+    let mut config = get_config()?;
+    lockfile::pin(&mut config, &lockfile::read_lockfile()?);
+
+    check_in_source(&config)?;
+    cmake_version::warn_if_raised(&config);
+    conan::write_conanfile(&config)?;
+    cpm::write_loader(&config)?;
+
+    let path = Path::new("CMakeLists.txt");
+    let existing = std::fs::read_to_string(path).unwrap_or_default();
+    std::fs::write(
+        path,
+        keep_blocks(&existing, cmake_lists(&config, hash_comment)),
+    )
+    .map_err(|err| ProjectError::FailedToCreateFile(path.to_owned(), err.to_string()))?;
+
+    status!(
+        "{}",
+        t!(
+            "{} creating CMakeLists.txt in {}s",
+            t!("Finished").green().bold(),
+            format!("{:.3}", instant.elapsed().as_secs_f32())
+        )
+    );
+
+    Ok(())
+}
@@ -0,0 +1,204 @@
+use std::path::{Path, PathBuf};
+
+use colored::Colorize;
+
+use crate::{
+    config::ConfigFile, error::ProjectError, messages::t, output::notice, util::get_global_config,
+};
+
+/// How to tell whether a generator's build tool is available
+enum Detect {
+    /// A program on the PATH
+    Program(&'static str),
+    /// A Visual Studio install in this version range, found with vswhere
+    VisualStudio(&'static str),
+}
+
+struct Generator {
+    name: &'static str,
+    /// Short name accepted by `--generator` and `cmake.generator`
+    alias: &'static str,
+    detect: Detect,
+}
+
+/// Generators cmakemake knows how to detect. Other generator names are passed to cmake
+/// unchecked.
+const GENERATORS: [Generator; 8] = [
+    Generator {
+        name: "Ninja",
+        alias: "ninja",
+        detect: Detect::Program("ninja"),
+    },
+    Generator {
+        name: "Ninja Multi-Config",
+        alias: "ninja-multi",
+        detect: Detect::Program("ninja"),
+    },
+    Generator {
+        name: "Unix Makefiles",
+        alias: "make",
+        detect: Detect::Program("make"),
+    },
+    Generator {
+        name: "MinGW Makefiles",
+        alias: "mingw",
+        detect: Detect::Program("mingw32-make"),
+    },
+    Generator {
+        name: "NMake Makefiles",
+        alias: "nmake",
+        detect: Detect::Program("nmake"),
+    },
+    Generator {
+        name: "Visual Studio 17 2022",
+        alias: "vs2022",
+        detect: Detect::VisualStudio("[17.0,18.0)"),
+    },
+    Generator {
+        name: "Visual Studio 16 2019",
+        alias: "vs2019",
+        detect: Detect::VisualStudio("[16.0,17.0)"),
+    },
+    Generator {
+        name: "Xcode",
+        alias: "xcode",
+        detect: Detect::Program("xcodebuild"),
+    },
+];
+
+fn on_path(program: &str) -> bool {
+    let program = match cfg!(windows) {
+        true => format!("{program}.exe"),
+        false => program.to_owned(),
+    };
+
+    std::env::var_os("PATH")
+        .is_some_and(|paths| std::env::split_paths(&paths).any(|dir| dir.join(&program).is_file()))
+}
+
+fn vswhere() -> Option<PathBuf> {
+    let program_files = std::env::var_os("ProgramFiles(x86)")?;
+    let path = Path::new(&program_files).join("Microsoft Visual Studio/Installer/vswhere.exe");
+
+    path.exists().then_some(path)
+}
+
+fn has_visual_studio(versions: &str) -> bool {
+    let vswhere = match vswhere() {
+        Some(vswhere) => vswhere,
+        None => return false,
+    };
+
+    duct::cmd!(
+        vswhere,
+        "-version",
+        versions,
+        "-property",
+        "installationPath"
+    )
+    .stdout_capture()
+    .unchecked()
+    .run()
+    .is_ok_and(|output| !output.stdout.trim_ascii().is_empty())
+}
+
+impl Generator {
+    fn is_installed(&self) -> bool {
+        match self.detect {
+            Detect::Program(program) => on_path(program),
+            Detect::VisualStudio(versions) => has_visual_studio(versions),
+        }
+    }
+}
+
+fn find(name: &str) -> Option<&'static Generator> {
+    GENERATORS.iter().find(|generator| {
+        generator.name.eq_ignore_ascii_case(name) || generator.alias.eq_ignore_ascii_case(name)
+    })
+}
+
+/// Generators that build every configuration in one build folder, putting each one's
+/// output in a folder named after it
+pub fn is_multi_config(generator: &str) -> bool {
+    generator.starts_with("Visual Studio")
+        || generator == "Xcode"
+        || generator == "Ninja Multi-Config"
+}
+
+/// Names of the known generators whose build tool is installed
+pub fn installed() -> Vec<&'static str> {
+    GENERATORS
+        .iter()
+        .filter(|generator| generator.is_installed())
+        .map(|generator| generator.name)
+        .collect()
+}
+
+/// Checks a generator picked by the user, turning aliases into the name cmake expects
+fn checked(name: &str) -> Result<String, ProjectError> {
+    match find(name) {
+        Some(generator) if generator.is_installed() => Ok(generator.name.to_owned()),
+        Some(generator) => Err(ProjectError::GeneratorNotInstalled(
+            generator.name.to_owned(),
+            installed().into_iter().map(String::from).collect(),
+        )),
+        None => Ok(name.to_owned()),
+    }
+}
+
+/// The generator to configure with, from `--generator`, `cmake.generator`, `CMAKE_GENERATOR`
+/// or the user's default, in that order. `None` leaves the choice to cmake.
+pub fn resolve(cli: Option<&str>, config: &ConfigFile) -> Result<Option<String>, ProjectError> {
+    if let Some(name) = cli.or(config.cmake.generator.as_deref()) {
+        return checked(name).map(Some);
+    }
+
+    // cmake reads this itself, it only needs passing on so cache checks see it
+    if let Some(name) = std::env::var("CMAKE_GENERATOR")
+        .ok()
+        .filter(|val| !val.is_empty())
+    {
+        return Ok(Some(name));
+    }
+
+    // The default from setup applies to every project, so one missing tool falls back
+    // to cmake's choice instead of breaking every build
+    let default = match get_global_config()?.generator {
+        Some(default) => default,
+        None => return Ok(None),
+    };
+
+    match checked(&default) {
+        Ok(name) => Ok(Some(name)),
+        Err(_) => {
+            notice!(
+                "{}",
+                t!(
+                    "{} default generator '{}' isn't installed, letting cmake choose",
+                    t!("warning:").yellow(),
+                    default
+                )
+            );
+            Ok(None)
+        }
+    }
+}
+
+/// `cmm generators`. Lists the known generators and which are installed.
+pub fn list_generators() -> Result<(), ProjectError> {
+    for generator in GENERATORS.iter() {
+        let status = match generator.is_installed() {
+            true => t!("installed").green(),
+            false => t!("not found").dimmed(),
+        };
+
+        println!(
+            "\t{: <24}{: <14}{}",
+            generator.name.cyan(),
+            generator.alias,
+            status
+        );
+    }
+
+    Ok(())
+}
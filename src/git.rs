@@ -0,0 +1,312 @@
+use std::path::{Path, PathBuf};
+
+use colored::Colorize;
+
+use crate::{
+    config::ConfigFile,
+    error::ProjectError,
+    output::{self, notice},
+    patches,
+    util::{remove_path, use_trash},
+};
+
+pub struct Submodule {
+    pub name: String,
+    pub path: String,
+    pub url: Option<String>,
+}
+
+/// Opens the repo containing the current directory. Uses discovery instead of expecting a
+/// `.git` folder so linked worktrees (where `.git` is a file) and projects nested inside a
+/// larger repo both work.
+pub fn open_repo() -> Result<git2::Repository, ProjectError> {
+    git2::Repository::discover(".").map_err(|err| ProjectError::FailedToOpenGit(err.to_string()))
+}
+
+pub fn is_inside_repo(path: &Path) -> bool {
+    git2::Repository::discover(path).is_ok()
+}
+
+/// Returns true if the path exists and is inside the work tree of the current repo
+pub fn is_within_repo(path: &Path) -> bool {
+    let workdir = match open_repo() {
+        Ok(repo) => repo
+            .workdir()
+            .and_then(|workdir| workdir.canonicalize().ok()),
+        Err(_) => None,
+    };
+
+    match (workdir, path.canonicalize()) {
+        (Some(workdir), Ok(path)) => path.starts_with(workdir),
+        _ => false,
+    }
+}
+
+/// Folder of the current project relative to the root of its repo
+fn project_prefix(repo: &git2::Repository) -> PathBuf {
+    let current_dir = std::env::current_dir()
+        .and_then(|dir| dir.canonicalize())
+        .unwrap_or_default();
+
+    repo.workdir()
+        .and_then(|workdir| workdir.canonicalize().ok())
+        .and_then(|workdir| current_dir.strip_prefix(workdir).ok().map(Path::to_owned))
+        .unwrap_or_default()
+}
+
+/// Directory git keeps submodule repos in. Shared between all worktrees of a repo.
+pub fn modules_dir(repo: &git2::Repository) -> PathBuf {
+    repo.commondir().join("modules")
+}
+
+/// Submodules inside the current project, with paths relative to the project folder
+pub fn read_submodules() -> Result<Vec<Submodule>, ProjectError> {
+    let repo = open_repo()?;
+    let prefix = project_prefix(&repo);
+
+    let submodules = repo
+        .submodules()
+        .map_err(|err| ProjectError::FailedToOpenGit(err.to_string()))?
+        .iter()
+        .filter_map(|submodule| {
+            let path = submodule.path().strip_prefix(&prefix).ok()?;
+
+            Some(Submodule {
+                name: submodule.name().unwrap_or_default().to_owned(),
+                path: path.to_string_lossy().replace('\\', "/"),
+                url: submodule.url().map(str::to_owned),
+            })
+        })
+        .collect();
+
+    Ok(submodules)
+}
+
+/// Clones any submodules of the project that haven't been checked out yet, at the commits
+/// the repo pins. Projects without a git repo or submodules have nothing to do.
+pub fn init_submodules() -> Result<(), ProjectError> {
+    let submodules = match read_submodules() {
+        Ok(submodules) if !submodules.is_empty() => submodules,
+        _ => return Ok(()),
+    };
+
+    let output = output::run(output::command(
+        "git",
+        ["submodule", "update", "--init", "--recursive", "--"]
+            .into_iter()
+            .chain(submodules.iter().map(|submodule| submodule.path.as_str())),
+    ))
+    .unwrap();
+
+    if !output.status.success() {
+        Err(ProjectError::FailedToRunProcess(
+            "git submodule update --init".into(),
+            output.status.code(),
+        ))?;
+    }
+
+    Ok(())
+}
+
+/// Deinits and removes a submodule, including its cached repo under the git dir
+pub fn remove_submodule(submodule: &Submodule) -> Result<(), ProjectError> {
+    let path = submodule.path.as_str();
+
+    // deinit deletes the checkout for good, so move it out of the way first
+    if use_trash() {
+        remove_path(Path::new(path))
+            .map_err(|err| ProjectError::FailedToRemove(path.into(), err.to_string()))?;
+    }
+
+    let output = output::run(output::command("git", ["submodule", "deinit", "-f", path])).unwrap();
+
+    if !output.status.success() {
+        Err(ProjectError::FailedToRunProcess(
+            format!("git submodule deinit -f {}", path),
+            output.status.code(),
+        ))?;
+    }
+
+    let output = output::run(output::command("git", ["rm", "-f", path])).unwrap();
+
+    if !output.status.success() {
+        Err(ProjectError::FailedToRunProcess(
+            format!("git rm -f {}", path),
+            output.status.code(),
+        ))?;
+    }
+
+    // Clean out the cached repo so the submodule can be re-added later
+    remove_path(&modules_dir(&open_repo()?).join(&submodule.name)).ok();
+
+    Ok(())
+}
+
+/// Runs git for cleanup that may find nothing to clean, so failures and output are ignored
+fn git_quietly(args: &[&str]) {
+    duct::cmd("git", args)
+        .stdout_capture()
+        .stderr_capture()
+        .unchecked()
+        .run()
+        .ok();
+}
+
+/// Removes whatever a failed `git submodule add` left behind for `path`: its index entry,
+/// its sections in .gitmodules and .git/config, its cached repo and its folder. Each step
+/// is skipped when there's nothing to remove, so it's safe on half added submodules.
+pub fn discard_submodule(path: &str) -> Result<(), ProjectError> {
+    let repo = open_repo()?;
+    let name = project_prefix(&repo)
+        .join(path)
+        .to_string_lossy()
+        .replace('\\', "/");
+    let section = format!("submodule.{}", name);
+
+    git_quietly(&[
+        "rm",
+        "--cached",
+        "-r",
+        "-f",
+        "-q",
+        "--ignore-unmatch",
+        "--",
+        path,
+    ]);
+
+    if let Some(gitmodules) = repo.workdir().map(|workdir| workdir.join(".gitmodules")) {
+        if gitmodules.exists() {
+            let gitmodules = gitmodules.to_string_lossy();
+            git_quietly(&["config", "-f", &gitmodules, "--remove-section", &section]);
+            git_quietly(&["add", "--", &gitmodules]);
+        }
+    }
+
+    git_quietly(&["config", "--remove-section", &section]);
+
+    let cached = modules_dir(&repo).join(&name);
+    if cached.exists() {
+        remove_path(&cached)
+            .map_err(|err| ProjectError::FailedToRemove(cached.clone(), err.to_string()))?;
+    }
+
+    if Path::new(path).exists() {
+        remove_path(Path::new(path))
+            .map_err(|err| ProjectError::FailedToRemove(path.into(), err.to_string()))?;
+    }
+
+    Ok(())
+}
+
+/// Cached submodule repos for folders in external/ that have no submodule any more, as
+/// project paths. git refuses to add a submodule at the same path again while one exists.
+pub fn stale_module_caches() -> Result<Vec<String>, ProjectError> {
+    let repo = open_repo()?;
+    let prefix = project_prefix(&repo);
+    let submodules = read_submodules()?;
+
+    let Ok(dir) = std::fs::read_dir(modules_dir(&repo).join(&prefix).join("external")) else {
+        return Ok(Vec::new());
+    };
+
+    let stale = dir
+        .filter_map(|entry| entry.ok())
+        .filter(|entry| entry.path().is_dir())
+        .map(|entry| format!("external/{}", entry.file_name().to_string_lossy()))
+        .filter(|path| !submodules.iter().any(|submodule| &submodule.path == path))
+        .collect();
+
+    Ok(stale)
+}
+
+pub struct DirtySubmodule {
+    pub path: String,
+    /// Working tree or index has local changes
+    pub modified: bool,
+    /// Checked out commit differs from the one pinned by the project
+    pub diverged: bool,
+}
+
+/// Finds submodules whose checkouts don't match what the project has pinned.
+/// Projects without a git repo have nothing to check.
+pub fn dirty_submodules() -> Result<Vec<DirtySubmodule>, ProjectError> {
+    let repo = match open_repo() {
+        Ok(repo) => repo,
+        Err(_) => return Ok(Vec::new()),
+    };
+
+    let dirty = read_submodules()?
+        .into_iter()
+        .filter_map(|submodule| {
+            let status = repo
+                .submodule_status(&submodule.name, git2::SubmoduleIgnore::Untracked)
+                .ok()?;
+
+            let modified = status.contains(git2::SubmoduleStatus::WD_INDEX_MODIFIED)
+                || status.is_wd_wd_modified();
+            let diverged = status.is_wd_modified();
+
+            match modified || diverged {
+                true => Some(DirtySubmodule {
+                    path: submodule.path,
+                    modified,
+                    diverged,
+                }),
+                false => None,
+            }
+        })
+        .collect();
+
+    Ok(dirty)
+}
+
+/// Warns about modified dependency checkouts, or fails if `frozen` is set.
+/// Dependencies whose only changes are their own patches aren't reported.
+pub fn check_dependency_checkouts(config: &ConfigFile, frozen: bool) -> Result<(), ProjectError> {
+    let dirty = dirty_submodules()?
+        .into_iter()
+        .filter_map(|mut submodule| {
+            let patched = config.dependencies.local.iter().any(|local| {
+                local.path == submodule.path && patches::only_patched(&local.name, &local.path)
+            });
+
+            if patched {
+                submodule.modified = false;
+            }
+
+            match submodule.modified || submodule.diverged {
+                true => Some(submodule),
+                false => None,
+            }
+        })
+        .collect::<Vec<_>>();
+
+    if dirty.is_empty() {
+        return Ok(());
+    }
+
+    dirty.iter().for_each(|submodule| {
+        let reason = match (submodule.modified, submodule.diverged) {
+            (true, true) => "has local changes and differs from the pinned commit",
+            (true, false) => "has local changes",
+            _ => "differs from the pinned commit",
+        };
+
+        notice!(
+            "{} dependency '{}' {}",
+            "warning:".yellow(),
+            submodule.path,
+            reason
+        );
+    });
+
+    if frozen {
+        return Err(ProjectError::DirtyDependencies(
+            dirty.into_iter().map(|submodule| submodule.path).collect(),
+        ));
+    }
+
+    notice!();
+
+    Ok(())
+}
@@ -0,0 +1,241 @@
+use std::{
+    hash::{Hash, Hasher},
+    path::{Path, PathBuf},
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use colored::Colorize;
+
+use crate::{
+    archive::project_root,
+    build::BuildOptions,
+    error::ProjectError,
+    messages::t,
+    util::{executable_path, get_config},
+    warnings::count_warnings,
+};
+
+/// Builds kept per project, older ones are dropped
+const MAX_BUILDS: usize = 200;
+
+#[derive(serde::Deserialize, serde::Serialize, Clone)]
+pub struct BuildRecord {
+    /// Seconds since the unix epoch the build finished at
+    pub time: u64,
+    pub profile: String,
+    pub seconds: f64,
+    /// Warnings in the build's output. Only counted when the output was captured, which
+    /// builds of projects with a warnings baseline do.
+    pub warnings: Option<usize>,
+    /// Size of the main project executable, none for libraries
+    pub binary_size: Option<u64>,
+}
+
+#[derive(serde::Deserialize, serde::Serialize, Default)]
+struct History {
+    builds: Vec<BuildRecord>,
+}
+
+/// Each project gets its own file in the cmakemake data folder, named after its folder
+fn history_path(root: &str) -> PathBuf {
+    let mut hasher = std::hash::DefaultHasher::new();
+    root.hash(&mut hasher);
+
+    let name = Path::new(root)
+        .file_name()
+        .map(|name| name.to_string_lossy().into_owned())
+        .unwrap_or_default();
+
+    let data_dir = directories::BaseDirs::new().unwrap().data_dir().to_owned();
+    data_dir
+        .join("cmakemake/history")
+        .join(format!("{}-{:016x}.toml", name, hasher.finish()))
+}
+
+fn read_history(path: &Path) -> Result<History, ProjectError> {
+    if !path.exists() {
+        return Ok(History::default());
+    }
+
+    let contents = std::fs::read_to_string(path)
+        .map_err(|err| ProjectError::CannotOpenFile(path.to_owned(), err.to_string()))?;
+
+    toml::from_str(&contents)
+        .map_err(|err| ProjectError::CannotOpenFile(path.to_owned(), err.to_string()))
+}
+
+/// Adds a finished build to the project's history. `output` is the build output when it
+/// was captured.
+pub fn record_build(
+    options: &BuildOptions,
+    seconds: f64,
+    output: Option<&str>,
+) -> Result<(), ProjectError> {
+    let config = get_config()?;
+    let root = project_root()?;
+
+    let build_type = config
+        .profile(&options.profile)
+        .map_or(String::new(), |profile| profile.build_type);
+    let binary = executable_path(&options.build_dir(), &build_type, &config.project.name);
+
+    let record = BuildRecord {
+        time: SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |time| time.as_secs()),
+        profile: options.profile.clone(),
+        seconds,
+        warnings: output.map(|output| count_warnings(output, &root).values().sum()),
+        binary_size: std::fs::metadata(binary)
+            .ok()
+            .filter(|metadata| metadata.is_file())
+            .map(|metadata| metadata.len()),
+    };
+
+    let path = history_path(&root);
+    let mut history = read_history(&path)?;
+    history.builds.push(record);
+
+    let excess = history.builds.len().saturating_sub(MAX_BUILDS);
+    history.builds.drain(..excess);
+
+    let dir = path.parent().unwrap();
+    std::fs::create_dir_all(dir)
+        .map_err(|err| ProjectError::FailedToCreateFolder(dir.to_owned(), err.to_string()))?;
+
+    std::fs::write(&path, toml::to_string(&history).unwrap())
+        .map_err(|err| ProjectError::FailedToCreateFile(path, err.to_string()))
+}
+
+/// `YYYY-MM-DD HH:MM` in UTC
+fn format_time(time: u64) -> String {
+    let days = (time / 86400) as i64;
+    let minutes = time % 86400 / 60;
+
+    // Howard Hinnant's civil_from_days
+    let z = days + 719468;
+    let era = z.div_euclid(146097);
+    let day_of_era = z.rem_euclid(146097);
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36524 - day_of_era / 146096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let mp = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = year_of_era + era * 400 + i64::from(month <= 2);
+
+    format!(
+        "{:04}-{:02}-{:02} {:02}:{:02}",
+        year,
+        month,
+        day,
+        minutes / 60,
+        minutes % 60
+    )
+}
+
+fn format_size(bytes: u64) -> String {
+    match bytes {
+        0..1024 => format!("{} B", bytes),
+        1024..1048576 => format!("{:.1} KiB", bytes as f64 / 1024.0),
+        _ => format!("{:.1} MiB", bytes as f64 / 1048576.0),
+    }
+}
+
+/// The change from `first` to `last` as a percentage, coloured so growth stands out
+fn change(first: f64, last: f64) -> String {
+    if first <= 0.0 {
+        return "-".into();
+    }
+
+    let percent = (last - first) / first * 100.0;
+    let text = format!("{:+.0}%", percent);
+
+    match percent {
+        p if p >= 10.0 => text.red().to_string(),
+        p if p <= -10.0 => text.green().to_string(),
+        _ => text,
+    }
+}
+
+/// `cmm history`. Lists the project's most recent builds, then how build time and binary
+/// size changed over them for each profile. Incremental builds only compile what changed,
+/// so compare full rebuilds for build time and warnings.
+pub fn show_history(profile: Option<&str>, limit: usize) -> Result<(), ProjectError> {
+    // Only so running it outside a project says so
+    get_config()?;
+
+    let root = project_root()?;
+    let history = read_history(&history_path(&root))?;
+
+    let builds = history
+        .builds
+        .into_iter()
+        .filter(|build| profile.is_none_or(|profile| build.profile == profile))
+        .collect::<Vec<_>>();
+    let builds = &builds[builds.len().saturating_sub(limit)..];
+
+    if builds.is_empty() {
+        println!("{}", t!("No builds recorded yet"));
+        return Ok(());
+    }
+
+    println!(
+        "{}",
+        format!(
+            "{:<17}  {:<10}  {:>9}  {:>8}  {:>10}",
+            t!("Date"),
+            t!("Profile"),
+            t!("Time"),
+            t!("Warnings"),
+            t!("Size")
+        )
+        .bold()
+    );
+
+    for build in builds {
+        println!(
+            "{:<17}  {:<10}  {:>9}  {:>8}  {:>10}",
+            format_time(build.time),
+            build.profile,
+            format!("{:.1}s", build.seconds),
+            build.warnings.map_or("-".into(), |count| count.to_string()),
+            build.binary_size.map_or("-".into(), format_size),
+        );
+    }
+
+    let mut profiles = builds
+        .iter()
+        .map(|build| build.profile.as_str())
+        .collect::<Vec<_>>();
+    profiles.sort();
+    profiles.dedup();
+
+    println!("\n{}", t!("Trends").bold());
+
+    for profile in profiles {
+        let runs = builds
+            .iter()
+            .filter(|build| build.profile == profile)
+            .collect::<Vec<_>>();
+        let (first, last) = (runs[0], runs[runs.len() - 1]);
+
+        let size = match (first.binary_size, last.binary_size) {
+            (Some(first), Some(last)) => change(first as f64, last as f64),
+            _ => "-".into(),
+        };
+
+        println!(
+            "{}",
+            t!(
+                "{}: {} builds, time {}, size {}",
+                profile,
+                runs.len(),
+                change(first.seconds, last.seconds),
+                size
+            )
+        );
+    }
+
+    Ok(())
+}
@@ -0,0 +1,37 @@
+//! Commands from the `[hooks]` section of the config, run through the shell from the project
+//! folder at points like `cmm run` starting the program
+
+use colored::Colorize;
+
+use crate::{
+    error::ProjectError,
+    messages::t,
+    output::{self, status},
+};
+
+/// The system shell, running `command` as one line
+fn shell(command: &str) -> duct::Expression {
+    match cfg!(windows) {
+        true => output::command("cmd", ["/C", command]),
+        false => output::command("sh", ["-c", command]),
+    }
+}
+
+/// Runs each hook in order, stopping at the first that fails
+pub fn run_hooks(hooks: &[String]) -> Result<(), ProjectError> {
+    for hook in hooks {
+        status!("{} {}", t!("Running hook").cyan().bold(), hook);
+
+        let output = output::run(shell(hook))
+            .map_err(|err| ProjectError::FailedToStartProcess(hook.clone(), err.to_string()))?;
+
+        if !output.status.success() {
+            return Err(ProjectError::FailedToRunProcess(
+                hook.clone(),
+                output.status.code(),
+            ));
+        }
+    }
+
+    Ok(())
+}
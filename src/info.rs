@@ -0,0 +1,315 @@
+use colored::Colorize;
+
+use crate::{
+    config::{self, ConfigFile},
+    error::ProjectError,
+    git::{read_submodules, Submodule},
+    output,
+    patches::patches_for,
+    util::get_config,
+};
+
+fn print_field(name: &str, value: &str) {
+    println!("\t{: <16}{}", name.cyan(), value);
+}
+
+fn is_linked(config: &ConfigFile, name: &str) -> bool {
+    config
+        .dependencies
+        .project_dependencies
+        .iter()
+        .any(|dep| dep == name)
+}
+
+/// FetchContent and CPM dependencies, which are described the same way
+fn fetch_dependencies(
+    config: &ConfigFile,
+) -> impl Iterator<Item = (&'static str, &config::FetchDependency)> {
+    let fetch = config
+        .dependencies
+        .fetch
+        .iter()
+        .map(|fetch| ("fetch", fetch));
+    let cpm = config.dependencies.cpm.iter().map(|cpm| ("cpm", cpm));
+
+    fetch.chain(cpm)
+}
+
+pub fn dependency_info(name: &str) -> Result<(), ProjectError> {
+    let config = get_config()?;
+
+    if let Some(find) = config
+        .dependencies
+        .find
+        .iter()
+        .find(|find| find.name == name)
+    {
+        println!("{}", find.name.green().bold());
+        print_field("type", "find_package");
+        print_field("required", &find.required.to_string());
+
+        if let Some(link_name) = &find.custom_link_name {
+            print_field("link name", link_name);
+        }
+
+        let link_name = find.custom_link_name.as_ref().unwrap_or(&find.name);
+        print_field("linked", &is_linked(&config, link_name).to_string());
+
+        return Ok(());
+    }
+
+    if let Some((kind, fetch)) = fetch_dependencies(&config).find(|(_, fetch)| fetch.name == name) {
+        println!("{}", fetch.name.green().bold());
+        print_field("type", kind);
+        print_field("repo", &fetch.repo);
+
+        if let Some(tag) = &fetch.tag {
+            print_field("tag", tag);
+        }
+
+        if let Some(link_name) = &fetch.custom_link_name {
+            print_field("link name", link_name);
+        }
+
+        let link_name = fetch.custom_link_name.as_ref().unwrap_or(&fetch.name);
+        print_field("linked", &is_linked(&config, link_name).to_string());
+
+        fetch
+            .variables
+            .iter()
+            .for_each(|(var, value)| print_field("variable", &format!("{} ={}", var, value)));
+
+        return Ok(());
+    }
+
+    if let Some(conan) = config
+        .dependencies
+        .conan
+        .iter()
+        .find(|conan| conan.name == name)
+    {
+        println!("{}", conan.name.green().bold());
+        print_field("type", "conan");
+        print_field("reference", &conan.reference());
+        print_field("package", conan.package());
+        print_field("link name", &conan.link_name());
+        print_field(
+            "linked",
+            &is_linked(&config, &conan.link_name()).to_string(),
+        );
+
+        return Ok(());
+    }
+
+    let local = config
+        .dependencies
+        .local
+        .iter()
+        .find(|local| local.name == name)
+        .ok_or(ProjectError::UnknownDependency(name.to_owned()))?;
+
+    println!("{}", local.name.green().bold());
+
+    match &local.local_type {
+        config::LocalType::CMake => print_field("type", "local (cmake)"),
+        config::LocalType::Source { dependencies, .. } => {
+            print_field("type", "local (source)");
+
+            if !dependencies.is_empty() {
+                print_field("depends on", &dependencies.join(", "));
+            }
+        }
+    }
+
+    print_field("path", &local.path);
+    print_field("linked", &is_linked(&config, &local.name).to_string());
+
+    local
+        .variables
+        .iter()
+        .for_each(|(var, value)| print_field("variable", &format!("{} ={}", var, value)));
+
+    let patches = patches_for(&local.name);
+
+    match patches.is_empty() {
+        true => print_field("patches", "none"),
+        false => patches.iter().for_each(|patch| {
+            print_field("patch", &patch.display().to_string());
+        }),
+    }
+
+    Ok(())
+}
+
+/// How a local dependency is kept: a monorepo folder, a submodule or plain files
+fn local_kind(local: &config::LocalDependency, submodules: &[Submodule]) -> &'static str {
+    match (
+        local.monorepo,
+        submodules
+            .iter()
+            .any(|submodule| submodule.path == local.path),
+    ) {
+        (true, _) => "monorepo",
+        (false, true) => "submodule",
+        (false, false) => "local",
+    }
+}
+
+/// `cmm list --output json`. One entry per dependency with its kind, whether it's linked
+/// and what the config says about it.
+fn report_dependencies(config: &ConfigFile, submodules: &[Submodule]) {
+    let variables = |variables: &[(String, String)]| {
+        variables
+            .iter()
+            .map(|(var, value)| (var.clone(), value.trim().to_owned()))
+            .collect::<std::collections::BTreeMap<_, _>>()
+    };
+
+    let find = config.dependencies.find.iter().map(|find| {
+        let link_name = find.custom_link_name.as_ref().unwrap_or(&find.name);
+        serde_json::json!({
+            "name": find.name,
+            "kind": "find",
+            "linked": is_linked(config, link_name),
+            "link_name": link_name,
+            "required": find.required,
+        })
+    });
+
+    let fetch = fetch_dependencies(config).map(|(kind, fetch)| {
+        let link_name = fetch.custom_link_name.as_ref().unwrap_or(&fetch.name);
+        serde_json::json!({
+            "name": fetch.name,
+            "kind": kind,
+            "linked": is_linked(config, link_name),
+            "link_name": link_name,
+            "repo": fetch.repo,
+            "tag": fetch.tag,
+            "variables": variables(&fetch.variables),
+        })
+    });
+
+    let conan = config.dependencies.conan.iter().map(|conan| {
+        serde_json::json!({
+            "name": conan.name,
+            "kind": "conan",
+            "linked": is_linked(config, &conan.link_name()),
+            "link_name": conan.link_name(),
+            "reference": conan.reference(),
+        })
+    });
+
+    let local = config.dependencies.local.iter().map(|local| {
+        let build = match local.local_type {
+            config::LocalType::CMake => "cmake",
+            config::LocalType::Source { .. } => "source",
+        };
+        serde_json::json!({
+            "name": local.name,
+            "kind": local_kind(local, submodules),
+            "linked": is_linked(config, &local.name),
+            "path": local.path,
+            "build": build,
+            "variables": variables(&local.variables),
+        })
+    });
+
+    output::report(
+        "dependencies",
+        find.chain(fetch)
+            .chain(conan)
+            .chain(local)
+            .collect::<Vec<_>>(),
+    );
+}
+
+pub fn list_dependencies() -> Result<(), ProjectError> {
+    let config = get_config()?;
+
+    let submodules = read_submodules().unwrap_or_default();
+
+    if output::is_json() {
+        report_dependencies(&config, &submodules);
+        return Ok(());
+    }
+
+    if config.dependencies.find.is_empty()
+        && config.dependencies.local.is_empty()
+        && config.dependencies.fetch.is_empty()
+        && config.dependencies.cpm.is_empty()
+        && config.dependencies.conan.is_empty()
+    {
+        println!("No dependencies configured");
+        return Ok(());
+    }
+
+    let linked = |name: &str| match is_linked(&config, name) {
+        true => "linked".green(),
+        false => "not linked".yellow(),
+    };
+
+    config.dependencies.find.iter().for_each(|find| {
+        let link_name = find.custom_link_name.as_ref().unwrap_or(&find.name);
+
+        let required = match find.required {
+            true => " required",
+            false => "",
+        };
+
+        println!(
+            "{: <24}{: <12}{}{}",
+            find.name.cyan().bold(),
+            "find",
+            linked(link_name),
+            required
+        );
+    });
+
+    fetch_dependencies(&config).for_each(|(kind, fetch)| {
+        let link_name = fetch.custom_link_name.as_ref().unwrap_or(&fetch.name);
+
+        println!(
+            "{: <24}{: <12}{} ({} {})",
+            fetch.name.cyan().bold(),
+            kind,
+            linked(link_name),
+            fetch.repo,
+            fetch.tag.as_deref().unwrap_or("default branch")
+        );
+    });
+
+    config.dependencies.conan.iter().for_each(|conan| {
+        println!(
+            "{: <24}{: <12}{} ({})",
+            conan.name.cyan().bold(),
+            "conan",
+            linked(&conan.link_name()),
+            conan.reference()
+        );
+    });
+
+    config.dependencies.local.iter().for_each(|local| {
+        let kind = local_kind(local, &submodules);
+
+        let build = match local.local_type {
+            config::LocalType::CMake => "cmake",
+            config::LocalType::Source { .. } => "source",
+        };
+
+        println!(
+            "{: <24}{: <12}{} ({}, {})",
+            local.name.cyan().bold(),
+            kind,
+            linked(&local.name),
+            local.path,
+            build
+        );
+
+        local
+            .variables
+            .iter()
+            .for_each(|(var, value)| println!("{: <24}set {} = {}", "", var, value.trim()));
+    });
+
+    Ok(())
+}
@@ -0,0 +1,252 @@
+use std::{
+    io::Write,
+    path::{Path, PathBuf},
+};
+
+use colored::Colorize;
+
+use crate::{
+    config::{ConfigFile, FileRole, IncludeType, ProjectFiles, SourceType},
+    error::ProjectError,
+    git,
+    messages::t,
+    util::{get_global_config, init_file},
+    CONFIG_NAME,
+};
+
+const SOURCE_EXTENSIONS: [&str; 4] = ["cpp", "cc", "cxx", "c"];
+
+/// Folders that never hold project sources
+const SKIPPED_DIRS: [&str; 12] = [
+    "build",
+    "external",
+    "tests",
+    "test",
+    "out",
+    "patches",
+    "res",
+    "examples",
+    "example",
+    "bench",
+    "benches",
+    "benchmarks",
+];
+
+/// Folders whose files are built into executables of their own instead
+const ROLE_DIRS: [(&str, FileRole); 5] = [
+    ("examples", FileRole::Example),
+    ("example", FileRole::Example),
+    ("bench", FileRole::Bench),
+    ("benches", FileRole::Bench),
+    ("benchmarks", FileRole::Bench),
+];
+
+const GITIGNORE_ENTRIES: [&str; 3] = ["build/", ".cache/", ".env.local"];
+
+fn is_skipped(path: &Path) -> bool {
+    let name = path.file_name().unwrap_or_default().to_string_lossy();
+
+    name.starts_with('.') || name.starts_with("cmake-build-") || SKIPPED_DIRS.contains(&&*name)
+}
+
+fn is_source(path: &Path) -> bool {
+    path.extension()
+        .is_some_and(|ext| SOURCE_EXTENSIONS.contains(&&*ext.to_string_lossy()))
+}
+
+fn dir_entries(dir: &Path) -> Vec<PathBuf> {
+    std::fs::read_dir(dir)
+        .map(|dir| {
+            dir.filter_map(|entry| entry.ok())
+                .map(|entry| entry.path())
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+fn has_sources(dir: &Path, recursive: bool) -> bool {
+    dir_entries(dir).iter().any(|path| match path.is_dir() {
+        true => recursive && !is_skipped(path) && has_sources(path, true),
+        false => is_source(path),
+    })
+}
+
+/// Example and benchmark folders with sources, relative to the source folder
+fn scan_role_dirs(source_dir: &str) -> Vec<(FileRole, Vec<String>)> {
+    let prefix = match source_dir {
+        "." => "",
+        _ => "../",
+    };
+
+    ROLE_DIRS
+        .iter()
+        .filter(|(dir, _)| has_sources(Path::new(dir), false))
+        .map(|(dir, role)| (*role, vec![format!("{prefix}{dir}")]))
+        .collect()
+}
+
+/// Works out where an existing codebase keeps its sources and headers. Returns the
+/// folder paths are relative to along with the files to build.
+fn scan_project_files() -> (String, ProjectFiles) {
+    let mut files = ProjectFiles {
+        source_files: Vec::new(),
+        include_dirs: Vec::new(),
+        exclude_files: Vec::new(),
+        roles: Vec::new(),
+    };
+
+    let has_include = Path::new("include").is_dir();
+
+    // The usual layout. Anything under src/ is part of the project.
+    if has_sources(Path::new("src"), true) {
+        files
+            .source_files
+            .push((SourceType::GlobRecurse, vec![".".into()]));
+
+        let mut includes = vec![".".to_owned()];
+        if has_include {
+            includes.push("../include".into());
+        }
+        files.include_dirs.push((IncludeType::Public, includes));
+
+        return ("src".into(), files);
+    }
+
+    // Otherwise pick up sources in the root and in any folder that has them
+    if has_sources(Path::new("."), false) {
+        files
+            .source_files
+            .push((SourceType::Glob, vec![".".into()]));
+    }
+
+    let mut dirs = dir_entries(Path::new("."))
+        .into_iter()
+        .filter(|path| path.is_dir() && !is_skipped(path) && has_sources(path, true))
+        .map(|path| path.file_name().unwrap().to_string_lossy().to_string())
+        .collect::<Vec<_>>();
+    dirs.sort();
+
+    if !dirs.is_empty() {
+        files.source_files.push((SourceType::GlobRecurse, dirs));
+    }
+
+    let mut includes = vec![".".to_owned()];
+    if has_include {
+        includes.push("include".into());
+    }
+    files.include_dirs.push((IncludeType::Public, includes));
+
+    (".".into(), files)
+}
+
+/// Adds the entries cmakemake needs to an existing .gitignore, or creates one
+fn update_gitignore() -> Result<(), ProjectError> {
+    let path = Path::new(".gitignore");
+
+    let existing = std::fs::read_to_string(path).unwrap_or_default();
+    let missing = GITIGNORE_ENTRIES
+        .iter()
+        .filter(|entry| !existing.lines().any(|line| line.trim() == **entry))
+        .copied()
+        .collect::<Vec<_>>();
+
+    if missing.is_empty() {
+        return Ok(());
+    }
+
+    let mut file = std::fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)
+        .map_err(|err| ProjectError::FailedToCreateFile(path.to_owned(), err.to_string()))?;
+
+    let separator = match existing.is_empty() || existing.ends_with('\n') {
+        true => "",
+        false => "\n",
+    };
+
+    writeln!(file, "{}{}", separator, missing.join("\n"))
+        .map_err(|err| ProjectError::FailedToCreateFile(path.to_owned(), err.to_string()))
+}
+
+/// `cmm init [NAME]`. Adopts the code in the current directory as a project.
+pub fn init_project(name: Option<String>) -> Result<(), ProjectError> {
+    if Path::new(CONFIG_NAME).exists() {
+        return Err(ProjectError::ProjectAlreadyExists);
+    }
+
+    let current_dir = std::env::current_dir().map_err(|_| ProjectError::InvalidProjectDirectory)?;
+
+    let name = name
+        .or_else(|| Some(current_dir.file_name()?.to_string_lossy().to_string()))
+        .ok_or(ProjectError::MissingName)?;
+
+    match git::is_inside_repo(&current_dir) {
+        true => println!(
+            "{}",
+            t!("Skipping git init, already inside a git repository")
+        ),
+        false => {
+            git2::Repository::init(&current_dir)
+                .map_err(|err| ProjectError::FailedToInitGit(err.to_string()))?;
+        }
+    }
+
+    update_gitignore()?;
+
+    // A hand written CMakeLists.txt would be replaced by the first build
+    let cmake_lists = Path::new("CMakeLists.txt");
+    if cmake_lists.exists() {
+        let backup = Path::new("CMakeLists.txt.orig");
+        std::fs::copy(cmake_lists, backup)
+            .map_err(|err| ProjectError::FailedToCreateFile(backup.to_owned(), err.to_string()))?;
+
+        println!(
+            "{}",
+            t!(
+                "{} existing CMakeLists.txt will be regenerated, a copy was saved to {}",
+                t!("warning:").yellow(),
+                backup.display()
+            )
+        );
+    }
+
+    let global = get_global_config()?;
+
+    let (source_dir, mut files) = scan_project_files();
+    files.roles = scan_role_dirs(&source_dir);
+
+    let mut config = ConfigFile::new(name.clone());
+    config.project.authors.extend(global.author);
+    config.project.license = global.license;
+    config.cmake.source_dir = source_dir;
+    config.cmake.files = files;
+
+    if config.cmake.files.source_files.is_empty() {
+        println!(
+            "{}",
+            t!(
+                "{} no source files found, add them to cmake.files in {}",
+                t!("warning:").yellow(),
+                CONFIG_NAME
+            )
+        );
+    }
+
+    init_file(
+        Path::new(CONFIG_NAME),
+        toml::to_string(&config).unwrap().as_bytes(),
+    )?;
+
+    println!(
+        "{}",
+        t!(
+            "{} initialising project '{}' with sources from '{}'",
+            t!("Finished").green().bold(),
+            name,
+            config.cmake.source_dir
+        )
+    );
+
+    Ok(())
+}
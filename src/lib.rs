@@ -0,0 +1,55 @@
+//! The project model and build logic behind the `cmm` command line tool, for tools that
+//! want to generate or build cmakemake projects without spawning the CLI.
+//!
+//! Everything works on the project in the current directory, like the CLI does.
+//!
+//! ```no_run
+//! use cmakemake_core::build::{build_project, BuildOptions};
+//!
+//! cmakemake_core::generate::generate_cmake(true)?;
+//! build_project(&BuildOptions::default())?;
+//! # Ok::<(), cmakemake_core::error::ProjectError>(())
+//! ```
+
+pub mod answers;
+pub mod archive;
+pub mod build;
+pub mod cmake_cache;
+pub mod cmake_version;
+pub mod codegen;
+pub mod compiler_cache;
+pub mod conan;
+pub mod config;
+pub mod coverage;
+pub mod cpm;
+pub mod dependencies;
+pub mod env_file;
+pub mod error;
+pub mod generate;
+pub mod generator;
+pub mod git;
+pub mod history;
+pub mod hooks;
+pub mod lock;
+pub mod lockfile;
+pub mod messages;
+pub mod notices;
+pub mod output;
+pub mod patches;
+pub mod refactor;
+pub mod requires;
+pub mod sanitizers;
+pub mod schema;
+pub mod starter;
+pub mod templates;
+pub mod testing;
+pub mod triage;
+pub mod update;
+pub mod util;
+pub mod validate;
+pub mod warnings;
+pub mod workspace;
+
+pub const CONFIG_NAME: &str = "CMakeMake.toml";
+/// The config from before cmm last changed it
+pub const CONFIG_BACKUP_NAME: &str = "CMakeMake.toml.bak";
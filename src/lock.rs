@@ -0,0 +1,86 @@
+use std::{
+    fs::{File, TryLockError},
+    io::{Read, Seek, Write},
+    path::Path,
+};
+
+use crate::{error::ProjectError, messages::t, output::notice, CONFIG_NAME};
+
+/// Kept out of build/ so `cmm clean` can remove that while holding the lock
+pub const LOCK_PATH: &str = ".cache/cmm.lock";
+
+/// Stops two cmm processes changing the config or build folder of a project at the same
+/// time. Released when dropped.
+pub struct ProjectLock(Option<File>);
+
+fn holder(file: &mut File) -> Option<u32> {
+    let mut pid = String::new();
+    file.read_to_string(&mut pid).ok()?;
+    pid.trim().parse().ok()
+}
+
+impl ProjectLock {
+    /// Locks the project in the current directory. If another process has it this waits
+    /// for it with `wait`, otherwise fails straight away. Folders without a config aren't
+    /// projects and are never locked.
+    pub fn acquire(wait: bool) -> Result<Self, ProjectError> {
+        if !Path::new(CONFIG_NAME).exists() {
+            return Ok(Self(None));
+        }
+
+        let path = Path::new(LOCK_PATH);
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent).map_err(|err| {
+                ProjectError::FailedToCreateFolder(parent.to_owned(), err.to_string())
+            })?;
+        }
+
+        let mut file = File::options()
+            .read(true)
+            .write(true)
+            .create(true)
+            .truncate(false)
+            .open(path)
+            .map_err(|err| ProjectError::CannotOpenFile(path.to_owned(), err.to_string()))?;
+
+        match file.try_lock() {
+            Ok(()) => {}
+
+            Err(TryLockError::WouldBlock) if wait => {
+                notice!(
+                    "{}",
+                    t!("Waiting for another cmm process to finish with this project")
+                );
+                file.lock().map_err(|err| {
+                    ProjectError::CannotOpenFile(path.to_owned(), err.to_string())
+                })?;
+            }
+
+            Err(TryLockError::WouldBlock) => {
+                return Err(ProjectError::ProjectLocked(holder(&mut file)))
+            }
+
+            Err(TryLockError::Error(err)) => {
+                return Err(ProjectError::CannotOpenFile(
+                    path.to_owned(),
+                    err.to_string(),
+                ))
+            }
+        }
+
+        // Only shown to other processes, so failing to write it doesn't matter
+        file.set_len(0).ok();
+        file.rewind().ok();
+        write!(file, "{}", std::process::id()).ok();
+
+        Ok(Self(Some(file)))
+    }
+}
+
+impl Drop for ProjectLock {
+    fn drop(&mut self) {
+        if let Some(file) = &self.0 {
+            file.unlock().ok();
+        }
+    }
+}
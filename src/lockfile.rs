@@ -0,0 +1,457 @@
+use std::path::Path;
+
+use colored::Colorize;
+
+use crate::{
+    config::{ConfigFile, FetchDependency},
+    error::ProjectError,
+    git::read_submodules,
+    messages::t,
+    output::{notice, status},
+    patches::{self, patches_for},
+};
+
+pub const LOCK_NAME: &str = "CMakeMake.lock";
+
+const HEADER: &str = "# Generated by cmakemake. Pins the commit each git dependency resolves to.\n# Run 'cmm lock' after changing dependencies by hand.\n\n";
+
+#[derive(serde::Deserialize, serde::Serialize, Default, Clone, PartialEq)]
+pub struct Lockfile {
+    #[serde(default, rename = "dependency")]
+    pub dependencies: Vec<LockedDependency>,
+}
+
+#[derive(serde::Deserialize, serde::Serialize, Clone, Copy, PartialEq)]
+pub enum LockedKind {
+    Submodule,
+    Fetch,
+    Cpm,
+}
+
+#[derive(serde::Deserialize, serde::Serialize, Clone, PartialEq)]
+pub struct LockedDependency {
+    pub name: String,
+    pub kind: LockedKind,
+    pub repo: String,
+    /// Tag, branch or commit the config asked for, so changing it resolves the commit again
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tag: Option<String>,
+    pub commit: String,
+    /// Patches from patches/<name>/ applied on top of the commit
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub patches: Vec<LockedPatch>,
+}
+
+#[derive(serde::Deserialize, serde::Serialize, Clone, PartialEq)]
+pub struct LockedPatch {
+    pub file: String,
+    /// git's hash of the patch file, so changing a patch changes the lockfile
+    pub hash: String,
+}
+
+impl Lockfile {
+    fn find(&self, name: &str, kind: LockedKind) -> Option<&LockedDependency> {
+        self.dependencies
+            .iter()
+            .find(|locked| locked.name == name && locked.kind == kind)
+    }
+}
+
+/// Reads the project's lockfile. A project without one has nothing locked yet.
+pub fn read_lockfile() -> Result<Lockfile, ProjectError> {
+    let path = Path::new(LOCK_NAME);
+
+    if !path.exists() {
+        return Ok(Lockfile::default());
+    }
+
+    let contents = std::fs::read_to_string(path)
+        .map_err(|err| ProjectError::CannotOpenFile(path.to_owned(), err.to_string()))?;
+
+    toml::from_str(&contents)
+        .map_err(|err| ProjectError::CannotOpenFile(path.to_owned(), err.to_string()))
+}
+
+fn write_lockfile(lock: &Lockfile) -> Result<(), ProjectError> {
+    let path = Path::new(LOCK_NAME);
+
+    std::fs::write(
+        path,
+        format!("{}{}", HEADER, toml::to_string(lock).unwrap()),
+    )
+    .map_err(|err| ProjectError::FailedToCreateFile(path.to_owned(), err.to_string()))
+}
+
+fn is_commit(tag: &str) -> bool {
+    tag.len() == 40 && tag.chars().all(|c| c.is_ascii_hexdigit())
+}
+
+/// Asks the remote which commit a tag or branch points to, or its default branch without
+/// one. Annotated tags are peeled to the commit they tag.
+pub fn resolve_remote(repo: &str, tag: Option<&str>) -> Option<String> {
+    if let Some(tag) = tag.filter(|tag| is_commit(tag)) {
+        return Some(tag.to_owned());
+    }
+
+    // The peeled commit of an annotated tag is only listed when asked for by name
+    let patterns = match tag {
+        Some(tag) => vec![tag.to_owned(), format!("{tag}^{{}}")],
+        None => vec!["HEAD".to_owned()],
+    };
+
+    let output = duct::cmd(
+        "git",
+        ["ls-remote", repo]
+            .into_iter()
+            .chain(patterns.iter().map(String::as_str)),
+    )
+    .stdout_capture()
+    .stderr_null()
+    .unchecked()
+    .run()
+    .ok()?;
+
+    if !output.status.success() {
+        return None;
+    }
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let refs = stdout
+        .lines()
+        .filter_map(|line| line.split_once('\t'))
+        .collect::<Vec<_>>();
+
+    let preferred = match tag {
+        Some(tag) => vec![
+            format!("refs/tags/{tag}^{{}}"),
+            format!("refs/tags/{tag}"),
+            format!("refs/heads/{tag}"),
+        ],
+        None => vec!["HEAD".to_owned()],
+    };
+
+    preferred
+        .iter()
+        .find_map(|name| refs.iter().find(|(_, reference)| reference == name))
+        .or(refs.first())
+        .map(|(commit, _)| commit.to_string())
+}
+
+/// The patches a dependency gets, in the order they are applied
+fn locked_patches(name: &str) -> Vec<LockedPatch> {
+    patches_for(name)
+        .iter()
+        .filter_map(|patch| {
+            let contents = std::fs::read(patch).ok()?;
+            let hash = git2::Oid::hash_object(git2::ObjectType::Blob, &contents).ok()?;
+
+            Some(LockedPatch {
+                file: patch.file_name()?.to_string_lossy().into_owned(),
+                hash: hash.to_string(),
+            })
+        })
+        .collect()
+}
+
+/// The commit checked out in a dependency folder
+fn checked_out_commit(path: &str) -> Option<String> {
+    let repo = git2::Repository::open(path).ok()?;
+    let commit = repo.head().ok()?.peel_to_commit().ok()?;
+
+    Some(commit.id().to_string())
+}
+
+pub fn has_local_changes(path: &str) -> bool {
+    let Ok(repo) = git2::Repository::open(path) else {
+        return false;
+    };
+
+    let mut options = git2::StatusOptions::new();
+    options.include_untracked(false);
+
+    repo.statuses(Some(&mut options))
+        .is_ok_and(|statuses| !statuses.is_empty())
+}
+
+/// Runs git in `path`, returning whether it succeeded
+pub fn git_in(path: &str, args: &[&str]) -> bool {
+    duct::cmd("git", args)
+        .dir(path)
+        .stdout_null()
+        .stderr_null()
+        .unchecked()
+        .run()
+        .is_ok_and(|output| output.status.success())
+}
+
+/// Checks out the locked commit in a submodule, fetching it first if it isn't there yet
+fn restore_checkout(path: &str, commit: &str) -> bool {
+    git_in(path, &["checkout", "--quiet", commit])
+        || (git_in(path, &["fetch", "--quiet", "origin"])
+            && git_in(path, &["checkout", "--quiet", commit]))
+}
+
+fn fetch_dependencies(config: &ConfigFile) -> impl Iterator<Item = (LockedKind, &FetchDependency)> {
+    let fetch = config
+        .dependencies
+        .fetch
+        .iter()
+        .map(|fetch| (LockedKind::Fetch, fetch));
+    let cpm = config
+        .dependencies
+        .cpm
+        .iter()
+        .map(|cpm| (LockedKind::Cpm, cpm));
+
+    fetch.chain(cpm)
+}
+
+/// Works out the lockfile for the current dependencies. Entries that still match the config
+/// are kept unless `refresh` is set. Submodules are recorded at their current checkout and
+/// fetched dependencies are resolved with the remote.
+fn resolve(config: &ConfigFile, lock: &Lockfile, refresh: bool) -> (Lockfile, Vec<String>) {
+    let mut resolved = Lockfile::default();
+    let mut unresolved = Vec::new();
+
+    let submodules = read_submodules().unwrap_or_default();
+
+    config
+        .dependencies
+        .local
+        .iter()
+        .filter_map(|local| {
+            let submodule = submodules.iter().find(|sub| sub.path == local.path)?;
+            Some((local, submodule))
+        })
+        .for_each(|(local, submodule)| {
+            let patches = locked_patches(&local.name);
+
+            if let Some(locked) = lock
+                .find(&local.name, LockedKind::Submodule)
+                .filter(|_| !refresh)
+            {
+                resolved.dependencies.push(LockedDependency {
+                    patches,
+                    ..locked.clone()
+                });
+                return;
+            }
+
+            match checked_out_commit(&local.path) {
+                Some(commit) => resolved.dependencies.push(LockedDependency {
+                    name: local.name.clone(),
+                    kind: LockedKind::Submodule,
+                    repo: submodule.url.clone().unwrap_or_default(),
+                    tag: None,
+                    commit,
+                    patches,
+                }),
+                None => unresolved.push(local.name.clone()),
+            }
+        });
+
+    fetch_dependencies(config).for_each(|(kind, fetch)| {
+        let kept = lock
+            .find(&fetch.name, kind)
+            .filter(|locked| !refresh && locked.repo == fetch.repo && locked.tag == fetch.tag);
+
+        if let Some(locked) = kept {
+            resolved.dependencies.push(locked.clone());
+            return;
+        }
+
+        match resolve_remote(&fetch.repo, fetch.tag.as_deref()) {
+            Some(commit) => resolved.dependencies.push(LockedDependency {
+                name: fetch.name.clone(),
+                kind,
+                repo: fetch.repo.clone(),
+                tag: fetch.tag.clone(),
+                commit,
+                patches: Vec::new(),
+            }),
+            None => unresolved.push(fetch.name.clone()),
+        }
+    });
+
+    (resolved, unresolved)
+}
+
+fn warn_unresolved(unresolved: &[String]) {
+    unresolved.iter().for_each(|name| {
+        notice!(
+            "{}",
+            t!(
+                "{} couldn't find the commit for dependency '{}', it won't be locked",
+                t!("warning:").yellow(),
+                name
+            )
+        )
+    });
+}
+
+/// Puts submodules back on their locked commits. Checkouts with local changes are left
+/// alone so work in progress is never thrown away, unless the changes are just the
+/// dependency's patches, which are applied again after the checkout.
+fn restore_submodules(config: &ConfigFile, lock: &Lockfile) -> Result<(), ProjectError> {
+    lock.dependencies
+        .iter()
+        .filter(|locked| locked.kind == LockedKind::Submodule)
+        .filter_map(|locked| {
+            let local = config
+                .dependencies
+                .local
+                .iter()
+                .find(|local| local.name == locked.name)?;
+            Some((locked, local.path.as_str()))
+        })
+        .filter(|(locked, path)| {
+            checked_out_commit(path).is_some_and(|commit| commit != locked.commit)
+        })
+        .try_for_each(|(locked, path)| {
+            let short = &locked.commit[..locked.commit.len().min(10)];
+
+            if has_local_changes(path) && !patches::only_patched(&locked.name, path) {
+                notice!(
+                    "{}",
+                    t!(
+                        "{} dependency '{}' has local changes, not restoring the locked commit {}",
+                        t!("warning:").yellow(),
+                        locked.name,
+                        short
+                    )
+                );
+                return Ok(());
+            }
+
+            let restored = patches::checkout_patched(&locked.name, path, || {
+                restore_checkout(path, &locked.commit)
+            })?;
+
+            match restored {
+                true => notice!(
+                    "{}",
+                    t!(
+                        "{} '{}' to locked commit {}",
+                        t!("Restored").green(),
+                        locked.name,
+                        short
+                    )
+                ),
+                false => notice!(
+                    "{}",
+                    t!(
+                        "{} failed to check out locked commit {} of '{}'",
+                        t!("warning:").yellow(),
+                        short,
+                        locked.name
+                    )
+                ),
+            }
+
+            Ok(())
+        })
+}
+
+/// Swaps the tags of fetched dependencies for their locked commits, so the generated
+/// CMakeLists.txt always downloads the same code
+pub fn pin(config: &mut ConfigFile, lock: &Lockfile) {
+    let pin_all = |dependencies: &mut Vec<FetchDependency>, kind: LockedKind| {
+        dependencies.iter_mut().for_each(|fetch| {
+            let locked = lock
+                .find(&fetch.name, kind)
+                .filter(|locked| locked.repo == fetch.repo && locked.tag == fetch.tag);
+
+            if let Some(locked) = locked {
+                fetch.tag = Some(locked.commit.clone());
+            }
+        })
+    };
+
+    pin_all(&mut config.dependencies.fetch, LockedKind::Fetch);
+    pin_all(&mut config.dependencies.cpm, LockedKind::Cpm);
+}
+
+/// Brings the lockfile up to date with the config, restores locked submodule checkouts and
+/// pins fetched dependencies in `config`. With `frozen` a lockfile that needs changing is
+/// an error instead.
+pub fn sync(config: &mut ConfigFile, frozen: bool) -> Result<(), ProjectError> {
+    let lock = read_lockfile()?;
+    let (resolved, unresolved) = resolve(config, &lock, false);
+
+    warn_unresolved(&unresolved);
+
+    if resolved != lock {
+        if frozen {
+            let changed = changed_names(&resolved, &lock);
+            return Err(ProjectError::LockfileOutdated(changed));
+        }
+
+        write_lockfile(&resolved)?;
+        status!("{}", t!("{} {}", t!("Updated").green(), LOCK_NAME));
+    }
+
+    restore_submodules(config, &resolved)?;
+    pin(config, &resolved);
+
+    Ok(())
+}
+
+/// Names of dependencies whose entries differ between two lockfiles
+fn changed_names(new: &Lockfile, old: &Lockfile) -> Vec<String> {
+    let mut names = new
+        .dependencies
+        .iter()
+        .filter(|locked| !old.dependencies.contains(locked))
+        .chain(
+            old.dependencies
+                .iter()
+                .filter(|locked| !new.dependencies.contains(locked)),
+        )
+        .map(|locked| locked.name.clone())
+        .collect::<Vec<_>>();
+
+    names.sort();
+    names.dedup();
+    names
+}
+
+/// Resolves the lockfile entries of `names` again after `cmm update` moved them, keeping
+/// every other dependency where it is
+pub fn update(config: &ConfigFile, names: &[String]) -> Result<(), ProjectError> {
+    let mut lock = read_lockfile()?;
+    lock.dependencies
+        .retain(|locked| !names.contains(&locked.name));
+
+    let (resolved, unresolved) = resolve(config, &lock, false);
+    warn_unresolved(&unresolved);
+
+    write_lockfile(&resolved)?;
+    status!("{}", t!("{} {}", t!("Updated").green(), LOCK_NAME));
+
+    Ok(())
+}
+
+/// `cmm lock`. Records the current submodule checkouts and resolves every fetched
+/// dependency again.
+pub fn relock(config: &ConfigFile) -> Result<(), ProjectError> {
+    let (resolved, unresolved) = resolve(config, &read_lockfile()?, true);
+
+    warn_unresolved(&unresolved);
+
+    write_lockfile(&resolved)?;
+
+    resolved.dependencies.iter().for_each(|locked| {
+        println!(
+            "\t{: <24}{}",
+            locked.name.cyan(),
+            &locked.commit[..locked.commit.len().min(10)]
+        )
+    });
+
+    status!(
+        "{}",
+        t!("{} writing {}", t!("Finished").green().bold(), LOCK_NAME)
+    );
+
+    Ok(())
+}
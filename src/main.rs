@@ -1,80 +1,238 @@
 use std::{
-    hash::{Hash, Hasher},
-    io::{BufRead, Write},
     path::{Path, PathBuf},
+    time::Duration,
 };
 
+use clap::{CommandFactory, Parser};
+use cli::{
+    AddCommand, CacheAction, Cli, Command, DepsAction, GenerateTarget, Headers, MigrateTarget,
+    OutputFormat, ProjectTemplate, ScaffoldTarget, WarningsAction, WorkspaceAction,
+};
+use cmakemake_core::{
+    answers, archive,
+    build::{
+        self, build_project, fetch_project, install_project, package_project, BuildOptions,
+        IN_SOURCE_FILES,
+    },
+    codegen, compiler_cache, config, coverage, dependencies, env_file, error,
+    generate::{self, generate_cmake, has_hash_comment},
+    generator, git, history, hooks,
+    lock::ProjectLock,
+    lockfile, messages, notices, output, patches, refactor, sanitizers, schema, starter,
+    templates::Template,
+    testing, update, util, validate, warnings, workspace, CONFIG_NAME,
+};
 use colored::Colorize;
-use config::ConfigFile;
+use config::{ConfigFile, ProjectKind};
 use error::{DisplayError, ProjectError};
+use messages::t;
+use output::{status, Verbosity};
 use util::*;
 
-mod config;
-mod dependencies;
-mod error;
-mod util;
+mod bugreport;
+mod cli;
+mod doctor;
+mod info;
+mod init;
+mod migrate;
+mod onboarding;
+mod repair;
+mod watch;
 
-fn main() -> Result<(), ProjectError> {
-    let command = match std::env::args().nth(1) {
-        Some(cmd) => cmd,
-        None => {
-            print_help();
-            return Ok(());
-        }
+fn main() {
+    // Before anything is printed, as the output goes to the shell completing
+    cli::complete();
+
+    let cli = Cli::parse_from(cli::args());
+
+    output::set_verbosity(Verbosity::from_flags(cli.quiet, cli.verbose));
+    answers::load(cli.answers.as_deref()).display_error();
+
+    let Some(command) = cli.command else {
+        Cli::command().print_help().ok();
+        return;
     };
 
-    match command.to_lowercase().as_str() {
-        "new" => new_project().display_error(),
-        "add" => add_dependency().display_error(),
-        "cmake" => generate_cmake().display_error(),
-        "build" => build_project().display_error(),
-        "run" => run_project().display_error(),
-        "ignore" => add_ignore().display_error(),
-        "clean" => clean_project().display_error(),
+    output::set_json(command.output_format() == OutputFormat::Json);
 
-        "help" => print_help(),
+    if !matches!(command, Command::Setup | Command::Completions { .. }) {
+        onboarding::first_run().display_error();
+        util::recover_config().display_error();
 
-        other => {
-            println!("{}: {}", "Unknown command".red(), other);
-            print_help();
+        if !matches!(command, Command::Migrate { .. }) {
+            schema::offer_upgrade().display_error();
         }
     }
 
-    Ok(())
-}
+    // Inherited by everything cmm runs, from cmake to the program `cmm run` starts
+    env_file::apply();
+
+    let wait = cli.wait;
+
+    let result = match command {
+        Command::New {
+            name,
+            headers,
+            build,
+            template,
+        } => new_project(&name, headers, build, template),
+        Command::Init { name } => init::init_project(name),
+        Command::Add(add) => locked(wait, || add_dependency(add)),
+        Command::Remove {
+            name,
+            purge,
+            keep_files,
+        } => locked(wait, || remove_dependency(&name, purge, keep_files)),
+        Command::Link { name, target } => locked(wait, || link_dependency(&name, &target)),
+        Command::Unlink { name, target } => locked(wait, || unlink_dependency(&name, &target)),
+        Command::Cmake {
+            no_hash_comment,
+            dry_run: true,
+        } => generate::preview_cmake(!no_hash_comment),
+        Command::Cmake {
+            no_hash_comment, ..
+        } => locked(wait, || generate_cmake(!no_hash_comment)),
+        Command::Build { target, build, .. } => {
+            let mut options = build.options(wait);
+            options.target = target;
+            build_command(&options)
+        }
+        Command::Fetch { build } => fetch_command(&build.options(wait)),
+        Command::Install { prefix, build } => {
+            install_command(&build.options(wait), prefix.as_deref())
+        }
+        Command::Test {
+            cases,
+            list,
+            pick,
+            report,
+            build,
+        } => {
+            let selection = match (list, pick) {
+                (true, _) => Some(testing::CaseSelection::List),
+                (_, true) => Some(testing::CaseSelection::Pick),
+                _ if !cases.is_empty() => Some(testing::CaseSelection::Named(cases)),
+                _ => None,
+            };
 
-fn print_help() {
-    println!("A c++ project setup tool\n");
+            test_command(build.options(wait), report.as_deref(), selection)
+        }
+        Command::Coverage { html, build } => coverage_command(build.options(wait), html),
+        Command::Package { format, build } => package_command(&build.options(wait), &format),
+        Command::Run {
+            skip_build,
+            bin,
+            backtrace,
+            pty,
+            sanitize,
+            print_env,
+            build,
+            program_args,
+        } => run_project(
+            bin,
+            &program_args,
+            skip_build.is_none(),
+            RunMode {
+                backtrace,
+                pty,
+                sanitize,
+                print_env,
+            },
+            build.options(wait),
+        ),
+        Command::Watch {
+            target,
+            clear,
+            debounce,
+            build,
+        } => {
+            let mut options = build.options(wait);
+            options.target = target;
+            watch::watch_project(options, clear, Duration::from_millis(debounce))
+        }
+        Command::Ignore => add_ignore(),
+        Command::Clean {
+            all,
+            in_source,
+            profile,
+            target,
+            force,
+            keep_cache_objects,
+            zero_stats,
+        } => locked(wait, || {
+            clean_project(
+                all.is_some(),
+                in_source,
+                profile,
+                target,
+                force,
+                keep_cache_objects,
+                zero_stats,
+            )
+        }),
+        Command::Setup => onboarding::setup(),
+        Command::Migrate {
+            target: MigrateTarget::Gitmodules,
+        } => locked(wait, migrate::migrate_gitmodules),
+        Command::Migrate {
+            target: MigrateTarget::Config,
+        } => locked(wait, schema::upgrade),
+        Command::Repair => locked(wait, repair::repair_project),
+        Command::Info { name } => info::dependency_info(&name),
+        Command::List { .. } => info::list_dependencies(),
+        Command::Workspace { action } => match action {
+            WorkspaceAction::New { name } => workspace::new_workspace(&name),
+            WorkspaceAction::Add { path } => workspace::add_existing_member(&path),
+        },
+        Command::Scaffold {
+            target: ScaffoldTarget::SanitizerSuppressions,
+        } => locked(wait, scaffold_suppressions),
+        Command::Check { .. } => validate::check_config(),
+        Command::Validate => validate::validate_project(),
+        Command::Mv { from, to } => locked(wait, || move_file(&from, &to)),
+        Command::Generate {
+            target: GenerateTarget::Class { name, namespace },
+        } => locked(wait, || generate_class(&name, namespace.as_deref())),
+        Command::Completions { shell } => {
+            cli::print_completions(shell);
+            Ok(())
+        }
+        Command::History { profile, limit } => history::show_history(profile.as_deref(), limit),
+        Command::Generators => generator::list_generators(),
+        Command::Doctor => doctor::check_environment(),
+        Command::Bugreport { output } => bugreport::write_report(&output),
+        Command::Lock => locked(wait, || lockfile::relock(&get_config()?)),
+        Command::Update { name, tag } => locked(wait, || {
+            update_dependencies(name.as_deref(), tag.as_deref())
+        }),
+        Command::Deps {
+            action: DepsAction::LicensesHeader,
+        } => locked(wait, || notices::write_notices(&get_config()?)),
+        Command::Cache { action } => locked(wait, || cache_command(action)),
+        Command::Warnings {
+            action: WarningsAction::Baseline { build },
+        } => warnings::record_baseline(&build.options(wait)),
+    };
 
-    println!(
-        "{}\t{}\t{}",
-        "Usage:".green().bold(),
-        "cmakemake".cyan(),
-        "[COMMAND]".cyan()
-    );
-    println!(
-        "\t{}\t\t{}\t (recommended alias)",
-        "cmm".cyan(),
-        "[COMMAND]".cyan()
-    );
+    output::finish_json(&result);
 
-    let print_command = |cmd: &str, desc: &str| {
-        println!("\t{}\t\t{}", cmd.cyan().bold(), desc);
-    };
+    // Exit codes are part of the interface, see error::EXIT_*
+    if let Err(err) = result {
+        if !output::is_json() {
+            eprintln!("{}", err);
+        }
+        std::process::exit(err.exit_code());
+    }
+}
 
-    println!("");
-    println!("{}", "Commands:".green().bold());
-    print_command("new", "Create a new project");
-    print_command("add", "Add a dependency");
-    print_command("cmake", "Generate cmake build script");
-    print_command("build", "Build project code");
-    print_command("run", "Build and run project code");
-    print_command(
-        "clean",
-        "remove c++ build files (and optionally cmake files)",
-    );
-    print_command("ignore", "Create a .ignore file for external/ and res/");
-    print_command("help", "Output this help message");
+/// Runs a command that changes the config or build folder while holding the project lock.
+/// Building takes the lock itself so `run` and `watch` don't hold it while the program runs.
+fn locked(
+    wait: bool,
+    command: impl FnOnce() -> Result<(), ProjectError>,
+) -> Result<(), ProjectError> {
+    let _lock = ProjectLock::acquire(wait)?;
+    command()
 }
 
 const DEFAULT_MAIN_FILE: &str = r#"#include <iostream>
@@ -86,26 +244,83 @@ int main(void)
 }
 "#;
 
-const CONFIG_NAME: &str = "CMakeMake.toml";
+/// Asks which C++ standard a new project uses, starting on the one picked in `cmm setup`.
+/// Scripts that don't answer get that default without being asked.
+fn cxx_standard(default: Option<u32>) -> Result<Option<u32>, ProjectError> {
+    if !answers::can_answer("new.cxx_standard") {
+        return Ok(default);
+    }
+
+    let default = default.map_or("None".to_owned(), |standard| standard.to_string());
+    let cursor = onboarding::STANDARDS
+        .iter()
+        .position(|standard| *standard == default)
+        .unwrap_or(0);
+
+    Ok(answers::select(
+        "new.cxx_standard",
+        inquire::Select::new(t!("C++ standard:"), onboarding::STANDARDS.to_vec())
+            .with_starting_cursor(cursor),
+    )?
+    .value
+    .parse()
+    .ok())
+}
 
-fn new_project() -> Result<(), ProjectError> {
-    // Get Project Name
-    let name = std::env::args()
-        .nth(2)
-        .ok_or_else(|| ProjectError::MissingName)?;
+fn new_project(
+    name: &str,
+    headers: Headers,
+    build: bool,
+    template: Option<ProjectTemplate>,
+) -> Result<(), ProjectError> {
+    let include_dir = matches!(headers, Headers::Include);
 
     let path = PathBuf::from(&name);
 
     // Init Project Folder
     create_dir(&path)?;
 
-    // Init Git Repo
-    git2::Repository::init(&path).map_err(|err| ProjectError::FailedToInitGit(err.to_string()))?;
+    // Init Git Repo. Projects created inside an existing repo or worktree become part of it.
+    match git::is_inside_repo(&path) {
+        true => status!(
+            "{}",
+            t!("Skipping git init, already inside a git repository")
+        ),
+        false => {
+            git2::Repository::init(&path)
+                .map_err(|err| ProjectError::FailedToInitGit(err.to_string()))?;
+        }
+    }
 
-    init_file(&path.join(Path::new(".gitignore")), b"build/\n.cache/")?;
+    init_file(
+        &path.join(Path::new(".gitignore")),
+        b"build/\n.cache/\n.env.local",
+    )?;
 
     // Init Config File
-    let config = ConfigFile::new(name);
+    let global = get_global_config()?;
+
+    let mut config = ConfigFile::new(name.to_owned());
+    config.project.authors.extend(global.author);
+    config.project.license = global.license;
+    config.cmake.cxx_standard = cxx_standard(global.cxx_standard)?;
+
+    let template = template.map(|template| match template {
+        ProjectTemplate::GlfwOpengl => Template::GlfwOpengl,
+        ProjectTemplate::Sdl2 => Template::Sdl2,
+        ProjectTemplate::Cli => Template::Cli,
+    });
+    if let Some(template) = template {
+        template.apply(&mut config);
+    }
+
+    // Include paths are relative to the source folder
+    if include_dir {
+        config.cmake.files.include_dirs = vec![(
+            config::IncludeType::Public,
+            vec![".".into(), "../include".into()],
+        )];
+    }
 
     init_file(
         &path.join(Path::new(CONFIG_NAME)),
@@ -115,480 +330,872 @@ fn new_project() -> Result<(), ProjectError> {
     // Init other project folders
     create_dir(&path.join("src"))?;
 
-    // Init main.cpp
-    init_file(&path.join("src/main.cpp"), DEFAULT_MAIN_FILE.as_bytes())?;
+    if include_dir {
+        create_dir(&path.join("include"))?;
+    }
+
+    // Init main.cpp, or the template's files, then the user's starter files over them
+    let mut files = match template {
+        Some(template) => template.files(),
+        None => vec![("src/main.cpp".into(), DEFAULT_MAIN_FILE)],
+    }
+    .into_iter()
+    .map(|(file, contents)| (file, contents.to_owned()))
+    .collect::<Vec<_>>();
+
+    for (file, contents) in starter::files(&global.starter, &config, template.is_none())? {
+        files.retain(|(existing, _)| *existing != file);
+        files.push((file, contents));
+    }
+
+    for (file, contents) in files {
+        let file = path.join(file);
+        // Starter files can be anywhere in the project, like .github/workflows/ci.yml
+        let dir = file.parent().unwrap();
+        std::fs::create_dir_all(dir)
+            .map_err(|err| ProjectError::FailedToCreateFolder(dir.to_owned(), err.to_string()))?;
+
+        init_file(&file, contents.as_bytes())?;
+    }
+
+    // Register with the enclosing workspace
+    if workspace::is_workspace_root() {
+        workspace::add_member(&path.to_string_lossy())?;
+    }
 
     // Finished Successfully
-    println!(
-        "{} {} {}",
-        "Finished".green().bold(),
-        "creating project at",
-        path.canonicalize().unwrap_or(path).display()
+    status!(
+        "{}",
+        t!(
+            "{} creating project at {}",
+            t!("Finished").green().bold(),
+            path.canonicalize().unwrap_or(path.clone()).display()
+        )
     );
 
+    if build {
+        bootstrap_build(&path);
+    }
+
     Ok(())
 }
 
-fn add_dependency() -> Result<(), ProjectError> {
+/// Builds and runs a freshly created project, so a broken compiler or cmake install shows
+/// up now instead of on the first real build. The project is kept either way.
+fn bootstrap_build(path: &Path) {
+    status!();
+
+    let result = workspace::in_member(&path.to_string_lossy(), || {
+        run_binary(None, &[], true, RunMode::default(), BuildOptions::default())
+    });
+
+    match result {
+        Ok(()) => status!(
+            "\n{}",
+            t!(
+                "{} your toolchain builds and runs projects",
+                t!("Finished").green().bold()
+            )
+        ),
+        Err(err) => {
+            Err::<(), _>(err).display_error();
+            println!(
+                "\n{}",
+                t!(
+                    "The project was created, but building it failed. Check that cmake and a C++ compiler are installed, then try '{}' inside it.",
+                    "cmm run".bold()
+                )
+            );
+        }
+    }
+}
+
+fn add_dependency(add: AddCommand) -> Result<(), ProjectError> {
     let mut config = get_config()?;
 
-    let dep_type = inquire::Select::new(
-        "Choose the Dependency Type:",
-        vec![
-            "Pre-Cached",    // 0
-            "Git Submodule", // 1
-            "Find",          // 2
-            "Local",         // 3
-        ],
-    )
-    .raw_prompt()
-    .unwrap();
+    if let Some(kind) = add.kind.clone() {
+        if kind == "test-framework" {
+            testing::add_test_framework(&mut config, add.value.as_deref())?;
+            write_config(config)?;
+
+            status!(
+                "{}",
+                t!("{} added test framework", t!("Successfully").green())
+            );
+            return Ok(());
+        }
+
+        dependencies::add_from_args(&mut config, &kind, add.args())?;
+        write_config(config)?;
+
+        status!("{}", t!("{} added dependency", t!("Successfully").green()));
+        return Ok(());
+    }
+
+    let dep_type = answers::select(
+        "add.type",
+        inquire::Select::new(
+            t!("Choose the Dependency Type:"),
+            vec![
+                t!("Pre-Cached"),     // 0
+                t!("Git Submodule"),  // 1
+                t!("Find"),           // 2
+                t!("Local"),          // 3
+                t!("Monorepo"),       // 4
+                t!("Test Framework"), // 5
+                t!("FetchContent"),   // 6
+                t!("Conan"),          // 7
+            ],
+        ),
+    )?;
 
     match dep_type.index {
         0 => dependencies::add_cached_dependency(&mut config)?,
         1 => dependencies::add_git_submodule(&mut config)?,
         2 => dependencies::add_find_dependency(&mut config)?,
         3 => dependencies::add_local_dependency(&mut config)?,
+        4 => dependencies::add_monorepo_dependency(&mut config)?,
+        5 => {
+            testing::add_test_framework(&mut config, None)?;
+            write_config(config)?;
+            return Ok(());
+        }
+        6 => dependencies::add_fetch_dependency(&mut config)?,
+        7 => dependencies::add_conan_dependency(&mut config)?,
         _ => return Err(ProjectError::UnknownArgument(dep_type.value.into())),
     }
 
     write_config(config)?;
 
-    println!("{} {}", "Successfully".green(), "added dependency");
+    status!("{}", t!("{} added dependency", t!("Successfully").green()));
 
     Ok(())
 }
 
-fn write_source_files(
-    file: &mut std::fs::File,
-    source_name: &str,
-    path: &str,
-    files: &config::ProjectFiles,
-) -> Result<(), std::io::Error> {
-    if files.source_files.is_empty() {
-        return Ok(());
-    }
+fn remove_dependency(name: &str, purge: bool, keep_files: bool) -> Result<(), ProjectError> {
+    let mut config = get_config()?;
 
-    let mut individual_files = Vec::new();
-    let mut glob_dirs = Vec::new();
-    let mut glob_recurse_dirs = Vec::new();
+    dependencies::remove_dependency(&mut config, name, purge, keep_files)?;
+    write_config(config)?;
 
-    files
-        .source_files
-        .iter()
-        .for_each(|(source_type, files)| match source_type {
-            config::SourceType::File => individual_files.extend(files),
-            config::SourceType::Glob => glob_dirs.extend(files),
-            config::SourceType::GlobRecurse => glob_recurse_dirs.extend(files),
-        });
+    Ok(())
+}
+
+fn link_dependency(name: &str, targets: &[String]) -> Result<(), ProjectError> {
+    let mut config = get_config()?;
 
-    let mut files_initialized = false;
+    dependencies::link_dependency(&mut config, name, targets)?;
+    write_config(config)
+}
 
-    if individual_files.is_empty() == false {
-        writeln!(
-            file,
-            "set({source_name} {})",
-            individual_files
-                .into_iter()
-                .fold(String::new(), |a, b| format!(r#"{}"{}/{}" "#, a, path, b)) // TODO - Check trim
-        )?;
-        files_initialized = true;
+fn unlink_dependency(name: &str, targets: &[String]) -> Result<(), ProjectError> {
+    let mut config = get_config()?;
+
+    dependencies::unlink_dependency(&mut config, name, targets)?;
+    write_config(config)
+}
+
+fn update_dependencies(name: Option<&str>, tag: Option<&str>) -> Result<(), ProjectError> {
+    let mut config = get_config()?;
+
+    let updated = update::update_dependencies(&mut config, name, tag)?;
+    if updated.is_empty() {
+        return Ok(());
     }
 
-    let mut write_glob_type = |glob_type: &str, dirs: Vec<&String>| -> Result<(), std::io::Error> {
-        if dirs.is_empty() {
-            return Ok(());
-        }
+    lockfile::update(&config, &updated)?;
+    notices::refresh_notices(&config)?;
+    write_config(config)
+}
 
-        let dirs_string = dirs.iter().fold(String::new(), |a, dir| {
-            let path = match dir.as_str() == "." {
-                true => format!("{path}"),
-                false => format!("{path}/{dir}"),
-            };
+fn scaffold_suppressions() -> Result<(), ProjectError> {
+    let mut config = get_config()?;
 
-            format!(
-                r#"{}"{path}/*.cpp" "{path}/*.c" "{path}/*.hpp" "{path}/*.h" "#,
-                a
-            )
-        });
+    sanitizers::scaffold_suppressions(&mut config)?;
+    write_config(config)
+}
 
-        let src_name = format!("{source_name}_{glob_type}");
+fn move_file(from: &str, to: &str) -> Result<(), ProjectError> {
+    let mut config = get_config()?;
 
-        match files_initialized {
-            true => {
-                writeln!(file, r#"file({glob_type} {src_name} {})"#, dirs_string)?;
-                writeln!(file, "list(APPEND {source_name} ${{{src_name}}})")?;
-            }
+    refactor::move_file(&mut config, from, to)?;
+    write_config(config)?;
 
-            false => {
-                writeln!(file, r#"file(GLOB {source_name} {})"#, dirs_string)?;
-                files_initialized = true;
-            }
-        }
+    // Keeps a file written with --no-hash-comment that way
+    let hash_comment = std::fs::read_to_string("CMakeLists.txt")
+        .map_or(true, |existing| has_hash_comment(&existing));
 
-        Ok(())
-    };
+    status!();
+    generate_cmake(hash_comment)
+}
+
+fn generate_class(name: &str, namespace: Option<&str>) -> Result<(), ProjectError> {
+    let mut config = get_config()?;
+
+    codegen::generate_class(&mut config, name, namespace)?;
+    write_config(config)
+}
+
+fn cache_command(action: CacheAction) -> Result<(), ProjectError> {
+    if !Path::new(CONFIG_NAME).exists() {
+        return Err(ProjectError::InvalidProjectDirectory);
+    }
 
-    write_glob_type("GLOB", glob_dirs)?;
-    write_glob_type("GLOB_RECURSE", glob_recurse_dirs)?;
+    match action {
+        CacheAction::Save { archive, build } => archive::save(&archive, build),
+        CacheAction::Restore { archive } => archive::restore(&archive),
+    }
+}
 
-    if files.exclude_files.is_empty() == false {
-        let to_remove = files
-            .exclude_files
-            .iter()
-            .fold(String::new(), |a, b| format!(r#"{}"{}/{}" "#, a, path, b));
+fn build_command(options: &BuildOptions) -> Result<(), ProjectError> {
+    if !workspace::is_workspace_root() {
+        return match options.package {
+            Some(_) => Err(ProjectError::NotInWorkspace),
+            None => build_project(options),
+        };
+    }
 
-        writeln!(file, "list(REMOVE_ITEM {source_name} {to_remove})")?;
+    for member in workspace::selected_members(options.package.as_deref())? {
+        status!(
+            "{}",
+            t!("{} member '{}'", t!("Building").cyan().bold(), member)
+        );
+        workspace::in_member(&member, || build_project(options))?;
+        status!();
     }
 
     Ok(())
 }
 
-fn write_include_dirs(
-    file: &mut std::fs::File,
-    name: &str,
-    path: &str,
-    files: &config::ProjectFiles,
-) -> Result<(), std::io::Error> {
-    let mut other = Vec::new();
-    let mut interfaces = Vec::new();
-
-    files
-        .include_dirs
-        .iter()
-        .for_each(|(include_type, dirs)| match include_type {
-            config::IncludeType::Public => other.extend(dirs),
-            config::IncludeType::Interface => interfaces.extend(dirs),
-        });
+fn fetch_command(options: &BuildOptions) -> Result<(), ProjectError> {
+    if !workspace::is_workspace_root() {
+        return match options.package {
+            Some(_) => Err(ProjectError::NotInWorkspace),
+            None => fetch_project(options),
+        };
+    }
 
-    let mut write_include_type =
-        |include_type: &str, dirs: Vec<&String>| -> Result<(), std::io::Error> {
-            if dirs.is_empty() {
-                return Ok(());
-            }
+    for member in workspace::selected_members(options.package.as_deref())? {
+        status!(
+            "{}",
+            t!("{} member '{}'", t!("Fetching").cyan().bold(), member)
+        );
+        workspace::in_member(&member, || fetch_project(options))?;
+        status!();
+    }
 
-            let dirs = dirs
-                .into_iter()
-                .fold(String::new(), |a, dir| match dir.as_str() == "." {
-                    true => format!(r#"{}"{}" "#, a, path),
-                    false => format!(r#"{}"{}/{}" "#, a, path, dir),
-                });
+    Ok(())
+}
 
-            writeln!(
-                file,
-                "target_include_directories({name} {include_type} {dirs})"
-            )?;
+fn install_command(options: &BuildOptions, prefix: Option<&Path>) -> Result<(), ProjectError> {
+    // Members are installed from their own folders, so a relative prefix has to be
+    // resolved first
+    let prefix = prefix
+        .map(std::path::absolute)
+        .transpose()
+        .map_err(|err| ProjectError::CannotOpenFile(prefix.unwrap().to_owned(), err.to_string()))?;
+
+    if !workspace::is_workspace_root() {
+        return match options.package {
+            Some(_) => Err(ProjectError::NotInWorkspace),
+            None => install_project(options, prefix.as_deref()),
+        };
+    }
 
-            Ok(())
+    for member in workspace::selected_members(options.package.as_deref())? {
+        status!(
+            "{}",
+            t!("{} member '{}'", t!("Installing").cyan().bold(), member)
+        );
+        workspace::in_member(&member, || install_project(options, prefix.as_deref()))?;
+        status!();
+    }
+
+    Ok(())
+}
+
+fn package_command(options: &BuildOptions, generators: &[String]) -> Result<(), ProjectError> {
+    if !workspace::is_workspace_root() {
+        return match options.package {
+            Some(_) => Err(ProjectError::NotInWorkspace),
+            None => package_project(options, generators),
         };
+    }
 
-    write_include_type("PUBLIC", other)?;
-    write_include_type("INTERFACE", interfaces)?;
+    for member in workspace::selected_members(options.package.as_deref())? {
+        status!(
+            "{}",
+            t!("{} member '{}'", t!("Packaging").cyan().bold(), member)
+        );
+        workspace::in_member(&member, || package_project(options, generators))?;
+        status!();
+    }
 
     Ok(())
 }
 
-fn generate_cmake() -> Result<(), ProjectError> {
-    println!("Generating CMakeLists.txt from config");
+fn test_command(
+    mut options: BuildOptions,
+    report: Option<&Path>,
+    selection: Option<testing::CaseSelection>,
+) -> Result<(), ProjectError> {
+    // Resolved before moving into a member's folder
+    let report = report
+        .map(std::path::absolute)
+        .transpose()
+        .map_err(|err| ProjectError::CannotOpenFile(report.unwrap().to_owned(), err.to_string()))?;
+
+    let package = options.package.take();
+    let run = || match &selection {
+        Some(selection) => testing::run_cases(&options, selection),
+        None => testing::run_tests(&options, report.as_deref()),
+    };
+
+    if workspace::is_workspace_root() {
+        let member = workspace::pick_member(package.as_deref())?;
+        return workspace::in_member(&member, run);
+    }
 
-    let instant = std::time::Instant::now();
+    if package.is_some() {
+        return Err(ProjectError::NotInWorkspace);
+    }
 
-    let config = get_config()?;
+    run()
+}
 
-    let mut file = std::fs::OpenOptions::new()
-        .write(true)
-        .create(true)
-        .truncate(true)
-        .open(Path::new("CMakeLists.txt"))
-        .unwrap();
+fn coverage_command(mut options: BuildOptions, html: bool) -> Result<(), ProjectError> {
+    if workspace::is_workspace_root() {
+        let member = workspace::pick_member(options.package.take().as_deref())?;
+        return workspace::in_member(&member, || coverage::coverage_report(options, html));
+    }
 
-    // Config Hash
-    let mut hasher = std::hash::DefaultHasher::new();
-    config.hash(&mut hasher);
-    let config_hash = hasher.finish();
+    if options.package.is_some() {
+        return Err(ProjectError::NotInWorkspace);
+    }
 
-    writeln!(file, "# {}\n", config_hash).unwrap();
+    coverage::coverage_report(options, html)
+}
 
-    // Project Setup
-    writeln!(
-        file,
-        "cmake_minimum_required(VERSION {})",
-        config.cmake.minimum_required
-    )
-    .unwrap();
+/// How `cmm run` starts the program
+#[derive(Default)]
+struct RunMode {
+    /// Rerun under gdb for a stack trace if the program crashes
+    backtrace: bool,
+    /// Give the program a pseudo-terminal
+    pty: bool,
+    /// Export the sanitizer runtime options from the config
+    sanitize: bool,
+    /// Show the program's environment instead of running it
+    print_env: bool,
+}
 
-    writeln!(file, r#"project("{}")"#, config.project.name).unwrap();
+fn run_project(
+    bin: Option<String>,
+    program_args: &[String],
+    rebuild: bool,
+    mode: RunMode,
+    mut build_options: BuildOptions,
+) -> Result<(), ProjectError> {
+    if workspace::is_workspace_root() {
+        let member = workspace::pick_member(build_options.package.take().as_deref())?;
+        return workspace::in_member(&member, || {
+            run_binary(bin, program_args, rebuild, mode, build_options)
+        });
+    }
 
-    // Project top config
-    writeln!(file, "\n#Project Config Flags:").unwrap();
+    if build_options.package.is_some() {
+        return Err(ProjectError::NotInWorkspace);
+    }
 
-    writeln!(file, "set(CMAKE_BUILD_TYPE Debug)").unwrap();
-    writeln!(file, "set(CMAKE_EXPORT_COMPILE_COMMANDS ON)").unwrap();
+    run_binary(bin, program_args, rebuild, mode, build_options)
+}
 
-    // Project Dependencies
-    writeln!(file, "\n#Project Dependencies: ").unwrap();
+fn run_binary(
+    bin: Option<String>,
+    program_args: &[String],
+    rebuild: bool,
+    mode: RunMode,
+    mut build_options: BuildOptions,
+) -> Result<(), ProjectError> {
+    let config = get_config()?;
 
-    config.dependencies.find.iter().for_each(|find| {
-        let required = match find.required {
-            true => " REQUIRED",
-            false => "",
-        };
+    let executables = generate::runnable_names(&config);
 
-        writeln!(file, "find_package({}{})", find.name, required).unwrap();
-    });
+    let bin = match bin {
+        Some(bin) => bin,
+        None => pick_binary(&config, executables.clone())?,
+    };
 
-    if config.dependencies.find.is_empty() == false {
-        writeln!(file, "").unwrap();
+    if !executables.contains(&bin) {
+        return Err(ProjectError::UnknownTarget(bin));
     }
 
-    config.dependencies.local.iter().for_each(|local| {
-        local
-            .variables
-            .iter()
-            .for_each(|var| writeln!(file, "set({: <20} {})", var.0, var.1).unwrap());
+    // Only build what's needed to run the chosen binary
+    if bin != config.project.name {
+        build_options.target = Some(bin.clone());
+    }
 
-        match &local.local_type {
-            config::LocalType::CMake => writeln!(file, "add_subdirectory({})", local.path).unwrap(),
+    let env = match mode.sanitize {
+        true => sanitizers::runtime_env(&config),
+        false => Vec::new(),
+    };
 
-            config::LocalType::Source {
-                files,
-                dependencies,
-            } => {
-                let name = &local.name;
+    if mode.print_env {
+        print_env(&env);
+        return Ok(());
+    }
 
-                let src_name = format!("{}_SOURCES", name.to_uppercase());
+    if rebuild {
+        build_project(&build_options)?;
+        status!();
+    }
 
-                write_source_files(&mut file, &src_name, &local.path, files).unwrap();
+    if !config.hooks.pre_run.is_empty() {
+        hooks::run_hooks(&config.hooks.pre_run)?;
+        status!();
+    }
 
-                match files.source_files.is_empty() {
-                    true => writeln!(file, "add_library({name} INTERFACE)").unwrap(),
-                    false => writeln!(file, "add_library({name} ${{{src_name}}})").unwrap(),
-                }
+    let build_type = build::build_type(&build_options)?;
+    let binary = executable_path(&build_options.build_dir(), &build_type, &bin)
+        .to_string_lossy()
+        .into_owned();
 
-                write_include_dirs(&mut file, name, &local.path, files).unwrap();
+    for (var, value) in &env {
+        println!("{}", t!("Sanitizer runtime: {}={}", var, value));
+    }
 
-                if dependencies.is_empty() == false {
-                    writeln!(
-                        file,
-                        "target_link_libraries({name} PUBLIC {})",
-                        dependencies
-                            .iter()
-                            .fold(String::new(), |a, b| format!("{} {}", a, b))
-                    )
-                    .unwrap();
-                }
-            }
-        }
+    let program = match mode.pty {
+        true => pty_command(&binary, program_args),
+        false => duct::cmd(&binary, program_args),
+    };
+    let program = env
+        .iter()
+        .fold(program, |program, (var, value)| program.env(var, value));
 
-        writeln!(file, "").unwrap();
-    });
+    // Ctrl-C is delivered to the whole foreground process group. Swallow it here so the
+    // program decides how to handle it and we can still report how it exited.
+    ctrlc::set_handler(|| {}).ok();
 
-    writeln!(file, "#Project Files:").unwrap();
+    // stdin is inherited so interactive programs can read from the terminal
+    let cmd_output = program
+        .stderr_to_stdout()
+        .unchecked()
+        .run()
+        .map_err(|err| ProjectError::FailedToStartProcess(binary.clone(), err.to_string()))?;
 
-    // Project files
-    write_source_files(&mut file, "SOURCES", "src", &config.cmake.files).unwrap();
+    match cmd_output.status.success() {
+        true => status!(
+            "{}",
+            t!(
+                "\n\n{} program execution with exit code {}",
+                t!("Finished").green().bold(),
+                cmd_output.status.code().unwrap_or(0)
+            )
+        ),
 
-    // Link files
-    writeln!(file, r#"add_executable("${{PROJECT_NAME}}" ${{SOURCES}})"#).unwrap();
+        false => status!(
+            "{}",
+            t!(
+                "\n\n{} program execution with exit code {}",
+                t!("Finished").red().bold(),
+                cmd_output.status.code().unwrap_or(255)
+            )
+        ),
+    }
 
-    if config.dependencies.project_dependencies.is_empty() == false {
-        writeln!(
-            file,
-            r#"target_link_libraries("${{PROJECT_NAME}}" PRIVATE {})"#,
-            config
-                .dependencies
-                .project_dependencies
+    if let Some(signal) = exit_signal(&cmd_output.status) {
+        println!(
+            "{}",
+            t!(
+                "{} program terminated by signal {}",
+                t!("warning:").yellow(),
+                signal
+            )
+        );
+
+        match mode.backtrace {
+            true => print_backtrace(&binary, program_args, &env)?,
+            false => println!(
+                "{}",
+                t!("Rerun with '--backtrace' to capture a stack trace")
+            ),
+        }
+    }
+
+    Ok(())
+}
+
+/// `cmm run --print-env`. Every variable the program would get, with where cmm got the
+/// ones it added from.
+fn print_env(runtime_env: &[(String, String)]) {
+    let mut variables = std::env::vars()
+        .filter(|(var, _)| !runtime_env.iter().any(|(runtime, _)| runtime == var))
+        .map(|(var, value)| {
+            let source = env_file::source(&var);
+            (var, value, source)
+        })
+        .chain(
+            runtime_env
                 .iter()
-                .fold(String::new(), |a, b| format!("{} {}", a, b))
+                .map(|(var, value)| (var.clone(), value.clone(), Some("--sanitize"))),
         )
-        .unwrap();
-    }
+        .collect::<Vec<_>>();
+    variables.sort();
 
-    file.flush().unwrap();
+    for (var, value, source) in variables {
+        match source {
+            Some(source) => println!("{}={} {}", var, value, format!("# {}", source).dimmed()),
+            None => println!("{}={}", var, value),
+        }
+    }
+}
 
-    println!(
-        "{} {} {:.3}s",
-        "Finished".green().bold(),
-        "creating CMakeLists.txt in",
-        instant.elapsed().as_secs_f32(),
-    );
+/// The binary to run when `--bin` isn't given. Executable projects default to their own
+/// binary when there's no one to ask, libraries always ask which example or test to run.
+fn pick_binary(config: &ConfigFile, executables: Vec<String>) -> Result<String, ProjectError> {
+    let library = config.project.kind == ProjectKind::Library;
+
+    match executables.len() {
+        0 => Err(ProjectError::NothingToRun),
+        1 => Ok(executables[0].clone()),
+        _ if !library && !answers::can_answer("run.bin") => Ok(config.project.name.clone()),
+        _ => Ok(answers::select(
+            "run.bin",
+            inquire::Select::new(t!("Choose a binary to run:"), executables),
+        )?
+        .value),
+    }
+}
 
-    Ok(())
+/// Quotes an argument for `sh`, which runs the command given to `script -c`
+fn shell_quote(arg: &str) -> String {
+    format!("'{}'", arg.replace('\'', r"'\''"))
 }
 
-fn build_project() -> Result<(), ProjectError> {
-    println!("Building Project");
+/// Wraps the program in `script` so it gets a pseudo-terminal even when our own output is piped
+fn pty_command(binary: &str, program_args: &[String]) -> duct::Expression {
+    match cfg!(target_os = "macos") {
+        true => {
+            let mut args = vec!["-q", "/dev/null", binary];
+            args.extend(program_args.iter().map(String::as_str));
 
-    let config = get_config()?;
+            duct::cmd("script", args)
+        }
+        false => {
+            let command = std::iter::once(binary)
+                .chain(program_args.iter().map(String::as_str))
+                .map(shell_quote)
+                .collect::<Vec<_>>()
+                .join(" ");
 
-    if Path::new("CMakeLists.txt").exists() == false {
-        println!("{} {}", "warning:".yellow(), "CMakeLists.txt doesn't exist");
-        generate_cmake()?;
-        println!("");
-    } else {
-        let mut hasher = std::hash::DefaultHasher::new();
-        config.hash(&mut hasher);
-        let config_hash = hasher.finish();
-
-        let cmake_file = open_file(&Path::new("CMakeLists.txt"))?;
-        let mut buffer = std::io::BufReader::new(cmake_file);
-        let mut first_line = String::new();
-        buffer.read_line(&mut first_line).unwrap();
-
-        if first_line != format!("# {}\n", config_hash) {
-            println!(
-                "{} {}",
-                "warning:".yellow(),
-                "CMakeLists.txt out of date. Regenerating."
-            );
-            generate_cmake()?;
-            println!("");
+            duct::cmd!("script", "-q", "-e", "-c", command, "/dev/null")
         }
     }
+}
 
-    println!("{}", "Generating CMake build system".green());
+fn print_backtrace(
+    binary: &str,
+    program_args: &[String],
+    env: &[(String, String)],
+) -> Result<(), ProjectError> {
+    status!("\n{}", t!("Capturing backtrace with gdb").green());
 
-    let instant = std::time::Instant::now();
+    // Batch mode runs the program once more and prints the stack of the crashing thread
+    let mut gdb_args = vec!["-batch", "-ex", "run", "-ex", "bt", "--args", binary];
+    gdb_args.extend(program_args.iter().map(String::as_str));
 
-    let output = duct::cmd!("cmake", "-B", "build")
+    let output = env
+        .iter()
+        .fold(duct::cmd("gdb", gdb_args), |gdb, (var, value)| {
+            gdb.env(var, value)
+        })
         .stderr_to_stdout()
         .unchecked()
         .run()
-        .unwrap();
+        .map_err(|_| ProjectError::MissingProgram("gdb".into()))?;
 
     if !output.status.success() {
         Err(ProjectError::FailedToRunProcess(
-            String::from("cmake -B build"),
+            "gdb -batch".into(),
             output.status.code(),
         ))?;
     }
 
-    println!("\n{}", "Compiling c++ project".green());
-
-    let output = duct::cmd!("cmake", "--build", "build")
-        .stderr_to_stdout()
-        .unchecked()
-        .run()
-        .unwrap();
+    Ok(())
+}
 
-    if !output.status.success() {
-        Err(ProjectError::FailedToRunProcess(
-            "cmake".into(),
-            output.status.code(),
-        ))?;
+fn clean_in_source() -> Result<(), ProjectError> {
+    // Without a cache there's nothing to say these files were made by cmake
+    if !Path::new("CMakeCache.txt").exists() {
+        println!("{}", t!("No in-source cmake files found"));
+        return Ok(());
     }
 
-    println!(
-        "{} {} {:.3}s",
-        "Finished".green().bold(),
-        "building c++ project in",
-        instant.elapsed().as_secs_f32()
+    IN_SOURCE_FILES.iter().map(Path::new).for_each(|path| {
+        if !path.exists() {
+            return;
+        }
+
+        let result = util::remove_path(path);
+
+        match result {
+            Ok(_) => println!("{}", t!("Removed '{}'", path.display())),
+            Err(e) => println!(
+                "{}",
+                t!(
+                    "{} failed to remove '{}' with error: {}",
+                    t!("warning:").yellow(),
+                    path.display(),
+                    e.to_string().red()
+                )
+            ),
+        }
+    });
+
+    status!(
+        "{}",
+        t!("{} removing in-source cmake files", t!("Finished").green())
     );
 
     Ok(())
 }
 
-fn run_project() -> Result<(), ProjectError> {
-    let config = get_config()?;
+/// Removes everything in `dir` apart from `keep` and the folders leading to it
+fn clear_dir(dir: &Path, keep: Option<&Path>) -> std::io::Result<()> {
+    for entry in std::fs::read_dir(dir)? {
+        let path = entry?.path();
 
-    let mut rebuild = true;
+        match keep {
+            Some(keep) if path == keep => {}
+            Some(keep) if keep.starts_with(&path) => clear_dir(&path, Some(keep))?,
+            _ => util::remove_path(&path)?,
+        }
+    }
+
+    Ok(())
+}
 
-    if let Some(arg) = std::env::args().nth(2) {
-        match arg.as_str() {
-            "skip_build" => rebuild = false,
-            _ => Err(ProjectError::UnknownArgument(arg.clone()))?,
+/// Removes the build folder, refusing to follow a symlink or remove anything outside the
+/// project unless forced. A forced symlink keeps the link and clears the folder it points to.
+/// `keep` is left in place when it's inside the folder. Refusing is an error, failing to
+/// remove files is only reported.
+fn remove_build_dir(
+    path: &Path,
+    force: bool,
+    keep: Option<&Path>,
+) -> Result<std::io::Result<()>, ProjectError> {
+    let metadata = match path.symlink_metadata() {
+        Ok(metadata) => metadata,
+        Err(e) => return Ok(Err(e)),
+    };
+
+    let target = path.canonicalize().unwrap_or(path.to_owned());
+
+    if metadata.is_symlink() {
+        if !force {
+            return Err(ProjectError::SymlinkedBuildDir(path.to_owned(), target));
         }
+
+        return Ok(clear_dir(path, keep));
     }
 
-    if rebuild {
-        build_project()?;
-        println!("");
+    let root = std::env::current_dir()
+        .and_then(|dir| dir.canonicalize())
+        .map_err(|_| ProjectError::InvalidProjectDirectory)?;
+
+    // Catches junctions and bind mounts that don't show up as symlinks
+    if !force && !target.starts_with(&root) {
+        return Err(ProjectError::OutsideProject(path.to_owned(), target));
     }
 
-    let cmd_output = duct::cmd!(format!("./build/{}", config.project.name))
-        .stderr_to_stdout()
-        .unchecked()
-        .run()
-        .unwrap();
+    match keep.filter(|keep| keep.starts_with(path)) {
+        Some(keep) => Ok(clear_dir(path, Some(keep))),
+        None => Ok(util::remove_path(path)),
+    }
+}
 
-    match cmd_output.status.success() {
-        true => println!(
-            "\n\n{} {} {}",
-            "Finished".green().bold(),
-            "program execution with exit code",
-            cmd_output.status.code().unwrap_or(0)
-        ),
+/// Folders named `<target>.dir` below `dir`, which is where every generator keeps a
+/// target's object files. Makefiles and Ninja put them in CMakeFiles/, Visual Studio next
+/// to it.
+fn target_object_dirs(dir: &Path, target: &str, found: &mut Vec<PathBuf>) {
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return;
+    };
+    let name = format!("{}.dir", target);
 
-        false => println!(
-            "\n\n{} {} {}",
-            "Finished".red().bold(),
-            "program execution with exit code",
-            cmd_output.status.code().unwrap_or(255)
-        ),
+    // file_type doesn't follow symlinks, so this stays inside the build folder
+    for entry in entries.filter_map(|entry| entry.ok()) {
+        if !entry.file_type().is_ok_and(|kind| kind.is_dir()) {
+            continue;
+        }
+
+        match entry.file_name().to_string_lossy() == name {
+            true => found.push(entry.path()),
+            false => target_object_dirs(&entry.path(), target, found),
+        }
     }
+}
+
+/// Removes one target's object files from each build folder so its next build compiles it
+/// from scratch. The build system notices they're gone, so nothing needs reconfiguring.
+fn clean_target(target: &str, build_dirs: &[String]) -> Result<(), ProjectError> {
+    let mut object_dirs = Vec::new();
+    build_dirs
+        .iter()
+        .for_each(|dir| target_object_dirs(Path::new(dir), target, &mut object_dirs));
+
+    if object_dirs.is_empty() {
+        println!("{}", t!("No build files found for target '{}'", target));
+        return Ok(());
+    }
+
+    for dir in object_dirs {
+        match util::remove_path(&dir) {
+            Ok(()) => println!("{}", t!("Removed '{}'", dir.display())),
+            Err(e) => println!(
+                "{}",
+                t!(
+                    "{} failed to remove folder '{}' with error: {}",
+                    t!("warning:").yellow(),
+                    dir.display(),
+                    e.to_string().red()
+                )
+            ),
+        }
+    }
+
+    status!("{}", t!("{} removing build files", t!("Finished").green()));
 
     Ok(())
 }
 
-fn clean_project() -> Result<(), ProjectError> {
-    println!("Cleaning build files");
-
-    if Path::new(CONFIG_NAME).exists() == false {
+fn clean_project(
+    clean_all: bool,
+    in_source: bool,
+    profile: Option<String>,
+    target: Option<String>,
+    force: bool,
+    keep_cache_objects: bool,
+    zero_stats: bool,
+) -> Result<(), ProjectError> {
+    status!("{}", t!("Cleaning build files"));
+
+    if !Path::new(CONFIG_NAME).exists() {
         return Err(ProjectError::InvalidProjectDirectory);
     }
 
-    let mut clean_all = false;
+    let config = get_config()?;
+
+    if in_source {
+        return clean_in_source();
+    }
+
+    if let Some(profile) = &profile {
+        if config.profile(profile).is_none() {
+            return Err(ProjectError::UnknownProfile(profile.clone()));
+        }
+    }
 
-    if let Some(arg) = std::env::args().nth(2) {
-        match arg.as_str() {
-            "all" => clean_all = true,
-            _ => Err(ProjectError::UnknownArgument(arg.clone()))?,
+    let profile_dir = |profile: String| {
+        BuildOptions {
+            profile,
+            ..Default::default()
         }
+        .build_dir()
+    };
+
+    if let Some(target) = target {
+        let build_dirs = match profile {
+            Some(profile) => vec![profile_dir(profile)],
+            None => config.profiles().into_keys().map(profile_dir).collect(),
+        };
+        return clean_target(&target, &build_dirs);
     }
 
-    if let Err(e) = std::fs::remove_dir_all(Path::new("build")) {
+    // Profiles build side by side, so one can go without reconfiguring the others
+    let build_dir = profile.map_or("build".into(), profile_dir);
+    let build_path = Path::new(&build_dir);
+
+    let cache_dir = compiler_cache::cache_dir(&config).filter(|_| keep_cache_objects);
+    if keep_cache_objects {
+        compiler_cache::show_stats(&config)?;
+    }
+
+    if build_dir != "build" && build_path.symlink_metadata().is_err() {
+        println!("{}", t!("Nothing to remove, '{}' doesn't exist", build_dir));
+    } else if is_protected_path(&config, build_path) {
+        println!(
+            "{}",
+            t!(
+                "{} not removing folder '{}' as it contains a monorepo dependency",
+                t!("warning:").yellow(),
+                build_dir
+            )
+        );
+    } else if let Err(e) = remove_build_dir(build_path, force, cache_dir.as_deref())? {
         println!(
-            "{} {} {}",
-            "warning:".yellow(),
-            "failed to remove folder 'build' with error:",
-            e.to_string().red()
+            "{}",
+            t!(
+                "{} failed to remove folder '{}' with error: {}",
+                t!("warning:").yellow(),
+                build_dir,
+                e.to_string().red()
+            )
         )
     }
 
+    if zero_stats {
+        compiler_cache::zero_stats(&config)?;
+    }
+
     if clean_all {
-        println!("Cleaning CMake Files");
+        status!("{}", t!("Cleaning CMake Files"));
 
-        if let Err(e) = std::fs::remove_file(Path::new("CMakeLists.txt")) {
+        if let Err(e) = util::remove_path(Path::new("CMakeLists.txt")) {
             println!(
-                "{} {} {}",
-                "warning:".yellow(),
-                "failed to remove file 'CMakeLists.txt' with error:",
-                e.to_string().red()
+                "{}",
+                t!(
+                    "{} failed to remove file 'CMakeLists.txt' with error: {}",
+                    t!("warning:").yellow(),
+                    e.to_string().red()
+                )
             )
         }
     }
 
-    println!("{} {}", "Finished".green(), "removing build files");
+    status!("{}", t!("{} removing build files", t!("Finished").green()));
 
     Ok(())
 }
 
 fn add_ignore() -> Result<(), ProjectError> {
-    println!("Adding .ignore");
+    status!("{}", t!("Adding .ignore"));
 
-    if Path::new(CONFIG_NAME).exists() == false {
-        return Err(ProjectError::InvalidProjectDirectory);
-    }
+    let config = get_config()?;
 
     let ignore_path = Path::new(".ignore");
 
     match ignore_path.exists() {
         true => {
-            println!(".ignore file already exists");
+            println!("{}", t!(".ignore file already exists"));
         }
         false => {
-            println!("Creating .ignore file");
-            init_file(&Path::new(".ignore"), b"external/\nres/\n")?;
+            status!("{}", t!("Creating .ignore file"));
+
+            let mut ignore = String::from("external/\nres/\n");
+
+            // Monorepo dependencies are project code, so keep them searchable
+            config
+                .dependencies
+                .local
+                .iter()
+                .filter(|local| local.monorepo)
+                .filter(|local| {
+                    local.path.starts_with("external/") || local.path.starts_with("res/")
+                })
+                .for_each(|local| ignore.push_str(&format!("!{}/\n", local.path)));
+
+            init_file(ignore_path, ignore.as_bytes())?;
         }
     }
 
@@ -0,0 +1,633 @@
+use std::{fmt::Display, sync::OnceLock};
+
+/// Translates a user facing message. The English text is the catalog key, so untranslated
+/// messages fall back to it. Arguments fill `{}` placeholders in order, or `{0}`, `{1}`...
+/// when a translation needs to reorder them.
+#[macro_export]
+macro_rules! t {
+    ($msg:literal) => {
+        $crate::messages::translate($msg)
+    };
+    ($msg:literal, $($arg:expr),+ $(,)?) => {
+        $crate::messages::fill(
+            $crate::messages::translate($msg),
+            &[$(&$arg as &dyn std::fmt::Display),+],
+        )
+    };
+}
+
+pub use crate::t;
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum Locale {
+    English,
+    German,
+}
+
+impl Locale {
+    fn from_tag(tag: &str) -> Option<Self> {
+        // Tags look like `de_DE.UTF-8` or `de`
+        let language = tag.split(['_', '.', '-', '@']).next()?.to_lowercase();
+
+        match language.as_str() {
+            "en" | "c" | "posix" => Some(Self::English),
+            "de" => Some(Self::German),
+            _ => None,
+        }
+    }
+
+    fn catalog(self) -> &'static [(&'static str, &'static str)] {
+        match self {
+            Self::English => &[],
+            Self::German => GERMAN,
+        }
+    }
+}
+
+/// The first locale set in `CMM_LANG`, `LC_ALL`, `LC_MESSAGES` or `LANG`
+pub fn locale() -> Locale {
+    static LOCALE: OnceLock<Locale> = OnceLock::new();
+
+    *LOCALE.get_or_init(|| {
+        ["CMM_LANG", "LC_ALL", "LC_MESSAGES", "LANG"]
+            .iter()
+            .filter_map(|var| std::env::var(var).ok())
+            .find(|val| !val.is_empty())
+            .and_then(|tag| Locale::from_tag(&tag))
+            .unwrap_or(Locale::English)
+    })
+}
+
+pub fn translate(msg: &'static str) -> &'static str {
+    locale()
+        .catalog()
+        .iter()
+        .find(|(english, _)| *english == msg)
+        .map(|(_, translated)| *translated)
+        .unwrap_or(msg)
+}
+
+pub fn fill(template: &str, args: &[&dyn Display]) -> String {
+    let mut output = String::with_capacity(template.len());
+    let mut next = 0;
+    let mut rest = template;
+
+    while let Some(start) = rest.find('{') {
+        output.push_str(&rest[..start]);
+        rest = &rest[start..];
+
+        let end = match rest.find('}') {
+            Some(end) => end,
+            None => break,
+        };
+
+        let index = match rest[1..end].parse::<usize>() {
+            Ok(index) => index,
+            Err(_) if end == 1 => {
+                next += 1;
+                next - 1
+            }
+            Err(_) => {
+                output.push_str(&rest[..=end]);
+                rest = &rest[end + 1..];
+                continue;
+            }
+        };
+
+        if let Some(arg) = args.get(index) {
+            output.push_str(&arg.to_string());
+        }
+
+        rest = &rest[end + 1..];
+    }
+
+    output.push_str(rest);
+    output
+}
+
+const GERMAN: &[(&str, &str)] = &[
+    // Labels
+    ("error:", "Fehler:"),
+    ("warning:", "Warnung:"),
+    ("hint:", "Hinweis:"),
+    ("Finished", "Fertig"),
+    ("Successfully", "Erfolgreich"),
+    ("Removed", "Entfernt"),
+    ("Cancelled", "Abgebrochen"),
+    ("not found", "nicht gefunden"),
+    ("Collecting bug report", "Sammle Informationen für den Fehlerbericht"),
+    ("No {} in the current folder", "Kein {} im aktuellen Ordner"),
+    ("No build recorded yet", "Noch kein Build aufgezeichnet"),
+    (
+        "Wrote '{}'. Check it over before attaching it to an issue.",
+        "'{}' geschrieben. Prüfe ihn, bevor du ihn an ein Issue anhängst.",
+    ),
+    ("Restored", "Wiederhergestellt"),
+    ("Updated", "Aktualisiert"),
+    ("Building", "Baue"),
+    ("Unknown command", "Unbekannter Befehl"),
+    // Errors
+    ("{} please provide a suitable project name", "{} bitte einen gültigen Projektnamen angeben"),
+    ("{} missing argument: {}", "{} fehlendes Argument: {}"),
+    ("{} unknown argument '{}'", "{} unbekanntes Argument '{}'"),
+    ("{} current directory doesn't contain a {}", "{} das aktuelle Verzeichnis enthält keine {}"),
+    (
+        "{} found CMakeCache.txt in the project folder from an in-source build. Remove it with '{}' or set cmake.allow_in_source",
+        "{} CMakeCache.txt eines In-Source-Builds im Projektordner gefunden. Mit '{}' entfernen oder cmake.allow_in_source setzen",
+    ),
+    (
+        "{} refusing to build with --frozen, {} is out of date for: {}",
+        "{} Build mit --frozen abgelehnt, {} ist veraltet für: {}",
+    ),
+    (
+        "{} dependency '{}' has local changes, not restoring the locked commit {}",
+        "{} Abhängigkeit '{}' hat lokale Änderungen, der gesperrte Commit {} wird nicht wiederhergestellt",
+    ),
+    (
+        "{} new compiler warnings in: {}. Fix them or accept them with '{}'",
+        "{} neue Compiler-Warnungen in: {}. Beheben oder mit '{}' akzeptieren",
+    ),
+    ("{} writing {}", "{} {} geschrieben"),
+    ("{} '{}' to locked commit {}", "{} '{}' auf gesperrten Commit {}"),
+    ("{} dependency '{}' has local changes, not updating it", "{} Abhängigkeit '{}' hat lokale Änderungen und wird nicht aktualisiert"),
+    ("{} couldn't list the tags of '{}'", "{} Tags von '{}' konnten nicht aufgelistet werden"),
+    ("'{}' is up to date", "'{}' ist aktuell"),
+    ("New tag for '{}':", "Neuer Tag für '{}':"),
+    ("Keep {}", "{} behalten"),
+    ("Keep current", "Aktuellen behalten"),
+    ("{} failed to check out '{}' in '{}'", "{} '{}' konnte in '{}' nicht ausgecheckt werden"),
+    ("{} '{}' {} -> {}", "{} '{}' {} -> {}"),
+    ("{} '{}' has no tag '{}'", "{} '{}' hat keinen Tag '{}'"),
+    ("{} no license file found for dependency '{}'", "{} keine Lizenzdatei für Abhängigkeit '{}' gefunden"),
+    ("{} writing notices for {} dependencies to {}", "{} Hinweise für {} Abhängigkeiten in {} geschrieben"),
+    ("{} nothing to save yet, run 'cmm fetch' first", "{} noch nichts zu speichern, zuerst 'cmm fetch' ausführen"),
+    ("Packed '{}'", "'{}' gepackt"),
+    ("{} saving cache to '{}'", "{} Cache in '{}' gespeichert"),
+    ("No cache at '{}', nothing restored", "Kein Cache unter '{}', nichts wiederhergestellt"),
+    ("Restored {} files", "{} Dateien wiederhergestellt"),
+    ("Moved paths in {} files from '{}' to '{}'", "Pfade in {} Dateien von '{}' nach '{}' verschoben"),
+    ("{} restoring cache from '{}'", "{} Cache aus '{}' wiederhergestellt"),
+    ("{} failed to check out locked commit {} of '{}'", "{} gesperrter Commit {} von '{}' konnte nicht ausgecheckt werden"),
+    (
+        "{} couldn't find the commit for dependency '{}', it won't be locked",
+        "{} Commit für Abhängigkeit '{}' nicht gefunden, sie wird nicht gesperrt",
+    ),
+    (
+        "{} nothing to install, add an {} section to {} listing binaries, headers or resources",
+        "{} nichts zu installieren, einen Abschnitt {} mit binaries, headers oder resources in {} anlegen",
+    ),
+    (
+        "{} packaging isn't set up, add a {} section to {}, eg. generators = [\"DEB\", \"ZIP\"]",
+        "{} Paketierung ist nicht eingerichtet, einen Abschnitt {} in {} anlegen, z. B. generators = [\"DEB\", \"ZIP\"]",
+    ),
+    ("{} project has no tests, set them up with '{}'", "{} Projekt hat keine Tests, mit '{}' einrichten"),
+    ("{} tests failed", "{} Tests fehlgeschlagen"),
+    ("{} {} tests failing: {}", "{} {} Tests schlagen fehl: {}"),
+    (
+        "{} another cmm process{} is using this project. Wait for it to finish or rerun with '{}'",
+        "{} ein anderer cmm-Prozess{} verwendet dieses Projekt. Warte, bis er fertig ist, oder starte erneut mit '{}'",
+    ),
+    ("Waiting for another cmm process to finish with this project", "Warte darauf, dass ein anderer cmm-Prozess mit diesem Projekt fertig wird"),
+    (
+        "{} '{}' is a symlink to '{}', not removing it. Use '{}' to clear the folder it points to",
+        "{} '{}' ist ein Symlink auf '{}' und wird nicht entfernt. Mit '{}' den Zielordner leeren",
+    ),
+    (
+        "{} '{}' resolves to '{}' outside the project, not removing it. Use '{}' to remove it anyway",
+        "{} '{}' zeigt auf '{}' außerhalb des Projekts und wird nicht entfernt. Mit '{}' trotzdem entfernen",
+    ),
+    ("{} failed to create folder '{}' with error: {}", "{} Ordner '{}' konnte nicht erstellt werden: {}"),
+    ("{} failed to init git repo with error: {}", "{} Git-Repository konnte nicht initialisiert werden: {}"),
+    ("{} failed to open git repo with error: {}", "{} Git-Repository konnte nicht geöffnet werden: {}"),
+    ("{} failed to create file '{}' with error: {}", "{} Datei '{}' konnte nicht erstellt werden: {}"),
+    ("{} failed to open file '{}' with error: {}", "{} Datei '{}' konnte nicht geöffnet werden: {}"),
+    (
+        "{} refusing to build with --frozen, modified dependencies: {}",
+        "{} Build mit --frozen abgelehnt, geänderte Abhängigkeiten: {}",
+    ),
+    ("{} current directory already contains a {}", "{} das aktuelle Verzeichnis enthält bereits eine {}"),
+    ("{} no dependency named '{}'", "{} keine Abhängigkeit namens '{}'"),
+    ("{} no build profile named '{}'", "{} kein Build-Profil namens '{}'"),
+    ("{} no executable target named '{}'", "{} kein ausführbares Target namens '{}'"),
+    ("{} no workspace member named '{}'", "{} kein Workspace-Mitglied namens '{}'"),
+    (
+        "{} failed to apply patch '{}' to dependency '{}'",
+        "{} Patch '{}' konnte nicht auf Abhängigkeit '{}' angewendet werden",
+    ),
+    ("exit code {}", "Exit-Code {}"),
+    ("an unknown error code", "einem unbekannten Fehlercode"),
+    ("{} run process '{}' exited with {}", "{} Prozess '{}' wurde beendet mit {}"),
+    ("{} failed to start process '{}' with error: {}", "{} Prozess '{}' konnte nicht gestartet werden: {}"),
+    (
+        "{} failed to find program '{}'. Is it installed and on your PATH?",
+        "{} Programm '{}' nicht gefunden. Ist es installiert und im PATH?",
+    ),
+    ("found version {}", "Version {} gefunden wurde"),
+    ("it wasn't found", "es nicht gefunden wurde"),
+    ("{} project requires {} {} but {}\n{} {}", "{} Projekt benötigt {} {}, aber {}\n{} {}"),
+    (
+        "{} generator '{}' isn't installed\n{} installed generators: {}",
+        "{} Generator '{}' ist nicht installiert\n{} installierte Generatoren: {}",
+    ),
+    ("none", "keine"),
+    (
+        "{} default generator '{}' isn't installed, letting cmake choose",
+        "{} Standard-Generator '{}' ist nicht installiert, cmake wählt selbst",
+    ),
+    ("installed", "installiert"),
+    ("not found", "nicht gefunden"),
+    // Project commands
+    ("Skipping git init, already inside a git repository", "Überspringe git init, bereits in einem Git-Repository"),
+    ("{} creating project at {}", "{} Projekt erstellt in {}"),
+    ("{} your toolchain builds and runs projects", "{} deine Toolchain kann Projekte bauen und ausführen"),
+    ("The project was created, but building it failed. Check that cmake and a C++ compiler are installed, then try '{}' inside it.", "Das Projekt wurde erstellt, aber das Bauen ist fehlgeschlagen. Prüfe, ob cmake und ein C++-Compiler installiert sind, und versuche dann '{}' darin."),
+    (
+        "{} existing CMakeLists.txt will be regenerated, a copy was saved to {}",
+        "{} vorhandene CMakeLists.txt wird neu erzeugt, eine Kopie wurde unter {} gespeichert",
+    ),
+    (
+        "{} no source files found, add them to cmake.files in {}",
+        "{} keine Quelldateien gefunden, bitte in {} unter cmake.files eintragen",
+    ),
+    (
+        "{} initialising project '{}' with sources from '{}'",
+        "{} Projekt '{}' mit Quellen aus '{}' initialisiert",
+    ),
+    ("{} added dependency", "{} Abhängigkeit hinzugefügt"),
+    ("Generating CMakeLists.txt from config", "Erzeuge CMakeLists.txt aus der Konfiguration"),
+    ("{} creating CMakeLists.txt in {}s", "{} CMakeLists.txt erstellt in {}s"),
+    ("{} member '{}'", "{} Mitglied '{}'"),
+    ("Building Project", "Baue Projekt"),
+    ("{} CMakeLists.txt doesn't exist", "{} CMakeLists.txt existiert nicht"),
+    ("{} CMakeLists.txt out of date. Regenerating.", "{} CMakeLists.txt ist veraltet. Wird neu erzeugt."),
+    ("{} CMakeLists.txt was edited by hand or not generated by cmakemake", "{} CMakeLists.txt wurde von Hand bearbeitet oder nicht von cmakemake erzeugt"),
+    ("Replace CMakeLists.txt? The current file is kept as {}", "CMakeLists.txt ersetzen? Die aktuelle Datei wird als {} behalten"),
+    ("Kept the old CMakeLists.txt as {}", "Die alte CMakeLists.txt wurde als {} behalten"),
+    (
+        "{} not replacing the hand-edited CMakeLists.txt. Rerun with '{}' to replace it, keeping a backup",
+        "{} die von Hand bearbeitete CMakeLists.txt wird nicht ersetzt. Mit '{}' erneut ausführen, um sie mit Sicherung zu ersetzen",
+    ),
+    ("Generating CMake build system", "Erzeuge CMake-Buildsystem"),
+    ("Using {}={} from the environment", "Verwende {}={} aus der Umgebung"),
+    (
+        "Ignoring {}={} from the environment, --generator or cmake.generator is set",
+        "Ignoriere {}={} aus der Umgebung, --generator oder cmake.generator ist gesetzt",
+    ),
+    ("{} cmake cache is stale: {}", "{} CMake-Cache ist veraltet: {}"),
+    ("Reconfiguring with --fresh", "Konfiguriere neu mit --fresh"),
+    ("Reconfiguring without the old cache", "Konfiguriere neu ohne den alten Cache"),
+    ("No C++ compiler was found", "Es wurde kein C++-Compiler gefunden"),
+    (
+        "Install a compiler: build-essential on Debian/Ubuntu, the Xcode command line tools on macOS or Visual Studio on Windows",
+        "Installiere einen Compiler: build-essential unter Debian/Ubuntu, die Xcode Command Line Tools unter macOS oder Visual Studio unter Windows",
+    ),
+    (
+        "If one is installed outside the PATH, point CC and CXX at it and build again",
+        "Ist einer außerhalb des PATH installiert, setze CC und CXX darauf und baue erneut",
+    ),
+    ("The compiler can't build a test program", "Der Compiler kann kein Testprogramm bauen"),
+    (
+        "Check CC, CXX and CMAKE_TOOLCHAIN_FILE point at the compiler you meant",
+        "Prüfe, ob CC, CXX und CMAKE_TOOLCHAIN_FILE auf den gewünschten Compiler zeigen",
+    ),
+    (
+        "Try another compiler by setting CC and CXX, then build again",
+        "Probiere einen anderen Compiler über CC und CXX aus und baue erneut",
+    ),
+    ("The generator's build tool isn't installed", "Das Build-Programm des Generators ist nicht installiert"),
+    ("Install it, eg. ninja or make", "Installiere es, z.B. ninja oder make"),
+    (
+        "Or pick an installed generator with --generator or cmake.generator in CMakeMake.toml",
+        "Oder wähle einen installierten Generator mit --generator oder cmake.generator in CMakeMake.toml",
+    ),
+    (
+        "The build folder was configured differently before",
+        "Der Build-Ordner wurde vorher anders konfiguriert",
+    ),
+    (
+        "Remove it with 'cmm clean --profile {}' and build again",
+        "Entferne ihn mit 'cmm clean --profile {}' und baue erneut",
+    ),
+    ("A newer version of cmake is needed", "Eine neuere cmake-Version wird benötigt"),
+    (
+        "Update cmake from cmake.org, your package manager or 'pip install cmake'",
+        "Aktualisiere cmake über cmake.org, deinen Paketmanager oder 'pip install cmake'",
+    ),
+    ("Downloading a fetch dependency failed", "Das Herunterladen einer Fetch-Abhängigkeit ist fehlgeschlagen"),
+    ("Check your network connection", "Prüfe deine Netzwerkverbindung"),
+    (
+        "Check the dependency's repo and tag in CMakeMake.toml",
+        "Prüfe Repo und Tag der Abhängigkeit in CMakeMake.toml",
+    ),
+    (
+        "Run 'cmm fetch' to retry the download without compiling",
+        "Führe 'cmm fetch' aus, um den Download ohne Kompilieren zu wiederholen",
+    ),
+    ("Install it, eg. 'sudo apt install {}'", "Installiere es, z.B. 'sudo apt install {}'"),
+    ("Install it, eg. 'brew install {}'", "Installiere es, z.B. 'brew install {}'"),
+    (
+        "Install {} with your package manager, or vcpkg on Windows",
+        "Installiere {} mit deinem Paketmanager, oder vcpkg unter Windows",
+    ),
+    ("find_package couldn't find {}", "find_package konnte {} nicht finden"),
+    (
+        "If it's installed somewhere unusual, add that folder to dependencies.prefix_paths",
+        "Ist es an einem ungewöhnlichen Ort installiert, füge den Ordner zu dependencies.prefix_paths hinzu",
+    ),
+    (
+        "Or build it from source instead with 'cmm add fetch <repo url>'",
+        "Oder baue es stattdessen aus dem Quellcode mit 'cmm add fetch <repo url>'",
+    ),
+    ("Folder {} is installed in: (optional)", "Ordner, in dem {} installiert ist: (optional)"),
+    (
+        "eg. ~/opt/ or /usr/local/, leave empty to skip",
+        "z.B. ~/opt/ oder /usr/local/, leer lassen zum Überspringen",
+    ),
+    (
+        "Added to dependencies.prefix_paths, build again to retry",
+        "Zu dependencies.prefix_paths hinzugefügt, baue erneut für einen neuen Versuch",
+    ),
+    ("Configuring failed. Look into why?", "Konfigurieren fehlgeschlagen. Ursache untersuchen?"),
+    ("Troubleshooting", "Fehlersuche"),
+    ("From {}:", "Aus {}:"),
+    ("Compiling c++ project", "Kompiliere C++-Projekt"),
+    ("{} building c++ project in {}s", "{} C++-Projekt gebaut in {}s"),
+    ("No builds recorded yet", "Noch keine Builds aufgezeichnet"),
+    ("Date", "Datum"),
+    ("Profile", "Profil"),
+    ("Time", "Dauer"),
+    ("Warnings", "Warnungen"),
+    ("Size", "Größe"),
+    ("Trends", "Trends"),
+    ("{}: {} builds, time {}, size {}", "{}: {} Builds, Dauer {}, Größe {}"),
+    ("{} '{}' has {} warnings, {} more than the baseline", "{} '{}' hat {} Warnungen, {} mehr als die Baseline"),
+    ("Rebuilding project to count warnings", "Baue Projekt neu, um Warnungen zu zählen"),
+    ("{} recording {} warnings in {} files to {}", "{} {} Warnungen in {} Dateien in {} festgehalten"),
+    ("Installing project", "Installiere Projekt"),
+    ("{} installing project", "{} Projekt installiert"),
+    ("Installing", "Installiere"),
+    ("Tests in '{}' are built and run with 'cmm test'", "Tests in '{}' werden mit 'cmm test' gebaut und ausgeführt"),
+    ("Running tests", "Führe Tests aus"),
+    ("Choose the tests to run:", "Auszuführende Tests wählen:"),
+    ("Writing coverage report", "Schreibe Abdeckungsbericht"),
+    ("Wrote coverage report to '{}'", "Abdeckungsbericht nach '{}' geschrieben"),
+    ("{} reporting coverage", "{} Abdeckung ermittelt"),
+    ("No tests chosen", "Keine Tests gewählt"),
+    ("Rerunning {} failed tests, attempt {} of {}", "Wiederhole {} fehlgeschlagene Tests, Versuch {} von {}"),
+    ("{} flaky tests, passed on a retry: {}", "{} instabile Tests, bei Wiederholung bestanden: {}"),
+    ("Wrote test report to '{}'", "Testbericht nach '{}' geschrieben"),
+    ("{} running tests", "{} Tests ausgeführt"),
+    ("Packaging project", "Paketiere Projekt"),
+    ("{} packaging project into {}/{}", "{} Projekt paketiert nach {}/{}"),
+    ("Packaging", "Paketiere"),
+    ("Fetching dependencies", "Lade Abhängigkeiten"),
+    ("Fetching", "Lade"),
+    ("{} fetching dependencies and configuring in {}s", "{} Abhängigkeiten geladen und konfiguriert in {}s"),
+    ("\n\n{} program execution with exit code {}", "\n\n{} Programmausführung mit Exit-Code {}"),
+    ("{} program terminated by signal {}", "{} Programm durch Signal {} beendet"),
+    ("Rerun with '--backtrace' to capture a stack trace", "Mit '--backtrace' erneut ausführen, um einen Stacktrace zu erhalten"),
+    ("Choose a binary to run:", "Auszuführendes Programm wählen:"),
+    ("Capturing backtrace with gdb", "Erfasse Backtrace mit gdb"),
+    ("Sanitizer runtime: {}={}", "Sanitizer-Laufzeit: {}={}"),
+    ("'{}' already exists", "'{}' existiert bereits"),
+    ("{} '{}' already exists", "{} '{}' existiert bereits"),
+    ("{} '{}' is not a valid C++ identifier", "{} '{}' ist kein gültiger C++-Bezeichner"),
+    ("Added '{}' to cmake.files", "'{}' zu cmake.files hinzugefügt"),
+    ("{} generating class '{}'", "{} Klasse '{}' erzeugt"),
+    ("{} '{}' is outside the project", "{} '{}' liegt außerhalb des Projekts"),
+    ("Moved '{}' to '{}'", "'{}' nach '{}' verschoben"),
+    ("Updated includes in '{}'", "Includes in '{}' aktualisiert"),
+    ("Updated file lists in the config", "Dateilisten in der Konfiguration aktualisiert"),
+    ("'{}' is excluded from the build, but '{}' declares it", "'{}' ist vom Build ausgeschlossen, wird aber in '{}' deklariert"),
+    ("'{}' isn't matched by any source entry, so it isn't built", "'{}' passt zu keinem Quelleintrag und wird nicht gebaut"),
+    ("'{}' is matched by {} entries and compiled more than once: {}", "'{}' passt zu {} Einträgen und wird mehrfach kompiliert: {}"),
+    ("{} found {} problems in the project", "{} {} Probleme im Projekt gefunden"),
+    ("{} validating project, no problems found", "{} Projekt geprüft, keine Probleme gefunden"),
+    ("{} moving '{}', updated includes in {} files", "{} '{}' verschoben, Includes in {} Dateien aktualisiert"),
+    ("Created '{}'", "'{}' erstellt"),
+    ("{} scaffolding sanitizer suppressions, used by '{}'", "{} Sanitizer-Unterdrückungen angelegt, verwendet von '{}'"),
+    ("Watching for changes. Press Ctrl-C to stop.", "Warte auf Änderungen. Mit Strg-C beenden."),
+    ("No in-source cmake files found", "Keine In-Source-CMake-Dateien gefunden"),
+    ("Removed '{}'", "'{}' entfernt"),
+    ("{} failed to remove '{}' with error: {}", "{} '{}' konnte nicht entfernt werden: {}"),
+    ("{} removing in-source cmake files", "{} In-Source-CMake-Dateien entfernt"),
+    ("Cleaning build files", "Entferne Build-Dateien"),
+    (
+        "{} not removing folder '{}' as it contains a monorepo dependency",
+        "{} Ordner '{}' wird nicht entfernt, da er eine Monorepo-Abhängigkeit enthält",
+    ),
+    ("{} failed to remove folder '{}' with error: {}", "{} Ordner '{}' konnte nicht entfernt werden: {}"),
+    ("Nothing to remove, '{}' doesn't exist", "Nichts zu entfernen, '{}' existiert nicht"),
+    ("No build files found for target '{}'", "Keine Build-Dateien für Target '{}' gefunden"),
+    ("Cleaning CMake Files", "Entferne CMake-Dateien"),
+    (
+        "{} failed to remove file 'CMakeLists.txt' with error: {}",
+        "{} Datei 'CMakeLists.txt' konnte nicht entfernt werden: {}",
+    ),
+    ("{} removing build files", "{} Build-Dateien entfernt"),
+    ("Adding .ignore", "Füge .ignore hinzu"),
+    (".ignore file already exists", ".ignore-Datei existiert bereits"),
+    ("Creating .ignore file", "Erstelle .ignore-Datei"),
+    // Dependencies
+    ("Choose the Dependency Type:", "Art der Abhängigkeit wählen:"),
+    ("Pre-Cached", "Aus dem Cache"),
+    ("Git Submodule", "Git-Submodul"),
+    ("Find", "find_package"),
+    ("Local", "Lokal"),
+    ("Monorepo", "Monorepo"),
+    ("Any variables/flags", "Variablen/Flags"),
+    ("Any Variables/Flags", "Variablen/Flags"),
+    ("Add as project dependency?", "Als Projektabhängigkeit hinzufügen?"),
+    ("Dependency Name:", "Name der Abhängigkeit:"),
+    ("C++ standard:", "C++-Standard:"),
+    ("Dependency uses CMake?", "Verwendet die Abhängigkeit CMake?"),
+    ("Included files", "Eingebundene Dateien"),
+    ("All", "Alle"),
+    ("Root", "Wurzelordner"),
+    ("Header Only", "Nur Header"),
+    ("Custom", "Benutzerdefiniert"),
+    ("Library dependencies", "Abhängigkeiten der Bibliothek"),
+    ("Press enter or esc to proceed", "Enter oder Esc zum Fortfahren"),
+    ("Path:", "Pfad:"),
+    ("Choose a path relative to the project folder", "Pfad relativ zum Projektordner wählen"),
+    (
+        "Choose a folder elsewhere in this repository, relative to the project",
+        "Ordner an anderer Stelle in diesem Repository wählen, relativ zum Projekt",
+    ),
+    ("Fetch Git Repo:", "Git-Repository:"),
+    ("Git Tag (optional):", "Git-Tag (optional):"),
+    ("Git Branch (optional):", "Git-Branch (optional):"),
+    ("Save dependency to cache?", "Abhängigkeit im Cache speichern?"),
+    ("Switching to '{}' on branch '{}'", "Wechsle zu '{}' auf Branch '{}'"),
+    ("Switching to '{}'", "Wechsle zu '{}'"),
+    ("Switching to branch '{}'", "Wechsle zu Branch '{}'"),
+    ("No cached dependencies available.", "Keine Abhängigkeiten im Cache vorhanden."),
+    ("Dependency required?", "Abhängigkeit erforderlich?"),
+    ("Specify Dependency link name: (optional)", "Link-Name der Abhängigkeit: (optional)"),
+    ("Install prefix to search: (optional)", "Zu durchsuchender Installationspfad: (optional)"),
+    (
+        "For packages installed outside the system folders, eg. ~/opt/",
+        "Für Pakete, die außerhalb der Systemordner installiert sind, z.B. ~/opt/",
+    ),
+    ("{} find dependency '{}'", "{} find-Abhängigkeit '{}'"),
+    ("{} fetch dependency '{}'", "{} FetchContent-Abhängigkeit '{}'"),
+    ("{} conan dependency '{}'", "{} Conan-Abhängigkeit '{}'"),
+    ("{} cpm dependency '{}'", "{} CPM-Abhängigkeit '{}'"),
+    ("Add dependency as:", "Abhängigkeit hinzufügen als:"),
+    ("CPM Package", "CPM-Paket"),
+    ("{} '{}' is built from its sources, adding it as a submodule", "{} '{}' wird aus den Quellen gebaut, wird als Submodul hinzugefügt"),
+    ("Conan Reference:", "Conan-Referenz:"),
+    ("Expected NAME/VERSION", "Erwartet NAME/VERSION"),
+    ("find_package name: (optional)", "find_package-Name: (optional)"),
+    ("Installing Conan dependencies", "Installiere Conan-Abhängigkeiten"),
+    ("{} dependency '{}' from config", "{} Abhängigkeit '{}' aus der Konfiguration"),
+    ("Also remove submodule and delete '{}'?", "Submodul ebenfalls entfernen und '{}' löschen?"),
+    ("{} submodule '{}'", "{} Submodul '{}'"),
+    (
+        "{} {} can't be read: {}\nThe version from before cmm last changed it is in {}, copy it over {} to restore it",
+        "{} {} kann nicht gelesen werden: {}\nDie Version vor der letzten Änderung durch cmm liegt in {}, kopiere sie über {}, um sie wiederherzustellen",
+    ),
+    ("{} {} can't be read: {}", "{} {} kann nicht gelesen werden: {}"),
+    (
+        "Restore it from {}, from before cmm last changed it?",
+        "Aus {} wiederherstellen, der Version vor der letzten Änderung durch cmm?",
+    ),
+    ("{}.{} = {} is now the string \"{}\"", "{}.{} = {} ist jetzt der String \"{}\""),
+    (
+        "{} {} is from a newer cmm (config version {}, this one reads {}). Update cmm if settings are missing.",
+        "{} {} stammt von einem neueren cmm (Konfigurationsversion {}, dieses liest {}). Aktualisiere cmm, falls Einstellungen fehlen.",
+    ),
+    (
+        "{} is from an older cmm, it's upgraded when loaded:",
+        "{} stammt von einem älteren cmm und wird beim Laden aktualisiert:",
+    ),
+    (
+        "run 'cmm migrate config' to save the upgrade",
+        "führe 'cmm migrate config' aus, um die Aktualisierung zu speichern",
+    ),
+    ("Save the upgraded {}?", "Aktualisierte {} speichern?"),
+    ("{} is already at config version {}", "{} hat bereits Konfigurationsversion {}"),
+    ("{} to config version {}", "{} auf Konfigurationsversion {}"),
+    ("{}.{}: file '{}' doesn't exist", "{}.{}: Datei '{}' existiert nicht"),
+    ("{}.{}: folder '{}' doesn't exist", "{}.{}: Ordner '{}' existiert nicht"),
+    ("{}: folder '{}' doesn't exist", "{}: Ordner '{}' existiert nicht"),
+    ("cmake.source_dir: folder '{}' doesn't exist", "cmake.source_dir: Ordner '{}' existiert nicht"),
+    ("cmake.resources: folder '{}' doesn't exist", "cmake.resources: Ordner '{}' existiert nicht"),
+    (
+        "dependencies.prefix_paths: folder '{}' doesn't exist",
+        "dependencies.prefix_paths: Ordner '{}' existiert nicht",
+    ),
+    ("tests.path: folder '{}' doesn't exist", "tests.path: Ordner '{}' existiert nicht"),
+    (
+        "{}: '{}' isn't a valid cmake name, use letters, digits and _ . + -",
+        "{}: '{}' ist kein gültiger CMake-Name, erlaubt sind Buchstaben, Ziffern und _ . + -",
+    ),
+    (
+        "{}: the name is used by another local dependency",
+        "{}: der Name wird von einer anderen lokalen Abhängigkeit verwendet",
+    ),
+    (
+        "{}: the name is used by the project or another target",
+        "{}: der Name wird vom Projekt oder einem anderen Target verwendet",
+    ),
+    (
+        "{}: '{}' isn't a dependency or target in the config",
+        "{}: '{}' ist keine Abhängigkeit und kein Target in der Konfiguration",
+    ),
+    ("{} checking {}, no problems found", "{} Prüfung von {}, keine Probleme gefunden"),
+    (
+        "Updating changed dependency variables in the cmake cache: {}",
+        "Aktualisiere geänderte Abhängigkeitsvariablen im CMake-Cache: {}",
+    ),
+    (
+        "{} alias '{}' is ignored, there's already a command with that name",
+        "{} Alias '{}' wird ignoriert, es gibt bereits einen Befehl mit diesem Namen",
+    ),
+    ("ok", "ok"),
+    ("compiler", "Compiler"),
+    ("generators", "Generatoren"),
+    ("needs {}", "benötigt {}"),
+    ("no build tool found", "kein Build-Tool gefunden"),
+    ("Checking the tools cmm needs", "Prüfe die Werkzeuge, die cmm braucht"),
+    (
+        "{} checking tools, no problems found",
+        "{} Prüfung der Werkzeuge, keine Probleme gefunden",
+    ),
+    (
+        "{} found {} problems with the tools cmm needs",
+        "{} {} Probleme mit den Werkzeugen gefunden, die cmm braucht",
+    ),
+    ("Diagnostics by origin:", "Meldungen nach Herkunft:"),
+    ("project", "Projekt"),
+    ("dependencies", "Abhängigkeiten"),
+    ("{} warnings, {} errors", "{} Warnungen, {} Fehler"),
+    (
+        "{} warnings and {} errors from dependencies hidden, see {}",
+        "{} Warnungen und {} Fehler aus Abhängigkeiten ausgeblendet, siehe {}",
+    ),
+    (
+        "{} library project has nothing to run, list examples in '{}' or add tests with '{}'",
+        "{} Bibliotheksprojekt hat nichts zum Ausführen, trage Beispiele in '{}' ein oder füge Tests mit '{}' hinzu",
+    ),
+    (
+        "CMakeLists.txt doesn't exist yet and would be created",
+        "CMakeLists.txt existiert noch nicht und würde erstellt",
+    ),
+    (
+        "CMakeLists.txt is up to date, nothing would change",
+        "CMakeLists.txt ist aktuell, nichts würde sich ändern",
+    ),
+    (
+        "if '{}' was left by an earlier failed add, 'cmm repair' cleans it up",
+        "falls '{}' von einem früheren fehlgeschlagenen Hinzufügen stammt, räumt 'cmm repair' es auf",
+    ),
+    (
+        "{} half added submodule '{}'",
+        "{} halb hinzugefügtes Submodul '{}'",
+    ),
+    (
+        "Checking out {} dependencies that were never cloned",
+        "Checke {} nie geklonte Abhängigkeiten aus",
+    ),
+    (
+        "{} submodules have no config entry, 'cmm migrate gitmodules' can import or remove them",
+        "{} Submodule haben keinen Config-Eintrag, 'cmm migrate gitmodules' kann sie importieren oder entfernen",
+    ),
+    ("{} nothing to repair", "{} nichts zu reparieren"),
+    (
+        "{} repairing {} problems",
+        "{} Reparatur von {} Problemen",
+    ),
+    (
+        "{} no compiler cache is installed, only removing build files",
+        "{} kein Compiler-Cache installiert, es werden nur Build-Dateien entfernt",
+    ),
+    (
+        "Statistics of {} before cleaning:",
+        "Statistik von {} vor dem Aufräumen:",
+    ),
+    (
+        "{} not zeroing the statistics of a shared compiler cache, set 'cmake.cache_dir' to give ccache a folder for this project",
+        "{} die Statistik eines geteilten Compiler-Caches wird nicht zurückgesetzt, setze 'cmake.cache_dir', um ccache einen Ordner für dieses Projekt zu geben",
+    ),
+    ("Zeroed the statistics of {}", "Statistik von {} zurückgesetzt"),
+    (
+        "{} ignoring line {} of {}, it isn't NAME=value",
+        "{} Zeile {} von {} wird ignoriert, sie hat nicht die Form NAME=Wert",
+    ),
+    ("Link '{}' into:", "'{}' verlinken mit:"),
+    ("Unlink '{}' from:", "'{}' entfernen aus:"),
+    ("'{}' already links '{}'", "'{}' verlinkt '{}' bereits"),
+    ("'{}' doesn't link '{}'", "'{}' verlinkt '{}' nicht"),
+    ("{} '{}' into '{}'", "{} '{}' mit '{}'"),
+    ("{} '{}' from '{}'", "{} '{}' aus '{}'"),
+    ("{} nothing links '{}'", "{} nichts verlinkt '{}'"),
+    ("Linked", "Verlinkt"),
+    ("Unlinked", "Entfernt"),
+    ("Running hook", "Führe Hook aus"),
+    ("{} no answer given for prompt '{}'", "{} keine Antwort für die Eingabe '{}' angegeben"),
+    ("{} prompt '{}' failed with error: {}", "{} Eingabe '{}' fehlgeschlagen mit Fehler: {}"),
+    ("{} target '{}' can't link itself", "{} Ziel '{}' kann sich nicht selbst verlinken"),
+    ("Source folders", "Quellordner"),
+    ("Include folders", "Include-Ordner"),
+];
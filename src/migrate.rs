@@ -0,0 +1,173 @@
+use std::path::Path;
+
+use colored::Colorize;
+
+use crate::{
+    answers,
+    dependencies::add_local_dependency_path,
+    dependencies::remove_local,
+    error::{DisplayError, ProjectError},
+    git::{read_submodules, remove_submodule},
+    output,
+    util::{get_config, write_config},
+};
+
+/// Whether a dependency folder exists and has something checked out in it
+pub fn is_populated(path: &Path) -> bool {
+    match std::fs::read_dir(path) {
+        Ok(mut dir) => dir.next().is_some(),
+        Err(_) => false,
+    }
+}
+
+fn reclone_submodule(path: &str) -> Result<(), ProjectError> {
+    let output = output::run(output::command(
+        "git",
+        ["submodule", "update", "--init", "--recursive", path],
+    ))
+    .unwrap();
+
+    if !output.status.success() {
+        Err(ProjectError::FailedToRunProcess(
+            format!("git submodule update --init --recursive {}", path),
+            output.status.code(),
+        ))?;
+    }
+
+    Ok(())
+}
+
+/// `cmm migrate gitmodules`. Brings .gitmodules, external/ and the config back in line.
+pub fn migrate_gitmodules() -> Result<(), ProjectError> {
+    let mut config = get_config()?;
+    let submodules = read_submodules()?;
+
+    let mut issues = 0;
+
+    // .gitmodules entries missing from the config
+    for submodule in &submodules {
+        if config
+            .dependencies
+            .local
+            .iter()
+            .any(|local| local.path == submodule.path)
+        {
+            continue;
+        }
+
+        issues += 1;
+        println!(
+            "{} submodule '{}' ({}) has no config entry",
+            "warning:".yellow(),
+            submodule.path,
+            submodule.url.as_deref().unwrap_or("no url")
+        );
+
+        let choice = answers::select(
+            "migrate.submodule",
+            inquire::Select::new(
+                "How should this be fixed?",
+                vec![
+                    "Import into config", // 0
+                    "Remove submodule",   // 1
+                    "Skip",               // 2
+                ],
+            ),
+        )?;
+
+        match choice.index {
+            0 => {
+                if !is_populated(Path::new(&submodule.path)) {
+                    reclone_submodule(&submodule.path)?;
+                }
+                add_local_dependency_path(&mut config, submodule.path.clone())?;
+            }
+            1 => remove_submodule(submodule)?,
+            _ => {}
+        }
+    }
+
+    // Config entries whose folders are missing
+    let missing = config
+        .dependencies
+        .local
+        .iter()
+        .filter(|local| !is_populated(Path::new(&local.path)))
+        .map(|local| local.path.clone())
+        .collect::<Vec<_>>();
+
+    for path in missing {
+        issues += 1;
+        println!(
+            "{} dependency folder '{}' is missing or empty",
+            "warning:".yellow(),
+            path
+        );
+
+        let in_gitmodules = submodules.iter().any(|submodule| submodule.path == path);
+
+        let mut options = vec!["Remove config entry", "Skip"];
+        if in_gitmodules {
+            options.insert(0, "Re-clone submodule");
+        }
+
+        match answers::select(
+            "migrate.missing",
+            inquire::Select::new("How should this be fixed?", options),
+        )?
+        .value
+        {
+            "Re-clone submodule" => reclone_submodule(&path).display_error(),
+            "Remove config entry" => remove_local(&mut config, &path),
+            _ => {}
+        }
+    }
+
+    // Folders in external/ that nothing knows about
+    if let Ok(dir) = std::fs::read_dir("external") {
+        let untracked = dir
+            .filter_map(|entry| entry.ok())
+            .filter(|entry| entry.path().is_dir())
+            .map(|entry| format!("external/{}", entry.file_name().to_string_lossy()))
+            .filter(|path| {
+                !submodules.iter().any(|submodule| &submodule.path == path)
+                    && !config
+                        .dependencies
+                        .local
+                        .iter()
+                        .any(|local| &local.path == path)
+            })
+            .collect::<Vec<_>>();
+
+        for path in untracked {
+            issues += 1;
+            println!(
+                "{} folder '{}' is not a submodule or dependency",
+                "warning:".yellow(),
+                path
+            );
+
+            if answers::confirm(
+                "migrate.import",
+                inquire::Confirm::new("Import as a local dependency?")
+                    .with_default(false)
+                    .with_placeholder("y/N"),
+            )? {
+                add_local_dependency_path(&mut config, path)?;
+            }
+        }
+    }
+
+    write_config(config)?;
+
+    match issues {
+        0 => println!("{} submodules are in sync", "Finished".green().bold()),
+        _ => println!(
+            "{} reviewing {} submodule issue(s)",
+            "Finished".green().bold(),
+            issues
+        ),
+    }
+
+    Ok(())
+}
@@ -0,0 +1,197 @@
+use std::{fmt::Write, path::Path};
+
+use colored::Colorize;
+
+use crate::{config::ConfigFile, error::ProjectError, git::read_submodules, messages::t};
+
+pub const NOTICES_NAME: &str = "THIRD_PARTY_NOTICES.md";
+
+/// File names dependencies keep their license in, compared case insensitively
+const LICENSE_FILES: [&str; 5] = ["license", "licence", "copying", "unlicense", "notice"];
+
+struct Notice {
+    name: String,
+    tag: Option<String>,
+    commit: Option<String>,
+    repo: Option<String>,
+    license: Option<(String, String)>,
+}
+
+/// The first license file in a dependency's folder, as its file name and text
+fn find_license(dir: &Path) -> Option<(String, String)> {
+    let mut files = std::fs::read_dir(dir)
+        .ok()?
+        .filter_map(|entry| entry.ok())
+        .filter(|entry| entry.path().is_file())
+        .map(|entry| entry.file_name().to_string_lossy().into_owned())
+        .filter(|name| {
+            let stem = name.split('.').next().unwrap_or_default().to_lowercase();
+            LICENSE_FILES.contains(&stem.as_str())
+        })
+        .collect::<Vec<_>>();
+
+    // LICENSE before NOTICE, and the same file every time
+    files.sort_by_key(|name| {
+        let stem = name.split('.').next().unwrap_or_default().to_lowercase();
+        let rank = LICENSE_FILES.iter().position(|file| *file == stem);
+        (rank, name.clone())
+    });
+
+    files.into_iter().find_map(|name| {
+        let text = std::fs::read_to_string(dir.join(&name)).ok()?;
+        Some((name, text))
+    })
+}
+
+/// Phrases identifying common licenses, checked in order so the more specific come first
+const KNOWN_LICENSES: [(&str, &[&str]); 11] = [
+    ("Apache-2.0", &["Apache License", "Version 2.0"]),
+    ("BSL-1.0", &["Boost Software License"]),
+    ("LGPL", &["GNU LESSER GENERAL PUBLIC LICENSE"]),
+    ("GPL-3.0", &["GNU GENERAL PUBLIC LICENSE", "Version 3"]),
+    ("GPL-2.0", &["GNU GENERAL PUBLIC LICENSE", "Version 2"]),
+    ("MPL-2.0", &["Mozilla Public License", "2.0"]),
+    ("Unlicense", &["This is free and unencumbered software"]),
+    ("MIT", &["Permission is hereby granted, free of charge"]),
+    ("Zlib", &["This software is provided 'as-is'"]),
+    (
+        "BSD-3-Clause",
+        &[
+            "Redistribution and use in source and binary forms",
+            "Neither the name",
+        ],
+    ),
+    (
+        "BSD-2-Clause",
+        &["Redistribution and use in source and binary forms"],
+    ),
+];
+
+/// A best guess at the SPDX identifier of a license text
+fn identify_license(text: &str) -> Option<&'static str> {
+    // License texts wrap lines at different places
+    let text = text.split_whitespace().collect::<Vec<_>>().join(" ");
+
+    KNOWN_LICENSES
+        .iter()
+        .find(|(_, phrases)| phrases.iter().all(|phrase| text.contains(phrase)))
+        .map(|(license, _)| *license)
+}
+
+fn checked_out_commit(dir: &Path) -> Option<String> {
+    let repo = git2::Repository::open(dir).ok()?;
+    let commit = repo.head().ok()?.peel_to_commit().ok()?;
+    Some(commit.id().to_string())
+}
+
+/// Every dependency checked out in external/, with the tag from the config when it has one
+fn collect_notices(config: &ConfigFile) -> Vec<Notice> {
+    let repos = read_submodules().unwrap_or_default();
+
+    let mut dirs = std::fs::read_dir("external")
+        .into_iter()
+        .flatten()
+        .filter_map(|entry| entry.ok())
+        .filter(|entry| entry.path().is_dir())
+        .map(|entry| format!("external/{}", entry.file_name().to_string_lossy()))
+        .collect::<Vec<_>>();
+    dirs.sort();
+
+    dirs.into_iter()
+        .map(|dir| {
+            let local = config
+                .dependencies
+                .local
+                .iter()
+                .find(|local| local.path.trim_end_matches('/') == dir);
+
+            Notice {
+                name: local.map_or_else(
+                    || dir.trim_start_matches("external/").to_owned(),
+                    |local| local.name.clone(),
+                ),
+                tag: local.and_then(|local| local.tag.clone()),
+                commit: checked_out_commit(Path::new(&dir)),
+                repo: repos
+                    .iter()
+                    .find(|submodule| submodule.path == dir)
+                    .and_then(|submodule| submodule.url.clone()),
+                license: find_license(Path::new(&dir)),
+            }
+        })
+        .collect()
+}
+
+fn render(notices: &[Notice]) -> String {
+    let mut file = String::from("# Third-Party Notices\n\n");
+    file += "This project includes the following third-party software. Generated by cmakemake,\n";
+    file += "run 'cmm deps licenses-header' to refresh it.\n";
+
+    for notice in notices {
+        let version = match (&notice.tag, &notice.commit) {
+            (Some(tag), Some(commit)) => format!("{} (commit {})", tag, commit),
+            (Some(tag), None) => tag.clone(),
+            (None, Some(commit)) => format!("commit {}", commit),
+            (None, None) => "unknown".into(),
+        };
+
+        write!(file, "\n## {}\n\n- Version: {}\n", notice.name, version).unwrap();
+
+        if let Some(repo) = &notice.repo {
+            writeln!(file, "- Repository: {}", repo).unwrap();
+        }
+
+        match &notice.license {
+            Some((name, text)) => {
+                let license = identify_license(text).unwrap_or("see below");
+                writeln!(file, "- License: {} ({})\n", license, name).unwrap();
+                writeln!(file, "```text\n{}\n```", text.trim_end()).unwrap();
+            }
+            None => writeln!(file, "- License: not found").unwrap(),
+        }
+    }
+
+    file
+}
+
+/// `cmm deps licenses-header`. Writes THIRD_PARTY_NOTICES.md with the name, version and
+/// license text of every dependency in external/, for products that ship attribution.
+/// Dependencies CMake or Conan download aren't in external/ and aren't listed.
+pub fn write_notices(config: &ConfigFile) -> Result<(), ProjectError> {
+    let notices = collect_notices(config);
+
+    for notice in notices.iter().filter(|notice| notice.license.is_none()) {
+        println!(
+            "{}",
+            t!(
+                "{} no license file found for dependency '{}'",
+                t!("warning:").yellow(),
+                notice.name
+            )
+        );
+    }
+
+    let path = Path::new(NOTICES_NAME);
+    std::fs::write(path, render(&notices))
+        .map_err(|err| ProjectError::FailedToCreateFile(path.to_owned(), err.to_string()))?;
+
+    println!(
+        "{}",
+        t!(
+            "{} writing notices for {} dependencies to {}",
+            t!("Finished").green().bold(),
+            notices.len(),
+            NOTICES_NAME
+        )
+    );
+
+    Ok(())
+}
+
+/// Rewrites the notices after dependencies change, for projects that have them
+pub fn refresh_notices(config: &ConfigFile) -> Result<(), ProjectError> {
+    match Path::new(NOTICES_NAME).exists() {
+        true => write_notices(config),
+        false => Ok(()),
+    }
+}
@@ -0,0 +1,105 @@
+use std::io::IsTerminal;
+
+use colored::Colorize;
+
+use crate::{
+    answers,
+    config::GlobalConfig,
+    error::ProjectError,
+    generator,
+    util::{global_config_path, write_global_config},
+};
+
+pub const STANDARDS: [&str; 6] = ["None", "11", "14", "17", "20", "23"];
+const LICENSES: [&str; 5] = ["None", "MIT", "Apache-2.0", "GPL-3.0", "BSD-3-Clause"];
+
+/// Runs the first run setup if no global config has been written yet.
+/// Skipped when not attached to a terminal so scripts never block on prompts.
+pub fn first_run() -> Result<(), ProjectError> {
+    if global_config_path().exists() || !std::io::stdin().is_terminal() {
+        return Ok(());
+    }
+
+    println!("{}", "Welcome to cmakemake!".green().bold());
+    println!("Answer a few questions to set your defaults for new projects.\n");
+
+    setup()?;
+
+    println!("\n{}", "Some things to try:".green().bold());
+    println!("\t{}\t\tcreate a new project", "cmm new <NAME>".cyan());
+    println!("\t{}\t\t\tbuild and run it", "cmm run".cyan());
+    println!("\t{}\t\tsee every command", "cmm help".cyan());
+    println!(
+        "Run '{}' at any time to change these defaults\n",
+        "cmm setup".cyan()
+    );
+
+    Ok(())
+}
+
+pub fn setup() -> Result<(), ProjectError> {
+    let author = answers::text_skippable(
+        "setup.author",
+        inquire::Text::new("Author name (optional):"),
+    )?
+    .and_then(|val| match val.is_empty() {
+        true => None,
+        false => Some(val),
+    });
+
+    let cxx_standard = answers::select(
+        "setup.cxx_standard",
+        inquire::Select::new("Preferred C++ standard:", STANDARDS.to_vec()).with_starting_cursor(3),
+    )?
+    .value
+    .parse()
+    .ok();
+
+    // Only offer generators that can actually be used on this machine
+    let mut generators = vec!["Default"];
+    generators.extend(generator::installed());
+
+    let generator = match answers::select(
+        "setup.generator",
+        inquire::Select::new("Preferred CMake generator:", generators),
+    )?
+    .value
+    {
+        "Default" => None,
+        other => Some(other.to_owned()),
+    };
+
+    let license = match answers::select(
+        "setup.license",
+        inquire::Select::new("Preferred license:", LICENSES.to_vec()),
+    )?
+    .value
+    {
+        "None" => None,
+        other => Some(other.to_owned()),
+    };
+
+    let use_trash = answers::confirm(
+        "setup.trash",
+        inquire::Confirm::new("Move removed files to the trash instead of deleting them?")
+            .with_default(false)
+            .with_placeholder("y/N"),
+    )?;
+
+    write_global_config(&GlobalConfig {
+        author,
+        cxx_standard,
+        generator,
+        license,
+        use_trash,
+        ..GlobalConfig::default()
+    })?;
+
+    println!(
+        "{} writing defaults to {}",
+        "Finished".green().bold(),
+        global_config_path().display()
+    );
+
+    Ok(())
+}
@@ -0,0 +1,186 @@
+use std::{
+    ffi::OsStr,
+    io::Write,
+    sync::{
+        atomic::{AtomicU8, Ordering},
+        Mutex,
+    },
+};
+
+use colored::Colorize;
+
+use crate::error::ProjectError;
+
+/// How much cmm and the tools it runs print
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Verbosity {
+    /// `-q`. No progress, and tool output only when the tool fails.
+    Quiet,
+    Normal,
+    /// `-v`. Also shows each command run.
+    Verbose,
+    /// `-vv`. Also asks cmake and the build tool to say everything they do.
+    Trace,
+}
+
+impl Verbosity {
+    pub fn from_flags(quiet: bool, verbose: u8) -> Self {
+        match (quiet, verbose) {
+            (true, _) => Self::Quiet,
+            (false, 0) => Self::Normal,
+            (false, 1) => Self::Verbose,
+            (false, _) => Self::Trace,
+        }
+    }
+}
+
+static VERBOSITY: AtomicU8 = AtomicU8::new(Verbosity::Normal as u8);
+
+pub fn set_verbosity(verbosity: Verbosity) {
+    VERBOSITY.store(verbosity as u8, Ordering::Relaxed);
+}
+
+pub fn verbosity() -> Verbosity {
+    match VERBOSITY.load(Ordering::Relaxed) {
+        0 => Verbosity::Quiet,
+        1 => Verbosity::Normal,
+        2 => Verbosity::Verbose,
+        _ => Verbosity::Trace,
+    }
+}
+
+/// Hides progress messages, leaving warnings, errors and what commands are asked for
+pub fn is_quiet() -> bool {
+    verbosity() == Verbosity::Quiet || is_json()
+}
+
+/// Fields of the object `--output json` prints when the command finishes. `None` when
+/// printing for people.
+static JSON: Mutex<Option<serde_json::Map<String, serde_json::Value>>> = Mutex::new(None);
+
+/// Switches to `--output json`: the command's results are collected into one object printed
+/// at the end, progress and tool output are left out and colours are turned off
+pub fn set_json(json: bool) {
+    *JSON.lock().unwrap() = json.then(serde_json::Map::new);
+
+    if json {
+        colored::control::set_override(false);
+    }
+}
+
+pub fn is_json() -> bool {
+    JSON.lock().unwrap().is_some()
+}
+
+/// Sets a field of the `--output json` object
+pub fn report(key: &str, value: impl serde::Serialize) {
+    if let Some(fields) = JSON.lock().unwrap().as_mut() {
+        fields.insert(key.to_owned(), serde_json::to_value(value).unwrap());
+    }
+}
+
+/// Adds to a list in the `--output json` object, eg. one entry per workspace member built
+pub fn report_item(key: &str, value: impl serde::Serialize) {
+    if let Some(fields) = JSON.lock().unwrap().as_mut() {
+        let list = fields
+            .entry(key)
+            .or_insert_with(|| serde_json::Value::Array(Vec::new()));
+
+        if let serde_json::Value::Array(list) = list {
+            list.push(serde_json::to_value(value).unwrap());
+        }
+    }
+}
+
+/// Prints the `--output json` object on one line, with whether the command succeeded and
+/// how it failed. Anything else the command printed comes before it.
+pub fn finish_json(result: &Result<(), ProjectError>) {
+    let Some(mut fields) = JSON.lock().unwrap().as_mut().map(std::mem::take) else {
+        return;
+    };
+
+    fields.insert("success".into(), result.is_ok().into());
+    if let Err(err) = result {
+        fields.insert("error".into(), err.to_string().into());
+        fields.insert("exit_code".into(), err.exit_code().into());
+    }
+
+    println!("{}", serde_json::Value::Object(fields));
+}
+
+/// Shows tool output that was held back because the tool failed. With `--output json` it
+/// goes to stderr to keep stdout for the result.
+pub fn show_held_back(output: &[u8]) {
+    match is_json() {
+        true => std::io::stderr().write_all(output).ok(),
+        false => std::io::stdout().write_all(output).ok(),
+    };
+}
+
+/// `println!` for progress messages like "Building Project", which `--quiet` hides
+#[macro_export]
+macro_rules! status {
+    ($($arg:tt)*) => {
+        if !$crate::output::is_quiet() {
+            println!($($arg)*)
+        }
+    };
+}
+
+pub use crate::status;
+
+/// `println!` for warnings and notes, which `--quiet` keeps. With `--output json` they go
+/// to stderr so stdout is only the result.
+#[macro_export]
+macro_rules! notice {
+    ($($arg:tt)*) => {
+        match $crate::output::is_json() {
+            true => eprintln!($($arg)*),
+            false => println!($($arg)*),
+        }
+    };
+}
+
+pub use crate::notice;
+
+/// Starts a command for a tool cmm runs, like cmake, git or conan, showing it with `-v`.
+/// Goes to stderr like a shell's `set -x`, so it doesn't mix with what commands print.
+pub fn command<I>(program: &str, args: I) -> duct::Expression
+where
+    I: IntoIterator,
+    I::Item: AsRef<OsStr>,
+{
+    let args = args
+        .into_iter()
+        .map(|arg| arg.as_ref().to_owned())
+        .collect::<Vec<_>>();
+
+    if verbosity() >= Verbosity::Verbose {
+        let line = args.iter().fold(program.to_owned(), |line, arg| {
+            format!("{} {}", line, arg.to_string_lossy())
+        });
+        eprintln!("{} {}", "$".dimmed(), line.dimmed());
+    }
+
+    duct::cmd(program, args)
+}
+
+/// Runs a tool with its output going to the terminal, or with `--quiet` kept back and only
+/// shown if the tool fails
+pub fn run(expression: duct::Expression) -> std::io::Result<std::process::Output> {
+    if !is_quiet() {
+        return expression.stderr_to_stdout().unchecked().run();
+    }
+
+    let output = expression
+        .stderr_to_stdout()
+        .stdout_capture()
+        .unchecked()
+        .run()?;
+
+    if !output.status.success() {
+        show_held_back(&output.stdout);
+    }
+
+    Ok(output)
+}
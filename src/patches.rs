@@ -0,0 +1,159 @@
+use std::path::{Path, PathBuf};
+
+use colored::Colorize;
+
+use crate::{config::ConfigFile, error::ProjectError, output::notice};
+
+const PATCHES_DIR: &str = "patches";
+
+/// Patch files for a dependency, in the order they are applied
+pub fn patches_for(name: &str) -> Vec<PathBuf> {
+    let dir = Path::new(PATCHES_DIR).join(name);
+
+    let mut patches = match std::fs::read_dir(&dir) {
+        Ok(dir) => dir
+            .filter_map(|entry| entry.ok())
+            .map(|entry| entry.path())
+            .filter(|path| path.extension().is_some_and(|ext| ext == "patch"))
+            .collect::<Vec<_>>(),
+        Err(_) => Vec::new(),
+    };
+
+    patches.sort();
+    patches
+}
+
+fn git_apply(dep_path: &str, patch: &Path, args: &[&str]) -> Result<bool, ProjectError> {
+    let patch = patch
+        .canonicalize()
+        .map_err(|err| ProjectError::CannotOpenFile(patch.to_owned(), err.to_string()))?;
+
+    let output = duct::cmd("git", args.iter().copied().chain([patch.to_str().unwrap()]))
+        .dir(dep_path)
+        .stdout_capture()
+        .stderr_capture()
+        .unchecked()
+        .run()
+        .map_err(|_| ProjectError::MissingProgram("git".into()))?;
+
+    Ok(output.status.success())
+}
+
+/// Runs git in a dependency folder with a scratch index instead of the real one, returning
+/// what it printed
+fn git_with_index(dep_path: &str, index: &Path, args: &[&str]) -> Option<String> {
+    let output = duct::cmd("git", args)
+        .dir(dep_path)
+        .env("GIT_INDEX_FILE", index)
+        .stdout_capture()
+        .stderr_null()
+        .unchecked()
+        .run()
+        .ok()?;
+
+    output
+        .status
+        .success()
+        .then(|| String::from_utf8_lossy(&output.stdout).trim().to_owned())
+}
+
+/// Whether a dependency's patches are all its checkout's changes, so they don't count as
+/// edits. The checkout is compared with its commit plus the patches, both written through
+/// a scratch index so the real index and files are left alone.
+pub fn only_patched(name: &str, dep_path: &str) -> bool {
+    let Ok(patches) = patches_for(name)
+        .iter()
+        .map(|patch| patch.canonicalize())
+        .collect::<Result<Vec<_>, _>>()
+    else {
+        return false;
+    };
+
+    if patches.is_empty() {
+        return false;
+    }
+
+    let index = std::env::temp_dir().join(format!(
+        "cmm-{}-{}.index",
+        std::process::id(),
+        name.replace(['/', '\\'], "_")
+    ));
+    let tree = |steps: &[Vec<&str>]| {
+        steps
+            .iter()
+            .try_for_each(|args| git_with_index(dep_path, &index, args).map(|_| ()))
+            .and_then(|_| git_with_index(dep_path, &index, &["write-tree"]))
+    };
+
+    let checkout = tree(&[vec!["read-tree", "HEAD"], vec!["add", "--update", "."]]);
+    let patched = tree(
+        &std::iter::once(vec!["read-tree", "HEAD"])
+            .chain(
+                patches
+                    .iter()
+                    .map(|patch| vec!["apply", "--cached", patch.to_str().unwrap()]),
+            )
+            .collect::<Vec<_>>(),
+    );
+
+    std::fs::remove_file(&index).ok();
+    checkout.is_some() && checkout == patched
+}
+
+/// Applies every patch for a dependency. Patches that are already applied are skipped
+/// so this can safely be run again after each clone or update.
+pub fn apply_patches(name: &str, dep_path: &str) -> Result<(), ProjectError> {
+    for patch in patches_for(name) {
+        let patch_name = patch.file_name().unwrap().to_string_lossy().to_string();
+
+        if git_apply(dep_path, &patch, &["apply", "--reverse", "--check"])? {
+            continue;
+        }
+
+        if !git_apply(dep_path, &patch, &["apply"])? {
+            return Err(ProjectError::FailedToApplyPatch(
+                name.to_owned(),
+                patch_name,
+            ));
+        }
+
+        notice!("{} patch '{}' to {}", "Applied".green(), patch_name, name);
+    }
+
+    Ok(())
+}
+
+/// Takes a dependency's applied patches back out, newest first, so a clean checkout is left
+fn reverse_patches(name: &str, dep_path: &str) -> Result<(), ProjectError> {
+    for patch in patches_for(name).iter().rev() {
+        if git_apply(dep_path, patch, &["apply", "--reverse", "--check"])? {
+            git_apply(dep_path, patch, &["apply", "--reverse"])?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Runs `checkout` on a dependency with its patches taken out first, then applies them
+/// again on whatever is checked out afterwards. Returns what `checkout` did.
+pub fn checkout_patched(
+    name: &str,
+    dep_path: &str,
+    checkout: impl FnOnce() -> bool,
+) -> Result<bool, ProjectError> {
+    reverse_patches(name, dep_path)?;
+    let checked_out = checkout();
+    apply_patches(name, dep_path)?;
+
+    Ok(checked_out)
+}
+
+/// Applies patches for every configured local dependency that has any
+pub fn apply_all_patches(config: &ConfigFile) -> Result<(), ProjectError> {
+    config
+        .dependencies
+        .local
+        .iter()
+        .filter(|local| Path::new(&local.path).exists())
+        .try_for_each(|local| apply_patches(&local.name, &local.path))
+}
@@ -0,0 +1,256 @@
+use std::path::Path;
+
+use colored::Colorize;
+
+use crate::{
+    config::{ConfigFile, ProjectFiles, SourceType},
+    error::ProjectError,
+    generate::join_path,
+    messages::t,
+    util::find_sources,
+};
+
+/// A path given on the command line, relative to the project folder with `.` and `..`
+/// resolved
+fn project_path(path: &str) -> Result<String, ProjectError> {
+    let outside = || ProjectError::PathOutsideProject(path.into());
+
+    let relative = match Path::new(path).is_absolute() {
+        true => {
+            let root = std::env::current_dir().map_err(|_| outside())?;
+            Path::new(path)
+                .strip_prefix(root)
+                .map_err(|_| outside())?
+                .to_string_lossy()
+                .replace('\\', "/")
+        }
+        false => path.replace('\\', "/"),
+    };
+
+    let normalized = join_path(".", &relative);
+    match normalized == "." || normalized == ".." || normalized.starts_with("../") {
+        true => Err(outside()),
+        false => Ok(normalized),
+    }
+}
+
+fn parent(path: &str) -> &str {
+    path.rsplit_once('/').map_or(".", |(dir, _)| dir)
+}
+
+/// `to` relative to the folder `from_dir`, eg. `../render/mesh.hpp`
+fn relative_path(from_dir: &str, to: &str) -> String {
+    let parts = |path: &str| {
+        path.split('/')
+            .filter(|part| !part.is_empty() && *part != ".")
+            .map(str::to_owned)
+            .collect::<Vec<_>>()
+    };
+
+    let from = parts(from_dir);
+    let to = parts(to);
+    let common = from.iter().zip(&to).take_while(|(a, b)| a == b).count();
+
+    std::iter::repeat_n("..".to_owned(), from.len() - common)
+        .chain(to[common..].iter().cloned())
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+/// Points entries in `files` naming `from` at `to`. Returns whether any changed.
+fn update_files(files: &mut ProjectFiles, base: &str, from: &str, to: &str) -> bool {
+    let relative = relative_path(base, to);
+    let mut changed = false;
+
+    let named = files
+        .source_files
+        .iter_mut()
+        .filter(|(source_type, _)| matches!(source_type, SourceType::File))
+        .flat_map(|(_, names)| names.iter_mut())
+        .chain(files.exclude_files.iter_mut());
+
+    for name in named {
+        if join_path(base, name) == from {
+            *name = relative.clone();
+            changed = true;
+        }
+    }
+
+    changed
+}
+
+/// Every folder the project and its targets search for headers
+fn include_dirs(config: &ConfigFile) -> Vec<String> {
+    let project = (&config.cmake.source_dir, &config.cmake.files);
+    let targets = config
+        .targets
+        .iter()
+        .map(|target| (&target.path, &target.files));
+
+    std::iter::once(project)
+        .chain(targets)
+        .flat_map(|(base, files)| {
+            files
+                .include_dirs
+                .iter()
+                .flat_map(|(_, dirs)| dirs)
+                .map(move |dir| join_path(base, dir))
+        })
+        .collect()
+}
+
+/// The path `#include`d by `line`, and whether it uses quotes rather than angle brackets
+fn included(line: &str) -> Option<(&str, bool)> {
+    let rest = line.trim_start().strip_prefix('#')?;
+    let rest = rest.trim_start().strip_prefix("include")?.trim_start();
+
+    let (close, quoted) = match rest.chars().next()? {
+        '"' => ('"', true),
+        '<' => ('>', false),
+        _ => return None,
+    };
+
+    let path = rest[1..].split(close).next()?;
+    Some((path, quoted))
+}
+
+/// Rewrites the includes in `contents`, of a file moving from `file_from` to `file_to`,
+/// so they still find their headers after `from` moves to `to`. Spellings relative to an
+/// include folder stay relative to one. Returns None when nothing changed.
+fn update_includes(
+    contents: &str,
+    (file_from, file_to): (&str, &str),
+    (from, to): (&str, &str),
+    include_dirs: &[String],
+) -> Option<String> {
+    let resolves_in_include_dir = |path: &str| {
+        include_dirs
+            .iter()
+            .any(|dir| Path::new(&join_path(dir, path)).is_file())
+    };
+
+    // Where `to` can be included from without a relative path
+    let to_spelling = include_dirs
+        .iter()
+        .find_map(|dir| to.strip_prefix(&format!("{dir}/")));
+
+    let mut changed = false;
+
+    let lines = contents.split_inclusive('\n').map(|line| {
+        let Some((path, quoted)) = included(line) else {
+            return line.to_owned();
+        };
+
+        let relative_target = join_path(parent(file_from), path);
+        let next_to_file = quoted && relative_target == from;
+        let in_include_dir = include_dirs.iter().any(|dir| join_path(dir, path) == from);
+
+        let replacement = if next_to_file {
+            relative_path(parent(file_to), to)
+        } else if in_include_dir {
+            to_spelling
+                .map(str::to_owned)
+                .unwrap_or_else(|| relative_path(parent(file_to), to))
+        } else if file_from != file_to
+            && quoted
+            && Path::new(&relative_target).is_file()
+            && !resolves_in_include_dir(path)
+        {
+            // The moved file's own includes of its old neighbours
+            relative_path(parent(file_to), &relative_target)
+        } else {
+            return line.to_owned();
+        };
+
+        if replacement == path {
+            return line.to_owned();
+        }
+
+        changed = true;
+        line.replacen(path, &replacement, 1)
+    });
+
+    let updated = lines.collect::<String>();
+    changed.then_some(updated)
+}
+
+/// `cmm mv <FROM> <TO>`. Moves a project file, points config entries naming it at the new
+/// path and rewrites includes of it in the project's own code. Includes are matched as
+/// text, so ones built by macros aren't found. The caller regenerates CMakeLists.txt.
+pub fn move_file(config: &mut ConfigFile, from: &str, to: &str) -> Result<(), ProjectError> {
+    let from = project_path(from)?;
+    let to = project_path(to)?;
+
+    std::fs::metadata(&from)
+        .map_err(|err| ProjectError::CannotOpenFile(from.clone().into(), err.to_string()))?;
+
+    if Path::new(&to).exists() {
+        return Err(ProjectError::FileAlreadyExists(to.into()));
+    }
+
+    // Work out every edit before moving anything, while includes still resolve
+    let include_dirs = include_dirs(config);
+    let mut files = Vec::new();
+    find_sources(Path::new("."), &mut files);
+
+    let mut edits = Vec::new();
+    for file in &files {
+        let Ok(contents) = std::fs::read_to_string(file) else {
+            continue;
+        };
+
+        let moved_to = match *file == from {
+            true => to.as_str(),
+            false => file.as_str(),
+        };
+
+        if let Some(updated) =
+            update_includes(&contents, (file, moved_to), (&from, &to), &include_dirs)
+        {
+            edits.push((moved_to.to_owned(), updated));
+        }
+    }
+
+    if let Some(parent) = Path::new(&to)
+        .parent()
+        .filter(|parent| !parent.as_os_str().is_empty())
+    {
+        std::fs::create_dir_all(parent).map_err(|err| {
+            ProjectError::FailedToCreateFolder(parent.to_owned(), err.to_string())
+        })?;
+    }
+
+    std::fs::rename(&from, &to)
+        .map_err(|err| ProjectError::FailedToCreateFile(to.clone().into(), err.to_string()))?;
+
+    println!("{}", t!("Moved '{}' to '{}'", from, to));
+
+    for (file, contents) in &edits {
+        std::fs::write(file, contents)
+            .map_err(|err| ProjectError::FailedToCreateFile(file.into(), err.to_string()))?;
+
+        println!("{}", t!("Updated includes in '{}'", file));
+    }
+
+    let source_dir = config.cmake.source_dir.clone();
+    let mut config_changed = update_files(&mut config.cmake.files, &source_dir, &from, &to);
+    for target in &mut config.targets {
+        config_changed |= update_files(&mut target.files, &target.path, &from, &to);
+    }
+
+    if config_changed {
+        println!("{}", t!("Updated file lists in the config"));
+    }
+
+    println!(
+        "{}",
+        t!(
+            "{} moving '{}', updated includes in {} files",
+            t!("Finished").green().bold(),
+            from,
+            edits.len()
+        )
+    );
+
+    Ok(())
+}
@@ -0,0 +1,112 @@
+use std::path::Path;
+
+use colored::Colorize;
+
+use cmakemake_core::{
+    error::ProjectError,
+    git::{discard_submodule, init_submodules, read_submodules, stale_module_caches},
+    messages::t,
+    util::get_config,
+};
+
+use crate::migrate::is_populated;
+
+/// `cmm repair`. Cleans up what interrupted `cmm add git` runs left behind: submodules
+/// that were registered but never checked out, empty folders in external/ and cached repos
+/// that stop a submodule being added again. Dependencies whose submodule was never checked
+/// out are cloned again. Submodules with files in them are left for `cmm migrate
+/// gitmodules`, which asks what to do with each.
+pub fn repair_project() -> Result<(), ProjectError> {
+    let config = get_config()?;
+    let submodules = read_submodules()?;
+
+    let is_dependency = |path: &str| {
+        config
+            .dependencies
+            .local
+            .iter()
+            .any(|local| local.path == path)
+    };
+
+    let mut partial = submodules
+        .iter()
+        .filter(|submodule| {
+            !is_dependency(&submodule.path) && !is_populated(Path::new(&submodule.path))
+        })
+        .map(|submodule| submodule.path.clone())
+        .collect::<Vec<_>>();
+
+    if let Ok(dir) = std::fs::read_dir("external") {
+        partial.extend(
+            dir.filter_map(|entry| entry.ok())
+                .filter(|entry| entry.path().is_dir() && !is_populated(&entry.path()))
+                .map(|entry| format!("external/{}", entry.file_name().to_string_lossy()))
+                .filter(|path| !is_dependency(path)),
+        );
+    }
+
+    partial.extend(stale_module_caches()?);
+    partial.sort();
+    partial.dedup();
+
+    for path in &partial {
+        discard_submodule(path)?;
+        println!(
+            "{}",
+            t!("{} half added submodule '{}'", t!("Removed").green(), path)
+        );
+    }
+
+    let missing = submodules
+        .iter()
+        .filter(|submodule| {
+            is_dependency(&submodule.path) && !is_populated(Path::new(&submodule.path))
+        })
+        .count();
+
+    if missing > 0 {
+        println!(
+            "{}",
+            t!(
+                "Checking out {} dependencies that were never cloned",
+                missing
+            )
+        );
+        init_submodules()?;
+    }
+
+    let unknown = submodules
+        .iter()
+        .filter(|submodule| {
+            !is_dependency(&submodule.path) && is_populated(Path::new(&submodule.path))
+        })
+        .count();
+
+    if unknown > 0 {
+        println!(
+            "{} {}",
+            t!("hint:").cyan(),
+            t!(
+                "{} submodules have no config entry, 'cmm migrate gitmodules' can import or remove them",
+                unknown
+            )
+        );
+    }
+
+    match partial.len() + missing {
+        0 => println!(
+            "{}",
+            t!("{} nothing to repair", t!("Finished").green().bold())
+        ),
+        fixed => println!(
+            "{}",
+            t!(
+                "{} repairing {} problems",
+                t!("Finished").green().bold(),
+                fixed
+            )
+        ),
+    }
+
+    Ok(())
+}
@@ -0,0 +1,139 @@
+use std::{cmp::Ordering, collections::BTreeMap};
+
+use colored::Colorize;
+
+use crate::{error::ProjectError, output::notice};
+
+/// A dotted version number such as `3.28.1`
+#[derive(Clone, PartialEq, Eq)]
+pub struct Version(Vec<u64>);
+
+impl Version {
+    pub fn parse(input: &str) -> Option<Self> {
+        let parts = input
+            .trim()
+            .split('.')
+            .map(|part| part.parse().ok())
+            .collect::<Option<Vec<u64>>>()?;
+
+        match parts.is_empty() {
+            true => None,
+            false => Some(Self(parts)),
+        }
+    }
+
+    /// Finds the first version number in a program's `--version` output
+    pub fn find_in(output: &str) -> Option<Self> {
+        output
+            .split(|c: char| !(c.is_ascii_digit() || c == '.'))
+            .map(|token| token.trim_matches('.'))
+            .filter(|token| token.contains('.') || !token.is_empty())
+            .find_map(Self::parse)
+    }
+}
+
+impl Ord for Version {
+    fn cmp(&self, other: &Self) -> Ordering {
+        let len = self.0.len().max(other.0.len());
+        let part = |version: &Version, i: usize| version.0.get(i).copied().unwrap_or(0);
+
+        (0..len)
+            .map(|i| part(self, i).cmp(&part(other, i)))
+            .find(|ordering| ordering.is_ne())
+            .unwrap_or(Ordering::Equal)
+    }
+}
+
+impl PartialOrd for Version {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl std::fmt::Display for Version {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let parts = self.0.iter().map(u64::to_string).collect::<Vec<_>>();
+        write!(f, "{}", parts.join("."))
+    }
+}
+
+/// Checks a version against a requirement like `*`, `>=3.20` or `>=12, <14`.
+/// A bare version is treated as a minimum.
+pub fn matches_requirement(version: &Version, requirement: &str) -> bool {
+    requirement
+        .split(',')
+        .map(str::trim)
+        .filter(|part| !part.is_empty() && *part != "*")
+        .all(|part| {
+            let (op, wanted) = [">=", "<=", "==", ">", "<", "="]
+                .iter()
+                .find_map(|op| part.strip_prefix(op).map(|rest| (*op, rest)))
+                .unwrap_or((">=", part));
+
+            let wanted = match Version::parse(wanted) {
+                Some(wanted) => wanted,
+                None => return false,
+            };
+
+            match op {
+                ">=" => version >= &wanted,
+                "<=" => version <= &wanted,
+                ">" => version > &wanted,
+                "<" => version < &wanted,
+                _ => version == &wanted,
+            }
+        })
+}
+
+/// Runs `<tool> --version` and returns the version it reports
+pub fn tool_version(tool: &str) -> Option<Version> {
+    let output = duct::cmd!(tool, "--version")
+        .stderr_to_stdout()
+        .stdout_capture()
+        .unchecked()
+        .run()
+        .ok()?;
+
+    Version::find_in(&String::from_utf8_lossy(&output.stdout))
+}
+
+pub fn install_hint(tool: &str) -> &'static str {
+    match tool {
+        "cmake" => {
+            "download it from https://cmake.org/download or install it with your package manager"
+        }
+        "ninja" => "install it with your package manager (usually 'ninja' or 'ninja-build')",
+        "gcc" | "g++" => {
+            "install it with your package manager (usually 'gcc' or 'build-essential')"
+        }
+        "clang" | "clang++" => {
+            "install it with your package manager or from https://releases.llvm.org"
+        }
+        "git" => "download it from https://git-scm.com/downloads",
+        "make" => "install it with your package manager (usually 'make' or 'build-essential')",
+        _ => "install it and make sure it is on your PATH",
+    }
+}
+
+/// Checks every `[requires]` entry, failing on the first tool that is missing or too old
+pub fn check_requirements(requires: &BTreeMap<String, String>) -> Result<(), ProjectError> {
+    for (tool, requirement) in requires {
+        match tool_version(tool) {
+            Some(version) if matches_requirement(&version, requirement) => {}
+
+            found => {
+                return Err(ProjectError::UnmetRequirement(
+                    tool.clone(),
+                    found.map(|version| version.to_string()),
+                    requirement.clone(),
+                ))
+            }
+        }
+    }
+
+    if !requires.is_empty() {
+        notice!("{} tool requirements", "Checked".green());
+    }
+
+    Ok(())
+}
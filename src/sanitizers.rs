@@ -0,0 +1,148 @@
+use std::path::Path;
+
+use colored::Colorize;
+
+use crate::{
+    config::{ConfigFile, SanitizerRuntime},
+    error::ProjectError,
+    messages::t,
+};
+
+/// Folder `cmm scaffold sanitizer-suppressions` puts suppression files in
+const SUPPRESSIONS_DIR: &str = "sanitizers";
+
+const ASAN_SUPPRESSIONS: &str =
+    "# AddressSanitizer suppressions, one per line. Only errors reported from
+# interceptors and ODR violations can be suppressed.
+#
+#   interceptor_via_fun:function_name
+#   interceptor_via_lib:libname.so
+#   odr_violation:global_name
+";
+
+const UBSAN_SUPPRESSIONS: &str =
+    "# UndefinedBehaviorSanitizer suppressions, one per line as check:file, function
+# or module. Needs the check to be recoverable (-fsanitize-recover).
+#
+#   signed-integer-overflow:legacy/math.cpp
+#   alignment:read_packet
+#   vptr:libthirdparty.so
+";
+
+const LSAN_SUPPRESSIONS: &str =
+    "# LeakSanitizer suppressions, one per line. Leaks whose stack contains a
+# matching function, file or library are ignored.
+#
+#   leak:init_global_cache
+#   leak:libfontconfig.so
+";
+
+/// The options variable each sanitizer runtime reads, its config and a default suppressions file
+fn runtimes(
+    config: &ConfigFile,
+) -> [(&'static str, &SanitizerRuntime, &'static str, &'static str); 3] {
+    let sanitizers = &config.sanitizers;
+
+    [
+        (
+            "ASAN_OPTIONS",
+            &sanitizers.address,
+            "asan.supp",
+            ASAN_SUPPRESSIONS,
+        ),
+        (
+            "UBSAN_OPTIONS",
+            &sanitizers.undefined,
+            "ubsan.supp",
+            UBSAN_SUPPRESSIONS,
+        ),
+        (
+            "LSAN_OPTIONS",
+            &sanitizers.leak,
+            "lsan.supp",
+            LSAN_SUPPRESSIONS,
+        ),
+    ]
+}
+
+/// Environment variables passing the configured options and suppressions to sanitizer
+/// runtimes. Options already set in the environment come last so they win.
+pub fn runtime_env(config: &ConfigFile) -> Vec<(String, String)> {
+    runtimes(config)
+        .into_iter()
+        .filter_map(|(var, runtime, ..)| {
+            // Runtimes resolve suppressions against the working directory, which may not
+            // be the project folder
+            let suppressions = runtime.suppressions.as_ref().map(|path| {
+                let path = std::fs::canonicalize(path).unwrap_or_else(|_| path.into());
+                format!("suppressions={}", path.display())
+            });
+
+            let options = runtime
+                .options
+                .iter()
+                .cloned()
+                .chain(suppressions)
+                .chain(
+                    std::env::var(var)
+                        .ok()
+                        .filter(|existing| !existing.is_empty()),
+                )
+                .collect::<Vec<_>>();
+
+            (!options.is_empty()).then(|| (var.to_owned(), options.join(":")))
+        })
+        .collect()
+}
+
+/// `cmm scaffold sanitizer-suppressions`. Creates a commented suppressions file for each
+/// sanitizer without one and points the config at it. Existing files are left alone.
+pub fn scaffold_suppressions(config: &mut ConfigFile) -> Result<(), ProjectError> {
+    let files = runtimes(config).map(|(_, runtime, file, template)| {
+        let path = runtime
+            .suppressions
+            .clone()
+            .unwrap_or_else(|| format!("{}/{}", SUPPRESSIONS_DIR, file));
+        (path, template)
+    });
+
+    for (path, template) in &files {
+        let file = Path::new(path);
+
+        if file.exists() {
+            println!("{}", t!("'{}' already exists", path));
+            continue;
+        }
+
+        if let Some(parent) = file
+            .parent()
+            .filter(|parent| !parent.as_os_str().is_empty())
+        {
+            std::fs::create_dir_all(parent).map_err(|err| {
+                ProjectError::FailedToCreateFolder(parent.to_owned(), err.to_string())
+            })?;
+        }
+
+        std::fs::write(file, template)
+            .map_err(|err| ProjectError::FailedToCreateFile(file.to_owned(), err.to_string()))?;
+
+        println!("{}", t!("Created '{}'", path));
+    }
+
+    let [address, undefined, leak] = files.map(|(path, _)| Some(path));
+    let sanitizers = &mut config.sanitizers;
+    sanitizers.address.suppressions = address;
+    sanitizers.undefined.suppressions = undefined;
+    sanitizers.leak.suppressions = leak;
+
+    println!(
+        "{}",
+        t!(
+            "{} scaffolding sanitizer suppressions, used by '{}'",
+            t!("Finished").green().bold(),
+            "cmm run --sanitize"
+        )
+    );
+
+    Ok(())
+}
@@ -0,0 +1,174 @@
+use std::path::Path;
+
+use colored::Colorize;
+
+use crate::{
+    answers,
+    error::ProjectError,
+    messages::t,
+    output::status,
+    util::{get_config, write_config},
+    CONFIG_NAME,
+};
+
+/// The layout of CMakeMake.toml this version of cmm reads and writes. Raised when a change
+/// would stop older configs loading, with a step in `MIGRATIONS` to upgrade them.
+pub const CONFIG_VERSION: u32 = 2;
+
+/// Upgrades a config from the version before, saying what it changed
+type Migration = fn(&mut toml::Table, &mut Vec<String>);
+
+/// `MIGRATIONS[n]` upgrades version `n + 1` to `n + 2`. Configs from before
+/// `config_version` existed are version 1.
+const MIGRATIONS: [Migration; 1] = [versions_to_strings];
+
+/// Version 2 writes versions as strings, as floats lose the difference between eg. cmake
+/// 3.1 and 3.10
+fn versions_to_strings(config: &mut toml::Table, changes: &mut Vec<String>) {
+    for (section, key) in [("project", "version"), ("cmake", "minimum_required")] {
+        let Some(value) = config
+            .get_mut(section)
+            .and_then(|section| section.as_table_mut())
+            .and_then(|section| section.get_mut(key))
+        else {
+            continue;
+        };
+
+        let version = match value {
+            toml::Value::Float(float) => format!("{:?}", float),
+            toml::Value::Integer(integer) => integer.to_string(),
+            _ => continue,
+        };
+
+        changes.push(t!(
+            "{}.{} = {} is now the string \"{}\"",
+            section,
+            key,
+            value,
+            version
+        ));
+        *value = version.into();
+    }
+}
+
+/// The layout a config was written in
+pub fn version_of(config: &toml::Table) -> u32 {
+    config
+        .get("config_version")
+        .and_then(|version| version.as_integer())
+        .map_or(1, |version| version.clamp(1, u32::MAX as i64) as u32)
+}
+
+/// Upgrades `config` to `CONFIG_VERSION` in place, returning what changed. Configs from a
+/// newer cmm are left as they are.
+pub fn migrate(config: &mut toml::Table) -> Vec<String> {
+    let version = version_of(config);
+    let mut changes = Vec::new();
+
+    if version >= CONFIG_VERSION {
+        return changes;
+    }
+
+    MIGRATIONS
+        .iter()
+        .skip(version as usize - 1)
+        .for_each(|migration| migration(config, &mut changes));
+
+    config.insert("config_version".into(), i64::from(CONFIG_VERSION).into());
+    changes
+}
+
+/// The config as written on disk, before any migration
+fn read_table() -> Option<toml::Table> {
+    toml::from_str(&std::fs::read_to_string(CONFIG_NAME).ok()?).ok()
+}
+
+/// Configs are upgraded whenever they're loaded, this offers to save the upgrade so it
+/// only happens once. Also warns about configs from a newer cmm.
+pub fn offer_upgrade() -> Result<(), ProjectError> {
+    let Some(mut table) = read_table() else {
+        return Ok(());
+    };
+
+    let version = version_of(&table);
+    if version > CONFIG_VERSION {
+        println!(
+            "{}",
+            t!(
+                "{} {} is from a newer cmm (config version {}, this one reads {}). Update cmm if settings are missing.",
+                t!("warning:").yellow(),
+                CONFIG_NAME,
+                version,
+                CONFIG_VERSION
+            )
+        );
+        return Ok(());
+    }
+
+    let changes = migrate(&mut table);
+    if changes.is_empty() {
+        return Ok(());
+    }
+
+    println!(
+        "{}",
+        t!(
+            "{} is from an older cmm, it's upgraded when loaded:",
+            CONFIG_NAME
+        )
+    );
+    changes
+        .iter()
+        .for_each(|change| println!("    - {}", change));
+
+    let key = "config.upgrade";
+    if !answers::can_answer(key) {
+        println!(
+            "{} {}",
+            t!("hint:").cyan(),
+            t!("run 'cmm migrate config' to save the upgrade")
+        );
+        return Ok(());
+    }
+
+    if answers::confirm(
+        key,
+        inquire::Confirm::new(&t!("Save the upgraded {}?", CONFIG_NAME))
+            .with_default(true)
+            .with_placeholder("Y/n"),
+    )? {
+        upgrade()?;
+    }
+
+    Ok(())
+}
+
+/// `cmm migrate config`. Writes the config back in the current layout, keeping the old
+/// one as the backup.
+pub fn upgrade() -> Result<(), ProjectError> {
+    if !Path::new(CONFIG_NAME).exists() {
+        return Err(ProjectError::InvalidProjectDirectory);
+    }
+
+    if read_table().is_some_and(|table| version_of(&table) >= CONFIG_VERSION) {
+        status!(
+            "{}",
+            t!(
+                "{} is already at config version {}",
+                CONFIG_NAME,
+                CONFIG_VERSION
+            )
+        );
+        return Ok(());
+    }
+
+    write_config(get_config()?)?;
+
+    status!(
+        "{} {}",
+        t!("Updated").green().bold(),
+        t!("{} to config version {}", CONFIG_NAME, CONFIG_VERSION)
+    );
+
+    Ok(())
+}
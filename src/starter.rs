@@ -0,0 +1,77 @@
+//! The `[starter]` section of the user config, an organisation's own skeleton for `cmm new`:
+//! a main.cpp to start from, one per C++ standard, and extra files like a .clang-format
+//! or a license header every new project gets.
+//!
+//! Paths are relative to the folder the user config is in, or start with `~`. In the files,
+//! `{{project}}`, `{{author}}`, `{{license}}` and `{{standard}}` are filled in.
+
+use std::path::PathBuf;
+
+use crate::{
+    config::{ConfigFile, Starter},
+    error::ProjectError,
+    util::global_config_path,
+};
+
+/// Where a `[starter]` path points on this machine
+fn resolve(path: &str) -> PathBuf {
+    let home = directories::BaseDirs::new().map(|dirs| dirs.home_dir().to_owned());
+
+    match (path.strip_prefix('~'), home) {
+        (Some(rest), Some(home)) => home.join(rest.trim_start_matches(['/', '\\'])),
+        _ => global_config_path().parent().unwrap().join(path),
+    }
+}
+
+/// The starter main.cpp for a project on `standard`, falling back to the one for any standard
+fn main_path(starter: &Starter, standard: Option<u32>) -> Option<&String> {
+    standard
+        .and_then(|standard| starter.main_by_standard.get(&standard.to_string()))
+        .or(starter.main.as_ref())
+}
+
+/// Fills in the placeholders starter files can use
+fn fill_in(contents: &str, config: &ConfigFile) -> String {
+    contents
+        .replace("{{project}}", &config.project.name)
+        .replace("{{author}}", &config.project.authors.join(", "))
+        .replace(
+            "{{license}}",
+            config.project.license.as_deref().unwrap_or(""),
+        )
+        .replace(
+            "{{standard}}",
+            &config
+                .cmake
+                .cxx_standard
+                .map_or(String::new(), |standard| standard.to_string()),
+        )
+}
+
+/// The files a new project gets from the user config, relative to the project folder.
+/// `with_main` is false for templates, which bring their own main.cpp.
+pub fn files(
+    starter: &Starter,
+    config: &ConfigFile,
+    with_main: bool,
+) -> Result<Vec<(String, String)>, ProjectError> {
+    let main = main_path(starter, config.cmake.cxx_standard)
+        .filter(|_| with_main)
+        .map(|main| ("src/main.cpp", main));
+
+    main.into_iter()
+        .chain(
+            starter
+                .files
+                .iter()
+                .map(|(file, source)| (file.as_str(), source)),
+        )
+        .map(|(file, source)| {
+            let source = resolve(source);
+            let contents = std::fs::read_to_string(&source)
+                .map_err(|err| ProjectError::CannotOpenFile(source, err.to_string()))?;
+
+            Ok((file.to_owned(), fill_in(&contents, config)))
+        })
+        .collect()
+}
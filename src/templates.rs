@@ -0,0 +1,261 @@
+use crate::config::{ConfigFile, FetchDependency, FindDependency, TestFramework, Tests};
+
+/// Folder template projects load assets from, copied next to the executable on each build
+pub const RESOURCES_DIR: &str = "resources";
+
+/// Starting points for `cmm new --template`, the files to start from and the dependencies
+/// they need
+#[derive(Clone, Copy)]
+pub enum Template {
+    GlfwOpengl,
+    Sdl2,
+    Cli,
+}
+
+const GLFW_OPENGL_MAIN: &str = r#"#include <GLFW/glfw3.h>
+
+#include <iostream>
+
+int main(void)
+{
+    if (!glfwInit())
+    {
+        std::cerr << "Failed to initialise GLFW\n";
+        return 1;
+    }
+
+    GLFWwindow* window = glfwCreateWindow(1280, 720, "Hello Window", nullptr, nullptr);
+    if (!window)
+    {
+        std::cerr << "Failed to create a window\n";
+        glfwTerminate();
+        return 1;
+    }
+
+    glfwMakeContextCurrent(window);
+    glfwSwapInterval(1);
+
+    // Assets in resources/ are copied next to the executable when building
+    while (!glfwWindowShouldClose(window))
+    {
+        glClearColor(0.1f, 0.1f, 0.15f, 1.0f);
+        glClear(GL_COLOR_BUFFER_BIT);
+
+        glfwSwapBuffers(window);
+        glfwPollEvents();
+    }
+
+    glfwDestroyWindow(window);
+    glfwTerminate();
+    return 0;
+}
+"#;
+
+const SDL2_MAIN: &str = r#"#include <SDL.h>
+
+#include <iostream>
+
+int main(int argc, char* argv[])
+{
+    if (SDL_Init(SDL_INIT_VIDEO) != 0)
+    {
+        std::cerr << "Failed to initialise SDL: " << SDL_GetError() << "\n";
+        return 1;
+    }
+
+    SDL_Window* window = SDL_CreateWindow("Hello Window", SDL_WINDOWPOS_CENTERED,
+                                          SDL_WINDOWPOS_CENTERED, 1280, 720, 0);
+    SDL_Renderer* renderer = window
+        ? SDL_CreateRenderer(window, -1, SDL_RENDERER_PRESENTVSYNC)
+        : nullptr;
+    if (!renderer)
+    {
+        std::cerr << "Failed to create a window: " << SDL_GetError() << "\n";
+        SDL_Quit();
+        return 1;
+    }
+
+    // Assets in resources/ are copied next to the executable when building
+    bool running = true;
+    while (running)
+    {
+        SDL_Event event;
+        while (SDL_PollEvent(&event))
+        {
+            if (event.type == SDL_QUIT)
+                running = false;
+        }
+
+        SDL_SetRenderDrawColor(renderer, 25, 25, 38, 255);
+        SDL_RenderClear(renderer);
+        SDL_RenderPresent(renderer);
+    }
+
+    SDL_DestroyRenderer(renderer);
+    SDL_DestroyWindow(window);
+    SDL_Quit();
+    return 0;
+}
+"#;
+
+const CLI_MAIN: &str = r#"#include "commands.hpp"
+
+#include <CLI/CLI.hpp>
+
+#include <iostream>
+
+int main(int argc, char* argv[])
+{
+    CLI::App app{"A command line tool"};
+    app.require_subcommand(1);
+
+    std::string name = "World";
+    auto* greet = app.add_subcommand("greet", "Print a greeting");
+    greet->add_option("name", name, "Who to greet");
+    greet->callback([&] { std::cout << commands::greet(name) << "\n"; });
+
+    bool loud = false;
+    auto* version = app.add_subcommand("version", "Print the version");
+    version->add_flag("-l,--loud", loud, "Shout it");
+    version->callback([&] { std::cout << commands::version(loud) << "\n"; });
+
+    CLI11_PARSE(app, argc, argv);
+    return 0;
+}
+"#;
+
+const CLI_COMMANDS: &str = r#"#pragma once
+
+#include <string>
+
+// What each subcommand does, kept out of main.cpp so tests can call it
+namespace commands
+{
+
+inline std::string greet(const std::string& name)
+{
+    return "Hello, " + name + "!";
+}
+
+inline std::string version(bool loud)
+{
+    return loud ? "VERSION 0.1.0" : "version 0.1.0";
+}
+
+} // namespace commands
+"#;
+
+const CLI_TEST: &str = r#"#include "commands.hpp"
+
+#include <catch2/catch_test_macros.hpp>
+
+TEST_CASE("greet", "[commands]")
+{
+    REQUIRE(commands::greet("cmm") == "Hello, cmm!");
+}
+
+TEST_CASE("version", "[commands]")
+{
+    REQUIRE(commands::version(true) == "VERSION 0.1.0");
+}
+"#;
+
+fn fetch(name: &str, repo: &str, tag: &str, variables: &[(&str, &str)]) -> FetchDependency {
+    FetchDependency {
+        name: name.into(),
+        repo: repo.into(),
+        tag: Some(tag.into()),
+        custom_link_name: None,
+        variables: variables
+            .iter()
+            .map(|(var, value)| (var.to_string(), value.to_string()))
+            .collect(),
+        extra_cmake: None,
+    }
+}
+
+impl Template {
+    /// Files to create, relative to the project folder
+    pub fn files(self) -> Vec<(String, &'static str)> {
+        let resources = format!("{}/.gitkeep", RESOURCES_DIR);
+
+        match self {
+            Template::GlfwOpengl => {
+                vec![("src/main.cpp".into(), GLFW_OPENGL_MAIN), (resources, "")]
+            }
+            Template::Sdl2 => vec![("src/main.cpp".into(), SDL2_MAIN), (resources, "")],
+            Template::Cli => vec![
+                ("src/main.cpp".into(), CLI_MAIN),
+                ("src/commands.hpp".into(), CLI_COMMANDS),
+                (
+                    format!("{}/commands_test.cpp", Tests::default_path()),
+                    CLI_TEST,
+                ),
+            ],
+        }
+    }
+
+    /// Adds the template's dependencies, downloaded with FetchContent so there is nothing to
+    /// install first. Windowing templates copy resources/ next to the executable, the cli
+    /// template sets up Catch2 tests.
+    pub fn apply(self, config: &mut ConfigFile) {
+        let dependencies = &mut config.dependencies;
+
+        match self {
+            Template::GlfwOpengl => {
+                dependencies.fetch.push(fetch(
+                    "glfw",
+                    "https://github.com/glfw/glfw",
+                    "3.4",
+                    &[
+                        ("GLFW_BUILD_DOCS", "OFF"),
+                        ("GLFW_BUILD_EXAMPLES", "OFF"),
+                        ("GLFW_BUILD_TESTS", "OFF"),
+                    ],
+                ));
+                dependencies.find.push(FindDependency {
+                    name: "OpenGL".into(),
+                    required: true,
+                    custom_link_name: Some("OpenGL::GL".into()),
+                    extra_cmake: None,
+                });
+                dependencies
+                    .project_dependencies
+                    .extend(["glfw".into(), "OpenGL::GL".into()]);
+            }
+
+            // Static so there is no SDL2.dll to ship next to the executable on Windows
+            Template::Sdl2 => {
+                dependencies.fetch.push(fetch(
+                    "SDL2",
+                    "https://github.com/libsdl-org/SDL",
+                    "release-2.30.9",
+                    // Cache entries, as SDL's option() calls ignore plain variables
+                    &[
+                        ("SDL_SHARED", r#"OFF CACHE BOOL """#),
+                        ("SDL_STATIC", r#"ON CACHE BOOL """#),
+                        ("SDL_TEST", r#"OFF CACHE BOOL """#),
+                    ],
+                ));
+                dependencies
+                    .project_dependencies
+                    .extend(["SDL2::SDL2main".into(), "SDL2::SDL2-static".into()]);
+            }
+
+            Template::Cli => {
+                let mut cli11 = fetch("CLI11", "https://github.com/CLIUtils/CLI11", "v2.4.2", &[]);
+                cli11.custom_link_name = Some("CLI11::CLI11".into());
+                dependencies.fetch.push(cli11);
+                dependencies
+                    .project_dependencies
+                    .push("CLI11::CLI11".into());
+
+                config.tests = Some(Tests::new(TestFramework::Catch2));
+            }
+        }
+
+        if matches!(self, Template::GlfwOpengl | Template::Sdl2) {
+            config.cmake.resources.push(RESOURCES_DIR.into());
+        }
+    }
+}
@@ -0,0 +1,463 @@
+use std::{io::Write, path::Path};
+
+use colored::Colorize;
+
+use crate::{
+    answers,
+    build::{build_project, build_type, BuildOptions},
+    config::{ConfigFile, FileRole, TestFramework, Tests},
+    error::ProjectError,
+    messages::t,
+    output::status,
+    requires::{tool_version, Version},
+    util::{create_dir, executable_path, get_config, init_file},
+};
+
+/// Name of the generated test executable
+pub const TEST_TARGET: &str = r#""${PROJECT_NAME}_tests""#;
+
+const GTEST_SAMPLE: &str = r#"#include <gtest/gtest.h>
+
+TEST(ExampleTest, Addition)
+{
+    EXPECT_EQ(1 + 1, 2);
+}
+"#;
+
+const CATCH2_SAMPLE: &str = r#"#include <catch2/catch_test_macros.hpp>
+
+TEST_CASE("Addition", "[example]")
+{
+    REQUIRE(1 + 1 == 2);
+}
+"#;
+
+fn parse_framework(name: &str) -> Result<TestFramework, ProjectError> {
+    match name.to_lowercase().as_str() {
+        "gtest" | "googletest" => Ok(TestFramework::GTest),
+        "catch2" | "catch" => Ok(TestFramework::Catch2),
+        _ => Err(ProjectError::UnknownArgument(name.into())),
+    }
+}
+
+fn pick_framework() -> Result<TestFramework, ProjectError> {
+    if !answers::can_answer("add.test_framework") {
+        return Err(ProjectError::MissingArgument("gtest | catch2".into()));
+    }
+
+    let choice = answers::select(
+        "add.test_framework",
+        inquire::Select::new(t!("Choose a test framework:"), vec!["GoogleTest", "Catch2"]),
+    )?;
+
+    match choice.index {
+        0 => Ok(TestFramework::GTest),
+        _ => Ok(TestFramework::Catch2),
+    }
+}
+
+/// `cmm add test-framework [gtest | catch2]`. Sets up the config and a tests folder with
+/// an example test.
+pub fn add_test_framework(
+    config: &mut ConfigFile,
+    framework: Option<&str>,
+) -> Result<(), ProjectError> {
+    let framework = match framework {
+        Some(name) => parse_framework(name)?,
+        None => pick_framework()?,
+    };
+
+    let tests = match config.tests.take() {
+        Some(mut tests) if tests.framework != framework => {
+            println!(
+                "{}",
+                t!(
+                    "{} replacing the existing test framework",
+                    t!("warning:").yellow()
+                )
+            );
+            tests.version = Tests::new(framework).version;
+            tests.framework = framework;
+            tests
+        }
+        Some(tests) => tests,
+        None => Tests::new(framework),
+    };
+
+    let path = Path::new(&tests.path);
+    if !path.exists() {
+        create_dir(path)?;
+    }
+
+    let has_sources = std::fs::read_dir(path)
+        .map(|dir| {
+            dir.filter_map(|entry| entry.ok())
+                .any(|entry| entry.path().extension().is_some_and(|ext| ext == "cpp"))
+        })
+        .unwrap_or(false);
+
+    if !has_sources {
+        let sample = match framework {
+            TestFramework::GTest => GTEST_SAMPLE,
+            TestFramework::Catch2 => CATCH2_SAMPLE,
+        };
+
+        init_file(&path.join("example_test.cpp"), sample.as_bytes())?;
+    }
+
+    println!(
+        "{}",
+        t!(
+            "Tests in '{}' are built and run with 'cmm test'",
+            tests.path
+        )
+    );
+
+    config.tests = Some(tests);
+
+    Ok(())
+}
+
+/// Fetches the framework and adds the test executable. The caller adds project include
+/// dirs and dependencies to `TEST_TARGET` afterwards.
+pub fn write_tests(file: &mut impl Write, tests: &Tests) -> std::io::Result<()> {
+    writeln!(file, "\n#Tests:")?;
+    writeln!(file, "enable_testing()")?;
+    writeln!(file, "include(FetchContent)")?;
+
+    match tests.framework {
+        TestFramework::GTest => {
+            writeln!(
+                file,
+                "FetchContent_Declare(googletest GIT_REPOSITORY https://github.com/google/googletest.git GIT_TAG {})",
+                tests.version
+            )?;
+            // Keeps gtest on the same runtime library as the project with MSVC
+            writeln!(
+                file,
+                r#"set(gtest_force_shared_crt ON CACHE BOOL "" FORCE)"#
+            )?;
+            writeln!(file, "FetchContent_MakeAvailable(googletest)")?;
+        }
+
+        TestFramework::Catch2 => {
+            writeln!(
+                file,
+                "FetchContent_Declare(Catch2 GIT_REPOSITORY https://github.com/catchorg/Catch2.git GIT_TAG {})",
+                tests.version
+            )?;
+            writeln!(file, "FetchContent_MakeAvailable(Catch2)")?;
+            writeln!(
+                file,
+                "list(APPEND CMAKE_MODULE_PATH ${{catch2_SOURCE_DIR}}/extras)"
+            )?;
+        }
+    }
+
+    writeln!(
+        file,
+        r#"file(GLOB_RECURSE TEST_SOURCES "{0}/*.cpp" "{0}/*.c")"#,
+        tests.path
+    )?;
+    writeln!(file, "add_executable({TEST_TARGET} ${{TEST_SOURCES}})")?;
+
+    match tests.framework {
+        TestFramework::GTest => {
+            writeln!(
+                file,
+                "target_link_libraries({TEST_TARGET} PRIVATE GTest::gtest_main)"
+            )?;
+            writeln!(file, "include(GoogleTest)")?;
+            writeln!(file, "gtest_discover_tests({TEST_TARGET})")?;
+        }
+
+        TestFramework::Catch2 => {
+            writeln!(
+                file,
+                "target_link_libraries({TEST_TARGET} PRIVATE Catch2::Catch2WithMain)"
+            )?;
+            writeln!(file, "include(Catch)")?;
+            writeln!(file, "catch_discover_tests({TEST_TARGET})")?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Which test cases `cmm test` runs directly from the test executable, instead of
+/// running every test through ctest
+pub enum CaseSelection {
+    /// Print the test cases without running them
+    List,
+    /// Choose the test cases from a list
+    Pick,
+    /// Test cases by name, or a pattern the framework understands
+    Named(Vec<String>),
+}
+
+/// Test cases in the test executable, as `Suite.Test` for GoogleTest and the test
+/// case name for Catch2
+fn list_cases(binary: &Path, framework: TestFramework) -> Result<Vec<String>, ProjectError> {
+    let args = match framework {
+        TestFramework::GTest => vec!["--gtest_list_tests"],
+        TestFramework::Catch2 => vec!["--list-tests", "--verbosity", "quiet"],
+    };
+
+    let output = duct::cmd(binary, args)
+        .stderr_null()
+        .read()
+        .map_err(|_| ProjectError::FailedToRunProcess(binary.display().to_string(), None))?;
+
+    let cases = match framework {
+        // Suites are unindented lines ending in '.', followed by their indented tests.
+        // Parameterised tests have their parameters in a trailing comment.
+        TestFramework::GTest => {
+            let mut suite = "";
+            let mut cases = Vec::new();
+
+            for line in output.lines() {
+                let name = line.split('#').next().unwrap_or_default().trim_end();
+
+                match line.starts_with(' ') {
+                    true if !name.trim().is_empty() => {
+                        cases.push(format!("{}{}", suite, name.trim()))
+                    }
+                    true => {}
+                    false => suite = name,
+                }
+            }
+
+            cases
+        }
+
+        TestFramework::Catch2 => output
+            .lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty())
+            .map(String::from)
+            .collect(),
+    };
+
+    Ok(cases)
+}
+
+/// Arguments making the test executable only run `cases`
+fn filter_args(framework: TestFramework, cases: &[String]) -> Vec<String> {
+    match framework {
+        TestFramework::GTest => vec![format!("--gtest_filter={}", cases.join(":"))],
+        TestFramework::Catch2 => cases.to_vec(),
+    }
+}
+
+/// `cmm test --list | --pick | <CASES>`. Runs individual test cases from the test
+/// executable with the framework's own filter flags, which is finer grained than ctest.
+pub fn run_cases(options: &BuildOptions, selection: &CaseSelection) -> Result<(), ProjectError> {
+    let config = get_config()?;
+    let Some(tests) = config.tests else {
+        return Err(ProjectError::NoTests);
+    };
+
+    if matches!(selection, CaseSelection::Pick) && !answers::can_answer("test.cases") {
+        return Err(ProjectError::MissingArgument("CASES".into()));
+    }
+
+    build_project(options)?;
+    status!();
+
+    let binary = executable_path(
+        &options.build_dir(),
+        &build_type(options)?,
+        &format!("{}_tests", config.project.name),
+    );
+
+    let cases = match selection {
+        CaseSelection::List => {
+            list_cases(&binary, tests.framework)?
+                .iter()
+                .for_each(|case| println!("{}", case));
+            return Ok(());
+        }
+
+        CaseSelection::Pick => answers::multi_select(
+            "test.cases",
+            inquire::MultiSelect::new(
+                t!("Choose the tests to run:"),
+                list_cases(&binary, tests.framework)?,
+            ),
+        )?
+        .into_iter()
+        .map(|case| case.value)
+        .collect(),
+
+        CaseSelection::Named(cases) => cases.clone(),
+    };
+
+    if cases.is_empty() {
+        println!("{}", t!("No tests chosen"));
+        return Ok(());
+    }
+
+    status!("{}", t!("Running tests").green());
+
+    let status = duct::cmd(&binary, filter_args(tests.framework, &cases))
+        .unchecked()
+        .run()
+        .map_err(|_| ProjectError::FailedToRunProcess(binary.display().to_string(), None))?
+        .status;
+
+    // The test framework has already said which cases failed
+    if !status.success() {
+        Err(ProjectError::FailingTests(Vec::new()))?;
+    }
+
+    status!("{}", t!("{} running tests", t!("Finished").green().bold()));
+
+    Ok(())
+}
+
+/// First ctest with `--output-junit`
+const JUNIT_CTEST: &str = "3.21";
+
+/// Where ctest lists the tests that failed in its last run, as `<index>:<name>` lines
+const LAST_FAILED: &str = "Testing/Temporary/LastTestsFailed.log";
+
+/// Runs ctest in the build folder, returning whether every test passed
+fn ctest(
+    options: &BuildOptions,
+    build_type: &str,
+    extra_args: &[String],
+) -> Result<bool, ProjectError> {
+    // Run from the build folder, as --test-dir needs ctest 3.20
+    let ctest_args = ["-C", build_type, "--output-on-failure"]
+        .into_iter()
+        .map(String::from)
+        .chain(extra_args.iter().cloned())
+        .collect::<Vec<_>>();
+
+    let output = duct::cmd("ctest", ctest_args)
+        .dir(options.build_dir())
+        .stderr_to_stdout()
+        .unchecked()
+        .run()
+        .map_err(|_| ProjectError::MissingProgram("ctest".into()))?;
+
+    Ok(output.status.success())
+}
+
+fn last_failed(options: &BuildOptions) -> Vec<String> {
+    std::fs::read_to_string(Path::new(&options.build_dir()).join(LAST_FAILED))
+        .unwrap_or_default()
+        .lines()
+        .filter_map(|line| line.split_once(':'))
+        .map(|(_, name)| name.to_owned())
+        .collect()
+}
+
+/// Reruns failed tests up to `retries` times. Returns the tests that never passed and
+/// those that passed on a retry.
+fn retry_failed(
+    options: &BuildOptions,
+    build_type: &str,
+    retries: u32,
+) -> Result<(Vec<String>, Vec<String>), ProjectError> {
+    let failed = last_failed(options);
+    let mut failing = failed.clone();
+
+    for attempt in 1..=retries {
+        if failing.is_empty() {
+            break;
+        }
+
+        println!(
+            "\n{}",
+            t!(
+                "Rerunning {} failed tests, attempt {} of {}",
+                failing.len(),
+                attempt,
+                retries
+            )
+            .yellow()
+        );
+
+        // ctest only rewrites the list of failures when there are some
+        failing = match ctest(options, build_type, &["--rerun-failed".into()])? {
+            true => Vec::new(),
+            false => last_failed(options),
+        };
+    }
+
+    let flaky = failed
+        .into_iter()
+        .filter(|test| !failing.contains(test))
+        .collect();
+
+    Ok((failing, flaky))
+}
+
+/// `cmm test [--report <FILE>]`. Builds the project and its tests, then runs them with
+/// ctest. Failed tests are rerun up to `tests.retries` times, and ones that pass on a
+/// retry are reported as flaky without failing the run. With `report` ctest also writes
+/// a JUnit XML report of the first run there, even when tests fail, for CI test dashboards.
+/// Files tagged as tests count without a framework set up.
+pub fn run_tests(options: &BuildOptions, report: Option<&Path>) -> Result<(), ProjectError> {
+    let config = get_config()?;
+    if config.tests.is_none() && config.cmake.files.role(FileRole::Test).is_empty() {
+        return Err(ProjectError::NoTests);
+    }
+    let retries = config.tests.map_or(0, |tests| tests.retries);
+
+    // Checked before building so CI doesn't find out after a long compile
+    if report.is_some() {
+        let version = tool_version("ctest");
+        if version.as_ref() < Version::parse(JUNIT_CTEST).as_ref() {
+            return Err(ProjectError::UnmetRequirement(
+                "ctest".into(),
+                version.map(|version| version.to_string()),
+                format!(">={}", JUNIT_CTEST),
+            ));
+        }
+    }
+
+    build_project(options)?;
+
+    status!("\n{}", t!("Running tests").green());
+
+    let build_type = build_type(options)?;
+    let report_args = match report {
+        Some(report) => vec![
+            "--output-junit".into(),
+            report.to_string_lossy().into_owned(),
+        ],
+        None => Vec::new(),
+    };
+
+    let passed = ctest(options, &build_type, &report_args)?;
+
+    if let Some(report) = report {
+        println!("{}", t!("Wrote test report to '{}'", report.display()));
+    }
+
+    if !passed {
+        let (failing, flaky) = retry_failed(options, &build_type, retries)?;
+
+        if !flaky.is_empty() {
+            println!(
+                "\n{}",
+                t!(
+                    "{} flaky tests, passed on a retry: {}",
+                    t!("warning:").yellow(),
+                    flaky.join(", ")
+                )
+            );
+        }
+
+        // Nothing flaky and nothing failing means ctest failed without listing any tests
+        if !failing.is_empty() || flaky.is_empty() {
+            Err(ProjectError::FailingTests(failing))?;
+        }
+    }
+
+    status!("{}", t!("{} running tests", t!("Finished").green().bold()));
+
+    Ok(())
+}
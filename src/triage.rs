@@ -0,0 +1,295 @@
+use std::path::Path;
+
+use colored::Colorize;
+
+use crate::{
+    answers,
+    dependencies::add_prefix_path,
+    error::ProjectError,
+    messages::{fill, t, translate},
+    util::{get_config, write_config},
+};
+
+/// A known way for configuring to fail. Any of `patterns` in cmake's output matches, and
+/// `{}` in the steps is the profile being built.
+struct Rule {
+    patterns: &'static [&'static str],
+    problem: &'static str,
+    steps: &'static [&'static str],
+    /// Whether cmake's own log of the checks it ran says more than its output
+    show_log: bool,
+}
+
+const RULES: [Rule; 6] = [
+    Rule {
+        patterns: &[
+            "No CMAKE_CXX_COMPILER could be found",
+            "No CMAKE_C_COMPILER could be found",
+        ],
+        problem: "No C++ compiler was found",
+        steps: &[
+            "Install a compiler: build-essential on Debian/Ubuntu, the Xcode command line tools on macOS or Visual Studio on Windows",
+            "If one is installed outside the PATH, point CC and CXX at it and build again",
+        ],
+        show_log: false,
+    },
+    Rule {
+        patterns: &["is not able to compile a simple test program"],
+        problem: "The compiler can't build a test program",
+        steps: &[
+            "Check CC, CXX and CMAKE_TOOLCHAIN_FILE point at the compiler you meant",
+            "Try another compiler by setting CC and CXX, then build again",
+        ],
+        show_log: true,
+    },
+    Rule {
+        patterns: &["unable to find a build program corresponding to"],
+        problem: "The generator's build tool isn't installed",
+        steps: &[
+            "Install it, eg. ninja or make",
+            "Or pick an installed generator with --generator or cmake.generator in CMakeMake.toml",
+        ],
+        show_log: false,
+    },
+    Rule {
+        patterns: &[
+            "Does not match the generator used previously",
+            "does not match the source",
+            "does not match the platform used previously",
+        ],
+        problem: "The build folder was configured differently before",
+        steps: &["Remove it with 'cmm clean --profile {}' and build again"],
+        show_log: false,
+    },
+    Rule {
+        patterns: &["or higher is required.  You are running version"],
+        problem: "A newer version of cmake is needed",
+        steps: &["Update cmake from cmake.org, your package manager or 'pip install cmake'"],
+        show_log: false,
+    },
+    Rule {
+        patterns: &["Failed to clone repository", "Failed to checkout tag"],
+        problem: "Downloading a fetch dependency failed",
+        steps: &[
+            "Check your network connection",
+            "Check the dependency's repo and tag in CMakeMake.toml",
+            "Run 'cmm fetch' to retry the download without compiling",
+        ],
+        show_log: false,
+    },
+];
+
+/// Where common find_package packages come from, as (find_package name, apt package,
+/// homebrew formula)
+const PACKAGES: [(&str, &str, &str); 16] = [
+    ("Boost", "libboost-all-dev", "boost"),
+    ("Catch2", "catch2", "catch2"),
+    ("CURL", "libcurl4-openssl-dev", "curl"),
+    ("Eigen3", "libeigen3-dev", "eigen"),
+    ("fmt", "libfmt-dev", "fmt"),
+    ("glfw3", "libglfw3-dev", "glfw"),
+    ("GTest", "libgtest-dev", "googletest"),
+    ("nlohmann_json", "nlohmann-json3-dev", "nlohmann-json"),
+    ("OpenGL", "libgl1-mesa-dev", "mesa"),
+    ("OpenSSL", "libssl-dev", "openssl"),
+    ("PkgConfig", "pkg-config", "pkg-config"),
+    ("PNG", "libpng-dev", "libpng"),
+    ("SDL2", "libsdl2-dev", "sdl2"),
+    ("spdlog", "libspdlog-dev", "spdlog"),
+    ("Vulkan", "libvulkan-dev", "vulkan-loader"),
+    ("ZLIB", "zlib1g-dev", "zlib"),
+];
+
+/// Lines of cmake's check log shown around its first error
+const LOG_LINES: usize = 15;
+
+struct Finding {
+    problem: String,
+    steps: Vec<String>,
+    show_log: bool,
+}
+
+/// Packages find_package gave up on, in the order cmake reported them
+fn missing_packages(output: &str) -> Vec<String> {
+    let quoted = |line: &str, prefix: &str| {
+        let rest = &line[line.find(prefix)? + prefix.len()..];
+        Some(rest[..rest.find('"')?].to_owned())
+    };
+
+    let mut packages = Vec::new();
+
+    // Optional packages that weren't found are reported as `-- Could NOT find ...`
+    for line in output.lines().filter(|line| !line.starts_with("-- ")) {
+        let package = quoted(line, "package configuration file provided by \"")
+            .or_else(|| {
+                quoted(line, "By not providing \"Find")
+                    .map(|module| module.trim_end_matches(".cmake").to_owned())
+            })
+            .or_else(|| {
+                let rest = &line[line.find("Could NOT find ")? + "Could NOT find ".len()..];
+                rest.split([' ', ':']).next().map(String::from)
+            });
+
+        if let Some(package) = package.filter(|package| !package.is_empty()) {
+            if !packages.contains(&package) {
+                packages.push(package);
+            }
+        }
+    }
+
+    packages
+}
+
+fn install_hint(package: &str) -> String {
+    let known = PACKAGES
+        .iter()
+        .find(|(name, _, _)| name.eq_ignore_ascii_case(package));
+
+    match (known, std::env::consts::OS) {
+        (Some((_, apt, _)), "linux") => t!("Install it, eg. 'sudo apt install {}'", apt),
+        (Some((_, _, brew)), "macos") => t!("Install it, eg. 'brew install {}'", brew),
+        _ => t!(
+            "Install {} with your package manager, or vcpkg on Windows",
+            package
+        ),
+    }
+}
+
+fn diagnose(output: &str, profile: &str) -> Vec<Finding> {
+    let mut findings = RULES
+        .iter()
+        .filter(|rule| rule.patterns.iter().any(|pattern| output.contains(pattern)))
+        .map(|rule| Finding {
+            problem: translate(rule.problem).into(),
+            steps: rule
+                .steps
+                .iter()
+                .map(|step| fill(translate(step), &[&profile]))
+                .collect(),
+            show_log: rule.show_log,
+        })
+        .collect::<Vec<_>>();
+
+    findings.extend(missing_packages(output).into_iter().map(|package| Finding {
+        problem: t!("find_package couldn't find {}", package),
+        steps: vec![
+            install_hint(&package),
+            t!("If it's installed somewhere unusual, add that folder to dependencies.prefix_paths")
+                .into(),
+            t!("Or build it from source instead with 'cmm add fetch <repo url>'").into(),
+        ],
+        show_log: false,
+    }));
+
+    findings
+}
+
+/// The start of the first error in the log cmake keeps of its compiler checks.
+/// CMakeError.log before cmake 3.26, CMakeConfigureLog.yaml since.
+fn log_excerpt(build_dir: &Path) -> Option<(String, String)> {
+    [
+        "CMakeFiles/CMakeError.log",
+        "CMakeFiles/CMakeConfigureLog.yaml",
+    ]
+    .into_iter()
+    .find_map(|name| {
+        let contents = std::fs::read_to_string(build_dir.join(name)).ok()?;
+        let lines = contents.lines().collect::<Vec<_>>();
+        let error = lines
+            .iter()
+            .position(|line| line.to_lowercase().contains("error"))?;
+
+        let start = error.saturating_sub(3);
+        let end = (start + LOG_LINES).min(lines.len());
+
+        Some((
+            format!("{}/{}", build_dir.display(), name),
+            lines[start..end].join("\n"),
+        ))
+    })
+}
+
+/// Asks for the folders packages find_package missed are installed in, and adds them to
+/// the config's prefix paths
+fn ask_prefix_paths(output: &str) -> Result<(), ProjectError> {
+    let packages = missing_packages(output);
+    if packages.is_empty() {
+        return Ok(());
+    }
+
+    let mut config = get_config()?;
+    let before = config.dependencies.prefix_paths.len();
+
+    for package in packages {
+        if !answers::can_answer("build.triage_prefix_path") {
+            break;
+        }
+
+        let path = answers::text_skippable(
+            "build.triage_prefix_path",
+            inquire::Text::new(&t!("Folder {} is installed in: (optional)", package))
+                .with_help_message(t!("eg. ~/opt/ or /usr/local/, leave empty to skip")),
+        )?
+        .unwrap_or_default();
+
+        add_prefix_path(&mut config, path);
+    }
+
+    if config.dependencies.prefix_paths.len() > before {
+        write_config(config)?;
+        println!(
+            "{}",
+            t!("Added to dependencies.prefix_paths, build again to retry")
+        );
+    }
+
+    Ok(())
+}
+
+/// Explains a failed configure from cmake's output, with steps to fix it. Offered first
+/// when there's someone to ask, printed straight away otherwise so CI logs have it.
+pub fn offer(build_dir: &str, profile: &str, output: &str) -> Result<(), ProjectError> {
+    let findings = diagnose(output, profile);
+
+    let excerpt = match findings.is_empty() || findings.iter().any(|finding| finding.show_log) {
+        true => log_excerpt(Path::new(build_dir)),
+        false => None,
+    };
+
+    if findings.is_empty() && excerpt.is_none() {
+        return Ok(());
+    }
+
+    let interactive = answers::can_answer("build.triage");
+    if interactive
+        && !answers::confirm(
+            "build.triage",
+            inquire::Confirm::new(t!("Configuring failed. Look into why?"))
+                .with_default(true)
+                .with_placeholder("Y/n"),
+        )?
+    {
+        return Ok(());
+    }
+
+    println!("\n{}", t!("Troubleshooting").bold());
+
+    for finding in &findings {
+        println!("\n{} {}", "*".yellow(), finding.problem.bold());
+        finding
+            .steps
+            .iter()
+            .for_each(|step| println!("    - {}", step));
+    }
+
+    if let Some((path, excerpt)) = excerpt {
+        println!("\n{}", t!("From {}:", path).bold());
+        excerpt
+            .lines()
+            .for_each(|line| println!("    {}", line.dimmed()));
+    }
+
+    println!();
+
+    ask_prefix_paths(output)
+}
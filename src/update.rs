@@ -0,0 +1,294 @@
+use std::cmp::Ordering;
+
+use colored::Colorize;
+
+use crate::{
+    answers,
+    config::ConfigFile,
+    error::ProjectError,
+    git::read_submodules,
+    lockfile::{git_in, has_local_changes},
+    messages::t,
+    patches,
+};
+
+/// Where a dependency's code comes from, which decides how moving it to a tag works
+#[derive(Clone, Copy, PartialEq)]
+enum Kind {
+    /// Checked out in the project, the tag is checked out in place
+    Submodule,
+    /// Downloaded by CMake, only the tag in the config changes
+    Fetch,
+    Cpm,
+}
+
+struct Candidate {
+    name: String,
+    kind: Kind,
+    /// Submodule folder or repo url
+    location: String,
+    current: Option<String>,
+}
+
+/// Orders tags by the numbers in them, so `v1.10` comes after `v1.9`
+fn compare_tags(a: &str, b: &str) -> Ordering {
+    let numbers = |tag: &str| {
+        tag.split(|c: char| !c.is_ascii_digit())
+            .filter(|part| !part.is_empty())
+            .map(|part| part.parse::<u64>().unwrap_or(u64::MAX))
+            .collect::<Vec<_>>()
+    };
+
+    numbers(a).cmp(&numbers(b)).then_with(|| a.cmp(b))
+}
+
+/// Tags of a remote, oldest first. Submodules are asked through their own origin so
+/// relative urls work.
+fn remote_tags(candidate: &Candidate) -> Option<Vec<String>> {
+    let cmd = match candidate.kind {
+        Kind::Submodule => {
+            duct::cmd!("git", "ls-remote", "--tags", "--refs", "origin").dir(&candidate.location)
+        }
+        Kind::Fetch | Kind::Cpm => {
+            duct::cmd!("git", "ls-remote", "--tags", "--refs", &candidate.location)
+        }
+    };
+
+    let output = cmd.stdout_capture().stderr_null().unchecked().run().ok()?;
+
+    if !output.status.success() {
+        return None;
+    }
+
+    let mut tags = String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .filter_map(|line| line.split_once("\trefs/tags/"))
+        .map(|(_, tag)| tag.to_owned())
+        .collect::<Vec<_>>();
+
+    tags.sort_by(|a, b| compare_tags(a, b));
+    Some(tags)
+}
+
+/// The tag a submodule checkout is exactly on, if any
+fn checked_out_tag(path: &str) -> Option<String> {
+    let output = duct::cmd!("git", "describe", "--tags", "--exact-match")
+        .dir(path)
+        .stdout_capture()
+        .stderr_null()
+        .unchecked()
+        .run()
+        .ok()?;
+
+    output
+        .status
+        .success()
+        .then(|| String::from_utf8_lossy(&output.stdout).trim().to_owned())
+}
+
+/// Dependencies that come from a git repo and can be moved to another tag
+fn candidates(config: &ConfigFile) -> Vec<Candidate> {
+    let submodules = read_submodules().unwrap_or_default();
+
+    let local = config
+        .dependencies
+        .local
+        .iter()
+        .filter(|local| submodules.iter().any(|sub| sub.path == local.path))
+        .map(|local| Candidate {
+            name: local.name.clone(),
+            kind: Kind::Submodule,
+            location: local.path.clone(),
+            current: local.tag.clone().or_else(|| checked_out_tag(&local.path)),
+        });
+
+    let fetched = |kind: Kind| {
+        move |fetch: &crate::config::FetchDependency| Candidate {
+            name: fetch.name.clone(),
+            kind,
+            location: fetch.repo.clone(),
+            current: fetch.tag.clone(),
+        }
+    };
+
+    local
+        .chain(config.dependencies.fetch.iter().map(fetched(Kind::Fetch)))
+        .chain(config.dependencies.cpm.iter().map(fetched(Kind::Cpm)))
+        .collect()
+}
+
+/// Asks which of the newer tags to move to. Returns None to keep the current one.
+fn choose_tag(candidate: &Candidate, newer: Vec<String>) -> Result<Option<String>, ProjectError> {
+    let keep = match &candidate.current {
+        Some(current) => t!("Keep {}", current),
+        None => t!("Keep current").to_owned(),
+    };
+
+    let options = newer
+        .into_iter()
+        .rev()
+        .chain(std::iter::once(keep))
+        .collect::<Vec<_>>();
+    let last = options.len() - 1;
+
+    let choice = answers::select(
+        "update.tag",
+        inquire::Select::new(&t!("New tag for '{}':", candidate.name), options),
+    )?;
+
+    Ok((choice.index != last).then_some(choice.value))
+}
+
+/// Checks out `tag` in a submodule, fetching it first since new tags aren't there yet
+fn checkout_tag(path: &str, tag: &str) -> bool {
+    let tag_ref = format!("refs/tags/{0}:refs/tags/{0}", tag);
+    let tags = format!("tags/{}", tag);
+
+    git_in(path, &["fetch", "--quiet", "origin", &tag_ref])
+        && git_in(path, &["checkout", "--quiet", &tags])
+}
+
+fn set_tag(config: &mut ConfigFile, candidate: &Candidate, tag: &str) {
+    let dependencies = &mut config.dependencies;
+
+    match candidate.kind {
+        Kind::Submodule => dependencies
+            .local
+            .iter_mut()
+            .filter(|local| local.name == candidate.name)
+            .for_each(|local| local.tag = Some(tag.to_owned())),
+
+        Kind::Fetch | Kind::Cpm => {
+            let fetched = match candidate.kind {
+                Kind::Cpm => &mut dependencies.cpm,
+                _ => &mut dependencies.fetch,
+            };
+
+            fetched
+                .iter_mut()
+                .filter(|fetch| fetch.name == candidate.name)
+                .for_each(|fetch| fetch.tag = Some(tag.to_owned()))
+        }
+    }
+}
+
+/// `cmm update [NAME] [--tag <TAG>]`. Looks for newer tags of git dependencies and moves
+/// the chosen ones to them. Returns the names of the dependencies that changed so their
+/// lockfile entries can be resolved again.
+pub fn update_dependencies(
+    config: &mut ConfigFile,
+    name: Option<&str>,
+    tag: Option<&str>,
+) -> Result<Vec<String>, ProjectError> {
+    let mut candidates = candidates(config);
+
+    match name {
+        Some(name) => {
+            candidates.retain(|candidate| candidate.name == name);
+            if candidates.is_empty() {
+                return Err(ProjectError::UnknownDependency(name.to_owned()));
+            }
+        }
+        None if tag.is_some() => {
+            return Err(ProjectError::MissingArgument("dependency name".into()))
+        }
+        None => {}
+    }
+
+    let mut updated = Vec::new();
+
+    for candidate in candidates {
+        // Never throw away work in progress by checking something else out over it
+        if candidate.kind == Kind::Submodule
+            && has_local_changes(&candidate.location)
+            && !patches::only_patched(&candidate.name, &candidate.location)
+        {
+            println!(
+                "{}",
+                t!(
+                    "{} dependency '{}' has local changes, not updating it",
+                    t!("warning:").yellow(),
+                    candidate.name
+                )
+            );
+            continue;
+        }
+
+        let Some(tags) = remote_tags(&candidate) else {
+            println!(
+                "{}",
+                t!(
+                    "{} couldn't list the tags of '{}'",
+                    t!("warning:").yellow(),
+                    candidate.name
+                )
+            );
+            continue;
+        };
+
+        let chosen = match tag {
+            Some(tag) if tags.iter().any(|known| known == tag) => Some(tag.to_owned()),
+            Some(tag) => {
+                return Err(ProjectError::UnknownTag(
+                    candidate.name.clone(),
+                    tag.to_owned(),
+                ))
+            }
+            None => {
+                let newer = tags
+                    .into_iter()
+                    .filter(|tag| match &candidate.current {
+                        Some(current) => compare_tags(tag, current) == Ordering::Greater,
+                        None => true,
+                    })
+                    .collect::<Vec<_>>();
+
+                if newer.is_empty() {
+                    println!("{}", t!("'{}' is up to date", candidate.name));
+                    continue;
+                }
+
+                choose_tag(&candidate, newer)?
+            }
+        };
+
+        let Some(chosen) = chosen.filter(|chosen| candidate.current.as_ref() != Some(chosen))
+        else {
+            continue;
+        };
+
+        if candidate.kind == Kind::Submodule
+            && !patches::checkout_patched(&candidate.name, &candidate.location, || {
+                checkout_tag(&candidate.location, &chosen)
+            })?
+        {
+            println!(
+                "{}",
+                t!(
+                    "{} failed to check out '{}' in '{}'",
+                    t!("warning:").yellow(),
+                    chosen,
+                    candidate.location
+                )
+            );
+            continue;
+        }
+
+        set_tag(config, &candidate, &chosen);
+
+        println!(
+            "{}",
+            t!(
+                "{} '{}' {} -> {}",
+                t!("Updated").green(),
+                candidate.name,
+                candidate.current.as_deref().unwrap_or("-"),
+                chosen
+            )
+        );
+
+        updated.push(candidate.name);
+    }
+
+    Ok(updated)
+}
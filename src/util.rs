@@ -6,7 +6,48 @@ use std::{
 
 use inquire::validator::{ErrorMessage, Validation};
 
-use crate::{config::Cache, error::ProjectError, ConfigFile, CONFIG_NAME};
+use colored::Colorize;
+
+use crate::{
+    answers,
+    cmake_cache::CMakeCache,
+    config::{Cache, ConfigFile, GlobalConfig},
+    error::ProjectError,
+    generate::join_path,
+    generator,
+    messages::t,
+    output::notice,
+    schema, CONFIG_BACKUP_NAME, CONFIG_NAME,
+};
+
+const SOURCE_EXTENSIONS: [&str; 10] = [
+    "c", "cc", "cpp", "cxx", "h", "hh", "hpp", "hxx", "inl", "ipp",
+];
+
+/// C and C++ files in the project, leaving out build output, dependencies and hidden folders
+pub(crate) fn find_sources(dir: &Path, files: &mut Vec<String>) {
+    let entries = std::fs::read_dir(dir).into_iter().flatten().flatten();
+
+    for entry in entries {
+        let path = entry.path();
+        let name = entry.file_name().to_string_lossy().into_owned();
+
+        if path.is_dir() {
+            if !name.starts_with('.') && name != "build" && name != "external" {
+                find_sources(&path, files);
+            }
+            continue;
+        }
+
+        let is_source = path
+            .extension()
+            .is_some_and(|ext| SOURCE_EXTENSIONS.contains(&ext.to_string_lossy().as_ref()));
+
+        if is_source {
+            files.push(join_path(".", &path.to_string_lossy()));
+        }
+    }
+}
 
 pub fn create_dir(path: &Path) -> Result<(), ProjectError> {
     std::fs::create_dir(path)
@@ -33,35 +74,127 @@ pub fn open_file(path: &Path) -> Result<File, ProjectError> {
     File::open(path).map_err(|err| ProjectError::CannotOpenFile(path.to_owned(), err.to_string()))
 }
 
-pub fn get_config() -> Result<ConfigFile, ProjectError> {
-    if !Path::new(CONFIG_NAME).exists() {
-        return Err(ProjectError::InvalidProjectDirectory);
-    }
+/// Returns the signal that terminated a process, if any
+#[cfg(unix)]
+pub fn exit_signal(status: &std::process::ExitStatus) -> Option<i32> {
+    use std::os::unix::process::ExitStatusExt;
+    status.signal()
+}
 
-    let mut config_file = open_file(&Path::new(CONFIG_NAME))?;
+#[cfg(not(unix))]
+pub fn exit_signal(_status: &std::process::ExitStatus) -> Option<i32> {
+    None
+}
+
+fn read_config(path: &Path) -> Result<ConfigFile, ProjectError> {
+    let mut config_file = open_file(path)?;
 
     let mut buffer = String::new();
     config_file
         .read_to_string(&mut buffer)
-        .map_err(|err| ProjectError::CannotOpenFile(PathBuf::from(CONFIG_NAME), err.to_string()))?;
+        .map_err(|err| ProjectError::CannotOpenFile(path.to_owned(), err.to_string()))?;
 
-    let config: ConfigFile = toml::from_str(&buffer)
-        .map_err(|err| ProjectError::CannotOpenFile(PathBuf::from(CONFIG_NAME), err.to_string()))?;
+    let mut table: toml::Table = toml::from_str(&buffer)
+        .map_err(|err| ProjectError::CannotOpenFile(path.to_owned(), err.to_string()))?;
 
-    Ok(config)
+    schema::migrate(&mut table);
+
+    table
+        .try_into()
+        .map_err(|err| ProjectError::CannotOpenFile(path.to_owned(), err.to_string()))
 }
 
+pub fn get_config() -> Result<ConfigFile, ProjectError> {
+    if !Path::new(CONFIG_NAME).exists() {
+        return Err(ProjectError::InvalidProjectDirectory);
+    }
+
+    read_config(Path::new(CONFIG_NAME)).map_err(|err| match err {
+        ProjectError::CannotOpenFile(_, error)
+            if read_config(Path::new(CONFIG_BACKUP_NAME)).is_ok() =>
+        {
+            ProjectError::CorruptConfig(error)
+        }
+        err => err,
+    })
+}
+
+/// Writes `data` to a file next to `path` and renames it over `path`, so an interrupted
+/// write leaves the old file rather than half of the new one
+pub fn write_atomic(path: &Path, data: &[u8]) -> Result<(), ProjectError> {
+    let mut temp_name = path.as_os_str().to_owned();
+    temp_name.push(".tmp");
+    let temp_path = PathBuf::from(temp_name);
+
+    let written = create_file(&temp_path).and_then(|mut file| {
+        file.write_all(data)
+            .and_then(|_| file.sync_all())
+            .map_err(|err| ProjectError::FailedToCreateFile(temp_path.clone(), err.to_string()))
+    });
+
+    let renamed = written.and_then(|_| {
+        std::fs::rename(&temp_path, path)
+            .map_err(|err| ProjectError::FailedToCreateFile(path.to_owned(), err.to_string()))
+    });
+
+    if renamed.is_err() {
+        std::fs::remove_file(&temp_path).ok();
+    }
+
+    renamed
+}
+
+/// Writes the config, keeping the one it replaces as CMakeMake.toml.bak unless the user
+/// turned backups off. A config that doesn't load isn't worth keeping, so it never
+/// replaces a good backup.
 pub fn write_config(config: ConfigFile) -> Result<(), ProjectError> {
-    let path = PathBuf::from(CONFIG_NAME);
+    let path = Path::new(CONFIG_NAME);
 
-    let mut file = std::fs::OpenOptions::new()
-        .write(true)
-        .create(true)
-        .open(&path)
-        .map_err(|err| ProjectError::CannotOpenFile(path.clone(), err.to_string()))?;
+    let keep_backup = !get_global_config().is_ok_and(|config| config.skip_config_backup);
+    if keep_backup && read_config(path).is_ok() {
+        std::fs::copy(path, CONFIG_BACKUP_NAME).map_err(|err| {
+            ProjectError::FailedToCreateFile(PathBuf::from(CONFIG_BACKUP_NAME), err.to_string())
+        })?;
+    }
+
+    write_atomic(path, toml::to_string(&config).unwrap().as_bytes())
+}
+
+/// Offers to put the backup back when the config doesn't load but its backup does. Without
+/// anyone to ask, commands fail with an error saying where the backup is.
+pub fn recover_config() -> Result<(), ProjectError> {
+    let Err(ProjectError::CorruptConfig(error)) = get_config() else {
+        return Ok(());
+    };
 
-    file.write(toml::to_string(&config).unwrap().as_bytes())
-        .map_err(|err| ProjectError::FailedToCreateFile(path.to_owned(), err.to_string()))?;
+    notice!(
+        "{}",
+        t!(
+            "{} {} can't be read: {}",
+            t!("warning:").yellow(),
+            CONFIG_NAME,
+            error.trim_end()
+        )
+    );
+
+    let key = "config.restore_backup";
+    let restore = answers::can_answer(key)
+        && answers::confirm(
+            key,
+            inquire::Confirm::new(&t!(
+                "Restore it from {}, from before cmm last changed it?",
+                CONFIG_BACKUP_NAME
+            ))
+            .with_default(true)
+            .with_placeholder("Y/n"),
+        )?;
+
+    if restore {
+        std::fs::copy(CONFIG_BACKUP_NAME, CONFIG_NAME).map_err(|err| {
+            ProjectError::FailedToCreateFile(PathBuf::from(CONFIG_NAME), err.to_string())
+        })?;
+        notice!("{} {}", t!("Restored").green().bold(), CONFIG_NAME);
+    }
 
     Ok(())
 }
@@ -70,7 +203,7 @@ pub fn get_cache() -> Result<Cache, ProjectError> {
     let data_dir = directories::BaseDirs::new().unwrap().data_dir().to_owned();
     let cache_path = data_dir.join("cmakemake/cache.toml");
 
-    if cache_path.exists() == false {
+    if !cache_path.exists() {
         return Ok(Cache::default());
     }
 
@@ -110,12 +243,68 @@ pub fn write_cache(cache: Cache) -> Result<(), ProjectError> {
     Ok(())
 }
 
+pub fn global_config_path() -> PathBuf {
+    let data_dir = directories::BaseDirs::new().unwrap().data_dir().to_owned();
+    data_dir.join("cmakemake/config.toml")
+}
+
+pub fn get_global_config() -> Result<GlobalConfig, ProjectError> {
+    let config_path = global_config_path();
+
+    if !config_path.exists() {
+        return Ok(GlobalConfig::default());
+    }
+
+    let mut config_file = open_file(&config_path)?;
+
+    let mut buffer = String::new();
+    config_file
+        .read_to_string(&mut buffer)
+        .map_err(|err| ProjectError::CannotOpenFile(config_path.clone(), err.to_string()))?;
+
+    let config: GlobalConfig = toml::from_str(&buffer)
+        .map_err(|err| ProjectError::CannotOpenFile(config_path.clone(), err.to_string()))?;
+
+    Ok(config)
+}
+
+pub fn write_global_config(config: &GlobalConfig) -> Result<(), ProjectError> {
+    let config_path = global_config_path();
+    let config_dir = config_path.parent().unwrap();
+
+    if !config_dir.exists() {
+        std::fs::create_dir_all(config_dir).map_err(|err| {
+            ProjectError::FailedToCreateFolder(config_dir.to_owned(), err.to_string())
+        })?;
+    }
+
+    write_atomic(&config_path, toml::to_string(config).unwrap().as_bytes())
+}
+
+/// Whether the user asked for removed files to go to the system trash
+pub fn use_trash() -> bool {
+    get_global_config().is_ok_and(|config| config.use_trash)
+}
+
+/// Deletes a file or folder, or moves it to the system trash if the user chose that in
+/// setup. Symlinks are removed themselves, never what they point to.
+pub fn remove_path(path: &Path) -> std::io::Result<()> {
+    if use_trash() {
+        return trash::delete(path).map_err(|err| std::io::Error::other(err.to_string()));
+    }
+
+    match path.symlink_metadata()?.is_dir() {
+        true => std::fs::remove_dir_all(path),
+        false => std::fs::remove_file(path),
+    }
+}
+
 #[derive(Clone)]
 pub struct FolderAutocomplete(pub PathBuf);
 
 impl inquire::Autocomplete for FolderAutocomplete {
     fn get_suggestions(&mut self, input: &str) -> Result<Vec<String>, inquire::CustomUserError> {
-        let mut input_splits = input.split(|char| char == '/');
+        let mut input_splits = input.split('/');
         let last_input = input_splits.next_back().unwrap_or_default();
 
         let path_str = input_splits
@@ -164,10 +353,7 @@ impl inquire::Autocomplete for FolderAutocomplete {
             None => {
                 let val = self.get_suggestions(input)?;
 
-                match val.into_iter().next() {
-                    Some(val) => Some(format!("{}/", val)),
-                    None => None,
-                }
+                val.into_iter().next().map(|val| format!("{}/", val))
             }
         };
 
@@ -207,33 +393,67 @@ pub fn folder_validator(input: &str) -> Result<Validation, inquire::CustomUserEr
 }
 
 pub fn not_own_folder_validator(input: &str) -> Result<Validation, inquire::CustomUserError> {
-    match input {
-        "./" => Ok(Validation::Invalid(ErrorMessage::Custom(
+    match input.trim() {
+        "." | "./" | ".\\" => Ok(Validation::Invalid(ErrorMessage::Custom(
             "Cannot be project director".into(),
         ))),
         _ => Ok(Validation::Valid),
     }
 }
 
-pub fn path_formater(path: &str) -> String {
-    let mut trimmed = path.trim();
-
-    if let Some(val) = path.split_at_checked(2) {
-        if val.0 == "./" {
-            let mut chars = trimmed.chars();
-            chars.next();
-            chars.next();
-            trimmed = chars.as_str()
-        }
+pub fn in_repo_validator(input: &str) -> Result<Validation, inquire::CustomUserError> {
+    match crate::git::is_within_repo(Path::new(input)) {
+        true => Ok(Validation::Valid),
+        false => Ok(Validation::Invalid(ErrorMessage::Custom(
+            "Folder must be inside the current git repository".into(),
+        ))),
     }
+}
 
-    let last_char = trimmed.chars().last().unwrap();
+/// Returns true if removing `path` would delete a monorepo dependency
+pub fn is_protected_path(config: &ConfigFile, path: &Path) -> bool {
+    let path = match path.canonicalize() {
+        Ok(path) => path,
+        Err(_) => return false,
+    };
+
+    config
+        .dependencies
+        .local
+        .iter()
+        .filter(|local| local.monorepo)
+        .filter_map(|local| Path::new(&local.path).canonicalize().ok())
+        .any(|dep_path| dep_path.starts_with(&path))
+}
+
+/// Tidies a path typed into a prompt into the form the config uses on every platform:
+/// forward slashes, no leading `./` and no trailing slash
+pub fn path_formater(path: &str) -> String {
+    let path = path.trim().replace('\\', "/");
+    let path = path.strip_prefix("./").unwrap_or(&path);
 
-    if last_char == '/' {
-        let mut chars = trimmed.chars();
-        chars.next_back();
-        return chars.as_str().to_owned();
+    match path.trim_end_matches('/') {
+        "" if path.starts_with('/') => "/".into(),
+        path => path.to_owned(),
     }
+}
+
+/// Where a target's executable ends up in a build folder. Single config generators put it
+/// straight in the build folder, multi config ones (Visual Studio, Xcode and Ninja
+/// Multi-Config) in a folder per build type. Windows executables end in `.exe`.
+pub fn executable_path(build_dir: &str, build_type: &str, name: &str) -> PathBuf {
+    let file = format!("{}{}", name, std::env::consts::EXE_SUFFIX);
+
+    let single_config = Path::new(build_dir).join(&file);
+    let multi_config = Path::new(build_dir).join(build_type).join(&file);
 
-    trimmed.to_owned()
+    // Without a cache to say which generator was used, go by where the file is
+    let is_multi_config = CMakeCache::read(Path::new(build_dir))
+        .and_then(|cache| cache.get("CMAKE_GENERATOR").map(generator::is_multi_config))
+        .unwrap_or(!single_config.is_file() && multi_config.is_file());
+
+    match is_multi_config {
+        true => multi_config,
+        false => single_config,
+    }
 }
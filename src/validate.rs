@@ -0,0 +1,374 @@
+use std::path::Path;
+
+use colored::Colorize;
+
+use crate::{
+    config::{ConfigFile, LocalType, ProjectFiles, SourceType},
+    error::ProjectError,
+    generate::{join_path, matching_entries},
+    messages::t,
+    output::{self, status},
+    util::{find_sources, get_config},
+    CONFIG_NAME,
+};
+
+const COMPILED_EXTENSIONS: [&str; 4] = ["c", "cc", "cpp", "cxx"];
+
+fn stem(file: &str) -> Option<&str> {
+    Path::new(file).file_stem()?.to_str()
+}
+
+fn is_compiled(file: &str) -> bool {
+    Path::new(file)
+        .extension()
+        .is_some_and(|ext| COMPILED_EXTENSIONS.contains(&ext.to_string_lossy().as_ref()))
+}
+
+/// `cmm validate`. Looks for source files the config builds wrong, which otherwise only
+/// show up as linker errors: sources no entry matches, sources matched by several entries
+/// and so compiled twice, and excluded sources that still have a header declaring them.
+pub fn validate_project() -> Result<(), ProjectError> {
+    let config = get_config()?;
+
+    // Every list of files that ends up in a target, with the folder it is relative to
+    let mut file_sets: Vec<(String, &str, &ProjectFiles)> = vec![(
+        "cmake.files".into(),
+        &config.cmake.source_dir,
+        &config.cmake.files,
+    )];
+    file_sets.extend(config.targets.iter().map(|target| {
+        (
+            format!("targets.{}", target.name),
+            target.path.as_str(),
+            &target.files,
+        )
+    }));
+    file_sets.extend(config.dependencies.local.iter().filter_map(
+        |local| match &local.local_type {
+            LocalType::Source { files, .. } => Some((
+                format!("dependencies.{}", local.name),
+                local.path.as_str(),
+                files,
+            )),
+            LocalType::CMake => None,
+        },
+    ));
+
+    // Tests are globbed into their own executable
+    let tests_dir = config
+        .tests
+        .as_ref()
+        .map(|tests| format!("{}/", join_path(".", &tests.path)));
+
+    let mut files = Vec::new();
+    find_sources(Path::new("."), &mut files);
+    files.sort();
+
+    let mut problems = Vec::new();
+
+    for file in files.iter().filter(|file| is_compiled(file)) {
+        if tests_dir.as_ref().is_some_and(|dir| file.starts_with(dir)) {
+            continue;
+        }
+
+        let entries = file_sets
+            .iter()
+            .flat_map(|(owner, path, files)| {
+                matching_entries(files, path, file)
+                    .into_iter()
+                    .map(move |entry| format!("{} {}", owner, entry))
+            })
+            .collect::<Vec<_>>();
+
+        match entries.len() {
+            0 => {
+                let excluded = file_sets.iter().any(|(_, path, files)| {
+                    files
+                        .exclude_files
+                        .iter()
+                        .any(|excluded| join_path(path, excluded) == *file)
+                });
+
+                let header = files
+                    .iter()
+                    .find(|other| !is_compiled(other) && stem(other) == stem(file));
+
+                match (excluded, header) {
+                    (true, Some(header)) => problems.push(t!(
+                        "'{}' is excluded from the build, but '{}' declares it",
+                        file,
+                        header
+                    )),
+                    (true, None) => {}
+                    (false, _) => problems.push(t!(
+                        "'{}' isn't matched by any source entry, so it isn't built",
+                        file
+                    )),
+                }
+            }
+            1 => {}
+            count => problems.push(t!(
+                "'{}' is matched by {} entries and compiled more than once: {}",
+                file,
+                count,
+                entries.join(", ")
+            )),
+        }
+    }
+
+    for problem in &problems {
+        println!("{} {}", t!("warning:").yellow(), problem);
+    }
+
+    if !problems.is_empty() {
+        return Err(ProjectError::ValidationFailed(problems.len()));
+    }
+
+    println!(
+        "{}",
+        t!(
+            "{} validating project, no problems found",
+            t!("Finished").green().bold()
+        )
+    );
+
+    Ok(())
+}
+
+/// Characters cmake allows in target names
+fn is_target_name(name: &str) -> bool {
+    !name.is_empty()
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || "_.+-".contains(c))
+}
+
+/// A config path on this machine, with `~` for the home folder
+fn expand_home(path: &str) -> String {
+    let home = directories::BaseDirs::new().map(|dirs| dirs.home_dir().to_owned());
+
+    match (path.strip_prefix('~'), home) {
+        (Some(rest), Some(home)) => format!("{}{}", home.display(), rest),
+        _ => path.to_owned(),
+    }
+}
+
+/// Paths in a list of files that don't exist. `key` is where the list is in the config.
+fn check_files(key: &str, path: &str, files: &ProjectFiles, problems: &mut Vec<String>) {
+    let mut check = |entry_key: &str, entry: &str, is_file: bool| {
+        let entry_path = join_path(path, entry);
+        let exists = match is_file {
+            true => Path::new(&entry_path).is_file(),
+            false => Path::new(&entry_path).is_dir(),
+        };
+
+        match (exists, is_file) {
+            (true, _) => {}
+            (false, true) => problems.push(t!(
+                "{}.{}: file '{}' doesn't exist",
+                key,
+                entry_key,
+                entry_path
+            )),
+            (false, false) => problems.push(t!(
+                "{}.{}: folder '{}' doesn't exist",
+                key,
+                entry_key,
+                entry_path
+            )),
+        }
+    };
+
+    for (source_type, entries) in &files.source_files {
+        let is_file = matches!(source_type, SourceType::File);
+        entries
+            .iter()
+            .for_each(|entry| check("source_files", entry, is_file));
+    }
+
+    for (_, entries) in &files.include_dirs {
+        entries
+            .iter()
+            .for_each(|entry| check("include_dirs", entry, false));
+    }
+
+    files
+        .exclude_files
+        .iter()
+        .for_each(|entry| check("exclude_files", entry, true));
+
+    for (_, entries) in &files.roles {
+        entries
+            .iter()
+            .for_each(|entry| check("roles", entry, is_compiled(entry)));
+    }
+}
+
+/// Names `project_dependencies` and target dependencies can use: dependencies, their link
+/// names and the config's own targets
+pub fn link_names(config: &ConfigFile) -> Vec<String> {
+    let dependencies = &config.dependencies;
+
+    config
+        .dependency_names()
+        .into_iter()
+        .chain(
+            dependencies
+                .find
+                .iter()
+                .filter_map(|find| find.custom_link_name.clone()),
+        )
+        .chain(
+            dependencies
+                .fetch
+                .iter()
+                .chain(&dependencies.cpm)
+                .filter_map(|fetch| fetch.custom_link_name.clone()),
+        )
+        .chain(dependencies.conan.iter().map(|conan| conan.link_name()))
+        .chain(config.targets.iter().map(|target| target.name.clone()))
+        .collect()
+}
+
+/// Problems with the config on its own, without looking at the sources
+fn config_problems(config: &ConfigFile) -> Vec<String> {
+    let mut problems = Vec::new();
+
+    if !Path::new(&config.cmake.source_dir).is_dir() {
+        problems.push(t!(
+            "cmake.source_dir: folder '{}' doesn't exist",
+            config.cmake.source_dir
+        ));
+    }
+    check_files(
+        "cmake.files",
+        &config.cmake.source_dir,
+        &config.cmake.files,
+        &mut problems,
+    );
+
+    for resource in &config.cmake.resources {
+        if !Path::new(resource).is_dir() {
+            problems.push(t!("cmake.resources: folder '{}' doesn't exist", resource));
+        }
+    }
+
+    for prefix_path in &config.dependencies.prefix_paths {
+        if !Path::new(&expand_home(prefix_path)).is_dir() {
+            problems.push(t!(
+                "dependencies.prefix_paths: folder '{}' doesn't exist",
+                prefix_path
+            ));
+        }
+    }
+
+    if let Some(tests) = &config.tests {
+        if !Path::new(&tests.path).is_dir() {
+            problems.push(t!("tests.path: folder '{}' doesn't exist", tests.path));
+        }
+    }
+
+    let mut names: Vec<&str> = Vec::new();
+    for local in &config.dependencies.local {
+        let key = format!("dependencies.local.{}", local.name);
+
+        if !is_target_name(&local.name) {
+            problems.push(t!(
+                "{}: '{}' isn't a valid cmake name, use letters, digits and _ . + -",
+                key,
+                local.name
+            ));
+        }
+        if names.contains(&local.name.as_str()) {
+            problems.push(t!("{}: the name is used by another local dependency", key));
+        }
+        names.push(&local.name);
+
+        if !Path::new(&local.path).is_dir() {
+            problems.push(t!("{}: folder '{}' doesn't exist", key, local.path));
+        } else if let LocalType::Source { files, .. } = &local.local_type {
+            check_files(&key, &local.path, files, &mut problems);
+        }
+    }
+
+    let mut target_names = vec![config.project.name.as_str()];
+    for target in &config.targets {
+        let key = format!("targets.{}", target.name);
+
+        if !is_target_name(&target.name) {
+            problems.push(t!(
+                "{}: '{}' isn't a valid cmake name, use letters, digits and _ . + -",
+                key,
+                target.name
+            ));
+        }
+        if target_names.contains(&target.name.as_str()) || names.contains(&target.name.as_str()) {
+            problems.push(t!(
+                "{}: the name is used by the project or another target",
+                key
+            ));
+        }
+        target_names.push(&target.name);
+
+        match Path::new(&target.path).is_dir() {
+            true => check_files(&key, &target.path, &target.files, &mut problems),
+            false => problems.push(t!("{}: folder '{}' doesn't exist", key, target.path)),
+        }
+    }
+
+    // Imported targets like `SDL2::SDL2main` come from packages and can't be checked
+    let known = link_names(config);
+    let linked = config
+        .dependencies
+        .project_dependencies
+        .iter()
+        .map(|name| ("dependencies.project_dependencies".to_owned(), name))
+        .chain(config.targets.iter().flat_map(|target| {
+            target
+                .dependencies
+                .iter()
+                .map(|name| (format!("targets.{}.dependencies", target.name), name))
+        }));
+
+    for (key, name) in linked {
+        if !name.contains("::") && !known.contains(name) {
+            problems.push(t!(
+                "{}: '{}' isn't a dependency or target in the config",
+                key,
+                name
+            ));
+        }
+    }
+
+    problems
+}
+
+/// `cmm check`. Checks the config without generating anything: the paths it names exist,
+/// names are valid and unique, and everything linked is declared. Reports every problem
+/// rather than stopping at the first.
+pub fn check_config() -> Result<(), ProjectError> {
+    let config = get_config()?;
+    let problems = config_problems(&config);
+
+    match output::is_json() {
+        true => output::report("problems", &problems),
+        false => problems
+            .iter()
+            .for_each(|problem| println!("{} {}", t!("warning:").yellow(), problem)),
+    }
+
+    if !problems.is_empty() {
+        return Err(ProjectError::ValidationFailed(problems.len()));
+    }
+
+    status!(
+        "{}",
+        t!(
+            "{} checking {}, no problems found",
+            t!("Finished").green().bold(),
+            CONFIG_NAME
+        )
+    );
+
+    Ok(())
+}
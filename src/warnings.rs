@@ -0,0 +1,426 @@
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    path::Path,
+};
+
+use colored::Colorize;
+
+use crate::{
+    archive::project_root,
+    build::{compile, configure_project, BuildOptions, LAST_BUILD_LOG},
+    config::{ConfigFile, NewWarnings, Warnings},
+    error::ProjectError,
+    lock::ProjectLock,
+    messages::t,
+    output::notice,
+};
+
+pub const BASELINE_NAME: &str = "CMakeMake.warnings.toml";
+
+const HEADER: &str = "# Generated by cmakemake. Compiler warnings per file that builds accept.\n# Run 'cmm warnings baseline' after fixing warnings to lower it.\n\n";
+
+/// Warning counts per file, relative to the project folder
+#[derive(serde::Deserialize, serde::Serialize, Default)]
+pub struct Baseline {
+    #[serde(default)]
+    pub files: BTreeMap<String, usize>,
+}
+
+/// Reads the project's warning baseline. Without one builds don't count warnings at all.
+pub fn read_baseline() -> Result<Option<Baseline>, ProjectError> {
+    let path = Path::new(BASELINE_NAME);
+
+    if !path.exists() {
+        return Ok(None);
+    }
+
+    let contents = std::fs::read_to_string(path)
+        .map_err(|err| ProjectError::CannotOpenFile(path.to_owned(), err.to_string()))?;
+
+    toml::from_str(&contents)
+        .map(Some)
+        .map_err(|err| ProjectError::CannotOpenFile(path.to_owned(), err.to_string()))
+}
+
+fn write_baseline(baseline: &Baseline) -> Result<(), ProjectError> {
+    let path = Path::new(BASELINE_NAME);
+
+    std::fs::write(
+        path,
+        format!("{}{}", HEADER, toml::to_string(baseline).unwrap()),
+    )
+    .map_err(|err| ProjectError::FailedToCreateFile(path.to_owned(), err.to_string()))
+}
+
+/// Drops terminal colour codes, in case a compiler was told to always use them
+fn strip_colours(line: &str) -> String {
+    let mut stripped = String::with_capacity(line.len());
+    let mut chars = line.chars();
+
+    while let Some(c) = chars.next() {
+        match c {
+            '\x1b' => {
+                chars.by_ref().find(|c| c.is_ascii_alphabetic());
+            }
+            c => stripped.push(c),
+        }
+    }
+
+    stripped
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Severity {
+    Warning,
+    Error,
+    /// Notes and context like `file: In function 'main':`
+    Other,
+}
+
+/// What follows the location in compiler messages
+const MARKERS: [(&str, Severity); 9] = [
+    (": warning:", Severity::Warning),
+    (": warning C", Severity::Warning),
+    (": error:", Severity::Error),
+    (": error C", Severity::Error),
+    (": fatal error", Severity::Error),
+    (": note:", Severity::Other),
+    (": In ", Severity::Other),
+    (": At global scope", Severity::Other),
+    (": required from", Severity::Other),
+];
+
+/// The file a compiler message is about, from `file:line:col: warning:` (gcc and clang) or
+/// `file(line,col): warning C4100:` (msvc), and what kind of message it is
+fn located(line: &str) -> Option<(&str, Severity)> {
+    let (start, severity) = MARKERS
+        .iter()
+        .filter_map(|(marker, severity)| Some((line.find(marker)?, *severity)))
+        .min_by_key(|(start, _)| *start)?;
+    let location = &line[..start];
+
+    let file = match location.strip_suffix(')') {
+        Some(msvc) => msvc.rsplit_once('(')?.0,
+        None => location.trim_end_matches(|c: char| c.is_ascii_digit() || c == ':'),
+    };
+
+    Some((file.trim(), severity)).filter(|(file, _)| !file.is_empty())
+}
+
+/// A compiler warning or error, as `--output json` reports them
+#[derive(serde::Serialize)]
+pub struct Diagnostic {
+    pub file: String,
+    pub line: Option<u32>,
+    pub column: Option<u32>,
+    pub severity: &'static str,
+    pub message: String,
+}
+
+fn diagnostic(line: &str) -> Option<Diagnostic> {
+    let (file, severity) = located(line)?;
+    let severity = match severity {
+        Severity::Warning => "warning",
+        Severity::Error => "error",
+        Severity::Other => return None,
+    };
+
+    // `:3:9: warning: text` or `(3,9): warning C4100: text`
+    let rest = &line[line.find(file)? + file.len()..];
+    let (position, message) = rest.split_once(": ")?;
+    let mut numbers = position
+        .split(|c: char| !c.is_ascii_digit())
+        .filter_map(|number| number.parse().ok());
+
+    let message = ["warning: ", "error: ", "fatal error: "]
+        .iter()
+        .find_map(|prefix| message.strip_prefix(prefix))
+        .unwrap_or(message);
+
+    Some(Diagnostic {
+        file: file.replace('\\', "/"),
+        line: numbers.next(),
+        column: numbers.next(),
+        severity,
+        message: message.to_owned(),
+    })
+}
+
+/// The warnings and errors in build output in the order they came. A warning in a header
+/// shows up once for every source including it, so repeats are left out.
+pub fn diagnostics(output: &str) -> Vec<Diagnostic> {
+    let mut seen = BTreeSet::new();
+
+    output
+        .lines()
+        .map(|line| strip_colours(line).trim().to_owned())
+        .filter(|line| seen.insert(line.clone()))
+        .filter_map(|line| diagnostic(&line))
+        .collect()
+}
+
+/// The file a warning is in
+fn warning_file(line: &str) -> Option<&str> {
+    located(line)
+        .filter(|(_, severity)| *severity == Severity::Warning)
+        .map(|(file, _)| file)
+}
+
+/// A file from compiler output relative to the project folder. `None` for files outside it.
+fn relative_path(file: &str, prefix: &str) -> Option<String> {
+    let file = file.replace('\\', "/");
+
+    match file.strip_prefix(prefix) {
+        Some(relative) => Some(relative.to_owned()),
+        None if Path::new(&file).is_absolute() || file.starts_with("..") => None,
+        None => Some(file.trim_start_matches("./").to_owned()),
+    }
+}
+
+/// Counts the warnings per project file in build output. A warning in a header shows up
+/// once for every source including it, so identical lines only count once. Warnings in
+/// files outside the project or in build/ belong to dependencies and are left out.
+pub fn count_warnings(output: &str, root: &str) -> BTreeMap<String, usize> {
+    let prefix = format!("{}/", root);
+
+    let unique = output
+        .lines()
+        .map(strip_colours)
+        .map(|line| line.trim().to_owned())
+        .collect::<BTreeSet<_>>();
+
+    let mut counts = BTreeMap::new();
+
+    for line in &unique {
+        let Some(file) = warning_file(line) else {
+            continue;
+        };
+
+        let Some(file) = relative_path(file, &prefix) else {
+            continue;
+        };
+
+        if file.starts_with("build/") {
+            continue;
+        }
+
+        *counts.entry(file).or_insert(0) += 1;
+    }
+
+    counts
+}
+
+/// Tells project files from dependency files in compiler output. Dependencies are
+/// everything in external/, build/ (where fetch and cpm dependencies are downloaded), local
+/// dependency folders and outside the project.
+pub struct Origins {
+    prefix: String,
+    dependency_dirs: Vec<String>,
+}
+
+impl Origins {
+    pub fn new(config: &ConfigFile) -> Result<Self, ProjectError> {
+        let mut dependency_dirs = vec!["external/".to_owned(), "build/".to_owned()];
+        dependency_dirs.extend(config.dependencies.local.iter().map(|local| {
+            let path = local.path.replace('\\', "/");
+            format!("{}/", path.trim_start_matches("./").trim_end_matches('/'))
+        }));
+
+        Ok(Self {
+            prefix: format!("{}/", project_root()?),
+            dependency_dirs,
+        })
+    }
+
+    fn is_dependency(&self, file: &str) -> bool {
+        match relative_path(file, &self.prefix) {
+            Some(file) => self.dependency_dirs.iter().any(|dir| file.starts_with(dir)),
+            None => true,
+        }
+    }
+}
+
+/// Prints compiler output as it comes, leaving out messages about dependency files when
+/// hiding them. A message's source excerpt and notes are indented below it and go with it,
+/// the `In file included from` lines above it are held back until it's known where it is.
+pub struct DependencyFilter<'a> {
+    origins: &'a Origins,
+    hide: bool,
+    hiding: bool,
+    included_from: Vec<String>,
+}
+
+impl<'a> DependencyFilter<'a> {
+    pub fn new(origins: &'a Origins, hide: bool) -> Self {
+        Self {
+            origins,
+            hide,
+            hiding: false,
+            included_from: Vec::new(),
+        }
+    }
+
+    pub fn print(&mut self, line: &str) {
+        if !self.hide {
+            println!("{}", line);
+            return;
+        }
+
+        let plain = strip_colours(line);
+        let trimmed = plain.trim_start();
+
+        let continues_includes = !self.included_from.is_empty() && trimmed.starts_with("from ");
+        if trimmed.starts_with("In file included from") || continues_includes {
+            self.included_from.push(line.to_owned());
+            return;
+        }
+
+        let indented = trimmed.len() < plain.len();
+        self.hiding = match located(&plain) {
+            Some((file, _)) => self.origins.is_dependency(file),
+            None => self.hiding && indented,
+        };
+
+        let included_from = std::mem::take(&mut self.included_from);
+        if !self.hiding {
+            included_from.iter().for_each(|line| println!("{}", line));
+            println!("{}", line);
+        }
+    }
+
+    /// Prints include lines still held back at the end of the output
+    pub fn finish(self) {
+        self.included_from
+            .iter()
+            .for_each(|line| println!("{}", line));
+    }
+}
+
+/// Prints how many warnings and errors in build output come from the project and how many
+/// from dependencies. Repeats of the same line, from headers included more than once,
+/// only count once.
+pub fn report_origins(output: &str, origins: &Origins, settings: &Warnings) {
+    let unique = output
+        .lines()
+        .map(strip_colours)
+        .map(|line| line.trim().to_owned())
+        .collect::<BTreeSet<_>>();
+
+    // [project, dependencies] by [warnings, errors]
+    let mut counts = [[0; 2]; 2];
+    for line in &unique {
+        let Some((file, severity)) = located(line) else {
+            continue;
+        };
+
+        let origin = origins.is_dependency(file) as usize;
+        match severity {
+            Severity::Warning => counts[origin][0] += 1,
+            Severity::Error => counts[origin][1] += 1,
+            Severity::Other => {}
+        }
+    }
+
+    let [project, dependencies] = counts;
+
+    if settings.report {
+        println!("\n{}", t!("Diagnostics by origin:"));
+        println!(
+            "\t{: <16}{}",
+            t!("project"),
+            t!("{} warnings, {} errors", project[0], project[1])
+        );
+    }
+
+    match settings.hide_dependencies {
+        true if dependencies != [0, 0] => println!(
+            "\t{}",
+            t!(
+                "{} warnings and {} errors from dependencies hidden, see {}",
+                dependencies[0],
+                dependencies[1],
+                LAST_BUILD_LOG
+            )
+            .dimmed()
+        ),
+        false if settings.report => println!(
+            "\t{: <16}{}",
+            t!("dependencies"),
+            t!("{} warnings, {} errors", dependencies[0], dependencies[1])
+        ),
+        _ => {}
+    }
+}
+
+/// Compares the warnings in build output with the baseline. Only files that were compiled
+/// show up in the output, so files missing from it are never treated as fixed.
+pub fn check_new_warnings(
+    baseline: &Baseline,
+    output: &str,
+    on_new: NewWarnings,
+) -> Result<(), ProjectError> {
+    let counts = count_warnings(output, &project_root()?);
+
+    let new = counts
+        .iter()
+        .filter_map(|(file, &count)| {
+            let allowed = baseline.files.get(file).copied().unwrap_or(0);
+            (count > allowed).then_some((file, count, allowed))
+        })
+        .collect::<Vec<_>>();
+
+    if new.is_empty() {
+        return Ok(());
+    }
+
+    notice!();
+    for (file, count, allowed) in &new {
+        notice!(
+            "{}",
+            t!(
+                "{} '{}' has {} warnings, {} more than the baseline",
+                t!("warning:").yellow(),
+                file,
+                count,
+                count - allowed
+            )
+        );
+    }
+
+    match on_new {
+        NewWarnings::Fail => Err(ProjectError::NewWarnings(
+            new.into_iter().map(|(file, ..)| file.clone()).collect(),
+        )),
+        NewWarnings::Warn => Ok(()),
+    }
+}
+
+/// `cmm warnings baseline`. Rebuilds the whole project so every file is compiled, then
+/// records how many warnings each file has. Later builds fail, or warn with
+/// `warnings.on_new = "warn"`, when a file has more.
+pub fn record_baseline(options: &BuildOptions) -> Result<(), ProjectError> {
+    println!("{}", t!("Rebuilding project to count warnings"));
+
+    let _lock = ProjectLock::acquire(options.wait)?;
+
+    let build_type = configure_project(options)?;
+    let output = compile(options, &build_type, true, true)?.unwrap_or_default();
+
+    let baseline = Baseline {
+        files: count_warnings(&output, &project_root()?),
+    };
+    write_baseline(&baseline)?;
+
+    println!(
+        "\n{}",
+        t!(
+            "{} recording {} warnings in {} files to {}",
+            t!("Finished").green().bold(),
+            baseline.files.values().sum::<usize>(),
+            baseline.files.len(),
+            BASELINE_NAME
+        )
+    );
+
+    Ok(())
+}
@@ -0,0 +1,134 @@
+use std::{
+    collections::{BTreeSet, HashMap},
+    path::{Path, PathBuf},
+    time::{Duration, SystemTime},
+};
+
+use colored::Colorize;
+
+use cmakemake_core::{
+    build::{build_project, BuildOptions},
+    config::ConfigFile,
+    error::{DisplayError, ProjectError},
+    messages::t,
+    util::get_config,
+    CONFIG_NAME,
+};
+
+const POLL_INTERVAL: Duration = Duration::from_millis(250);
+pub const DEFAULT_DEBOUNCE_MS: u64 = 300;
+
+/// Folders to watch for this config. Include dirs are relative to the folder their
+/// files are.
+fn watched_paths(config: &ConfigFile) -> BTreeSet<PathBuf> {
+    let mut paths = BTreeSet::from([PathBuf::from(CONFIG_NAME)]);
+
+    let source_dir = Path::new(&config.cmake.source_dir);
+    paths.insert(source_dir.to_owned());
+    config
+        .cmake
+        .files
+        .include_dirs
+        .iter()
+        .flat_map(|(_, dirs)| dirs)
+        .for_each(|dir| {
+            paths.insert(source_dir.join(dir));
+        });
+
+    config.targets.iter().for_each(|target| {
+        paths.insert(PathBuf::from(&target.path));
+    });
+
+    if let Some(tests) = &config.tests {
+        paths.insert(PathBuf::from(&tests.path));
+    }
+
+    paths
+}
+
+/// Modified times of every file under `path`. Hidden folders and build output are skipped
+/// so editor swap files and our own builds don't trigger rebuilds.
+fn collect_times(path: &Path, times: &mut HashMap<PathBuf, SystemTime>) {
+    let name = path.file_name().unwrap_or_default().to_string_lossy();
+    if (name.starts_with('.') && name.len() > 1) || name == "build" {
+        return;
+    }
+
+    if path.is_dir() {
+        std::fs::read_dir(path)
+            .into_iter()
+            .flatten()
+            .filter_map(|entry| entry.ok())
+            .for_each(|entry| collect_times(&entry.path(), times));
+        return;
+    }
+
+    if let Ok(modified) = path.metadata().and_then(|meta| meta.modified()) {
+        times.insert(path.to_owned(), modified);
+    }
+}
+
+fn snapshot(paths: &BTreeSet<PathBuf>) -> HashMap<PathBuf, SystemTime> {
+    let mut times = HashMap::new();
+    paths
+        .iter()
+        .for_each(|path| collect_times(&path.canonicalize().unwrap_or(path.clone()), &mut times));
+    times
+}
+
+/// Blocks until a watched file changes, then until nothing has changed for `debounce` so
+/// saving several files at once only builds once
+fn wait_for_change(paths: &BTreeSet<PathBuf>, debounce: Duration) {
+    let before = snapshot(paths);
+
+    let mut current = loop {
+        std::thread::sleep(POLL_INTERVAL);
+
+        let current = snapshot(paths);
+        if current != before {
+            break current;
+        }
+    };
+
+    loop {
+        std::thread::sleep(debounce);
+
+        let next = snapshot(paths);
+        if next == current {
+            return;
+        }
+        current = next;
+    }
+}
+
+fn clear_screen() {
+    print!("\x1b[2J\x1b[H");
+}
+
+/// `cmm watch`. Rebuilds the project whenever its sources or config change.
+pub fn watch_project(
+    options: BuildOptions,
+    clear: bool,
+    debounce: Duration,
+) -> Result<(), ProjectError> {
+    loop {
+        if clear {
+            clear_screen();
+        }
+
+        // Failed builds are reported and watching carries on
+        build_project(&options).display_error();
+
+        // Reread every time as the config can add folders to watch
+        let paths = get_config()
+            .map(|config| watched_paths(&config))
+            .unwrap_or_else(|_| BTreeSet::from([PathBuf::from(CONFIG_NAME)]));
+
+        println!(
+            "\n{}",
+            t!("Watching for changes. Press Ctrl-C to stop.").cyan()
+        );
+
+        wait_for_change(&paths, debounce);
+    }
+}
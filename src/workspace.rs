@@ -0,0 +1,224 @@
+use std::{
+    hash::{Hash, Hasher},
+    io::{Read, Write},
+    path::{Path, PathBuf},
+};
+
+use colored::Colorize;
+
+use crate::{
+    answers, env_file,
+    error::ProjectError,
+    util::{create_dir, create_file, open_file, write_atomic, write_file},
+};
+
+pub const WORKSPACE_CONFIG_NAME: &str = "CMakeMakeWorkspace.toml";
+
+#[derive(serde::Deserialize, serde::Serialize, Default, Hash)]
+#[serde(default)]
+pub struct WorkspaceConfig {
+    pub workspace: Workspace,
+}
+
+#[derive(serde::Deserialize, serde::Serialize, Hash)]
+#[serde(default)]
+pub struct Workspace {
+    pub name: String,
+    pub minimum_required: ordered_float::OrderedFloat<f64>,
+    /// Member project folders, relative to the workspace root
+    pub members: Vec<String>,
+}
+
+impl Default for Workspace {
+    fn default() -> Self {
+        Self {
+            name: String::from("Unnamed Workspace"),
+            minimum_required: ordered_float::OrderedFloat(3.15),
+            members: Vec::new(),
+        }
+    }
+}
+
+pub fn is_workspace_root() -> bool {
+    Path::new(WORKSPACE_CONFIG_NAME).exists()
+}
+
+pub fn get_workspace_config() -> Result<WorkspaceConfig, ProjectError> {
+    let path = PathBuf::from(WORKSPACE_CONFIG_NAME);
+
+    let mut file = open_file(&path)?;
+
+    let mut buffer = String::new();
+    file.read_to_string(&mut buffer)
+        .map_err(|err| ProjectError::CannotOpenFile(path.clone(), err.to_string()))?;
+
+    toml::from_str(&buffer).map_err(|err| ProjectError::CannotOpenFile(path, err.to_string()))
+}
+
+pub fn write_workspace_config(config: &WorkspaceConfig) -> Result<(), ProjectError> {
+    let path = PathBuf::from(WORKSPACE_CONFIG_NAME);
+
+    write_atomic(&path, toml::to_string(config).unwrap().as_bytes())
+}
+
+/// Finds a member by its folder path or by the name of its final folder
+fn find_member(config: &WorkspaceConfig, package: &str) -> Result<String, ProjectError> {
+    let package = package.trim_end_matches('/');
+
+    config
+        .workspace
+        .members
+        .iter()
+        .find(|member| {
+            *member == package
+                || Path::new(member)
+                    .file_name()
+                    .is_some_and(|name| name == package)
+        })
+        .cloned()
+        .ok_or(ProjectError::UnknownMember(package.to_owned()))
+}
+
+/// Members to build from the workspace root: the one chosen with `-p`, otherwise all of them
+pub fn selected_members(package: Option<&str>) -> Result<Vec<String>, ProjectError> {
+    let config = get_workspace_config()?;
+
+    match package {
+        Some(package) => Ok(vec![find_member(&config, package)?]),
+        None => Ok(config.workspace.members),
+    }
+}
+
+/// The single member to run from the workspace root. Asks when there's more than one
+/// and `-p` wasn't given.
+pub fn pick_member(package: Option<&str>) -> Result<String, ProjectError> {
+    let config = get_workspace_config()?;
+
+    if let Some(package) = package {
+        return find_member(&config, package);
+    }
+
+    match config.workspace.members.len() {
+        0 => Err(ProjectError::UnknownMember(String::new())),
+        1 => Ok(config.workspace.members[0].clone()),
+        _ if answers::can_answer("run.member") => Ok(answers::select(
+            "run.member",
+            inquire::Select::new("Which member do you want to run?", config.workspace.members),
+        )?
+        .value),
+        _ => Err(ProjectError::MissingArgument("-p <MEMBER>".into())),
+    }
+}
+
+/// Runs `f` from inside a member's folder with its env files loaded, returning to the
+/// workspace root afterwards
+pub fn in_member<T>(
+    member: &str,
+    f: impl FnOnce() -> Result<T, ProjectError>,
+) -> Result<T, ProjectError> {
+    let root = std::env::current_dir().map_err(|_| ProjectError::InvalidProjectDirectory)?;
+
+    std::env::set_current_dir(member)
+        .map_err(|_| ProjectError::UnknownMember(member.to_owned()))?;
+
+    // Members can have env files of their own
+    let applied = env_file::apply();
+    let result = f();
+    env_file::unapply(&applied);
+
+    std::env::set_current_dir(root).map_err(|_| ProjectError::InvalidProjectDirectory)?;
+
+    result
+}
+
+/// `cmm workspace add <PATH>`. Registers a project folder as a member of the workspace in
+/// the current directory.
+pub fn add_existing_member(member: &str) -> Result<(), ProjectError> {
+    if !is_workspace_root() {
+        return Err(ProjectError::NotInWorkspace);
+    }
+
+    if !Path::new(member).join(crate::CONFIG_NAME).exists() {
+        return Err(ProjectError::UnknownMember(member.to_owned()));
+    }
+
+    add_member(member)
+}
+
+/// `cmm workspace new <NAME>`
+pub fn new_workspace(name: &str) -> Result<(), ProjectError> {
+    let path = PathBuf::from(name);
+    create_dir(&path)?;
+
+    let mut config = WorkspaceConfig::default();
+    config.workspace.name = name.to_owned();
+
+    let root = std::env::current_dir().map_err(|_| ProjectError::InvalidProjectDirectory)?;
+    std::env::set_current_dir(&path)
+        .map_err(|err| ProjectError::FailedToCreateFolder(path.clone(), err.to_string()))?;
+
+    let result = write_workspace_config(&config).and_then(|_| generate_workspace_cmake(&config));
+
+    std::env::set_current_dir(root).map_err(|_| ProjectError::InvalidProjectDirectory)?;
+    result?;
+
+    println!(
+        "{} creating workspace at {}",
+        "Finished".green().bold(),
+        path.canonicalize().unwrap_or(path).display()
+    );
+    println!("Run 'cmm new <NAME>' inside it to add member projects");
+
+    Ok(())
+}
+
+/// Registers a new member and regenerates the workspace CMakeLists.txt
+pub fn add_member(member: &str) -> Result<(), ProjectError> {
+    let mut config = get_workspace_config()?;
+
+    let member = member.trim_end_matches('/').to_owned();
+
+    if !config.workspace.members.contains(&member) {
+        config.workspace.members.push(member.clone());
+    }
+
+    write_workspace_config(&config)?;
+    generate_workspace_cmake(&config)?;
+
+    println!(
+        "{} '{}' to workspace '{}'",
+        "Added".green().bold(),
+        member,
+        config.workspace.name
+    );
+
+    Ok(())
+}
+
+/// Writes the root CMakeLists.txt that pulls in every member project
+pub fn generate_workspace_cmake(config: &WorkspaceConfig) -> Result<(), ProjectError> {
+    let path = Path::new("CMakeLists.txt");
+    let mut file = create_file(path)?;
+
+    let mut hasher = std::hash::DefaultHasher::new();
+    config.hash(&mut hasher);
+
+    let mut contents = String::new();
+    contents.push_str(&format!("# {}\n\n", hasher.finish()));
+    contents.push_str(&format!(
+        "cmake_minimum_required(VERSION {})\n",
+        config.workspace.minimum_required
+    ));
+    contents.push_str(&format!("project(\"{}\")\n", config.workspace.name));
+
+    contents.push_str("\n#Workspace Members:\n");
+    config.workspace.members.iter().for_each(|member| {
+        contents.push_str(&format!("add_subdirectory({})\n", member));
+    });
+
+    write_file(path, &mut file, contents.as_bytes())?;
+    file.flush()
+        .map_err(|err| ProjectError::FailedToCreateFile(path.to_owned(), err.to_string()))?;
+
+    Ok(())
+}
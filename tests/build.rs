@@ -0,0 +1,1326 @@
+#![cfg(unix)]
+
+mod common;
+
+use std::{path::Path, process::Command};
+
+use common::TestProject;
+
+#[test]
+fn build_configures_and_compiles() {
+    let project = TestProject::new("demo");
+
+    project.cmm_ok(&["build"]);
+
+    assert!(project.dir().join("CMakeLists.txt").exists());
+    assert_eq!(
+        project.cmake_calls(),
+        [
+            "-B build/debug -DCMM_PROFILE=debug -DCMAKE_BUILD_TYPE=Debug -DCMM_CACHE_LAUNCHER=ON",
+            "--build build/debug --config Debug",
+        ]
+    );
+}
+
+#[test]
+fn build_sanitizer_profiles() {
+    let project = TestProject::new("demo");
+
+    project.edit_config(|config| {
+        let mut fuzz = toml::Table::new();
+        fuzz.insert("sanitize".into(), vec!["address", "undefined"].into());
+        config["profiles"]
+            .as_table_mut()
+            .unwrap()
+            .insert("fuzz".into(), fuzz.into());
+    });
+
+    project.cmm_ok(&["build", "--asan"]);
+    project.cmm_ok(&["build", "--profile", "fuzz"]);
+
+    assert_eq!(
+        project.cmake_calls(),
+        [
+            "-B build/asan -DCMM_PROFILE=asan -DCMAKE_BUILD_TYPE=Debug -DCMM_CACHE_LAUNCHER=ON",
+            "--build build/asan --config Debug",
+            "-B build/fuzz -DCMM_PROFILE=fuzz -DCMAKE_BUILD_TYPE=Debug -DCMM_CACHE_LAUNCHER=ON",
+            "--build build/fuzz --config Debug",
+        ]
+    );
+
+    let cmake = project.read("CMakeLists.txt");
+    assert!(cmake.contains(
+        r#"string(APPEND CMAKE_EXE_LINKER_FLAGS_DEBUG " -fsanitize=address,undefined")"#
+    ));
+}
+
+#[test]
+fn build_release_target() {
+    let project = TestProject::new("demo");
+
+    project.cmm_ok(&["build", "demo", "--release"]);
+
+    assert_eq!(
+        project.cmake_calls(),
+        [
+            "-B build/release -DCMM_PROFILE=release -DCMAKE_BUILD_TYPE=Release -DCMM_CACHE_LAUNCHER=ON",
+            "--build build/release --config Release --target demo",
+        ]
+    );
+}
+
+#[test]
+fn build_with_generator() {
+    let project = TestProject::new("demo");
+
+    // Generators cmakemake doesn't know are passed through unchecked
+    project.edit_config(|config| {
+        config["cmake"]
+            .as_table_mut()
+            .unwrap()
+            .insert("generator".into(), "Custom Generator".into());
+    });
+
+    project.cmm_ok(&["build"]);
+    assert!(project.cmake_calls()[0].ends_with("-G Custom Generator"));
+
+    project.cmm_ok(&["build", "-G", "Other Generator"]);
+    assert!(project.cmake_calls()[2].ends_with("-G Other Generator"));
+}
+
+#[test]
+fn build_without_cache_launcher() {
+    let project = TestProject::new("demo");
+
+    project.cmm_ok(&["build", "--no-cache-launcher"]);
+    assert!(project.cmake_calls()[0].ends_with("-DCMM_CACHE_LAUNCHER=OFF"));
+
+    project.edit_config(|config| {
+        config["cmake"]
+            .as_table_mut()
+            .unwrap()
+            .insert("cache_launcher".into(), "none".into());
+    });
+
+    project.cmm_ok(&["build"]);
+    assert!(!project.cmake_calls()[2].contains("CMM_CACHE_LAUNCHER"));
+    assert!(!project.read("CMakeLists.txt").contains("#Compiler Cache:"));
+}
+
+#[test]
+fn history_records_builds() {
+    let project = TestProject::new("demo");
+    assert!(project
+        .cmm_ok(&["history"])
+        .contains("No builds recorded yet"));
+
+    project.cmm_ok(&["build"]);
+    project.write("build/debug/demo", &"x".repeat(2048));
+    project.cmm_ok(&["build"]);
+    project.cmm_ok(&["build", "--release"]);
+
+    let history = project.cmm_ok(&["history"]);
+    let builds = history
+        .lines()
+        .filter(|line| line.starts_with(|c: char| c.is_ascii_digit()))
+        .collect::<Vec<_>>();
+    assert_eq!(builds.len(), 3);
+    assert!(builds[0].contains("debug") && builds[0].ends_with("-"));
+    assert!(builds[1].ends_with("2.0 KiB"));
+    assert!(builds[2].contains("release"));
+    assert!(history.contains("debug: 2 builds"));
+    assert!(history.contains("release: 1 builds"));
+
+    let release = project.cmm_ok(&["history", "--profile", "release"]);
+    assert!(!release.contains("debug"));
+}
+
+#[test]
+fn build_uses_cmake_environment() {
+    let project = TestProject::new("demo");
+    let toolchain = project.dir().join("toolchain.cmake");
+    let toolchain = toolchain.to_str().unwrap();
+
+    let output = project.cmm_env(
+        &["build"],
+        &[
+            ("CMAKE_TOOLCHAIN_FILE", toolchain),
+            ("CXX", "clang++"),
+            ("CMAKE_GENERATOR", "Ninja"),
+        ],
+    );
+    common::assert_success(&output);
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains(&format!(
+        "Using CMAKE_TOOLCHAIN_FILE={toolchain} from the environment"
+    )));
+    assert!(stdout.contains("Using CXX=clang++ from the environment"));
+    assert!(stdout.contains("Using CMAKE_GENERATOR=Ninja from the environment"));
+    assert!(
+        project.cmake_calls()[0].ends_with(&format!("-G Ninja -DCMAKE_TOOLCHAIN_FILE={toolchain}"))
+    );
+
+    let output = project.cmm_env(&["build", "-G", "Other"], &[("CMAKE_GENERATOR", "Ninja")]);
+    assert!(String::from_utf8_lossy(&output.stdout)
+        .contains("Ignoring CMAKE_GENERATOR=Ninja from the environment"));
+}
+
+#[test]
+fn configure_failure_troubleshooting() {
+    let project = TestProject::new("demo");
+
+    project.set_configure_error(
+        r#"-- Could NOT find PNG (missing: PNG_LIBRARY)
+CMake Error at CMakeLists.txt:20 (find_package):
+  By not providing "Findglfw3.cmake" in CMAKE_MODULE_PATH this project has
+  asked CMake to find a package configuration file provided by "glfw3", but
+  CMake did not find one.
+"#,
+    );
+
+    let output = project.cmm_env(
+        &["build"],
+        &[("CMM_ANSWER_BUILD_TRIAGE_PREFIX_PATH", "~/opt/glfw")],
+    );
+    assert!(String::from_utf8_lossy(&output.stderr).contains("error:"));
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("find_package couldn't find glfw3"));
+    assert!(!stdout.contains("couldn't find PNG"));
+    assert!(project.read("CMakeMake.toml").contains("~/opt/glfw"));
+
+    // Compiler checks point at cmake's own log
+    project.set_configure_error(
+        "  The C compiler\n\n    \"/usr/bin/cc\"\n\n  is not able to compile a simple test program.\n",
+    );
+    project.write(
+        "build/debug/CMakeFiles/CMakeError.log",
+        "Determining if the C compiler works failed with the following output:\n\
+         cc: error: unrecognized command-line option '-fbroken'\n",
+    );
+
+    let output = project.cmm(&["build"]);
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("The compiler can't build a test program"));
+    assert!(stdout.contains("unrecognized command-line option '-fbroken'"));
+}
+
+#[test]
+fn build_keeps_no_hash_comment() {
+    let project = TestProject::new("demo");
+
+    project.cmm_ok(&["cmake", "--no-hash-comment"]);
+    let generated = project.read("CMakeLists.txt");
+
+    let output = project.cmm_ok(&["build"]);
+    assert!(!output.contains("out of date"));
+    assert_eq!(project.read("CMakeLists.txt"), generated);
+
+    project.cmm_ok(&["add", "find", "Threads"]);
+
+    let output = project.cmm_ok(&["build"]);
+    assert!(output.contains("out of date"));
+
+    let regenerated = project.read("CMakeLists.txt");
+    assert!(regenerated.starts_with("# Generated by cmakemake"));
+    assert!(regenerated.contains("find_package(Threads)"));
+}
+
+#[test]
+fn build_asks_before_replacing_edited_cmakelists() {
+    let project = TestProject::new("demo");
+    let handwritten =
+        "cmake_minimum_required(VERSION 3.20)\nproject(demo)\nadd_executable(demo src/main.cpp)\n";
+    project.write("CMakeLists.txt", handwritten);
+
+    let output = project.cmm(&["build"]);
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("was edited by hand or not generated by cmakemake"));
+    assert!(stdout.contains("-add_executable(demo src/main.cpp)"));
+    assert!(stdout.contains("+#Project Files:"));
+    assert!(String::from_utf8_lossy(&output.stderr).contains("not replacing the hand-edited"));
+    assert_eq!(project.read("CMakeLists.txt"), handwritten);
+    assert!(project.cmake_calls().is_empty());
+
+    let output = project.cmm_env(&["build"], &[("CMM_ANSWER_BUILD_REPLACE_CMAKELISTS", "no")]);
+    assert!(String::from_utf8_lossy(&output.stderr).contains("not replacing the hand-edited"));
+    assert_eq!(project.read("CMakeLists.txt"), handwritten);
+
+    project.cmm_ok(&["build", "--force"]);
+    assert_eq!(project.read("CMakeLists.txt.bak"), handwritten);
+    assert!(project.read("CMakeLists.txt").contains("#Project Files:"));
+
+    // Generated files are regenerated without asking
+    project.cmm_ok(&["add", "find", "Threads"]);
+    let output = project.cmm_ok(&["build"]);
+    assert!(!output.contains("edited by hand"));
+}
+
+#[test]
+fn unknown_profile() {
+    let project = TestProject::new("demo");
+
+    let output = project.cmm(&["build", "--profile", "missing"]);
+    assert!(String::from_utf8_lossy(&output.stderr).contains("no build profile named 'missing'"));
+    assert!(project.cmake_calls().is_empty());
+}
+
+#[test]
+fn run_forwards_arguments() {
+    use std::os::unix::fs::PermissionsExt;
+
+    let project = TestProject::new("demo");
+
+    let binary = project.dir().join("build/debug/demo");
+    project.write(
+        "build/debug/demo",
+        "#!/bin/sh\nfor arg in \"$@\"; do echo \"arg: $arg\"; done\n",
+    );
+    std::fs::set_permissions(&binary, std::fs::Permissions::from_mode(0o755)).unwrap();
+
+    let output = project.cmm_ok(&["run", "skip_build", "--", "one", "two words", "--release"]);
+    assert!(output.contains("arg: one\narg: two words\narg: --release\n"));
+    assert!(project.cmake_calls().is_empty());
+}
+
+#[test]
+fn run_finds_multi_config_binaries() {
+    use std::os::unix::fs::PermissionsExt;
+
+    let project = TestProject::new("demo");
+
+    // Visual Studio, Xcode and Ninja Multi-Config put binaries in a folder per build type
+    let binary = project.dir().join("build/release/Release/demo");
+    project.write(
+        "build/release/Release/demo",
+        "#!/bin/sh\necho multi config\n",
+    );
+    std::fs::set_permissions(&binary, std::fs::Permissions::from_mode(0o755)).unwrap();
+
+    let output = project.cmm_ok(&["run", "skip_build", "--release"]);
+    assert!(output.contains("multi config"));
+
+    // The generator in the cache wins over a binary left from a single config build
+    project.write("build/release/demo", "#!/bin/sh\necho single config\n");
+    std::fs::set_permissions(
+        project.dir().join("build/release/demo"),
+        std::fs::Permissions::from_mode(0o755),
+    )
+    .unwrap();
+    project.write(
+        "build/release/CMakeCache.txt",
+        "CMAKE_GENERATOR:INTERNAL=Visual Studio 17 2022\n",
+    );
+
+    let output = project.cmm_ok(&["run", "skip_build", "--release"]);
+    assert!(output.contains("multi config"));
+}
+
+#[test]
+fn run_sanitize_exports_runtime_options() {
+    use std::os::unix::fs::PermissionsExt;
+
+    let project = TestProject::new("demo");
+
+    let binary = project.dir().join("build/debug/demo");
+    project.write(
+        "build/debug/demo",
+        "#!/bin/sh\necho \"asan: $ASAN_OPTIONS\"\necho \"lsan: $LSAN_OPTIONS\"\n",
+    );
+    std::fs::set_permissions(&binary, std::fs::Permissions::from_mode(0o755)).unwrap();
+
+    project.cmm_ok(&["scaffold", "sanitizer-suppressions"]);
+    assert!(project.read("sanitizers/lsan.supp").contains("leak:"));
+
+    project.edit_config(|config| {
+        let address = config["sanitizers"]["address"].as_table_mut().unwrap();
+        address.insert("options".into(), toml::Value::from(vec!["halt_on_error=0"]));
+    });
+
+    let lsan = project
+        .dir()
+        .join("sanitizers/lsan.supp")
+        .canonicalize()
+        .unwrap();
+
+    let output = project.cmm_ok(&["run", "skip_build"]);
+    assert!(output.contains("asan: \n"));
+
+    let output = project.cmm_ok(&["run", "skip_build", "--sanitize"]);
+    assert!(output.contains("asan: halt_on_error=0:suppressions="));
+    assert!(output.contains(&format!("lsan: suppressions={}\n", lsan.display())));
+}
+
+#[test]
+fn run_loads_env_files() {
+    use std::os::unix::fs::PermissionsExt;
+
+    let project = TestProject::new("demo");
+
+    let binary = project.dir().join("build/debug/demo");
+    project.write(
+        "build/debug/demo",
+        "#!/bin/sh\necho \"key: $DEMO_KEY\"\necho \"plugins: $DEMO_PLUGINS\"\n",
+    );
+    std::fs::set_permissions(&binary, std::fs::Permissions::from_mode(0o755)).unwrap();
+
+    project.write(
+        ".env",
+        "# Shared settings\nexport DEMO_KEY=shared\nDEMO_PLUGINS=\"a b\" # quoted\nnot an assignment\n",
+    );
+    project.write(".env.local", "DEMO_KEY='from local'\n");
+
+    let output = project.cmm_ok(&["run", "skip_build"]);
+    assert!(output.contains("key: from local\nplugins: a b\n"));
+    assert!(output.contains("ignoring line 4 of .env"));
+
+    let output = project.cmm_ok(&["run", "--print-env"]);
+    assert!(output.contains("DEMO_KEY=from local # .env.local\n"));
+    assert!(output.contains("DEMO_PLUGINS=a b # .env\n"));
+    assert!(!output.contains("key:"));
+    assert!(project.cmake_calls().is_empty());
+
+    // The environment cmm is started with wins
+    let output = project.cmm_env(&["run", "--print-env"], &[("DEMO_KEY", "shell")]);
+    assert!(String::from_utf8_lossy(&output.stdout).contains("DEMO_KEY=shell\n"));
+}
+
+#[test]
+fn run_runs_pre_run_hooks() {
+    use std::os::unix::fs::PermissionsExt;
+
+    let project = TestProject::new("demo");
+
+    let binary = project.dir().join("build/debug/demo");
+    project.write(
+        "build/debug/demo",
+        "#!/bin/sh\necho \"assets: $(cat assets.txt)\"\n",
+    );
+    std::fs::set_permissions(&binary, std::fs::Permissions::from_mode(0o755)).unwrap();
+
+    project.edit_config(|config| {
+        let mut hooks = toml::Table::new();
+        hooks.insert(
+            "pre_run".into(),
+            vec!["echo fresh > assets.txt", "echo hook ran"].into(),
+        );
+        config.insert("hooks".into(), hooks.into());
+    });
+
+    let output = project.cmm_ok(&["run", "skip_build"]);
+    assert!(output.contains("hook ran\n"));
+    assert!(output.contains("assets: fresh\n"));
+
+    // A failing hook stops the run before the program starts
+    project.edit_config(|config| {
+        config["hooks"]["pre_run"] = vec!["exit 3", "echo too far"].into();
+    });
+
+    let output = project.cmm(&["run", "skip_build"]);
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("'exit 3' exited with exit code 3"));
+    assert!(!stdout.contains("too far"));
+    assert!(!stdout.contains("assets:"));
+}
+
+#[test]
+fn install_builds_then_installs() {
+    let project = TestProject::new("demo");
+
+    let output = project.cmm(&["install"]);
+    assert!(String::from_utf8_lossy(&output.stderr).contains("nothing to install"));
+
+    project.edit_config(|config| {
+        config.insert("install".into(), toml::toml! { binaries = ["demo"] }.into());
+    });
+    project.cmm_ok(&["install", "--release", "--prefix", "dist"]);
+
+    let prefix = project.dir().canonicalize().unwrap().join("dist");
+    assert_eq!(
+        project.cmake_calls().last().unwrap(),
+        &format!(
+            "--install build/release --config Release --prefix {}",
+            prefix.display()
+        )
+    );
+}
+
+#[test]
+fn package_runs_cpack_in_build_folder() {
+    let project = TestProject::new("demo");
+
+    let output = project.cmm(&["package"]);
+    assert!(String::from_utf8_lossy(&output.stderr).contains("packaging isn't set up"));
+
+    project.edit_config(|config| {
+        config.insert("install".into(), toml::toml! { binaries = ["demo"] }.into());
+        config.insert(
+            "packaging".into(),
+            toml::toml! { generators = ["ZIP"] }.into(),
+        );
+    });
+    // Made by configuring, which the stub cmake doesn't do
+    std::fs::create_dir_all(project.dir().join("build/release")).unwrap();
+    project.cmm_ok(&["package", "--release", "--format", "DEB", "--format", "RPM"]);
+
+    let build_dir = project.dir().canonicalize().unwrap().join("build/release");
+    assert_eq!(
+        project.cpack_calls(),
+        [format!(
+            "{}: -C Release -B packages -G DEB;RPM",
+            build_dir.display()
+        )]
+    );
+}
+
+#[test]
+fn test_writes_junit_report() {
+    let project = TestProject::new("demo");
+
+    let output = project.cmm(&["test"]);
+    assert!(String::from_utf8_lossy(&output.stderr).contains("project has no tests"));
+
+    project.cmm_ok(&["add", "test-framework", "gtest"]);
+    // Made by configuring, which the stub cmake doesn't do
+    std::fs::create_dir_all(project.dir().join("build/debug")).unwrap();
+
+    project.cmm_ok(&["test", "--report", "results.xml"]);
+
+    let root = project.dir().canonicalize().unwrap();
+    assert_eq!(
+        project.ctest_calls(),
+        [format!(
+            "{}: -C Debug --output-on-failure --output-junit {}",
+            root.join("build/debug").display(),
+            root.join("results.xml").display()
+        )]
+    );
+}
+
+#[test]
+fn test_retries_flaky_tests() {
+    let project = TestProject::new("demo");
+    project.cmm_ok(&["add", "test-framework", "gtest"]);
+    std::fs::create_dir_all(project.dir().join("build/debug")).unwrap();
+
+    let failing = [
+        ("CMM_TEST_CTEST_FAILED", "Math.Add Net.Timeout"),
+        ("CMM_TEST_CTEST_FLAKY", "Net.Timeout"),
+    ];
+
+    let output = project.cmm_env(&["test"], &failing);
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("2 tests failing: Math.Add, Net.Timeout"));
+
+    project.edit_config(|config| {
+        config["tests"]
+            .as_table_mut()
+            .unwrap()
+            .insert("retries".into(), 2.into());
+    });
+
+    let output = project.cmm_env(&["test"], &failing);
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stdout.contains("flaky tests, passed on a retry: Net.Timeout"));
+    assert!(stderr.contains("1 tests failing: Math.Add"));
+    assert_eq!(project.ctest_calls().len(), 1 + 3);
+
+    let output = project.cmm_env(
+        &["test"],
+        &[
+            ("CMM_TEST_CTEST_FAILED", "Net.Timeout"),
+            ("CMM_TEST_CTEST_FLAKY", "Net.Timeout"),
+        ],
+    );
+    assert!(output.stderr.is_empty());
+}
+
+#[test]
+fn test_runs_chosen_cases() {
+    let project = TestProject::new("demo");
+    project.cmm_ok(&["add", "test-framework", "gtest"]);
+
+    // Stands in for the gtest executable the stub cmake doesn't build
+    project.write(
+        "build/debug/demo_tests",
+        "#!/bin/sh\nif [ \"$1\" = --gtest_list_tests ]; then\n    printf 'Math.\\n  Add\\n  Sub\\nParam/Net.\\n  Get/0  # GetParam() = 1\\n'\n    exit 0\nfi\necho \"$@\" >> cases.log\n",
+    );
+    let binary = project.dir().join("build/debug/demo_tests");
+    std::fs::set_permissions(binary, std::os::unix::fs::PermissionsExt::from_mode(0o755)).unwrap();
+
+    let output = project.cmm_ok(&["test", "--list"]);
+    assert!(output.contains("Math.Add\nMath.Sub\nParam/Net.Get/0\n"));
+
+    project.cmm_ok(&["test", "Math.*", "Param/Net.Get/0"]);
+    let output = project.cmm_env(
+        &["test", "--pick"],
+        &[("CMM_ANSWER_TEST_CASES", "Math.Sub")],
+    );
+    assert!(output.status.success());
+
+    assert_eq!(
+        project.read("cases.log"),
+        "--gtest_filter=Math.*:Param/Net.Get/0\n--gtest_filter=Math.Sub\n"
+    );
+    assert!(project.ctest_calls().is_empty());
+}
+
+#[test]
+fn coverage_report() {
+    let project = TestProject::new("demo");
+    project.cmm_ok(&["add", "test-framework", "gtest"]);
+    project.write("build/coverage/CMakeFiles/demo.dir/main.cpp.gcda", "stale");
+
+    project.cmm_ok(&["coverage", "--html"]);
+
+    assert!(!project
+        .dir()
+        .join("build/coverage/CMakeFiles/demo.dir/main.cpp.gcda")
+        .exists());
+    assert!(project.cmake_calls()[0].starts_with("-B build/coverage -DCMM_PROFILE=coverage"));
+    assert_eq!(project.ctest_calls().len(), 1);
+
+    let root = project.dir().canonicalize().unwrap();
+    assert_eq!(
+        project.gcovr_calls(),
+        [format!(
+            "{}: --root . --exclude build/ --exclude external/ --exclude tests/ --html-details build/coverage/coverage-html/index.html build/coverage",
+            root.display()
+        )]
+    );
+    assert!(project
+        .read("CMakeLists.txt")
+        .contains(r#"string(APPEND CMAKE_EXE_LINKER_FLAGS_DEBUG " --coverage")"#));
+}
+
+#[test]
+fn build_installs_conan_dependencies() {
+    let project = TestProject::new("demo");
+
+    project.cmm_ok(&["add", "conan", "fmt/11.0.2"]);
+    project.cmm_ok(&["build"]);
+
+    assert_eq!(
+        project.conan_calls(),
+        [
+            "profile detect --exist-ok",
+            "install . --output-folder=build/debug/conan --build=missing -s build_type=Debug",
+        ]
+    );
+
+    // Unchanged dependencies aren't installed again
+    project.cmm_ok(&["build"]);
+    assert_eq!(project.conan_calls().len(), 2);
+
+    project.cmm_ok(&["build", "--release"]);
+    assert_eq!(
+        project.conan_calls().last().unwrap(),
+        "install . --output-folder=build/release/conan --build=missing -s build_type=Release"
+    );
+}
+
+#[test]
+fn fetch_configures_without_compiling() {
+    let project = TestProject::new("demo");
+
+    project.cmm_ok(&["add", "conan", "fmt/11.0.2"]);
+    project.cmm_ok(&["fetch"]);
+
+    assert_eq!(project.conan_calls().len(), 2);
+    assert_eq!(
+        project.cmake_calls(),
+        ["-B build/debug -DCMM_PROFILE=debug -DCMAKE_BUILD_TYPE=Debug -DCMM_CACHE_LAUNCHER=ON"]
+    );
+}
+
+#[test]
+fn new_with_build_checks_toolchain() {
+    let project = TestProject::empty("workspace");
+
+    let output = project.cmm(&["new", "demo", "--build"]);
+    let stdout = String::from_utf8_lossy(&output.stdout);
+
+    assert!(project.dir().join("demo/CMakeMake.toml").exists());
+    assert_eq!(
+        project.cmake_calls(),
+        [
+            "-B build/debug -DCMM_PROFILE=debug -DCMAKE_BUILD_TYPE=Debug -DCMM_CACHE_LAUNCHER=ON",
+            "--build build/debug --config Debug",
+        ]
+    );
+
+    // The stub cmake doesn't compile anything, so there's no program to run
+    assert!(stdout.contains("The project was created, but building it failed"));
+}
+
+#[cfg(unix)]
+#[test]
+fn clean_refuses_symlinked_build_dir() {
+    let project = TestProject::new("demo");
+    let shared = TestProject::empty("shared");
+    shared.write("keep.txt", "shared data");
+
+    std::os::unix::fs::symlink(shared.dir(), project.dir().join("build")).unwrap();
+
+    let output = project.cmm(&["clean"]);
+    assert!(String::from_utf8_lossy(&output.stderr).contains("is a symlink"));
+    assert!(shared.dir().join("keep.txt").exists());
+
+    // Forcing clears the folder but keeps the link
+    project.cmm_ok(&["clean", "--force"]);
+    assert!(!shared.dir().join("keep.txt").exists());
+    assert!(shared.dir().exists());
+    assert!(project.dir().join("build").is_symlink());
+}
+
+#[test]
+fn clean_single_profile() {
+    let project = TestProject::new("demo");
+
+    project.write("build/debug/CMakeCache.txt", "");
+    project.write("build/release/CMakeCache.txt", "");
+
+    project.cmm_ok(&["clean", "--profile", "release"]);
+    assert!(!project.dir().join("build/release").exists());
+    assert!(project.dir().join("build/debug/CMakeCache.txt").exists());
+
+    let output = project.cmm(&["clean", "--profile", "nope"]);
+    assert!(String::from_utf8_lossy(&output.stderr).contains("error:"));
+    assert!(project.dir().join("build/debug").exists());
+
+    project.cmm_ok(&["clean"]);
+    assert!(!project.dir().join("build").exists());
+}
+
+#[test]
+fn clean_single_target() {
+    let project = TestProject::new("demo");
+
+    project.write("build/debug/CMakeFiles/demo.dir/main.cpp.o", "");
+    project.write("build/debug/CMakeFiles/other.dir/other.cpp.o", "");
+    project.write("build/release/CMakeFiles/demo.dir/main.cpp.o", "");
+    project.write("build/release/demo.dir/Release/main.obj", "");
+
+    project.cmm_ok(&["clean", "--target", "demo", "--profile", "debug"]);
+    assert!(!project
+        .dir()
+        .join("build/debug/CMakeFiles/demo.dir")
+        .exists());
+    assert!(project
+        .dir()
+        .join("build/debug/CMakeFiles/other.dir")
+        .exists());
+    assert!(project
+        .dir()
+        .join("build/release/CMakeFiles/demo.dir")
+        .exists());
+
+    project.cmm_ok(&["clean", "--target", "demo"]);
+    assert!(!project
+        .dir()
+        .join("build/release/CMakeFiles/demo.dir")
+        .exists());
+    assert!(!project.dir().join("build/release/demo.dir").exists());
+    assert!(project.dir().join("build/release/CMakeFiles").exists());
+
+    let output = project.cmm_ok(&["clean", "--target", "demo"]);
+    assert!(output.contains("No build files found for target 'demo'"));
+}
+
+#[cfg(unix)]
+#[test]
+fn clean_keeps_compiler_cache() {
+    use std::os::unix::fs::PermissionsExt;
+
+    let project = TestProject::new("demo");
+
+    // Records which folder it was asked about, like ccache reads CCACHE_DIR
+    project.write(
+        "tools/ccache",
+        "#!/bin/sh\ncase \"$1\" in\n--version) echo \"ccache version 4.9.1\" ;;\n*) echo \"ccache $1 in $CCACHE_DIR\" ;;\nesac\n",
+    );
+    let ccache = project.dir().join("tools/ccache");
+    std::fs::set_permissions(&ccache, std::fs::Permissions::from_mode(0o755)).unwrap();
+
+    project.edit_config(|config| {
+        let cmake = config["cmake"].as_table_mut().unwrap();
+        cmake.insert("cache_launcher".into(), ccache.to_str().unwrap().into());
+    });
+
+    // Only the project's own cache has its statistics reset
+    let output = project.cmm_ok(&["clean", "--keep-cache-objects", "--zero-stats"]);
+    assert!(output.contains("ccache -s in \n"));
+    assert!(output.contains("not zeroing the statistics of a shared compiler cache"));
+
+    project.edit_config(|config| {
+        let cmake = config["cmake"].as_table_mut().unwrap();
+        cmake.insert("cache_dir".into(), "./build/ccache".into());
+    });
+    project.cmm_ok(&["cmake"]);
+    assert!(project.read("CMakeLists.txt").contains(
+        r#"set(CMAKE_CXX_COMPILER_LAUNCHER "${CMAKE_COMMAND}" -E env "CCACHE_DIR=${CMAKE_CURRENT_SOURCE_DIR}/build/ccache" "${CMM_CACHE_PROGRAM}")"#
+    ));
+
+    project.write("build/ccache/objects/a.o", "");
+    project.write("build/debug/CMakeCache.txt", "");
+
+    let output = project.cmm_ok(&["clean", "--keep-cache-objects", "--zero-stats"]);
+    assert!(output.contains("ccache -s in build/ccache"));
+    assert!(output.contains("ccache -z in build/ccache"));
+    assert!(project.dir().join("build/ccache/objects/a.o").exists());
+    assert!(!project.dir().join("build/debug").exists());
+
+    project.cmm_ok(&["clean"]);
+    assert!(!project.dir().join("build").exists());
+}
+
+#[cfg(target_os = "linux")]
+#[test]
+fn clean_moves_to_trash() {
+    let project = TestProject::new("demo");
+
+    let global = project.home().join(".local/share/cmakemake");
+    std::fs::create_dir_all(&global).unwrap();
+    std::fs::write(global.join("config.toml"), "use_trash = true\n").unwrap();
+
+    project.cmm_ok(&["build"]);
+    project.cmm_ok(&["clean", "all"]);
+
+    assert!(!project.dir().join("build").exists());
+    assert!(!project.dir().join("CMakeLists.txt").exists());
+
+    let trash = project.home().join(".local/share/Trash/files");
+    assert!(trash.join("CMakeLists.txt").exists());
+}
+
+#[test]
+fn locked_project() {
+    let project = TestProject::new("demo");
+
+    std::fs::create_dir_all(project.dir().join(".cache")).unwrap();
+    let lock = std::fs::File::create(project.dir().join(".cache/cmm.lock")).unwrap();
+    lock.lock().unwrap();
+
+    let output = project.cmm(&["add", "find", "ZLIB"]);
+    assert!(String::from_utf8_lossy(&output.stderr).contains("another cmm process"));
+    assert_eq!(output.status.code(), Some(6));
+    assert!(!project.read("CMakeMake.toml").contains("ZLIB"));
+
+    // --wait carries on once the other process is done
+    let release = std::thread::spawn(move || {
+        std::thread::sleep(std::time::Duration::from_millis(300));
+        drop(lock);
+    });
+
+    project.cmm_ok(&["add", "find", "ZLIB", "--wait"]);
+    assert!(project.read("CMakeMake.toml").contains("ZLIB"));
+
+    release.join().unwrap();
+}
+
+fn git(dir: &Path, args: &[&str]) -> String {
+    let output = Command::new("git")
+        .args(["-c", "user.name=test", "-c", "user.email=test@example.com"])
+        .args(args)
+        .current_dir(dir)
+        .output()
+        .unwrap();
+    assert!(output.status.success(), "git {:?} failed", args);
+
+    String::from_utf8_lossy(&output.stdout).trim().to_owned()
+}
+
+/// A git repo to fetch from with a commit tagged for each of `tags`
+fn tagged_repo(tags: &[&str]) -> TestProject {
+    let remote = TestProject::empty("remote");
+    git(remote.dir(), &["init", "--quiet"]);
+
+    for tag in tags {
+        remote.write("version.txt", tag);
+        git(remote.dir(), &["add", "."]);
+        git(remote.dir(), &["commit", "--quiet", "-m", tag]);
+        git(remote.dir(), &["tag", "-a", tag, "-m", tag]);
+    }
+
+    remote
+}
+
+#[test]
+fn build_locks_fetch_dependencies() {
+    let project = TestProject::new("demo");
+    let remote = tagged_repo(&["v1", "v2"]);
+    let repo = remote.dir().to_str().unwrap();
+
+    project.cmm_ok(&["add", "fetch", repo, "--name", "lib", "--tag", "v1"]);
+    project.cmm_ok(&["build"]);
+
+    let v1 = git(remote.dir(), &["rev-parse", "v1^{commit}"]);
+    assert!(project.read("CMakeMake.lock").contains(&v1));
+    assert!(project
+        .read("CMakeLists.txt")
+        .contains(&format!("GIT_TAG {v1}")));
+
+    // Moving the tag in the config needs the lockfile updating, which --frozen refuses
+    project.edit_config(|config| {
+        config["dependencies"]["fetch"][0]["tag"] = "v2".into();
+    });
+
+    let output = project.cmm(&["build", "--frozen"]);
+    assert!(String::from_utf8_lossy(&output.stderr).contains("CMakeMake.lock is out of date"));
+
+    project.cmm_ok(&["build"]);
+
+    let v2 = git(remote.dir(), &["rev-parse", "v2^{commit}"]);
+    assert!(project.read("CMakeMake.lock").contains(&v2));
+    assert!(project
+        .read("CMakeLists.txt")
+        .contains(&format!("GIT_TAG {v2}")));
+}
+
+#[test]
+fn build_updates_changed_dependency_variables() {
+    let project = TestProject::new("demo");
+    let remote = tagged_repo(&["v1"]);
+    let repo = remote.dir().to_str().unwrap();
+
+    project.cmm_ok(&["add", "fetch", repo, "--name", "lib", "--tag", "v1"]);
+    project.edit_config(|config| {
+        config["dependencies"]["fetch"][0]["variables"] = toml::Value::Array(vec![
+            toml::Value::Array(vec!["LIB_DOCS".into(), "ON CACHE BOOL \"\"".into()]),
+            toml::Value::Array(vec!["LIB_NAME".into(), "\"old\"".into()]),
+        ]);
+    });
+    project.cmm_ok(&["build"]);
+
+    // Nothing changed, so nothing to pass on
+    project.cmm_ok(&["build"]);
+    assert!(!project.cmake_calls()[2].contains("LIB_"));
+
+    project.edit_config(|config| {
+        config["dependencies"]["fetch"][0]["variables"] = toml::Value::Array(vec![
+            toml::Value::Array(vec!["LIB_DOCS".into(), "OFF CACHE BOOL \"\"".into()]),
+        ]);
+    });
+    project.cmm_ok(&["build"]);
+
+    let configure = &project.cmake_calls()[4];
+    assert!(configure.ends_with(" -DLIB_DOCS:BOOL=OFF -ULIB_NAME"));
+    assert!(!configure.contains("--fresh"));
+}
+
+#[test]
+fn stale_cache_is_configured_from_scratch() {
+    let project = TestProject::new("demo");
+    let stale = |project: &TestProject| {
+        project.write(
+            "build/debug/CMakeCache.txt",
+            "CMAKE_HOME_DIRECTORY:INTERNAL=/somewhere/else\n",
+        );
+        project.write("build/debug/CMakeFiles/cmake.check_cache", "");
+    };
+
+    stale(&project);
+    project.cmm_ok(&["build"]);
+    assert!(project
+        .cmake_calls()
+        .iter()
+        .any(|call| call.contains("--fresh")));
+
+    // --fresh is new in CMake 3.24, older versions get the cache deleted instead
+    let project = TestProject::new("demo");
+    stale(&project);
+    let output = project.cmm_env(&["build"], &[("CMM_TEST_CMAKE_VERSION", "3.22.1")]);
+    assert!(output.status.success());
+    assert!(!project
+        .cmake_calls()
+        .iter()
+        .any(|call| call.contains("--fresh")));
+    assert!(!project.dir().join("build/debug/CMakeCache.txt").exists());
+    assert!(!project.dir().join("build/debug/CMakeFiles").exists());
+}
+
+#[test]
+fn update_moves_dependencies_to_newer_tags() {
+    let project = TestProject::new("demo");
+    let remote = tagged_repo(&["v1", "v2", "v10"]);
+    let repo = remote.dir().to_str().unwrap();
+
+    // Test projects live inside this crate's repo, submodules need one of their own
+    git(project.dir(), &["init", "--quiet"]);
+
+    // git refuses to clone submodules from a local path by default
+    std::fs::create_dir_all(project.home()).unwrap();
+    std::fs::write(
+        project.home().join(".gitconfig"),
+        "[protocol \"file\"]\n\tallow = always\n",
+    )
+    .unwrap();
+
+    project.cmm_ok(&["add", "git", repo, "--name", "lib", "--tag", "v1"]);
+    project.cmm_ok(&["add", "fetch", repo, "--name", "fetched", "--tag", "v1"]);
+    project.cmm_ok(&["lock"]);
+    project.cmm_ok(&["deps", "licenses-header"]);
+
+    project.cmm_ok(&["update", "lib", "--tag", "v10"]);
+
+    let v10 = git(remote.dir(), &["rev-parse", "v10^{commit}"]);
+    assert_eq!(
+        git(
+            &project.dir().join("external/remote"),
+            &["rev-parse", "HEAD"]
+        ),
+        v10
+    );
+    assert!(project.read("CMakeMake.toml").contains("tag = \"v10\""));
+    assert!(project.read("CMakeMake.lock").contains(&v10));
+    assert!(project
+        .read("THIRD_PARTY_NOTICES.md")
+        .contains(&format!("- Version: v10 (commit {v10})")));
+
+    // Newer tags are offered newest first, v10 sorting after v2
+    project.write("answers.toml", "[update]\ntag = 1\n");
+    project.cmm_ok(&["update", "fetched", "--answers", "answers.toml"]);
+
+    let v2 = git(remote.dir(), &["rev-parse", "v2^{commit}"]);
+    assert!(project.read("CMakeMake.toml").contains("tag = \"v2\""));
+    assert!(project.read("CMakeMake.lock").contains(&v2));
+    assert!(project.read("CMakeMake.lock").contains(&v10));
+}
+
+/// A project with the submodule `lib`, from a repo with `version.txt` and `other.txt`, and
+/// a patch for it. The remote is returned too so it outlives the test.
+fn patched_submodule() -> (TestProject, TestProject) {
+    let project = TestProject::new("demo");
+    let remote = TestProject::empty("remote");
+    git(remote.dir(), &["init", "--quiet"]);
+    remote.write("version.txt", "v1\n");
+    remote.write("other.txt", "other\n");
+    git(remote.dir(), &["add", "."]);
+    git(remote.dir(), &["commit", "--quiet", "-m", "v1"]);
+    git(remote.dir(), &["tag", "-a", "v1", "-m", "v1"]);
+    let repo = remote.dir().to_str().unwrap().to_owned();
+
+    git(project.dir(), &["init", "--quiet"]);
+    std::fs::create_dir_all(project.home()).unwrap();
+    std::fs::write(
+        project.home().join(".gitconfig"),
+        "[protocol \"file\"]\n\tallow = always\n",
+    )
+    .unwrap();
+
+    project.cmm_ok(&["add", "git", &repo, "--name", "lib", "--tag", "v1"]);
+
+    // The patch is made the way it would be by hand, from an edit to the checkout
+    let dep = project.dir().join("external/remote");
+    std::fs::write(dep.join("version.txt"), "patched\n").unwrap();
+    let patch = git(&dep, &["diff"]);
+    git(&dep, &["checkout", "--quiet", "--", "."]);
+    project.write("patches/lib/0001-version.patch", &format!("{patch}\n"));
+
+    (project, remote)
+}
+
+#[test]
+fn frozen_build_only_allows_patch_changes() {
+    let (project, _remote) = patched_submodule();
+    let dep = project.dir().join("external/remote");
+
+    project.cmm_ok(&["build"]);
+    assert_eq!(
+        std::fs::read_to_string(dep.join("version.txt")).unwrap(),
+        "patched\n"
+    );
+
+    let lock = project.read("CMakeMake.lock");
+    assert!(lock.contains("file = \"0001-version.patch\""));
+    assert!(lock.contains("hash = \""));
+
+    // The patch is the only change, so the checkout still counts as locked
+    project.cmm_ok(&["build", "--frozen"]);
+
+    // Anything else is an edit --frozen has to catch
+    std::fs::write(dep.join("other.txt"), "edited by accident\n").unwrap();
+    let output = project.cmm(&["build", "--frozen"]);
+    assert!(!output.status.success());
+    assert!(
+        String::from_utf8_lossy(&output.stderr).contains("modified dependencies: external/remote")
+    );
+}
+
+#[test]
+fn patched_submodules_keep_their_patches_when_moved() {
+    let (project, remote) = patched_submodule();
+    let dep = project.dir().join("external/remote");
+
+    project.cmm_ok(&["build"]);
+    let config = project.read("CMakeMake.toml");
+    let lock = project.read("CMakeMake.lock");
+
+    remote.write("other.txt", "other v2\n");
+    git(remote.dir(), &["commit", "--quiet", "-am", "v2"]);
+    git(remote.dir(), &["tag", "-a", "v2", "-m", "v2"]);
+
+    // The patch alone doesn't count as local changes that block the update
+    project.cmm_ok(&["update", "lib", "--tag", "v2"]);
+    let v2 = git(remote.dir(), &["rev-parse", "v2^{commit}"]);
+    assert_eq!(git(&dep, &["rev-parse", "HEAD"]), v2);
+    assert_eq!(
+        std::fs::read_to_string(dep.join("other.txt")).unwrap(),
+        "other v2\n"
+    );
+    assert_eq!(
+        std::fs::read_to_string(dep.join("version.txt")).unwrap(),
+        "patched\n"
+    );
+
+    // Nor when going back to the locked commit
+    project.write("CMakeMake.toml", &config);
+    project.write("CMakeMake.lock", &lock);
+    project.cmm_ok(&["build"]);
+    let v1 = git(remote.dir(), &["rev-parse", "v1^{commit}"]);
+    assert_eq!(git(&dep, &["rev-parse", "HEAD"]), v1);
+    assert_eq!(
+        std::fs::read_to_string(dep.join("version.txt")).unwrap(),
+        "patched\n"
+    );
+}
+
+#[test]
+fn repair_cleans_up_half_added_submodules() {
+    let project = TestProject::new("demo");
+    let remote = tagged_repo(&["v1"]);
+    let repo = remote.dir().to_str().unwrap();
+
+    git(project.dir(), &["init", "--quiet"]);
+    std::fs::create_dir_all(project.home()).unwrap();
+    std::fs::write(
+        project.home().join(".gitconfig"),
+        "[protocol \"file\"]\n\tallow = always\n",
+    )
+    .unwrap();
+
+    // Left as a dropped connection would: registered and cached, but nothing checked out
+    // and no config entry
+    git(
+        project.dir(),
+        &[
+            "-c",
+            "protocol.file.allow=always",
+            "submodule",
+            "add",
+            "--quiet",
+            repo,
+            "external/remote",
+        ],
+    );
+    std::fs::remove_dir_all(project.dir().join("external/remote")).unwrap();
+    std::fs::create_dir_all(project.dir().join("external/remote")).unwrap();
+    std::fs::create_dir_all(project.dir().join("external/empty")).unwrap();
+
+    let output = project.cmm_ok(&["repair"]);
+    assert!(output.contains("half added submodule 'external/remote'"));
+    assert!(output.contains("half added submodule 'external/empty'"));
+    assert!(!project.dir().join("external/empty").exists());
+    assert!(!project.dir().join(".git/modules/external/remote").exists());
+    assert!(!project.read(".gitmodules").contains("external/remote"));
+
+    // Adding it again works now
+    project.cmm_ok(&["add", "git", repo, "--name", "lib", "--tag", "v1"]);
+    assert!(project.dir().join("external/remote/version.txt").exists());
+
+    let output = project.cmm_ok(&["repair"]);
+    assert!(output.contains("nothing to repair"));
+}
+
+#[test]
+fn cache_restores_dependencies_in_another_folder() {
+    let project = TestProject::new("demo");
+    let root = project.dir().canonicalize().unwrap();
+    let root = root.to_str().unwrap();
+
+    project.write("build/debug/_deps/lib-src/lib.h", "int lib();\n");
+    project.write(
+        "build/debug/_deps/lib-subbuild/CMakeCache.txt",
+        &format!("CMAKE_CACHEFILE_DIR:INTERNAL={root}/build/debug/_deps/lib-subbuild\n"),
+    );
+    project.write("build/debug/main.o", "compiled");
+
+    let archive = project.dir().parent().unwrap().join("deps.tar.gz");
+    let archive = archive.to_str().unwrap();
+    project.cmm_ok(&["cache", "save", archive]);
+
+    let moved = TestProject::new("moved");
+    let moved_root = moved.dir().canonicalize().unwrap();
+
+    moved.cmm_ok(&["cache", "restore", archive]);
+
+    assert_eq!(
+        moved.read("build/debug/_deps/lib-src/lib.h"),
+        "int lib();\n"
+    );
+    assert_eq!(
+        moved.read("build/debug/_deps/lib-subbuild/CMakeCache.txt"),
+        format!(
+            "CMAKE_CACHEFILE_DIR:INTERNAL={}/build/debug/_deps/lib-subbuild\n",
+            moved_root.display()
+        )
+    );
+    // Only dependencies are saved without --build
+    assert!(!moved.dir().join("build/debug/main.o").exists());
+
+    // A cache miss in CI isn't an error
+    let output = moved.cmm_ok(&["cache", "restore", "missing.tar.gz"]);
+    assert!(output.contains("nothing restored"));
+}
+
+#[test]
+fn warnings_baseline_fails_on_new_warnings() {
+    let project = TestProject::new("demo");
+    let root = project.dir().canonicalize().unwrap();
+    let root = root.to_str().unwrap();
+
+    let unused =
+        format!("{root}/src/main.cpp:3:9: warning: unused variable 'x' [-Wunused-variable]");
+    let shadow = format!(
+        "{root}/src/main.cpp:5:9: warning: declaration shadows a local variable [-Wshadow]"
+    );
+    let dependency = format!(
+        "{root}/build/debug/_deps/fmt-src/format.h:10:1: warning: deprecated [-Wdeprecated]"
+    );
+
+    // The same header warning from two sources only counts once
+    project.set_compiler_output(&format!("{unused}\n{unused}\n{dependency}\n"));
+    project.cmm_ok(&["warnings", "baseline"]);
+
+    assert!(project
+        .cmake_calls()
+        .iter()
+        .any(|call| call.ends_with("--clean-first")));
+    assert!(project
+        .read("CMakeMake.warnings.toml")
+        .contains("\"src/main.cpp\" = 1\n"));
+
+    project.cmm_ok(&["build"]);
+
+    project.set_compiler_output(&format!("{unused}\n{shadow}\n"));
+    let output = project.cmm(&["build"]);
+    assert!(
+        String::from_utf8_lossy(&output.stderr).contains("new compiler warnings in: src/main.cpp")
+    );
+
+    project.edit_config(|config| {
+        config.insert("warnings".into(), toml::toml! { on_new = "warn" }.into());
+    });
+    let output = project.cmm_ok(&["build"]);
+    assert!(output.contains("'src/main.cpp' has 2 warnings, 1 more than the baseline"));
+}
+
+#[test]
+fn dependency_diagnostics_report() {
+    let project = TestProject::new("demo");
+    let root = project.dir().canonicalize().unwrap();
+    let root = root.to_str().unwrap();
+
+    let output = [
+        "[1/2] Building CXX object CMakeFiles/demo.dir/src/main.cpp.o".to_owned(),
+        format!("In file included from {root}/src/main.cpp:1:"),
+        format!("{root}/external/glfw/include/glfw3.h:12:5: warning: unused parameter 'w'"),
+        "   12 | void f(int w) {}".to_owned(),
+        "      |            ^".to_owned(),
+        format!("{root}/src/main.cpp:3:9: warning: unused variable 'x'"),
+        "    3 |     int x;".to_owned(),
+        "/usr/include/c++/13/bits/stl.h:40:1: error: expected ';'".to_owned(),
+        "[2/2] Linking CXX executable demo".to_owned(),
+    ];
+    project.set_compiler_output(&output.join("\n"));
+
+    project.edit_config(|config| {
+        config.insert("warnings".into(), toml::toml! { report = true }.into());
+    });
+    let stdout = project.cmm_ok(&["build"]);
+    assert!(stdout.contains("glfw3.h:12:5: warning"));
+    assert!(stdout.contains("project         1 warnings, 0 errors"));
+    assert!(stdout.contains("dependencies    1 warnings, 1 errors"));
+
+    project.edit_config(|config| {
+        config.insert(
+            "warnings".into(),
+            toml::toml! { hide_dependencies = true }.into(),
+        );
+    });
+    let stdout = project.cmm_ok(&["build"]);
+
+    // Dependency messages go with their include lines and source excerpt
+    assert!(!stdout.contains("In file included from"));
+    assert!(!stdout.contains("glfw3.h"));
+    assert!(!stdout.contains("void f(int w)"));
+    assert!(!stdout.contains("stl.h"));
+    assert!(stdout.contains("main.cpp:3:9: warning: unused variable 'x'\n    3 |     int x;\n"));
+    assert!(stdout.contains("[2/2] Linking CXX executable demo"));
+    assert!(stdout
+        .contains("1 warnings and 1 errors from dependencies hidden, see .cache/last-build.log"));
+    assert!(!stdout.contains("Diagnostics by origin"));
+
+    assert!(project
+        .read(".cache/last-build.log")
+        .contains("glfw3.h:12:5: warning"));
+}
+
+#[test]
+fn run_library_examples() {
+    use std::os::unix::fs::PermissionsExt;
+
+    let project = TestProject::new("demo");
+    project.edit_config(|config| {
+        config["project"]["kind"] = "library".into();
+    });
+
+    let output = project.cmm(&["run", "skip_build"]);
+    assert_eq!(output.status.code(), Some(3));
+    assert!(String::from_utf8_lossy(&output.stderr).contains("library project has nothing to run"));
+
+    project.write("examples/basic.cpp", "int main() {}\n");
+    project.write("examples/window.cpp", "int main() {}\n");
+    project.edit_config(|config| {
+        let roles = toml::Value::try_from(vec![("example", vec!["../examples"])]).unwrap();
+        config["cmake"]["files"]
+            .as_table_mut()
+            .unwrap()
+            .insert("roles".into(), roles);
+    });
+
+    project.cmm_ok(&["cmake"]);
+    let cmake_lists = project.read("CMakeLists.txt");
+    assert!(cmake_lists.contains(r#"add_library("${PROJECT_NAME}" ${SOURCES})"#));
+    assert!(cmake_lists
+        .contains(r#"target_link_libraries("example_${name}" PRIVATE "${PROJECT_NAME}")"#));
+
+    for example in ["basic", "window"] {
+        let binary = project.dir().join(format!("build/debug/example_{example}"));
+        project.write(
+            &format!("build/debug/example_{example}"),
+            &format!("#!/bin/sh\necho \"running {example}\"\n"),
+        );
+        std::fs::set_permissions(&binary, std::fs::Permissions::from_mode(0o755)).unwrap();
+    }
+
+    // Nothing to default to, so which example is asked for
+    let output = project.cmm(&["run", "skip_build"]);
+    assert_eq!(output.status.code(), Some(2));
+    assert!(
+        String::from_utf8_lossy(&output.stderr).contains("no answer given for prompt 'run.bin'")
+    );
+
+    let output = project.cmm_env(
+        &["run", "skip_build"],
+        &[("CMM_ANSWER_RUN_BIN", "example_window")],
+    );
+    assert!(String::from_utf8_lossy(&output.stdout).contains("running window"));
+
+    let output = project.cmm_ok(&["run", "--bin", "example_basic"]);
+    assert!(output.contains("running basic"));
+    assert!(project.cmake_calls()[1].ends_with("--target example_basic"));
+}
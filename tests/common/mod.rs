@@ -0,0 +1,338 @@
+//! Helpers for driving the `cmm` binary against throwaway projects.
+//!
+//! Each project lives in its own folder under cargo's test temp dir with its own home
+//! folder, so the user's global config and dependency cache are never touched. Stub
+//! `cmake`, `conan`, `cpack`, `ctest` and `gcovr` programs on the PATH record their
+//! arguments instead of doing anything.
+
+#![allow(dead_code)]
+
+use std::{
+    path::{Path, PathBuf},
+    process::{Command, Output, Stdio},
+    sync::atomic::{AtomicUsize, Ordering},
+};
+
+static NEXT_ID: AtomicUsize = AtomicUsize::new(0);
+
+const STUB_CMAKE: &str = r#"#!/bin/sh
+echo "$@" >> "$CMM_TEST_CMAKE_LOG"
+if [ "$1" = "--version" ]; then
+    echo "cmake version ${CMM_TEST_CMAKE_VERSION:-3.28.0}"
+fi
+if [ "$1" = "-B" ] && [ -f "$CMM_TEST_CONFIGURE_ERROR" ]; then
+    cat "$CMM_TEST_CONFIGURE_ERROR"
+    exit 1
+fi
+if [ "$1" = "--build" ] && [ -f "$CMM_TEST_COMPILER_OUTPUT" ]; then
+    cat "$CMM_TEST_COMPILER_OUTPUT"
+fi
+exit 0
+"#;
+
+const STUB_CONAN: &str = r#"#!/bin/sh
+echo "$@" >> "$CMM_TEST_CONAN_LOG"
+for arg in "$@"; do
+    case "$arg" in
+        --output-folder=*) mkdir -p "${arg#--output-folder=}" ;;
+    esac
+done
+exit 0
+"#;
+
+const STUB_CPACK: &str = r#"#!/bin/sh
+echo "$(pwd): $@" >> "$CMM_TEST_CPACK_LOG"
+exit 0
+"#;
+
+const STUB_GCOVR: &str = r#"#!/bin/sh
+if [ "$1" = "--version" ]; then
+    echo "gcovr 7.2"
+    exit 0
+fi
+echo "$(pwd): $@" >> "$CMM_TEST_GCOVR_LOG"
+exit 0
+"#;
+
+const STUB_CTEST: &str = r#"#!/bin/sh
+if [ "$1" = "--version" ]; then
+    echo "ctest version 3.28.0"
+    exit 0
+fi
+echo "$(pwd): $@" >> "$CMM_TEST_CTEST_LOG"
+# Tests in CMM_TEST_CTEST_FAILED fail, except on reruns for those in CMM_TEST_CTEST_FLAKY
+failed="$CMM_TEST_CTEST_FAILED"
+case "$*" in *--rerun-failed*)
+    failed=""
+    for test in $CMM_TEST_CTEST_FAILED; do
+        case " $CMM_TEST_CTEST_FLAKY " in *" $test "*) ;; *) failed="$failed $test" ;; esac
+    done
+esac
+[ -z "$failed" ] && exit 0
+mkdir -p Testing/Temporary
+: > Testing/Temporary/LastTestsFailed.log
+i=0
+for test in $failed; do
+    i=$((i + 1))
+    echo "$i:$test" >> Testing/Temporary/LastTestsFailed.log
+done
+exit 8
+"#;
+
+pub struct TestProject {
+    root: PathBuf,
+    dir: PathBuf,
+}
+
+impl TestProject {
+    /// An empty folder to run commands in, for commands like `init` that adopt one
+    pub fn empty(name: &str) -> Self {
+        let id = NEXT_ID.fetch_add(1, Ordering::SeqCst);
+        let root = Path::new(env!("CARGO_TARGET_TMPDIR")).join(format!(
+            "cmm-{}-{}-{}",
+            name,
+            std::process::id(),
+            id
+        ));
+
+        if root.exists() {
+            std::fs::remove_dir_all(&root).unwrap();
+        }
+
+        let dir = root.join(name);
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::create_dir_all(root.join("home")).unwrap();
+        std::fs::create_dir_all(root.join("bin")).unwrap();
+
+        let project = Self { root, dir };
+        project.install_stubs();
+        project
+    }
+
+    /// A project made with `cmm new <NAME>`
+    pub fn new(name: &str) -> Self {
+        Self::new_with(name, &[])
+    }
+
+    /// A project made with `cmm new <NAME>` and extra arguments, eg. a template
+    pub fn new_with(name: &str, args: &[&str]) -> Self {
+        let project = Self::empty(name);
+        std::fs::remove_dir(&project.dir).unwrap();
+
+        let args = ["new", name]
+            .iter()
+            .chain(args)
+            .copied()
+            .collect::<Vec<_>>();
+        let output = project.command(&project.root, &args, &[]);
+        assert_success(&output);
+
+        project
+    }
+
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    /// The private home folder `cmm` runs with
+    pub fn home(&self) -> PathBuf {
+        self.root.join("home")
+    }
+
+    #[cfg(unix)]
+    fn install_stubs(&self) {
+        use std::os::unix::fs::PermissionsExt;
+
+        for (name, script) in [
+            ("cmake", STUB_CMAKE),
+            ("conan", STUB_CONAN),
+            ("cpack", STUB_CPACK),
+            ("ctest", STUB_CTEST),
+            ("gcovr", STUB_GCOVR),
+        ] {
+            let path = self.root.join("bin").join(name);
+            std::fs::write(&path, script).unwrap();
+            std::fs::set_permissions(&path, std::fs::Permissions::from_mode(0o755)).unwrap();
+        }
+    }
+
+    #[cfg(not(unix))]
+    fn install_stubs(&self) {}
+
+    fn command(&self, dir: &Path, args: &[&str], env: &[(&str, &str)]) -> Output {
+        let path = std::env::join_paths(std::iter::once(self.root.join("bin")).chain(
+            std::env::split_paths(&std::env::var_os("PATH").unwrap_or_default()),
+        ))
+        .unwrap();
+
+        Command::new(env!("CARGO_BIN_EXE_cmakemake"))
+            .args(args)
+            .current_dir(dir)
+            .env("HOME", self.root.join("home"))
+            .env_remove("XDG_CONFIG_HOME")
+            .env_remove("XDG_DATA_HOME")
+            .env_remove("CMAKE_GENERATOR")
+            .env_remove("CMAKE_TOOLCHAIN_FILE")
+            .env_remove("CMAKE_PREFIX_PATH")
+            .env_remove("CC")
+            .env_remove("CXX")
+            .env_remove("CMM_ANSWERS")
+            .env("PATH", path)
+            .env("CMM_LANG", "en")
+            .env("NO_COLOR", "1")
+            .env("CMM_TEST_CMAKE_LOG", self.root.join("cmake.log"))
+            .env("CMM_TEST_CONAN_LOG", self.root.join("conan.log"))
+            .env("CMM_TEST_CPACK_LOG", self.root.join("cpack.log"))
+            .env("CMM_TEST_CTEST_LOG", self.root.join("ctest.log"))
+            .env("CMM_TEST_GCOVR_LOG", self.root.join("gcovr.log"))
+            .env("CMM_TEST_COMPILER_OUTPUT", self.root.join("compiler.txt"))
+            .env("CMM_TEST_CONFIGURE_ERROR", self.root.join("configure.txt"))
+            .envs(env.iter().copied())
+            .stdin(Stdio::null())
+            .output()
+            .unwrap()
+    }
+
+    /// Runs `cmm` in the project folder
+    pub fn cmm(&self, args: &[&str]) -> Output {
+        self.command(&self.dir, args, &[])
+    }
+
+    /// Runs `cmm` in the project folder with extra environment variables
+    pub fn cmm_env(&self, args: &[&str], env: &[(&str, &str)]) -> Output {
+        self.command(&self.dir, args, env)
+    }
+
+    /// Runs `cmm` in the project folder and fails the test if it reports an error
+    pub fn cmm_ok(&self, args: &[&str]) -> String {
+        let output = self.cmm(args);
+        assert_success(&output);
+        String::from_utf8_lossy(&output.stdout).into_owned()
+    }
+
+    pub fn read(&self, path: &str) -> String {
+        std::fs::read_to_string(self.dir.join(path))
+            .unwrap_or_else(|err| panic!("failed to read '{}': {}", path, err))
+    }
+
+    pub fn write(&self, path: &str, contents: &str) {
+        let path = self.dir.join(path);
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent).unwrap();
+        }
+        std::fs::write(path, contents).unwrap();
+    }
+
+    /// Applies `edit` to the project's config
+    pub fn edit_config(&self, edit: impl FnOnce(&mut toml::Table)) {
+        let mut config = self.read("CMakeMake.toml").parse::<toml::Table>().unwrap();
+        edit(&mut config);
+        self.write("CMakeMake.toml", &toml::to_string(&config).unwrap());
+    }
+
+    /// Makes the stub cmake print `output` when building, as if the compiler had
+    pub fn set_compiler_output(&self, output: &str) {
+        std::fs::write(self.root.join("compiler.txt"), output).unwrap();
+    }
+
+    /// Makes the stub cmake fail configuring, printing `output`
+    pub fn set_configure_error(&self, output: &str) {
+        std::fs::write(self.root.join("configure.txt"), output).unwrap();
+    }
+
+    fn calls(&self, log: &str) -> Vec<String> {
+        std::fs::read_to_string(self.root.join(log))
+            .unwrap_or_default()
+            .lines()
+            .map(String::from)
+            .collect()
+    }
+
+    /// Every call made to the stub cmake so far, one line of arguments each
+    pub fn cmake_calls(&self) -> Vec<String> {
+        self.calls("cmake.log")
+    }
+
+    /// Every call made to the stub conan so far, one line of arguments each
+    pub fn conan_calls(&self) -> Vec<String> {
+        self.calls("conan.log")
+    }
+
+    /// Every call made to the stub cpack so far, as `<working dir>: <arguments>`
+    pub fn cpack_calls(&self) -> Vec<String> {
+        self.calls("cpack.log")
+    }
+
+    /// Every test run with the stub ctest so far, as `<working dir>: <arguments>`
+    pub fn ctest_calls(&self) -> Vec<String> {
+        self.calls("ctest.log")
+    }
+
+    /// Every report made with the stub gcovr so far, as `<working dir>: <arguments>`
+    pub fn gcovr_calls(&self) -> Vec<String> {
+        self.calls("gcovr.log")
+    }
+}
+
+impl Drop for TestProject {
+    fn drop(&mut self) {
+        // Keep the project around to look at when the test failed
+        if !std::thread::panicking() {
+            std::fs::remove_dir_all(&self.root).ok();
+        }
+    }
+}
+
+/// `cmm` exits successfully even when a command fails, so check what it printed too
+pub fn assert_success(output: &Output) {
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let stderr = String::from_utf8_lossy(&output.stderr);
+
+    assert!(
+        output.status.success() && !stderr.contains("error:"),
+        "cmm failed\nstdout:\n{}\nstderr:\n{}",
+        stdout,
+        stderr
+    );
+}
+
+/// Drops the config hash from the first line, which changes whenever the config format does
+fn without_hash(contents: &str) -> String {
+    match contents.split_once('\n') {
+        Some((first, rest))
+            if first.starts_with("# ") && first[2..].chars().all(|c| c.is_ascii_digit()) =>
+        {
+            rest.to_owned()
+        }
+        _ => contents.to_owned(),
+    }
+}
+
+/// Compares `actual` with `tests/snapshots/<name>.txt`. Run with `UPDATE_SNAPSHOTS=1` to
+/// write the snapshot instead after checking the change is intended.
+pub fn assert_snapshot(name: &str, actual: &str) {
+    let path = Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("tests/snapshots")
+        .join(format!("{name}.txt"));
+    let actual = without_hash(actual);
+
+    if std::env::var_os("UPDATE_SNAPSHOTS").is_some() {
+        std::fs::write(&path, &actual).unwrap();
+        return;
+    }
+
+    let expected = std::fs::read_to_string(&path).unwrap_or_else(|_| {
+        panic!(
+            "missing snapshot '{}', run with UPDATE_SNAPSHOTS=1 to create it",
+            path.display()
+        )
+    });
+
+    assert!(
+        expected == actual,
+        "snapshot '{}' doesn't match\n--- expected\n{}\n--- actual\n{}",
+        name,
+        expected,
+        actual
+    );
+}
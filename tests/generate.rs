@@ -0,0 +1,913 @@
+mod common;
+
+use common::{assert_snapshot, TestProject};
+
+#[test]
+fn new_project() {
+    let project = TestProject::new("demo");
+
+    assert!(project.dir().join("src/main.cpp").exists());
+    assert!(project.dir().join("include").is_dir());
+    assert!(project.read(".gitignore").contains("build/"));
+
+    project.cmm_ok(&["cmake"]);
+    assert_snapshot("new_project", &project.read("CMakeLists.txt"));
+}
+
+#[test]
+fn headers_next_to_sources() {
+    let project = TestProject::empty("workspace");
+    project.cmm_ok(&["new", "demo", "--headers", "src"]);
+
+    let dir = project.dir().join("demo");
+    assert!(!dir.join("include").exists());
+
+    let config = std::fs::read_to_string(dir.join("CMakeMake.toml")).unwrap();
+    assert!(config.contains(r#"include_dirs = [["Public", ["."]]]"#));
+}
+
+#[cfg(target_os = "linux")]
+#[test]
+fn new_uses_starter_files() {
+    let workspace = TestProject::empty("workspace");
+
+    let global = workspace.home().join(".local/share/cmakemake");
+    std::fs::create_dir_all(global.join("skeleton")).unwrap();
+    std::fs::write(
+        global.join("config.toml"),
+        "author = \"Ada\"\n\n[starter]\nmain = \"skeleton/main.cpp\"\n\n\
+         [starter.main_by_standard]\n\"20\" = \"skeleton/main20.cpp\"\n\n\
+         [starter.files]\n\".clang-format\" = \"skeleton/clang-format\"\n         \".github/workflows/ci.yml\" = \"skeleton/ci.yml\"\n",
+    )
+    .unwrap();
+    std::fs::write(
+        global.join("skeleton/main.cpp"),
+        "// {{project}} by {{author}}\n",
+    )
+    .unwrap();
+    std::fs::write(global.join("skeleton/main20.cpp"), "// C++{{standard}}\n").unwrap();
+    std::fs::write(global.join("skeleton/clang-format"), "BasedOnStyle: LLVM\n").unwrap();
+    std::fs::write(global.join("skeleton/ci.yml"), "name: {{project}}\n").unwrap();
+
+    let standard = ("CMM_ANSWER_NEW_CXX_STANDARD", "17");
+    workspace.cmm_env(&["new", "demo"], &[standard]);
+    let demo = workspace.dir().join("demo");
+    let main = std::fs::read_to_string(demo.join("src/main.cpp")).unwrap();
+    assert_eq!(main, "// demo by Ada\n");
+    assert!(demo.join(".clang-format").exists());
+    let ci = std::fs::read_to_string(demo.join(".github/workflows/ci.yml")).unwrap();
+    assert_eq!(ci, "name: demo\n");
+
+    let standard = ("CMM_ANSWER_NEW_CXX_STANDARD", "20");
+    workspace.cmm_env(&["new", "modern"], &[standard]);
+    let main = std::fs::read_to_string(workspace.dir().join("modern/src/main.cpp")).unwrap();
+    assert_eq!(main, "// C++20\n");
+
+    // Templates keep their own main.cpp but still get the extra files
+    workspace.cmm_ok(&["new", "tool", "--template", "cli"]);
+    let tool = workspace.dir().join("tool");
+    let main = std::fs::read_to_string(tool.join("src/main.cpp")).unwrap();
+    assert!(!main.starts_with("//"));
+    assert!(tool.join(".clang-format").exists());
+}
+
+#[test]
+fn glfw_template() {
+    let project = TestProject::new_with("demo", &["--template", "glfw-opengl"]);
+
+    assert!(project.read("src/main.cpp").contains("glfwCreateWindow"));
+    assert!(project.dir().join("resources").is_dir());
+
+    project.cmm_ok(&["cmake"]);
+    assert_snapshot("glfw_template", &project.read("CMakeLists.txt"));
+}
+
+#[test]
+fn sdl2_template() {
+    let project = TestProject::new_with("demo", &["--template", "sdl2"]);
+    assert!(project.read("src/main.cpp").contains("SDL_CreateWindow"));
+
+    project.cmm_ok(&["cmake"]);
+    let cmake = project.read("CMakeLists.txt");
+    assert!(cmake.contains(r#"set(SDL_SHARED           OFF CACHE BOOL "")"#));
+    assert!(cmake.contains(
+        r#"target_link_libraries("${PROJECT_NAME}" PRIVATE SDL2::SDL2main SDL2::SDL2-static)"#
+    ));
+}
+
+#[test]
+fn cli_template() {
+    let project = TestProject::new_with("demo", &["--template", "cli"]);
+
+    assert!(project.read("src/main.cpp").contains("CLI::App"));
+    assert!(project
+        .read("tests/commands_test.cpp")
+        .contains("commands::greet"));
+    assert!(!project.dir().join("resources").exists());
+
+    project.cmm_ok(&["cmake"]);
+    let cmake = project.read("CMakeLists.txt");
+    assert!(cmake.contains(
+        "FetchContent_Declare(CLI11 GIT_REPOSITORY https://github.com/CLIUtils/CLI11 GIT_TAG v2.4.2)"
+    ));
+    assert!(cmake.contains("target_link_libraries(\"${PROJECT_NAME}\" PRIVATE CLI11::CLI11)"));
+    assert!(cmake.contains("catch_discover_tests("));
+}
+
+#[test]
+fn find_dependency() {
+    let project = TestProject::new("demo");
+
+    project.cmm_ok(&["add", "find", "OpenGL", "--required"]);
+    project.cmm_ok(&["add", "find", "Threads", "--link-name", "Threads::Threads"]);
+    project.cmm_ok(&["cmake"]);
+
+    assert_snapshot("find_dependency", &project.read("CMakeLists.txt"));
+}
+
+#[test]
+fn find_dependency_prefix_paths() {
+    let project = TestProject::new("demo");
+
+    project.cmm_ok(&["add", "find", "glfw3", "--prefix-path", "~/opt/glfw"]);
+    project.cmm_ok(&["add", "find", "Foo", "--prefix-path", "third_party/foo/"]);
+    project.cmm_ok(&["add", "find", "Bar", "--prefix-path", "~/opt/glfw"]);
+    project.cmm_ok(&["cmake"]);
+
+    let config = project.read("CMakeMake.toml");
+    assert_eq!(config.matches("~/opt/glfw").count(), 1);
+
+    let cmake = project.read("CMakeLists.txt");
+    let home = cmake
+        .find(r#"list(APPEND CMAKE_PREFIX_PATH "$ENV{HOME}/opt/glfw")"#)
+        .unwrap();
+    let local = cmake
+        .find(r#"list(APPEND CMAKE_PREFIX_PATH "${CMAKE_CURRENT_SOURCE_DIR}/third_party/foo")"#)
+        .unwrap();
+    let find = cmake.find("find_package(glfw3)").unwrap();
+    assert!(home < local && local < find);
+}
+
+#[test]
+fn fetch_dependency() {
+    let project = TestProject::new("demo");
+
+    project.cmm_ok(&[
+        "add",
+        "fetch",
+        "https://github.com/fmtlib/fmt.git",
+        "--tag",
+        "11.0.2",
+        "--link-name",
+        "fmt::fmt",
+        "--var",
+        "FMT_INSTALL=OFF",
+    ]);
+    project.cmm_ok(&["cmake"]);
+
+    assert_snapshot("fetch_dependency", &project.read("CMakeLists.txt"));
+
+    project.cmm_ok(&["remove", "fmt"]);
+    project.cmm_ok(&["cmake"]);
+    assert!(!project.read("CMakeLists.txt").contains("FetchContent"));
+}
+
+#[test]
+fn link_and_unlink() {
+    let project = TestProject::new("demo");
+    project.cmm_ok(&[
+        "add",
+        "find",
+        "ZLIB",
+        "--no-link",
+        "--link-name",
+        "ZLIB::ZLIB",
+    ]);
+
+    let linked = |project: &TestProject| {
+        let config = project
+            .read("CMakeMake.toml")
+            .parse::<toml::Table>()
+            .unwrap();
+        let project_dependencies = config["dependencies"]["project_dependencies"].clone();
+        let tool = config["targets"][0]["dependencies"].clone();
+        (project_dependencies, tool)
+    };
+
+    project.edit_config(|config| {
+        let mut target = toml::Table::new();
+        target.insert("name".into(), "tool".into());
+        config.insert("targets".into(), vec![target].into());
+    });
+
+    project.cmm_ok(&["link", "ZLIB"]);
+    project.cmm_ok(&["link", "ZLIB", "--target", "tool"]);
+    let (project_dependencies, tool) = linked(&project);
+    assert_eq!(project_dependencies, vec!["ZLIB::ZLIB"].into());
+    assert_eq!(tool, vec!["ZLIB::ZLIB"].into());
+
+    // Without anyone to ask, unlink takes it out of everything that links it
+    project.cmm_ok(&["unlink", "ZLIB"]);
+    let (project_dependencies, tool) = linked(&project);
+    assert_eq!(project_dependencies, toml::Value::Array(Vec::new()));
+    assert_eq!(tool, toml::Value::Array(Vec::new()));
+    assert!(project.read("CMakeMake.toml").contains("name = \"ZLIB\""));
+
+    let output = project.cmm(&["link", "glm"]);
+    assert!(!output.status.success());
+    let output = project.cmm(&["link", "ZLIB", "--target", "nope"]);
+    assert!(!output.status.success());
+
+    project.cmm_ok(&["add", "find", "tool", "--no-link"]);
+    let output = project.cmm(&["link", "tool", "--target", "tool"]);
+    assert!(String::from_utf8_lossy(&output.stderr).contains("target 'tool' can't link itself"));
+    let (_, tool) = linked(&project);
+    assert_eq!(tool, toml::Value::Array(Vec::new()));
+}
+
+#[test]
+fn add_local_dependency_with_custom_files() {
+    let project = TestProject::new("demo");
+    project.write("libs/foo/src/foo.cpp", "");
+    project.write(
+        "answers.toml",
+        "[add]\ntype = \"Local\"\npath = \"libs/foo\"\nname = \"foo\"\nvariables = []\n\
+         uses_cmake = false\nfiles = \"Custom\"\nsource_folders = [\"src\"]\n\
+         include_folders = [\"include\"]\nlibrary_dependencies = []\nlink = true\n",
+    );
+
+    project.cmm_ok(&["add", "--answers", "answers.toml"]);
+
+    let config = project
+        .read("CMakeMake.toml")
+        .parse::<toml::Table>()
+        .unwrap();
+    let files = &config["dependencies"]["local"][0]["local_type"]["Source"]["files"];
+    assert_eq!(
+        files["source_files"].to_string(),
+        r#"[["GlobRecurse", ["src"]]]"#
+    );
+    assert_eq!(
+        files["include_dirs"].to_string(),
+        r#"[["Public", ["include"]]]"#
+    );
+}
+
+#[test]
+fn cpm_dependency() {
+    let project = TestProject::new("demo");
+
+    project.cmm_ok(&[
+        "add",
+        "git",
+        "https://github.com/gabime/spdlog.git",
+        "--cpm",
+        "--tag",
+        "v1.14.1",
+        "--var",
+        "SPDLOG_BUILD_EXAMPLE=OFF",
+    ]);
+    project.cmm_ok(&["cmake"]);
+
+    // Added without cloning anything
+    assert!(!project.dir().join("external").exists());
+    assert!(project
+        .read("cmake/CPM.cmake")
+        .contains("CPM_DOWNLOAD_VERSION"));
+
+    assert_snapshot("cpm_dependency", &project.read("CMakeLists.txt"));
+}
+
+#[test]
+fn conan_dependency() {
+    let project = TestProject::new("demo");
+
+    project.cmm_ok(&["add", "conan", "zlib/1.3.1", "--package", "ZLIB"]);
+    project.cmm_ok(&["add", "conan", "fmt/11.0.2"]);
+    project.cmm_ok(&["cmake"]);
+
+    assert_snapshot("conan_dependency", &project.read("CMakeLists.txt"));
+    assert_eq!(
+        project.read("conanfile.txt"),
+        "# Generated by cmakemake from CMakeMake.toml\n\n[requires]\nfmt/11.0.2\nzlib/1.3.1\n\n[generators]\nCMakeDeps\n"
+    );
+}
+
+#[test]
+fn no_hash_comment() {
+    let project = TestProject::new("demo");
+
+    project.cmm_ok(&["add", "find", "ZLIB"]);
+    project.cmm_ok(&["add", "find", "Boost"]);
+    project.cmm_ok(&["cmake", "--no-hash-comment"]);
+
+    let cmake = project.read("CMakeLists.txt");
+    assert!(cmake.starts_with("# Generated by cmakemake from CMakeMake.toml\n"));
+
+    // Dependencies come out sorted, whatever order they were added in
+    assert_snapshot("no_hash_comment", &cmake);
+
+    project.cmm_ok(&["cmake", "--no-hash-comment"]);
+    assert_eq!(project.read("CMakeLists.txt"), cmake);
+}
+
+#[test]
+fn test_framework() {
+    let project = TestProject::new("demo");
+
+    project.cmm_ok(&["add", "test-framework", "gtest"]);
+    assert!(project.dir().join("tests/example_test.cpp").exists());
+
+    project.cmm_ok(&["cmake"]);
+    assert_snapshot("test_framework", &project.read("CMakeLists.txt"));
+}
+
+#[test]
+fn file_roles() {
+    let project = TestProject::new("demo");
+
+    project.write("examples/hello.cpp", "int main() {}\n");
+    project.write("bench/sort.cpp", "int main() {}\n");
+    project.write("src/checks/parse.cpp", "int main() { return 0; }\n");
+
+    project.edit_config(|config| {
+        let roles = toml::Value::try_from(vec![
+            ("example", vec!["../examples"]),
+            ("bench", vec!["../bench/sort.cpp"]),
+            ("test", vec!["checks"]),
+        ])
+        .unwrap();
+
+        config["cmake"]["files"]
+            .as_table_mut()
+            .unwrap()
+            .insert("roles".into(), roles);
+    });
+
+    project.cmm_ok(&["cmake"]);
+    assert_snapshot("file_roles", &project.read("CMakeLists.txt"));
+
+    // Each file is built once, by its role
+    project.cmm_ok(&["validate"]);
+
+    // With a framework, test files go into its executable
+    project.cmm_ok(&["add", "test-framework", "catch2"]);
+    project.cmm_ok(&["cmake"]);
+
+    let lists = project.read("CMakeLists.txt");
+    assert!(
+        lists.contains(r#"target_sources("${PROJECT_NAME}_tests" PRIVATE ${CMM_TEST_SOURCES})"#)
+    );
+    assert!(!lists.contains(r#"add_executable("test_${name}""#));
+}
+
+#[test]
+fn install_rules() {
+    let project = TestProject::new("demo");
+
+    project.edit_config(|config| {
+        config.insert(
+            "install".into(),
+            toml::toml! {
+                binaries = ["demo"]
+                headers = ["include/"]
+                resources = ["res"]
+                prefix = "/opt/demo"
+            }
+            .into(),
+        );
+    });
+    project.cmm_ok(&["cmake"]);
+
+    assert_snapshot("install_rules", &project.read("CMakeLists.txt"));
+}
+
+#[test]
+fn library_consumer_options() {
+    let project = TestProject::new("my-lib");
+
+    project.write("examples/hello.cpp", "int main() {}\n");
+    project.edit_config(|config| {
+        config["project"]["kind"] = "library".into();
+
+        let roles = toml::Value::try_from(vec![("example", vec!["../examples"])]).unwrap();
+        config["cmake"]["files"]
+            .as_table_mut()
+            .unwrap()
+            .insert("roles".into(), roles);
+
+        config.insert(
+            "install".into(),
+            toml::toml! {
+                binaries = ["my-lib"]
+                headers = ["include/"]
+            }
+            .into(),
+        );
+    });
+    project.cmm_ok(&["add", "test-framework", "gtest"]);
+    project.cmm_ok(&["cmake"]);
+
+    assert_snapshot("library_consumer_options", &project.read("CMakeLists.txt"));
+}
+
+#[test]
+fn packaging() {
+    let project = TestProject::new("demo");
+
+    project.edit_config(|config| {
+        let project = config["project"].as_table_mut().unwrap();
+        project.insert(
+            "authors".into(),
+            toml::Value::from(vec!["Ada <ada@example.com>"]),
+        );
+        project.insert("license".into(), "MIT".into());
+
+        config.insert("install".into(), toml::toml! { binaries = ["demo"] }.into());
+        config.insert(
+            "packaging".into(),
+            toml::toml! {
+                generators = ["DEB", "ZIP"]
+                description = "A \"small\" demo"
+            }
+            .into(),
+        );
+    });
+    project.cmm_ok(&["cmake"]);
+
+    assert_snapshot("packaging", &project.read("CMakeLists.txt"));
+}
+
+#[test]
+fn cxx_standard() {
+    let workspace = TestProject::empty("workspace");
+    let output = workspace.cmm_env(&["new", "demo"], &[("CMM_ANSWER_NEW_CXX_STANDARD", "20")]);
+    assert!(output.status.success());
+
+    let config = std::fs::read_to_string(workspace.dir().join("demo/CMakeMake.toml")).unwrap();
+    assert!(config.contains("cxx_standard = 20"));
+
+    let project = TestProject::new("demo");
+    project.edit_config(|config| {
+        let cmake = config["cmake"].as_table_mut().unwrap();
+        cmake.insert("cxx_standard".into(), 23.into());
+        cmake.insert("cxx_extensions".into(), true.into());
+    });
+    project.cmm_ok(&["cmake"]);
+
+    let cmake = project.read("CMakeLists.txt");
+    assert!(cmake.contains("cmake_minimum_required(VERSION 3.20)"));
+    assert!(cmake.contains(
+        "set(CMAKE_CXX_STANDARD 23)\nset(CMAKE_CXX_STANDARD_REQUIRED ON)\nset(CMAKE_CXX_EXTENSIONS ON)"
+    ));
+}
+
+#[test]
+fn generate_class() {
+    let project = TestProject::new("demo");
+
+    project.cmm_ok(&["generate", "class", "render/HttpServer"]);
+
+    let header = project.read("include/demo/render/http_server.hpp");
+    assert!(header.starts_with("#pragma once\n\nnamespace demo\n{\n\nclass HttpServer\n"));
+
+    let source = project.read("src/render/http_server.cpp");
+    assert!(source.starts_with("#include \"demo/render/http_server.hpp\"\n"));
+    assert!(source.contains("HttpServer::~HttpServer()"));
+
+    let output = project.cmm(&["generate", "class", "render/HttpServer"]);
+    assert!(String::from_utf8_lossy(&output.stderr).contains("already exists"));
+
+    // Sources listed one by one get the new file added
+    project.edit_config(|config| {
+        let files = config["cmake"]["files"].as_table_mut().unwrap();
+        files.insert(
+            "source_files".into(),
+            toml::Value::try_from([("File", ["main.cpp"])]).unwrap(),
+        );
+
+        let mut codegen = toml::Table::new();
+        codegen.insert("include_guards".into(), true.into());
+        config.insert("codegen".into(), codegen.into());
+    });
+
+    project.cmm_ok(&["generate", "class", "Mesh", "--namespace", "gfx"]);
+
+    let header = project.read("include/demo/mesh.hpp");
+    assert!(header.starts_with("#ifndef DEMO_MESH_HPP\n#define DEMO_MESH_HPP\n\nnamespace gfx\n"));
+    assert!(project
+        .read("CMakeMake.toml")
+        .contains(r#"source_files = [["File", ["main.cpp", "mesh.cpp"]]]"#));
+}
+
+#[test]
+fn move_file() {
+    let project = TestProject::new("demo");
+
+    project.write("include/demo/util.hpp", "#pragma once\n");
+    project.write(
+        "src/helper.hpp",
+        "#pragma once\n#include \"demo/util.hpp\"\n",
+    );
+    project.write("src/helper.cpp", "#include \"helper.hpp\"\n");
+    project.write(
+        "src/main.cpp",
+        "#include <vector>\n#include \"demo/util.hpp\"\n#include \"helper.hpp\"\n",
+    );
+    project.edit_config(|config| {
+        let files = config["cmake"]["files"].as_table_mut().unwrap();
+        files.insert(
+            "source_files".into(),
+            toml::Value::try_from([("File", ["main.cpp", "helper.cpp"])]).unwrap(),
+        );
+    });
+
+    project.cmm_ok(&["mv", "include/demo/util.hpp", "include/demo/core/util.hpp"]);
+    project.cmm_ok(&["mv", "src/helper.hpp", "src/detail/helper.hpp"]);
+    project.cmm_ok(&["mv", "./src/helper.cpp", "src/detail/helper.cpp"]);
+
+    assert_eq!(
+        project.read("src/main.cpp"),
+        "#include <vector>\n#include \"demo/core/util.hpp\"\n#include \"detail/helper.hpp\"\n"
+    );
+    assert_eq!(
+        project.read("src/detail/helper.hpp"),
+        "#pragma once\n#include \"demo/core/util.hpp\"\n"
+    );
+    assert_eq!(
+        project.read("src/detail/helper.cpp"),
+        "#include \"detail/helper.hpp\"\n"
+    );
+
+    assert!(project
+        .read("CMakeMake.toml")
+        .contains(r#"source_files = [["File", ["main.cpp", "detail/helper.cpp"]]]"#));
+    assert!(project
+        .read("CMakeLists.txt")
+        .contains(r#"set(SOURCES "src/main.cpp" "src/detail/helper.cpp")"#));
+
+    let output = project.cmm(&["mv", "src/main.cpp", "../main.cpp"]);
+    assert!(String::from_utf8_lossy(&output.stderr).contains("is outside the project"));
+}
+
+#[test]
+fn validate_source_entries() {
+    let project = TestProject::new("demo");
+    project.cmm_ok(&["validate"]);
+
+    project.write("src/net/socket.cpp", "");
+    project.write("include/demo/cache.hpp", "");
+    project.write("src/cache.cpp", "");
+    project.write("tools/gen.cpp", "");
+    project.edit_config(|config| {
+        let files = config["cmake"]["files"].as_table_mut().unwrap();
+        files.insert(
+            "source_files".into(),
+            toml::Value::try_from([("GlobRecurse", ["."]), ("Glob", ["net"])]).unwrap(),
+        );
+        files.insert("exclude_files".into(), vec!["cache.cpp"].into());
+    });
+
+    let output = project.cmm(&["validate"]);
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains(
+        "'src/cache.cpp' is excluded from the build, but 'include/demo/cache.hpp' declares it"
+    ));
+    assert!(stdout.contains(r#"'src/net/socket.cpp' is matched by 2 entries and compiled more than once: cmake.files GLOB_RECURSE "src", cmake.files GLOB "src/net""#));
+    assert!(stdout.contains("'tools/gen.cpp' isn't matched by any source entry"));
+    assert!(String::from_utf8_lossy(&output.stderr).contains("found 3 problems"));
+}
+
+#[test]
+fn check_config_problems() {
+    let project = TestProject::new("demo");
+    project.cmm_ok(&["add", "find", "OpenGL"]);
+    project.cmm_ok(&["check"]);
+
+    project.edit_config(|config| {
+        let files = config["cmake"]["files"].as_table_mut().unwrap();
+        files.insert(
+            "source_files".into(),
+            toml::Value::try_from([("GlobRecurse", ["."]), ("File", ["gone.cpp"])]).unwrap(),
+        );
+
+        let dependencies = config["dependencies"].as_table_mut().unwrap();
+        dependencies.insert(
+            "project_dependencies".into(),
+            vec!["OpenGL", "glm", "SDL2::SDL2main"].into(),
+        );
+
+        let mut target = toml::Table::new();
+        target.insert("name".into(), "demo".into());
+        target.insert("path".into(), "tools".into());
+        config.insert("targets".into(), vec![target].into());
+    });
+
+    let output = project.cmm(&["check"]);
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("cmake.files.source_files: file 'src/gone.cpp' doesn't exist"));
+    assert!(stdout.contains("targets.demo: the name is used by the project or another target"));
+    assert!(stdout.contains("targets.demo: folder 'tools' doesn't exist"));
+    assert!(stdout.contains(
+        "dependencies.project_dependencies: 'glm' isn't a dependency or target in the config"
+    ));
+    assert!(!stdout.contains("SDL2"));
+    assert!(String::from_utf8_lossy(&output.stderr).contains("found 4 problems"));
+    assert_eq!(output.status.code(), Some(3));
+
+    // Nothing is generated
+    assert!(!project.dir().join("CMakeLists.txt").exists());
+}
+
+#[test]
+fn third_party_notices() {
+    let project = TestProject::new("demo");
+    project.write(
+        "external/zlib-ng/LICENSE.md",
+        "Permission is hereby granted, free of charge, to any person\nobtaining a copy\n",
+    );
+    project.write("external/vendored/include/vendored.h", "");
+
+    let stdout = project.cmm_ok(&["deps", "licenses-header"]);
+    assert!(stdout.contains("no license file found for dependency 'vendored'"));
+
+    let notices = project.read("THIRD_PARTY_NOTICES.md");
+    assert!(notices.contains("## vendored\n\n- Version: unknown\n- License: not found\n"));
+    assert!(notices.contains(
+        "## zlib-ng\n\n- Version: unknown\n- License: MIT (LICENSE.md)\n\n```text\nPermission is hereby granted"
+    ));
+    assert!(notices.find("## vendored") < notices.find("## zlib-ng"));
+}
+
+#[test]
+fn configured_cache_launcher() {
+    let project = TestProject::new("demo");
+    project.edit_config(|config| {
+        config["cmake"]
+            .as_table_mut()
+            .unwrap()
+            .insert("cache_launcher".into(), "ccache".into());
+    });
+
+    project.cmm_ok(&["cmake"]);
+    assert!(project
+        .read("CMakeLists.txt")
+        .contains("    find_program(CMM_CACHE_PROGRAM NAMES ccache)\n"));
+}
+
+#[test]
+fn compiler_flags() {
+    let project = TestProject::new("demo");
+
+    project.edit_config(|config| {
+        let cmake = config["cmake"].as_table_mut().unwrap();
+        cmake.insert("cxx_flags".into(), vec!["-Wall", "-Wextra"].into());
+        cmake.insert(
+            "cxx_flags_debug".into(),
+            vec!["-fno-omit-frame-pointer"].into(),
+        );
+        cmake.insert("cxx_flags_release".into(), vec!["-march=native"].into());
+        cmake.insert("link_flags_release".into(), vec!["-flto"].into());
+    });
+
+    project.cmm_ok(&["cmake"]);
+
+    assert_snapshot("compiler_flags", &project.read("CMakeLists.txt"));
+}
+
+#[test]
+fn warning_level_presets() {
+    let project = TestProject::new("demo");
+
+    project.edit_config(|config| {
+        let cmake = config["cmake"].as_table_mut().unwrap();
+        cmake.insert("warning_level".into(), "high".into());
+        cmake.insert("cxx_flags".into(), vec!["-Wno-unused"].into());
+    });
+
+    project.cmm_ok(&["cmake"]);
+
+    let lists = project.read("CMakeLists.txt");
+    let msvc = lists
+        .find(r#""$<$<STREQUAL:${CMAKE_CXX_COMPILER_FRONTEND_VARIANT},MSVC>:/W4>""#)
+        .unwrap();
+    let gcc = lists
+        .find(r#""$<$<NOT:$<STREQUAL:${CMAKE_CXX_COMPILER_FRONTEND_VARIANT},MSVC>>:-Wall;-Wextra;-Wpedantic>""#)
+        .unwrap();
+
+    // Configured flags come after, so they can turn single warnings back off
+    assert!(msvc < gcc && gcc < lists.find(r#""-Wno-unused""#).unwrap());
+}
+
+#[test]
+fn output_styles() {
+    let project = TestProject::new("demo");
+
+    let set_style = |style: &str| {
+        project.edit_config(|config| {
+            config["cmake"]
+                .as_table_mut()
+                .unwrap()
+                .insert("output_style".into(), style.into());
+        });
+        project.cmm_ok(&["cmake"]);
+        project.read("CMakeLists.txt")
+    };
+
+    let normal = set_style("normal");
+    assert!(normal.contains("\n#Project Files:\n"));
+
+    let verbose = set_style("verbose");
+    assert!(
+        verbose.contains("\n# Project Files\n# The project's executable, built from its sources,")
+    );
+    assert!(!verbose.contains("#Project Files:"));
+
+    let compact = set_style("compact");
+    assert!(!compact.contains("#Project"));
+    assert!(!compact.contains("\n\n"));
+
+    // Every style keeps the first line, which marks the file as generated
+    for contents in [&normal, &verbose, &compact] {
+        assert!(contents.starts_with("# "));
+    }
+
+    // and isn't mistaken for a hand-edited file when building regenerates it
+    project.cmm_ok(&["build"]);
+}
+
+#[test]
+fn profile_definitions_raise_minimum_version() {
+    let project = TestProject::new("demo");
+
+    project.edit_config(|config| {
+        let mut debug = toml::Table::new();
+        debug.insert("definitions".into(), vec!["DEBUG_LOG=1"].into());
+
+        let mut profiles = toml::Table::new();
+        profiles.insert("debug".into(), debug.into());
+        config.insert("profiles".into(), profiles.into());
+
+        config["cmake"]
+            .as_table_mut()
+            .unwrap()
+            .insert("minimum_required".into(), "3.11".into());
+    });
+
+    let output = project.cmm_ok(&["cmake"]);
+    assert!(output.contains("raising cmake_minimum_required from 3.11 to 3.12"));
+
+    let cmake = project.read("CMakeLists.txt");
+    assert!(cmake.contains("cmake_minimum_required(VERSION 3.12)"));
+    assert!(cmake.contains("DEBUG_LOG=1"));
+}
+
+#[test]
+fn init_existing_sources() {
+    let project = TestProject::empty("legacy");
+
+    project.write("main.cpp", "int main() {}\n");
+    project.write("lib/util.cc", "void util() {}\n");
+    project.write("include/util.h", "void util();\n");
+    project.write("build/generated.cpp", "\n");
+
+    project.cmm_ok(&["init"]);
+
+    let config = project.read("CMakeMake.toml");
+    assert!(config.contains(r#"name = "legacy""#));
+    assert!(config.contains(r#"source_dir = ".""#));
+    assert!(config.contains(r#"source_files = [["Glob", ["."]], ["GlobRecurse", ["lib"]]]"#));
+    assert!(config.contains(r#"include_dirs = [["Public", [".", "include"]]]"#));
+    assert!(project.read(".gitignore").contains("build/"));
+
+    // A second init would overwrite the config
+    let output = project.cmm(&["init"]);
+    assert!(String::from_utf8_lossy(&output.stderr).contains("already contains"));
+}
+
+#[test]
+fn cmake_dry_run() {
+    let project = TestProject::new("demo");
+
+    project.cmm_ok(&["cmake", "--no-hash-comment"]);
+    let before = project.read("CMakeLists.txt");
+
+    let output = project.cmm_ok(&["cmake", "--dry-run", "--no-hash-comment"]);
+    assert!(output.contains("nothing would change"));
+
+    project.cmm_ok(&["add", "find", "ZLIB"]);
+    project.write("CMakeLists.txt", &before);
+
+    let output = project.cmm_ok(&["cmake", "--dry-run", "--no-hash-comment"]);
+    assert!(output.contains("@@ line"));
+    assert!(output.contains("+find_package(ZLIB"));
+    assert!(!output.lines().any(|line| line.starts_with("-")));
+
+    // Nothing was written
+    assert_eq!(project.read("CMakeLists.txt"), before);
+}
+
+#[test]
+fn keep_blocks_survive_regeneration() {
+    let project = TestProject::new("demo");
+
+    project.cmm_ok(&["cmake", "--no-hash-comment"]);
+    let cmake = project.read("CMakeLists.txt").replacen(
+        "project(\"demo\")\n",
+        "project(\"demo\")\n# cmm:keep-start\nset(DEMO_TWEAK ON)\n# cmm:keep-end\n",
+        1,
+    ) + "# cmm:keep-start\ninclude(extra.cmake)\n# cmm:keep-end\n";
+    project.write("CMakeLists.txt", &cmake);
+
+    // Still up to date with the blocks in it
+    let output = project.cmm_ok(&["build"]);
+    assert!(!output.contains("out of date"));
+
+    project.cmm_ok(&["add", "find", "ZLIB"]);
+    project.cmm_ok(&["cmake", "--no-hash-comment"]);
+
+    let regenerated = project.read("CMakeLists.txt");
+    assert!(regenerated.contains("find_package(ZLIB"));
+    assert!(regenerated
+        .contains("project(\"demo\")\n# cmm:keep-start\nset(DEMO_TWEAK ON)\n# cmm:keep-end\n"));
+    assert!(regenerated.contains(
+        "PUBLIC \"src\" \"include\")\n# cmm:keep-start\ninclude(extra.cmake)\n# cmm:keep-end\n"
+    ));
+
+    project.cmm_ok(&["cmake", "--no-hash-comment"]);
+    assert_eq!(project.read("CMakeLists.txt"), regenerated);
+}
+
+#[test]
+fn keep_blocks_at_the_top_stay_at_the_top() {
+    let project = TestProject::new("demo");
+
+    project.cmm_ok(&["cmake"]);
+    let block = "# cmm:keep-start\ncmake_policy(SET CMP0077 NEW)\n# cmm:keep-end\n";
+    project.write(
+        "CMakeLists.txt",
+        &format!("{block}{}", project.read("CMakeLists.txt")),
+    );
+
+    // Right after the hash comment, which has to stay first
+    project.cmm_ok(&["cmake"]);
+    let regenerated = project.read("CMakeLists.txt");
+    let (hash, rest) = regenerated.split_once('\n').unwrap();
+    assert!(hash.starts_with("# "));
+    assert!(rest.starts_with(block));
+    assert_eq!(regenerated.matches("cmm:keep-start").count(), 1);
+
+    project.cmm_ok(&["cmake"]);
+    assert_eq!(project.read("CMakeLists.txt"), regenerated);
+}
+
+#[test]
+fn config_cmake_snippets() {
+    let project = TestProject::new("demo");
+
+    project.cmm_ok(&["add", "find", "ZLIB"]);
+    project.cmm_ok(&[
+        "add",
+        "fetch",
+        "https://github.com/fmtlib/fmt.git",
+        "--name",
+        "fmt",
+        "--tag",
+        "11.0.2",
+    ]);
+
+    project.edit_config(|config| {
+        let cmake = config["cmake"].as_table_mut().unwrap();
+        cmake.insert(
+            "prelude".into(),
+            "set(CMAKE_OSX_DEPLOYMENT_TARGET 12.0)\n".into(),
+        );
+        cmake.insert(
+            "postlude".into(),
+            "set_target_properties(demo PROPERTIES UNITY_BUILD ON)".into(),
+        );
+
+        let dependencies = config["dependencies"].as_table_mut().unwrap();
+        for (kind, snippet) in [
+            ("find", "message(STATUS \"zlib ${ZLIB_VERSION_STRING}\")"),
+            ("fetch", "set_target_properties(fmt PROPERTIES FOLDER deps)"),
+        ] {
+            dependencies[kind].as_array_mut().unwrap()[0]
+                .as_table_mut()
+                .unwrap()
+                .insert("extra_cmake".into(), snippet.into());
+        }
+    });
+
+    project.cmm_ok(&["cmake"]);
+    let cmake = project.read("CMakeLists.txt");
+
+    assert!(cmake.contains(
+        "cmake_minimum_required(VERSION 3.15)\nset(CMAKE_OSX_DEPLOYMENT_TARGET 12.0)\nproject(\"demo\")\n"
+    ));
+    assert!(cmake.contains("find_package(ZLIB)\nmessage(STATUS \"zlib ${ZLIB_VERSION_STRING}\")\n"));
+    assert!(cmake.contains(
+        "FetchContent_MakeAvailable(fmt)\nset_target_properties(fmt PROPERTIES FOLDER deps)\n"
+    ));
+    assert!(cmake.ends_with("\n\nset_target_properties(demo PROPERTIES UNITY_BUILD ON)\n"));
+}